@@ -0,0 +1,935 @@
+use std::{hash::Hash, sync::Arc};
+
+use crate::{
+    CentralPanel, Context, CursorIcon, DragAndDrop, Id, InnerResponse, LayerId, Order, Pos2, Rect,
+    Response, ScrollArea, Sense, Ui, UiBuilder, Vec2, ViewportBuilder, ViewportId, WidgetText,
+    Window, util::id_type_map::SerializableAny, vec2,
+};
+
+use super::splitter::{SplitDirection, divider};
+
+/// Shows a pane in the native window of a torn-off pane, see [`DockArea::tear_off`].
+type ViewportPaneUi<Pane> = Arc<dyn Fn(&mut Ui, &Pane) + Send + Sync>;
+
+/// Where a pane is docked, relative to the pane it is dropped onto.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DockZone {
+    /// As another tab of the same tab group.
+    Center,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl DockZone {
+    const ALL: [Self; 5] = [
+        Self::Center,
+        Self::Left,
+        Self::Right,
+        Self::Top,
+        Self::Bottom,
+    ];
+
+    /// The zone of `rect` that `pos` is in: near an edge, or the center.
+    fn at(rect: Rect, pos: Pos2) -> Self {
+        let t = (pos - rect.min) / rect.size();
+        let (distance, zone) = [
+            (t.x, Self::Left),
+            (1.0 - t.x, Self::Right),
+            (t.y, Self::Top),
+            (1.0 - t.y, Self::Bottom),
+        ]
+        .into_iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap_or((1.0, Self::Center));
+        if distance < 0.25 { zone } else { Self::Center }
+    }
+
+    /// The split this zone makes, and whether the new pane goes first.
+    fn split(self) -> Option<(SplitDirection, bool)> {
+        match self {
+            Self::Center => None,
+            Self::Left => Some((SplitDirection::Horizontal, true)),
+            Self::Right => Some((SplitDirection::Horizontal, false)),
+            Self::Top => Some((SplitDirection::Vertical, true)),
+            Self::Bottom => Some((SplitDirection::Vertical, false)),
+        }
+    }
+
+    /// Where a pane docked in this zone of `rect` would end up.
+    fn preview(self, rect: Rect) -> Rect {
+        let center = rect.center();
+        match self {
+            Self::Center => rect,
+            Self::Left => rect.with_max_x(center.x),
+            Self::Right => rect.with_min_x(center.x),
+            Self::Top => rect.with_max_y(center.y),
+            Self::Bottom => rect.with_min_y(center.y),
+        }
+    }
+
+    /// The button for this zone, in the compass shown while dragging a floating pane.
+    fn compass_rect(self, center: Pos2, size: f32) -> Rect {
+        let offset = match self {
+            Self::Center => Vec2::ZERO,
+            Self::Left => -Vec2::X,
+            Self::Right => Vec2::X,
+            Self::Top => -Vec2::Y,
+            Self::Bottom => Vec2::Y,
+        };
+        Rect::from_center_size(center + 1.25 * size * offset, Vec2::splat(size))
+    }
+}
+
+/// A node in the layout of a [`DockArea`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum DockNode<Pane> {
+    /// One or more panes, shown as tabs.
+    Tabs { panes: Vec<Pane>, active: usize },
+
+    /// Two nodes, with a draggable divider between them.
+    Split {
+        direction: SplitDirection,
+
+        /// How much of the space goes to `first`, in `0..=1`.
+        fraction: f32,
+        first: Box<Self>,
+        second: Box<Self>,
+    },
+}
+
+impl<Pane> DockNode<Pane> {
+    /// Panes shown as tabs, with the first one active.
+    pub fn tabs(panes: Vec<Pane>) -> Self {
+        Self::Tabs { panes, active: 0 }
+    }
+
+    /// `left` and `right` side by side, with `fraction` of the width going to `left`.
+    pub fn horizontal(fraction: f32, left: Self, right: Self) -> Self {
+        Self::Split {
+            direction: SplitDirection::Horizontal,
+            fraction,
+            first: Box::new(left),
+            second: Box::new(right),
+        }
+    }
+
+    /// `top` above `bottom`, with `fraction` of the height going to `top`.
+    pub fn vertical(fraction: f32, top: Self, bottom: Self) -> Self {
+        Self::Split {
+            direction: SplitDirection::Vertical,
+            fraction,
+            first: Box::new(top),
+            second: Box::new(bottom),
+        }
+    }
+
+    /// Replace splits with an empty side by their other side.
+    ///
+    /// Returns `None` if there are no panes left.
+    fn simplify(self) -> Option<Self> {
+        match self {
+            Self::Tabs { panes, .. } if panes.is_empty() => None,
+            Self::Split {
+                direction,
+                fraction,
+                first,
+                second,
+            } => match (first.simplify(), second.simplify()) {
+                (Some(first), Some(second)) => Some(Self::Split {
+                    direction,
+                    fraction,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (Some(node), None) | (None, Some(node)) => Some(node),
+                (None, None) => None,
+            },
+            tabs @ Self::Tabs { .. } => Some(tabs),
+        }
+    }
+}
+
+impl<Pane: PartialEq> DockNode<Pane> {
+    pub fn contains(&self, pane: &Pane) -> bool {
+        match self {
+            Self::Tabs { panes, .. } => panes.contains(pane),
+            Self::Split { first, second, .. } => first.contains(pane) || second.contains(pane),
+        }
+    }
+
+    /// Remove `pane`, leaving an empty tab group if it was the last one.
+    fn remove(&mut self, pane: &Pane) -> bool {
+        match self {
+            Self::Tabs { panes, active } => {
+                let Some(index) = panes.iter().position(|p| p == pane) else {
+                    return false;
+                };
+                panes.remove(index);
+                if index < *active {
+                    *active -= 1;
+                }
+                *active = (*active).min(panes.len().saturating_sub(1));
+                true
+            }
+            Self::Split { first, second, .. } => first.remove(pane) || second.remove(pane),
+        }
+    }
+
+    /// Put `pane` in `zone` of the tab group with `next_to`, or give it back if there is no such group.
+    fn insert(&mut self, pane: Pane, next_to: &Pane, zone: DockZone) -> Result<(), Pane> {
+        match self {
+            Self::Tabs { panes, .. } if !panes.contains(next_to) => Err(pane),
+            Self::Tabs { panes, active } => {
+                match zone.split() {
+                    None => {
+                        panes.push(pane);
+                        *active = panes.len() - 1;
+                    }
+                    Some((direction, pane_first)) => {
+                        let old = std::mem::replace(self, Self::tabs(Vec::new()));
+                        let new = Self::tabs(vec![pane]);
+                        let (first, second) = if pane_first { (new, old) } else { (old, new) };
+                        *self = Self::Split {
+                            direction,
+                            fraction: 0.5,
+                            first: Box::new(first),
+                            second: Box::new(second),
+                        };
+                    }
+                }
+                Ok(())
+            }
+            Self::Split { first, second, .. } => first
+                .insert(pane, next_to, zone)
+                .or_else(|pane| second.insert(pane, next_to, zone)),
+        }
+    }
+
+    /// The first tab group, top left.
+    fn first_tabs_mut(&mut self) -> (&mut Vec<Pane>, &mut usize) {
+        match self {
+            Self::Tabs { panes, active } => (panes, active),
+            Self::Split { first, .. } => first.first_tabs_mut(),
+        }
+    }
+}
+
+/// A pane that is not docked, but shown in a [`Window`] or its own viewport.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+struct FloatingPane<Pane> {
+    pane: Pane,
+
+    /// Where the pane was last shown.
+    rect: Rect,
+
+    /// Move the window to `rect` this frame, since it was just undocked.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    reposition: bool,
+}
+
+/// The layout of a [`DockArea`]: which panes are docked where, and which are floating.
+///
+/// It is stored in [`crate::Memory`], and persisted with the `persistence` feature.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DockState<Pane> {
+    root: Option<DockNode<Pane>>,
+    floating: Vec<FloatingPane<Pane>>,
+}
+
+impl<Pane> Default for DockState<Pane> {
+    fn default() -> Self {
+        Self {
+            root: None,
+            floating: Vec::new(),
+        }
+    }
+}
+
+impl<Pane> DockState<Pane> {
+    pub fn new(root: DockNode<Pane>) -> Self {
+        Self {
+            root: Some(root),
+            floating: Vec::new(),
+        }
+    }
+
+    /// The docked panes, or `None` if all panes are floating or closed.
+    pub fn root(&self) -> Option<&DockNode<Pane>> {
+        self.root.as_ref()
+    }
+
+    pub fn root_mut(&mut self) -> Option<&mut DockNode<Pane>> {
+        self.root.as_mut()
+    }
+
+    /// The panes that are not docked.
+    pub fn floating_panes(&self) -> impl Iterator<Item = &Pane> {
+        self.floating.iter().map(|floating| &floating.pane)
+    }
+}
+
+impl<Pane: SerializableAny> DockState<Pane> {
+    pub fn load(ctx: &Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    pub fn store(self, ctx: &Context, id: Id) {
+        ctx.data_mut(|d| d.insert_persisted(id, self));
+    }
+}
+
+impl<Pane: PartialEq> DockState<Pane> {
+    /// Is the pane docked or floating?
+    pub fn contains(&self, pane: &Pane) -> bool {
+        self.root.as_ref().is_some_and(|root| root.contains(pane))
+            || self.floating.iter().any(|floating| floating.pane == *pane)
+    }
+
+    /// Add a pane as a tab of the first tab group, and make it active.
+    pub fn add(&mut self, pane: Pane) {
+        if let Some(root) = &mut self.root {
+            let (panes, active) = root.first_tabs_mut();
+            panes.push(pane);
+            *active = panes.len() - 1;
+        } else {
+            self.root = Some(DockNode::tabs(vec![pane]));
+        }
+    }
+
+    /// Remove a docked or floating pane.
+    ///
+    /// Splits that become empty are removed too.
+    pub fn remove(&mut self, pane: &Pane) -> bool {
+        let docked = self.root.as_mut().is_some_and(|root| root.remove(pane));
+        if docked {
+            self.root = self.root.take().and_then(DockNode::simplify);
+        }
+        let num_floating = self.floating.len();
+        self.floating.retain(|floating| floating.pane != *pane);
+        docked || self.floating.len() != num_floating
+    }
+
+    /// Move `pane` to `zone` of the tab group that has `next_to`.
+    ///
+    /// If `next_to` is not docked, `pane` is added like with [`Self::add`].
+    pub fn dock(&mut self, pane: Pane, next_to: &Pane, zone: DockZone) {
+        if pane == *next_to {
+            return;
+        }
+        self.remove(&pane);
+        let result = match &mut self.root {
+            Some(root) => root.insert(pane, next_to, zone),
+            None => Err(pane),
+        };
+        if let Err(pane) = result {
+            self.add(pane);
+        }
+    }
+
+    /// Undock `pane` into a window at `rect`.
+    pub fn float(&mut self, pane: Pane, rect: Rect) {
+        self.remove(&pane);
+        self.floating.push(FloatingPane {
+            pane,
+            rect,
+            reposition: true,
+        });
+    }
+}
+
+/// What [`DockArea::show`] returns.
+pub struct DockOutput<Pane> {
+    /// The response of the whole dock area.
+    ///
+    /// It is [`Response::changed`] when the user docked, undocked or closed a pane.
+    pub response: Response,
+
+    /// The panes the user closed this frame, which have already been removed.
+    pub closed: Vec<Pane>,
+}
+
+/// Panes that can be split, tabbed, and undocked into windows, e.g. for an IDE.
+///
+/// Panes are identified by your own `Pane` type.
+/// The layout is a [`DockState`], which is stored in [`crate::Memory`].
+///
+/// The user can:
+/// * resize splits by dragging the dividers,
+/// * drag tabs onto other panes to make new splits or tab groups,
+/// * drag tabs out of the dock area to undock them into a [`Window`],
+/// * drag floating windows onto the compass that shows up over each pane to dock them again.
+///
+/// With [`Self::tear_off`], undocked panes are shown in their own native window instead
+/// (if the backend supports multiple viewports). Closing that window docks the pane again.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::{DockArea, DockNode, DockState};
+///
+/// let output = DockArea::new(ui.make_persistent_id("dock")).closable(true).show(
+///     ui,
+///     || {
+///         DockState::new(DockNode::horizontal(
+///             0.25,
+///             DockNode::tabs(vec!["Files".to_owned()]),
+///             DockNode::vertical(
+///                 0.7,
+///                 DockNode::tabs(vec!["main.rs".to_owned(), "lib.rs".to_owned()]),
+///                 DockNode::tabs(vec!["Terminal".to_owned()]),
+///             ),
+///         ))
+///     },
+///     |pane| pane.into(),
+///     |ui, pane| {
+///         ui.label(format!("The contents of {pane}"));
+///     },
+/// );
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct DockArea<Pane> {
+    id: Id,
+    closable: bool,
+    viewport_ui: Option<ViewportPaneUi<Pane>>,
+}
+
+impl<Pane: Hash + PartialEq + SerializableAny> DockArea<Pane> {
+    /// The `id` must be unique, e.g. from [`Ui::make_persistent_id`].
+    /// The [`DockState`] is stored under it.
+    pub fn new(id: Id) -> Self {
+        Self {
+            id,
+            closable: false,
+            viewport_ui: None,
+        }
+    }
+
+    /// Can the user close panes, with the close button of floating windows,
+    /// the context menu of a tab, or by middle-clicking a tab?
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn closable(mut self, closable: bool) -> Self {
+        self.closable = closable;
+        self
+    }
+
+    /// Show undocked panes in their own native window, with [`Context::show_viewport_deferred`].
+    ///
+    /// Since those windows are painted separately, `pane_ui` can't borrow anything,
+    /// so it is given here instead of in [`Self::show`].
+    ///
+    /// If [`Context::embed_viewports`] is `true`, undocked panes are shown in a [`Window`] as usual.
+    #[inline]
+    pub fn tear_off(mut self, pane_ui: impl Fn(&mut Ui, &Pane) + Send + Sync + 'static) -> Self {
+        self.viewport_ui = Some(Arc::new(pane_ui));
+        self
+    }
+
+    /// Fill the available space of `ui` with the docked panes, and show the floating ones.
+    ///
+    /// `default_state` is used the first time, before there is a [`DockState`] in [`crate::Memory`].
+    pub fn show(
+        self,
+        ui: &mut Ui,
+        default_state: impl FnOnce() -> DockState<Pane>,
+        title: impl Fn(&Pane) -> WidgetText,
+        mut pane_ui: impl FnMut(&mut Ui, &Pane),
+    ) -> DockOutput<Pane> {
+        let ctx = ui.ctx().clone();
+        let mut state = DockState::load(&ctx, self.id).unwrap_or_else(default_state);
+
+        let rect = ui.available_rect_before_wrap();
+        let mut response = ui.allocate_rect(rect, Sense::hover());
+        let mut pass = DockPass {
+            dock_id: self.id,
+            closable: self.closable,
+            title: &title,
+            pane_ui: &mut pane_ui,
+            leaves: Vec::new(),
+            actions: Vec::new(),
+        };
+        if let Some(root) = &mut state.root {
+            pass.node(ui, root, rect, self.id.with("root"));
+        }
+        pass.floating(&ctx, &mut state.floating, self.viewport_ui.as_ref());
+        pass.drop_dragged_tab(ui, rect);
+
+        let mut closed = Vec::new();
+        if !pass.actions.is_empty() {
+            response.mark_changed();
+        }
+        for action in pass.actions {
+            match action {
+                DockAction::Close(pane) => {
+                    state.remove(&pane);
+                    closed.push(pane);
+                }
+                DockAction::Dock {
+                    pane,
+                    next_to,
+                    zone,
+                } => state.dock(pane, &next_to, zone),
+                DockAction::Float { pane, rect } => state.float(pane, rect),
+                DockAction::Redock(pane) => {
+                    state.remove(&pane);
+                    state.add(pane);
+                }
+            }
+        }
+
+        state.store(&ctx, self.id);
+        DockOutput { response, closed }
+    }
+}
+
+/// Something the user did, done once the whole dock area is shown.
+enum DockAction<Pane> {
+    Close(Pane),
+    Dock {
+        pane: Pane,
+        next_to: Pane,
+        zone: DockZone,
+    },
+    Float {
+        pane: Pane,
+        rect: Rect,
+    },
+
+    /// The native window of a torn-off pane was closed.
+    Redock(Pane),
+}
+
+/// The [`DragAndDrop`] payload while a tab is dragged.
+struct DraggedPane<Pane> {
+    dock_id: Id,
+    pane: Pane,
+}
+
+/// A tab group as it was shown this frame.
+struct Leaf<Pane> {
+    rect: Rect,
+    panes: Vec<Pane>,
+}
+
+/// Shows a [`DockState`], see [`DockArea::show`].
+struct DockPass<'a, Pane> {
+    dock_id: Id,
+    closable: bool,
+    title: &'a dyn Fn(&Pane) -> WidgetText,
+    pane_ui: &'a mut dyn FnMut(&mut Ui, &Pane),
+
+    /// The tab groups shown this frame, to drop panes onto.
+    leaves: Vec<Leaf<Pane>>,
+    actions: Vec<DockAction<Pane>>,
+}
+
+impl<Pane: Hash + PartialEq + SerializableAny> DockPass<'_, Pane> {
+    fn node(&mut self, ui: &mut Ui, node: &mut DockNode<Pane>, rect: Rect, id: Id) {
+        match node {
+            DockNode::Tabs { panes, active } => self.tabs(ui, panes, active, rect, id),
+            DockNode::Split {
+                direction,
+                fraction,
+                first,
+                second,
+            } => {
+                let gap = ui.spacing().item_spacing;
+                let (first_rect, second_rect, divider_pos, cross_range) = match direction {
+                    SplitDirection::Horizontal => {
+                        let x = rect.left() + *fraction * rect.width();
+                        (
+                            rect.with_max_x(x - 0.5 * gap.x),
+                            rect.with_min_x(x + 0.5 * gap.x),
+                            x,
+                            rect.y_range(),
+                        )
+                    }
+                    SplitDirection::Vertical => {
+                        let y = rect.top() + *fraction * rect.height();
+                        (
+                            rect.with_max_y(y - 0.5 * gap.y),
+                            rect.with_min_y(y + 0.5 * gap.y),
+                            y,
+                            rect.x_range(),
+                        )
+                    }
+                };
+                self.node(ui, first, first_rect, id.with(0));
+                self.node(ui, second, second_rect, id.with(1));
+
+                // The divider is on top of both sides, like the resize handle of a panel,
+                // so it gets the drags even if they contain a `ScrollArea`:
+                let response =
+                    divider(ui, id.with("divider"), *direction, divider_pos, cross_range);
+                if response.dragged()
+                    && let Some(pointer) = response.interact_pointer_pos()
+                {
+                    let t = (pointer - rect.min) / rect.size();
+                    let t = match direction {
+                        SplitDirection::Horizontal => t.x,
+                        SplitDirection::Vertical => t.y,
+                    };
+                    *fraction = t.clamp(0.1, 0.9);
+                }
+            }
+        }
+    }
+
+    /// A tab group: a row of draggable tabs above the active pane.
+    fn tabs(&mut self, ui: &mut Ui, panes: &[Pane], active: &mut usize, rect: Rect, id: Id) {
+        *active = (*active).min(panes.len().saturating_sub(1));
+        let mut leaf_ui = ui.new_child(UiBuilder::new().id_salt(id).max_rect(rect));
+        leaf_ui.set_clip_rect(rect.intersect(ui.clip_rect()));
+
+        leaf_ui.horizontal(|ui| {
+            #[cfg(feature = "accesskit")]
+            ui.ctx().accesskit_node_builder(ui.unique_id(), |node| {
+                node.set_role(accesskit::Role::TabList);
+            });
+
+            for (index, pane) in panes.iter().enumerate() {
+                let tab = ui
+                    .selectable_label(index == *active, (self.title)(pane))
+                    .interact(Sense::click_and_drag());
+                if tab.drag_started() {
+                    let payload = DraggedPane {
+                        dock_id: self.dock_id,
+                        pane: pane.clone(),
+                    };
+                    DragAndDrop::set_payload(ui.ctx(), payload);
+                }
+                if tab.dragged() {
+                    ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+                }
+                if tab.clicked() {
+                    *active = index;
+                }
+                if self.closable && tab.middle_clicked() {
+                    self.actions.push(DockAction::Close(pane.clone()));
+                }
+                tab.context_menu(|ui| {
+                    if ui.button("Undock").clicked() {
+                        self.actions.push(DockAction::Float {
+                            pane: pane.clone(),
+                            rect: rect.shrink(rect.width().min(rect.height()) * 0.1),
+                        });
+                        ui.close();
+                    }
+                    if self.closable && ui.button("Close").clicked() {
+                        self.actions.push(DockAction::Close(pane.clone()));
+                        ui.close();
+                    }
+                });
+
+                #[cfg(feature = "accesskit")]
+                ui.ctx().accesskit_node_builder(tab.id, |builder| {
+                    builder.set_role(accesskit::Role::Tab);
+                    builder.set_selected(index == *active);
+                });
+            }
+        });
+        leaf_ui.separator();
+
+        if let Some(pane) = panes.get(*active) {
+            ScrollArea::both()
+                .id_salt(("pane", pane))
+                .auto_shrink(false)
+                .show(&mut leaf_ui, |ui| {
+                    #[cfg(feature = "accesskit")]
+                    ui.ctx().accesskit_node_builder(ui.unique_id(), |node| {
+                        node.set_role(accesskit::Role::TabPanel);
+                    });
+                    (self.pane_ui)(ui, pane);
+                });
+        }
+
+        self.leaves.push(Leaf {
+            rect,
+            panes: panes.to_vec(),
+        });
+    }
+
+    /// Show the panes that are not docked, and dock them when they are dropped onto the compass.
+    fn floating(
+        &mut self,
+        ctx: &Context,
+        floating: &mut [FloatingPane<Pane>],
+        viewport_ui: Option<&ViewportPaneUi<Pane>>,
+    ) {
+        for floating in floating {
+            let window_id = self.dock_id.with(("floating", &floating.pane));
+            if let Some(viewport_ui) = viewport_ui
+                && !ctx.embed_viewports()
+            {
+                self.viewport(ctx, floating, window_id, viewport_ui);
+                continue;
+            }
+
+            let mut open = true;
+            let mut window = Window::new((self.title)(&floating.pane))
+                .id(window_id)
+                .default_pos(floating.rect.min)
+                .default_size(floating.rect.size());
+            if floating.reposition {
+                window = window.current_pos(floating.rect.min);
+                floating.reposition = false;
+            }
+            if self.closable {
+                window = window.open(&mut open);
+            }
+            let pane_ui = &mut self.pane_ui;
+            let Some(InnerResponse { response, .. }) =
+                window.show(ctx, |ui| pane_ui(ui, &floating.pane))
+            else {
+                continue;
+            };
+            floating.rect = response.rect;
+
+            // Preview the drop target while dragging, and dock when released:
+            if !open {
+                self.actions.push(DockAction::Close(floating.pane.clone()));
+            } else if (response.dragged() || response.drag_stopped())
+                && let Some(pointer) = ctx.pointer_interact_pos()
+                && let Some((next_to, zone)) = self.drop_target(ctx, pointer, &floating.pane, true)
+                && response.drag_stopped()
+            {
+                self.actions.push(DockAction::Dock {
+                    pane: floating.pane.clone(),
+                    next_to,
+                    zone,
+                });
+            }
+        }
+    }
+
+    /// Show a torn-off pane in its own native window.
+    fn viewport(
+        &mut self,
+        ctx: &Context,
+        floating: &FloatingPane<Pane>,
+        window_id: Id,
+        viewport_ui: &ViewportPaneUi<Pane>,
+    ) {
+        let redock_id = window_id.with("redock");
+        if ctx.data_mut(|d| d.remove_temp::<bool>(redock_id)).is_some() {
+            self.actions.push(DockAction::Redock(floating.pane.clone()));
+            return;
+        }
+
+        // The pane was floating inside this window, so put it at the same place on the screen:
+        let screen_offset = ctx
+            .input(|i| i.viewport().inner_rect)
+            .map_or(Vec2::ZERO, |rect| rect.min.to_vec2());
+        let builder = ViewportBuilder::default()
+            .with_title((self.title)(&floating.pane).text())
+            .with_position(floating.rect.min + screen_offset)
+            .with_inner_size(floating.rect.size());
+
+        let pane = floating.pane.clone();
+        let viewport_ui = Arc::clone(viewport_ui);
+        ctx.show_viewport_deferred(
+            ViewportId::from_hash_of(window_id),
+            builder,
+            move |ctx, _class| {
+                CentralPanel::default().show(ctx, |ui| viewport_ui(ui, &pane));
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    ctx.data_mut(|d| d.insert_temp(redock_id, true));
+                    ctx.request_repaint_of(ViewportId::ROOT);
+                }
+            },
+        );
+    }
+
+    /// Preview and do the drop of a dragged tab: onto another pane, or outside to undock it.
+    fn drop_dragged_tab(&mut self, ui: &Ui, dock_rect: Rect) {
+        let ctx = ui.ctx();
+        let Some(payload) = DragAndDrop::payload::<DraggedPane<Pane>>(ctx) else {
+            return;
+        };
+        if payload.dock_id != self.dock_id {
+            return;
+        }
+        let Some(pointer) = ctx.pointer_interact_pos() else {
+            return;
+        };
+
+        let target = self.drop_target(ctx, pointer, &payload.pane, false);
+        if !ui.input(|i| i.pointer.any_released()) {
+            return;
+        }
+        DragAndDrop::clear_payload(ctx);
+        let pane = payload.pane.clone();
+        if let Some((next_to, zone)) = target {
+            self.actions.push(DockAction::Dock {
+                pane,
+                next_to,
+                zone,
+            });
+        } else if !dock_rect.contains(pointer) {
+            let size = self
+                .leaves
+                .iter()
+                .find(|leaf| leaf.panes.contains(&pane))
+                .map_or(vec2(300.0, 200.0), |leaf| 0.8 * leaf.rect.size());
+            self.actions.push(DockAction::Float {
+                pane,
+                rect: Rect::from_min_size(pointer, size),
+            });
+        }
+    }
+
+    /// The pane to dock `dragged` next to, and where, with a preview of where it would go.
+    ///
+    /// With `compass`, the zones are small buttons in the middle of the pane under the pointer,
+    /// so that floating windows can be moved over the dock area without docking them.
+    fn drop_target(
+        &self,
+        ctx: &Context,
+        pointer: Pos2,
+        dragged: &Pane,
+        compass: bool,
+    ) -> Option<(Pane, DockZone)> {
+        let leaf = self
+            .leaves
+            .iter()
+            .find(|leaf| leaf.rect.contains(pointer))?;
+        let next_to = leaf.panes.iter().find(|pane| *pane != dragged)?;
+
+        let painter = ctx.layer_painter(LayerId::new(
+            Order::Foreground,
+            self.dock_id.with("drop_preview"),
+        ));
+        let visuals = ctx.style().visuals.clone();
+        let zone = if compass {
+            let size = 1.5 * ctx.style().spacing.interact_size.y;
+            let mut hovered_zone = None;
+            for zone in DockZone::ALL {
+                let rect = zone.compass_rect(leaf.rect.center(), size);
+                let widget_visuals = if rect.contains(pointer) {
+                    hovered_zone = Some(zone);
+                    &visuals.widgets.hovered
+                } else {
+                    &visuals.widgets.inactive
+                };
+                painter.rect(
+                    rect,
+                    widget_visuals.corner_radius,
+                    widget_visuals.bg_fill,
+                    widget_visuals.bg_stroke,
+                    crate::StrokeKind::Inside,
+                );
+                painter.rect_filled(
+                    zone.preview(rect.shrink(0.2 * size)),
+                    0.0,
+                    visuals.selection.bg_fill,
+                );
+            }
+            hovered_zone?
+        } else {
+            DockZone::at(leaf.rect, pointer)
+        };
+
+        painter.rect_filled(
+            zone.preview(leaf.rect),
+            visuals.window_corner_radius,
+            visuals.selection.bg_fill.gamma_multiply(0.4),
+        );
+        Some((next_to.clone(), zone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DockNode, DockState, DockZone};
+    use crate::Rect;
+
+    #[test]
+    fn test_dock_state() {
+        let mut state = DockState::new(DockNode::horizontal(
+            0.3,
+            DockNode::tabs(vec!["files"]),
+            DockNode::tabs(vec!["main.rs", "lib.rs"]),
+        ));
+
+        // Split a pane out of its tab group:
+        state.dock("lib.rs", &"main.rs", DockZone::Bottom);
+        assert_eq!(
+            state.root(),
+            Some(&DockNode::horizontal(
+                0.3,
+                DockNode::tabs(vec!["files"]),
+                DockNode::vertical(
+                    0.5,
+                    DockNode::tabs(vec!["main.rs"]),
+                    DockNode::tabs(vec!["lib.rs"]),
+                ),
+            ))
+        );
+
+        // Moving the only pane of a split removes the split:
+        state.dock("files", &"lib.rs", DockZone::Center);
+        assert_eq!(
+            state.root(),
+            Some(&DockNode::vertical(
+                0.5,
+                DockNode::tabs(vec!["main.rs"]),
+                DockNode::Tabs {
+                    panes: vec!["lib.rs", "files"],
+                    active: 1,
+                },
+            ))
+        );
+
+        state.float(
+            "main.rs",
+            Rect::from_min_size(Default::default(), [100.0, 100.0].into()),
+        );
+        assert_eq!(state.floating_panes().collect::<Vec<_>>(), [&"main.rs"]);
+        assert!(state.contains(&"main.rs"));
+        assert_eq!(
+            state.root(),
+            Some(&DockNode::Tabs {
+                panes: vec!["lib.rs", "files"],
+                active: 1,
+            })
+        );
+
+        state.dock("main.rs", &"files", DockZone::Left);
+        assert_eq!(state.floating_panes().count(), 0);
+        assert_eq!(
+            state.root(),
+            Some(&DockNode::horizontal(
+                0.5,
+                DockNode::tabs(vec!["main.rs"]),
+                DockNode::Tabs {
+                    panes: vec!["lib.rs", "files"],
+                    active: 1,
+                },
+            ))
+        );
+
+        assert!(state.remove(&"main.rs"));
+        assert!(state.remove(&"lib.rs"));
+        assert!(state.remove(&"files"));
+        assert!(!state.remove(&"files"));
+        assert_eq!(state.root(), None);
+
+        state.add("files");
+        assert_eq!(state.root(), Some(&DockNode::tabs(vec!["files"])));
+    }
+
+    #[test]
+    fn test_dock_zone() {
+        let rect = Rect::from_min_size(Default::default(), [100.0, 100.0].into());
+        assert_eq!(DockZone::at(rect, [50.0, 50.0].into()), DockZone::Center);
+        assert_eq!(DockZone::at(rect, [10.0, 50.0].into()), DockZone::Left);
+        assert_eq!(DockZone::at(rect, [90.0, 20.0].into()), DockZone::Right);
+        assert_eq!(DockZone::at(rect, [50.0, 95.0].into()), DockZone::Bottom);
+    }
+}
@@ -0,0 +1,660 @@
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::{
+    Context, Frame, Id, InnerResponse, NumExt as _, Rangef, Rect, Response, Ui, UiBuilder, Vec2,
+    Widget,
+};
+
+/// How the space left over in a line of a [`Flex`] is distributed, when no item wants to grow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlexJustify {
+    /// Pack the items at the start of the line.
+    #[default]
+    Start,
+
+    /// Pack the items at the end of the line.
+    End,
+
+    /// Pack the items in the middle of the line.
+    Center,
+
+    /// The first item at the start, the last at the end, and the same space between all items.
+    SpaceBetween,
+
+    /// The same space on both sides of each item.
+    SpaceAround,
+
+    /// The same space between all items, and before the first and after the last.
+    SpaceEvenly,
+}
+
+/// How an item of a [`Flex`] is placed across its line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlexAlign {
+    /// At the top (or the left, in a [`Flex::vertical`]) of the line.
+    Start,
+
+    /// At the bottom (or the right, in a [`Flex::vertical`]) of the line.
+    End,
+
+    /// In the middle of the line.
+    #[default]
+    Center,
+
+    /// As large as the line, i.e. as large as the largest item of the line.
+    ///
+    /// The contents keep their size, but the [`FlexItem::frame`] fills the whole line.
+    Stretch,
+}
+
+/// How a single item of a [`Flex`] is sized.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::{Flex, FlexItem};
+///
+/// Flex::horizontal().show(ui, |flex| {
+///     flex.add(FlexItem::new(), egui::Button::new("Fixed"));
+///     flex.add(FlexItem::new().grow(1.0), egui::Button::new("Takes the rest"));
+/// });
+/// # });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlexItem {
+    grow: f32,
+    shrink: f32,
+    basis: Option<f32>,
+    align_self: Option<FlexAlign>,
+    frame: Frame,
+}
+
+impl Default for FlexItem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlexItem {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            grow: 0.0,
+            shrink: 1.0,
+            basis: None,
+            align_self: None,
+            frame: Frame::NONE,
+        }
+    }
+
+    /// How much of the free space in the line this item gets, relative to the other items.
+    ///
+    /// Default: `0.0`.
+    #[inline]
+    pub fn grow(mut self, grow: f32) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    /// How much this item gives up when the line doesn't fit, relative to the other items.
+    ///
+    /// The items shrink in proportion to their size times this factor.
+    /// A line only shrinks if it can't wrap, see [`Flex::wrap`].
+    ///
+    /// Default: `1.0`.
+    #[inline]
+    pub fn shrink(mut self, shrink: f32) -> Self {
+        self.shrink = shrink;
+        self
+    }
+
+    /// The size along the main axis before growing or shrinking.
+    ///
+    /// Default: the size of the contents.
+    /// Set this for contents that fill the available space, like a [`crate::TextEdit`] or a [`crate::Separator`].
+    #[inline]
+    pub fn basis(mut self, basis: f32) -> Self {
+        self.basis = Some(basis);
+        self
+    }
+
+    /// Place this item differently across the line than [`Flex::align_items`].
+    #[inline]
+    pub fn align_self(mut self, align: FlexAlign) -> Self {
+        self.align_self = Some(align);
+        self
+    }
+
+    /// A [`Frame`] around the contents, that fills all the space given to the item.
+    ///
+    /// Default: [`Frame::NONE`].
+    #[inline]
+    pub fn frame(mut self, frame: Frame) -> Self {
+        self.frame = frame;
+        self
+    }
+}
+
+/// The items of a [`Flex`] and their measured sizes, from the previous pass.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct State {
+    items: Vec<(FlexItem, Vec2)>,
+}
+
+impl State {
+    /// Sizes that differ less than this are considered the same,
+    /// so that sub-pixel jitter in the measured sizes doesn't cause a new layout pass every frame.
+    const SIZE_TOLERANCE: f32 = 0.5;
+
+    pub fn load(ctx: &Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_temp(id))
+    }
+
+    pub fn store(self, ctx: &Context, id: Id) {
+        // Like `Grid`, we don't persist this: the sizes come from the code.
+        ctx.data_mut(|d| d.insert_temp(id, self));
+    }
+
+    /// Did any item change, or change its size by more than [`Self::SIZE_TOLERANCE`]?
+    fn differs_from(&self, other: &Self) -> bool {
+        self.items.len() != other.items.len()
+            || self
+                .items
+                .iter()
+                .zip(&other.items)
+                .any(|((a, a_size), (b, b_size))| {
+                    a != b || (*a_size - *b_size).abs().max_elem() > Self::SIZE_TOLERANCE
+                })
+    }
+}
+
+/// A flexbox-style layout, where items grow, shrink and wrap to fit the available space.
+///
+/// The items are laid out along the main axis (left to right for [`Flex::horizontal`]).
+/// If [`Flex::wrap`] is enabled, items that don't fit go on a new line.
+/// In each line, the free space goes to the items that [`FlexItem::grow`], or is distributed
+/// according to [`Flex::justify`].
+/// If a line can't wrap and doesn't fit, the items [`FlexItem::shrink`].
+///
+/// The sizes of the items are measured and remembered in [`crate::Memory`], so the first frame
+/// (or any frame where an item changes size) is laid out again before it is shown.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::{Flex, FlexAlign, FlexItem, FlexJustify};
+///
+/// Flex::horizontal()
+///     .wrap(true)
+///     .justify(FlexJustify::SpaceBetween)
+///     .align_items(FlexAlign::Stretch)
+///     .show(ui, |flex| {
+///         for card in ["Short", "A somewhat longer card", "Card\nwith two lines"] {
+///             flex.add_ui(FlexItem::new().frame(egui::Frame::group(flex.ui().style())), |ui| {
+///                 ui.label(card);
+///             });
+///         }
+///     });
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct Flex {
+    id_salt: Option<Id>,
+    horizontal: bool,
+    wrap: bool,
+    justify: FlexJustify,
+    align_items: FlexAlign,
+    gap: Option<Vec2>,
+}
+
+impl Flex {
+    /// Lay out the items left to right.
+    ///
+    /// With [`Self::wrap`], the items that don't fit go on new rows.
+    pub fn horizontal() -> Self {
+        Self {
+            id_salt: None,
+            horizontal: true,
+            wrap: false,
+            justify: FlexJustify::default(),
+            align_items: FlexAlign::default(),
+            gap: None,
+        }
+    }
+
+    /// Lay out the items top down.
+    ///
+    /// With [`Self::wrap`], the items that don't fit go on new columns.
+    /// Wrapping and growing need a limited height, e.g. from [`Ui::set_max_height`].
+    pub fn vertical() -> Self {
+        Self {
+            horizontal: false,
+            ..Self::horizontal()
+        }
+    }
+
+    /// A source for the unique [`Id`], e.g. `.id_salt("toolbar")` or `.id_salt(loop_index)`.
+    ///
+    /// You need this when there are several [`Flex`]es in the same [`Ui`].
+    #[inline]
+    pub fn id_salt(mut self, id_salt: impl Hash) -> Self {
+        self.id_salt = Some(Id::new(id_salt));
+        self
+    }
+
+    /// Put items that don't fit on a new line, instead of shrinking them.
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// How to distribute the free space of a line, when no item in it wants to grow.
+    ///
+    /// Default: [`FlexJustify::Start`].
+    #[inline]
+    pub fn justify(mut self, justify: FlexJustify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    /// How to place the items across their line.
+    ///
+    /// Default: [`FlexAlign::Center`].
+    #[inline]
+    pub fn align_items(mut self, align: FlexAlign) -> Self {
+        self.align_items = align;
+        self
+    }
+
+    /// The space between items, and between lines.
+    ///
+    /// Default: [`crate::Spacing::item_spacing`].
+    #[inline]
+    pub fn gap(mut self, gap: Vec2) -> Self {
+        self.gap = Some(gap);
+        self
+    }
+
+    pub fn show<R>(
+        self,
+        ui: &mut Ui,
+        add_items: impl FnOnce(&mut FlexUi<'_>) -> R,
+    ) -> InnerResponse<R> {
+        let id_salt = self.id_salt.unwrap_or_else(|| Id::new("flex"));
+        let id = ui.make_persistent_id(id_salt);
+        ui.ctx().check_for_id_clash(
+            id,
+            Rect::from_min_size(ui.available_rect_before_wrap().min, Vec2::ZERO),
+            "Flex",
+        );
+        let prev_state = State::load(ui.ctx(), id);
+
+        let mut ui_builder = UiBuilder::new();
+        if prev_state.is_none() {
+            if ui.is_visible() {
+                // Cover up the first frame, where we don't know the sizes of the items yet:
+                ui.ctx().request_discard("new Flex");
+            }
+            ui_builder = ui_builder.sizing_pass().invisible();
+        }
+
+        ui.scope_builder(ui_builder, |ui| {
+            let is_first_frame = prev_state.is_none();
+            let prev_state = prev_state.unwrap_or_default();
+            let gap = self.gap.unwrap_or_else(|| ui.spacing().item_spacing);
+            let max_rect = ui.available_rect_before_wrap();
+            let placement = self.place(ui, &prev_state, max_rect, gap);
+
+            let mut flex_ui = FlexUi {
+                ui,
+                placement,
+                next_pos: max_rect.min,
+                gap,
+                horizontal: self.horizontal,
+                prev_items: prev_state.items.clone(),
+                state: State::default(),
+                bounds: Rect::NOTHING,
+            };
+            let inner = add_items(&mut flex_ui);
+            let FlexUi {
+                ui, state, bounds, ..
+            } = flex_ui;
+            if !state.items.is_empty() {
+                ui.advance_cursor_after_rect(bounds);
+            }
+
+            if state.differs_from(&prev_state) || is_first_frame {
+                if !is_first_frame {
+                    ui.ctx().request_discard("Flex items changed size");
+                }
+                state.store(ui.ctx(), id);
+            }
+            inner
+        })
+    }
+
+    /// Where each item goes, based on the sizes measured in the previous pass.
+    fn place(&self, ui: &Ui, state: &State, max_rect: Rect, gap: Vec2) -> Vec<Rect> {
+        let main = |v: Vec2| if self.horizontal { v.x } else { v.y };
+        let cross = |v: Vec2| if self.horizontal { v.y } else { v.x };
+
+        let available = if ui.is_sizing_pass() {
+            // As small as possible:
+            f32::INFINITY
+        } else {
+            main(max_rect.size())
+        };
+        let bases: Vec<f32> = state
+            .items
+            .iter()
+            .map(|(item, size)| item.basis.unwrap_or(main(*size)))
+            .collect();
+
+        let mut rects = vec![Rect::NOTHING; state.items.len()];
+        let mut line_start = 0.0;
+        for line in wrap_lines(&bases, main(gap), available, self.wrap) {
+            let items = &state.items[line.clone()];
+            let line_size = items
+                .iter()
+                .map(|(_, size)| cross(*size))
+                .fold(0.0, f32::max);
+            let main_ranges = distribute(
+                &items.iter().map(|(item, _)| *item).collect::<Vec<_>>(),
+                &bases[line.clone()],
+                main(gap),
+                available,
+                self.justify,
+            );
+
+            for ((index, (item, size)), main_range) in line.zip(items).zip(main_ranges) {
+                let cross_size = cross(*size);
+                let (cross_min, cross_size) = match item.align_self.unwrap_or(self.align_items) {
+                    FlexAlign::Start => (0.0, cross_size),
+                    FlexAlign::End => (line_size - cross_size, cross_size),
+                    FlexAlign::Center => (0.5 * (line_size - cross_size), cross_size),
+                    FlexAlign::Stretch => (0.0, line_size),
+                };
+                let cross_min = cross(max_rect.min.to_vec2()) + line_start + cross_min;
+                let main_min = main(max_rect.min.to_vec2()) + main_range.min;
+                rects[index] = if self.horizontal {
+                    Rect::from_min_size(
+                        crate::pos2(main_min, cross_min),
+                        Vec2::new(main_range.span(), cross_size),
+                    )
+                } else {
+                    Rect::from_min_size(
+                        crate::pos2(cross_min, main_min),
+                        Vec2::new(cross_size, main_range.span()),
+                    )
+                };
+            }
+            line_start += line_size + cross(gap);
+        }
+        rects
+    }
+}
+
+/// Add items to a [`Flex`] with this.
+pub struct FlexUi<'a> {
+    ui: &'a mut Ui,
+
+    /// Where the items go, from the sizes of the previous pass.
+    placement: Vec<Rect>,
+
+    /// Where to put items that weren't there in the previous pass.
+    next_pos: crate::Pos2,
+
+    gap: Vec2,
+    horizontal: bool,
+
+    /// The items of the previous pass.
+    prev_items: Vec<(FlexItem, Vec2)>,
+
+    /// The items of this pass.
+    state: State,
+
+    /// The space used by all items so far.
+    bounds: Rect,
+}
+
+impl FlexUi<'_> {
+    /// The [`Ui`] containing the [`Flex`], e.g. for reading the style.
+    pub fn ui(&self) -> &Ui {
+        self.ui
+    }
+
+    /// Add a widget as an item.
+    pub fn add(&mut self, item: FlexItem, widget: impl Widget) -> Response {
+        self.add_ui(item, |ui| ui.add(widget)).inner
+    }
+
+    /// Add the contents of an item.
+    ///
+    /// The response covers all of the space given to the item.
+    pub fn add_ui<R>(
+        &mut self,
+        item: FlexItem,
+        add_contents: impl FnOnce(&mut Ui) -> R,
+    ) -> InnerResponse<R> {
+        let index = self.state.items.len();
+        let rect = self.placement.get(index).copied().unwrap_or_else(|| {
+            // A new item: show it where it doesn't overlap the others, until we know its size.
+            Rect::from_min_size(self.next_pos, self.ui.available_rect_before_wrap().size())
+        });
+
+        let mut item_ui = self.ui.new_child(UiBuilder::new().max_rect(rect));
+        let mut prepared = item.frame.begin(&mut item_ui);
+        let inner = add_contents(&mut prepared.content_ui);
+        let margin = item.frame.total_margin().sum();
+        let measured = prepared.content_ui.min_rect().size() + margin;
+        prepared
+            .content_ui
+            .expand_to_include_rect(rect - item.frame.total_margin());
+        let response = prepared.end(&mut item_ui);
+        self.bounds |= response.rect;
+
+        // A shrunk item keeps its size from before it was shrunk, so it can grow back:
+        let main = |v: Vec2| if self.horizontal { v.x } else { v.y };
+        let mut size = measured;
+        if let Some((_, prev_size)) = self.prev_items.get(index)
+            && main(rect.size()) + 0.5 < main(*prev_size)
+        {
+            if self.horizontal {
+                size.x = size.x.max(prev_size.x);
+            } else {
+                size.y = size.y.max(prev_size.y);
+            }
+        }
+        self.state.items.push((item, size));
+
+        if self.horizontal {
+            self.next_pos.x = self.next_pos.x.max(response.rect.max.x + self.gap.x);
+        } else {
+            self.next_pos.y = self.next_pos.y.max(response.rect.max.y + self.gap.y);
+        }
+
+        InnerResponse::new(inner, response)
+    }
+}
+
+/// Split the items into lines, each as long as fits in `available`.
+fn wrap_lines(bases: &[f32], gap: f32, available: f32, wrap: bool) -> Vec<Range<usize>> {
+    if !wrap {
+        return std::iter::once(0..bases.len()).collect();
+    }
+
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut length = 0.0;
+    for (index, basis) in bases.iter().enumerate() {
+        if start < index && available < length + gap + basis {
+            lines.push(start..index);
+            start = index;
+            length = 0.0;
+        }
+        length += if start < index { gap + basis } else { *basis };
+    }
+    if start < bases.len() {
+        lines.push(start..bases.len());
+    }
+    lines
+}
+
+/// The position and size of each item of a line, along the main axis.
+fn distribute(
+    items: &[FlexItem],
+    bases: &[f32],
+    gap: f32,
+    available: f32,
+    justify: FlexJustify,
+) -> Vec<Rangef> {
+    let num_gaps = items.len().saturating_sub(1) as f32;
+    let length = bases.iter().sum::<f32>() + gap * num_gaps;
+    let free = if available.is_finite() {
+        available - length
+    } else {
+        0.0
+    };
+
+    let total_grow: f32 = items.iter().map(|item| item.grow).sum();
+    let total_shrink: f32 = items
+        .iter()
+        .zip(bases)
+        .map(|(item, basis)| item.shrink * basis)
+        .sum();
+    let sizes: Vec<f32> = if 0.0 < free && 0.0 < total_grow {
+        items
+            .iter()
+            .zip(bases)
+            .map(|(item, basis)| basis + free * item.grow / total_grow)
+            .collect()
+    } else if free < 0.0 && 0.0 < total_shrink {
+        items
+            .iter()
+            .zip(bases)
+            .map(|(item, basis)| (basis + free * item.shrink * basis / total_shrink).at_least(0.0))
+            .collect()
+    } else {
+        bases.to_vec()
+    };
+
+    let free = free.at_least(0.0);
+    let n = items.len() as f32;
+    let (start, extra_gap) = if 0.0 < total_grow {
+        (0.0, 0.0)
+    } else {
+        match justify {
+            FlexJustify::Start => (0.0, 0.0),
+            FlexJustify::End => (free, 0.0),
+            FlexJustify::Center => (0.5 * free, 0.0),
+            FlexJustify::SpaceBetween => (0.0, free / (n - 1.0).at_least(1.0)),
+            FlexJustify::SpaceAround => (0.5 * free / n, free / n),
+            FlexJustify::SpaceEvenly => (free / (n + 1.0), free / (n + 1.0)),
+        }
+    };
+
+    let mut pos = start;
+    sizes
+        .into_iter()
+        .map(|size| {
+            let range = Rangef::new(pos, pos + size);
+            pos += size + gap + extra_gap;
+            range
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlexItem, FlexJustify, State, distribute, wrap_lines};
+    use crate::vec2;
+
+    #[test]
+    fn test_wrap_lines() {
+        let bases = [30.0, 30.0, 30.0, 80.0];
+        assert_eq!(wrap_lines(&bases, 10.0, 100.0, false), vec![0..4]);
+        assert_eq!(
+            wrap_lines(&bases, 10.0, 100.0, true),
+            vec![0..2, 2..3, 3..4]
+        );
+        assert_eq!(wrap_lines(&bases, 10.0, 110.0, true), vec![0..3, 3..4]);
+        assert_eq!(
+            wrap_lines(&bases, 10.0, 50.0, true),
+            vec![0..1, 1..2, 2..3, 3..4],
+            "An item that is too large gets a line of its own"
+        );
+        assert_eq!(wrap_lines(&bases, 10.0, f32::INFINITY, true), vec![0..4]);
+    }
+
+    #[test]
+    fn test_distribute() {
+        let spans = |items: &[FlexItem], available: f32, justify: FlexJustify| {
+            distribute(items, &[20.0, 40.0], 10.0, available, justify)
+                .into_iter()
+                .map(|range| (range.min, range.max))
+                .collect::<Vec<_>>()
+        };
+        let items = [FlexItem::new(), FlexItem::new()];
+
+        assert_eq!(
+            spans(&items, 100.0, FlexJustify::Start),
+            [(0.0, 20.0), (30.0, 70.0)]
+        );
+        assert_eq!(
+            spans(&items, 100.0, FlexJustify::End),
+            [(30.0, 50.0), (60.0, 100.0)]
+        );
+        assert_eq!(
+            spans(&items, 100.0, FlexJustify::SpaceBetween),
+            [(0.0, 20.0), (60.0, 100.0)]
+        );
+        assert_eq!(
+            spans(&items, 100.0, FlexJustify::SpaceEvenly),
+            [(10.0, 30.0), (50.0, 90.0)]
+        );
+        assert_eq!(
+            spans(
+                &[FlexItem::new().grow(1.0), FlexItem::new().grow(3.0)],
+                100.0,
+                FlexJustify::End
+            ),
+            [(0.0, 27.5), (37.5, 100.0)],
+            "Growing items take the free space, so justify does nothing"
+        );
+        assert_eq!(
+            spans(&items, 40.0, FlexJustify::Start),
+            [(0.0, 10.0), (20.0, 40.0)],
+            "Items shrink in proportion to their size"
+        );
+        assert_eq!(
+            spans(
+                &[FlexItem::new().shrink(0.0), FlexItem::new()],
+                40.0,
+                FlexJustify::Start
+            ),
+            [(0.0, 20.0), (30.0, 40.0)]
+        );
+    }
+
+    #[test]
+    fn test_state_differs_from() {
+        let state = |size| State {
+            items: vec![(FlexItem::new(), size)],
+        };
+        let prev = state(vec2(100.0, 20.0));
+        assert!(
+            !state(vec2(100.2, 19.9)).differs_from(&prev),
+            "Sub-pixel jitter is not a change"
+        );
+        assert!(state(vec2(101.0, 20.0)).differs_from(&prev));
+        assert!(
+            State {
+                items: vec![(FlexItem::new().grow(1.0), vec2(100.0, 20.0))],
+            }
+            .differs_from(&prev)
+        );
+        assert!(State::default().differs_from(&prev));
+    }
+}
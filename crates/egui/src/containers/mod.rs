@@ -6,6 +6,8 @@ pub(crate) mod area;
 mod close_tag;
 pub mod collapsing_header;
 mod combo_box;
+mod dock;
+mod flex;
 pub mod frame;
 pub mod menu;
 pub mod modal;
@@ -16,7 +18,10 @@ pub(crate) mod resize;
 mod scene;
 pub mod scroll_area;
 mod sides;
+mod splitter;
+mod tabs;
 mod tooltip;
+mod tree_view;
 pub(crate) mod window;
 
 pub use {
@@ -24,6 +29,8 @@ pub use {
     close_tag::ClosableTag,
     collapsing_header::{CollapsingHeader, CollapsingResponse},
     combo_box::*,
+    dock::{DockArea, DockNode, DockOutput, DockState, DockZone},
+    flex::{Flex, FlexAlign, FlexItem, FlexJustify, FlexUi},
     frame::Frame,
     modal::{Modal, ModalResponse},
     old_popup::*,
@@ -33,6 +40,11 @@ pub use {
     scene::{DragPanButtons, Scene},
     scroll_area::ScrollArea,
     sides::Sides,
+    splitter::{SplitDirection, Splitter, SplitterState},
+    tabs::{Tabs, TabsOutput, TabsState},
     tooltip::*,
+    tree_view::{
+        DropPosition, TreeView, TreeViewAction, TreeViewBuilder, TreeViewOutput, TreeViewState,
+    },
     window::Window,
 };
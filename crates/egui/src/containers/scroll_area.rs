@@ -80,6 +80,104 @@ impl State {
     }
 }
 
+/// The measured row heights of [`ScrollArea::show_virtual_rows`], and where that puts the rows.
+///
+/// The rows are only laid out again when they or their heights change,
+/// so that long lists don't cost anything per row each frame.
+#[derive(Clone, Default)]
+struct VirtualRows {
+    heights: crate::IdMap<f32>,
+
+    /// The sum of [`Self::heights`], for the average height of the rows that were not measured yet.
+    height_sum: f32,
+
+    /// The id of each row.
+    ids: Vec<Id>,
+
+    /// The top of each row, followed by the bottom of the last row plus the spacing.
+    tops: Vec<f32>,
+
+    /// The spacing and default row height that [`Self::tops`] was computed with,
+    /// or `None` if it needs to be computed again.
+    layout: Option<(f32, f32)>,
+
+    /// The index and id of the row at the top of the viewport, and how far into that row the viewport starts.
+    anchor: Option<(usize, Id, f32)>,
+}
+
+impl VirtualRows {
+    /// Update [`Self::ids`] and [`Self::tops`], if the rows or their heights changed.
+    ///
+    /// Rows that have not been measured get the average height, or `default_height` if none have been.
+    fn update(
+        &mut self,
+        num_rows: usize,
+        row_id: impl Fn(usize) -> Id,
+        spacing: f32,
+        default_height: f32,
+    ) {
+        // Asking for the ids at both ends and at the anchor catches rows that were added or removed
+        // there, e.g. in a log that drops its oldest lines, without asking for every id each frame:
+        let ids_changed = self.ids.len() != num_rows
+            || [
+                (0 < num_rows).then_some(0),
+                num_rows.checked_sub(1),
+                self.anchor.map(|(index, ..)| index),
+            ]
+            .into_iter()
+            .flatten()
+            .filter(|index| *index < num_rows)
+            .any(|index| self.ids.get(index) != Some(&row_id(index)));
+        if ids_changed {
+            self.ids = (0..num_rows).map(row_id).collect();
+            self.anchor = self.anchor.and_then(|(_, anchor, offset_in_row)| {
+                let index = self.ids.iter().position(|id| *id == anchor)?;
+                Some((index, anchor, offset_in_row))
+            });
+            self.layout = None;
+        }
+
+        if self.layout != Some((spacing, default_height)) {
+            let estimated_height = if self.heights.is_empty() {
+                default_height
+            } else {
+                self.height_sum / self.heights.len() as f32
+            };
+
+            self.tops.clear();
+            self.tops.reserve(num_rows + 1);
+            let mut top = 0.0;
+            self.tops.push(top);
+            for id in &self.ids {
+                top += self.heights.get(id).copied().unwrap_or(estimated_height) + spacing;
+                self.tops.push(top);
+            }
+            self.layout = Some((spacing, default_height));
+        }
+    }
+
+    /// Remember the height of the row at `index`.
+    ///
+    /// Returns how much higher it is than in [`Self::tops`], which may be an estimate.
+    fn measure(&mut self, index: usize, id: Id, height: f32, spacing: f32) -> f32 {
+        let old_height = self.heights.insert(id, height);
+        self.height_sum += height - old_height.unwrap_or(0.0);
+        if old_height.is_none_or(|old| (old - height).abs() > 0.5) {
+            self.layout = None;
+        }
+        height - (self.tops[index + 1] - self.tops[index] - spacing)
+    }
+
+    /// Forget the heights of rows that are gone, e.g. from the start of a long event log.
+    fn forget_removed_rows(&mut self) {
+        if 2 * self.ids.len() < self.heights.len() {
+            let ids: crate::id::IdSet = self.ids.iter().copied().collect();
+            self.heights.retain(|id, _| ids.contains(id));
+            self.height_sum = self.heights.values().sum();
+        }
+    }
+}
+
 pub struct ScrollAreaOutput<R> {
     /// What the user closure returned.
     pub inner: R,
@@ -952,6 +1050,122 @@ impl ScrollArea {
         })
     }
 
+    /// Efficiently show only the visible part of a large number of rows of different heights.
+    ///
+    /// Each row is identified by `row_id(index)`, and its height is measured the first time it is shown,
+    /// and then remembered in [`crate::Memory`].
+    /// The rows that have never been shown are assumed to be as high as the average measured row.
+    ///
+    /// To not cost anything per row each frame, `row_id` is only called for all rows when `num_rows`
+    /// or the id of the first, last or top visible row changes.
+    /// Other rows that are replaced are noticed when they are shown.
+    ///
+    /// When rows above the viewport are inserted or change height, the viewport stays at the same row,
+    /// unless the scroll area is stuck to the bottom (see [`Self::stick_to_bottom`]).
+    ///
+    /// Returns the range of rows that were shown.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let messages = vec![(1, "Hello".to_owned()), (2, "Hi!\nHow are you?".to_owned())];
+    /// egui::ScrollArea::vertical().stick_to_bottom(true).show_virtual_rows(
+    ///     ui,
+    ///     messages.len(),
+    ///     |row| egui::Id::new(messages[row].0),
+    ///     |ui, row| {
+    ///         ui.label(&messages[row].1);
+    ///     },
+    /// );
+    /// # });
+    /// ```
+    pub fn show_virtual_rows(
+        mut self,
+        ui: &mut Ui,
+        num_rows: usize,
+        row_id: impl Fn(usize) -> Id,
+        mut add_row: impl FnMut(&mut Ui, usize),
+    ) -> ScrollAreaOutput<std::ops::Range<usize>> {
+        let ctx = ui.ctx().clone();
+        let id_salt = self.id_salt.unwrap_or_else(|| Id::new("scroll_area"));
+        let id = ui.make_persistent_id(id_salt);
+        let rows_id = id.with("virtual_rows");
+        // Taken out instead of cloned, since it has an entry per row:
+        let mut rows =
+            ctx.data_mut(|d| std::mem::take(d.get_temp_mut_or_default::<VirtualRows>(rows_id)));
+
+        let spacing = ui.spacing().item_spacing.y;
+        rows.update(num_rows, &row_id, spacing, ui.spacing().interact_size.y);
+        let total_height = (rows.tops[num_rows] - spacing).at_least(0.0);
+
+        // Keep the viewport at the same row, in case rows above it were inserted or changed height:
+        if self.offset_y.is_none()
+            && let Some(state) = State::load(&ctx, id)
+            && !(self.stick_to_end[1] && state.scroll_stuck_to_end.y)
+            && let Some((index, _, offset_in_row)) = rows.anchor
+        {
+            let offset = rows.tops[index] + offset_in_row;
+            if 0.5 < (offset - state.offset.y).abs() {
+                self.offset_y = Some(offset);
+            }
+        }
+
+        let mut rows_moved = false;
+        let mut ids_changed = false;
+        let output = self.show_viewport(ui, |ui, viewport| {
+            ui.set_height(total_height);
+
+            let tops = &rows.tops[..num_rows];
+            let first = tops
+                .partition_point(|&top| top <= viewport.min.y)
+                .saturating_sub(1);
+            let end = tops.partition_point(|&top| top < viewport.max.y);
+            let left_top = ui.max_rect().left_top();
+
+            // How much lower the next row belongs than where it is shown:
+            let mut shift: f32 = 0.0;
+            for index in first..end {
+                rows_moved |= 0.5 < shift.abs();
+
+                let row_id = row_id(index);
+                ids_changed |= row_id != rows.ids[index];
+
+                let rect = Rect::from_min_size(
+                    left_top + Vec2::new(0.0, rows.tops[index]),
+                    Vec2::new(ui.available_width(), f32::INFINITY),
+                );
+                let mut row_ui = ui.new_child(UiBuilder::new().id_salt(row_id).max_rect(rect));
+                add_row(&mut row_ui, index);
+                ui.expand_to_include_rect(row_ui.min_rect());
+
+                shift += rows.measure(index, row_id, row_ui.min_rect().height(), spacing);
+            }
+            // Rows that were shorter than estimated can leave room for more rows:
+            rows_moved |= end < num_rows && rows.tops[end] + shift < viewport.max.y;
+
+            first.min(end)..end
+        });
+
+        let offset = output.state.offset.y;
+        let index = rows.tops[..num_rows]
+            .partition_point(|&top| top <= offset)
+            .saturating_sub(1);
+        rows.anchor = (rows.ids.get(index)).map(|&id| (index, id, offset - rows.tops[index]));
+
+        rows.forget_removed_rows();
+        if ids_changed {
+            // Get all the ids again next pass:
+            rows.ids.clear();
+        }
+        if rows_moved || ids_changed {
+            // Lay out again, with the rows where they belong.
+            // Measurements that match the estimate don't move any row, so they need no discard.
+            ctx.request_discard("ScrollArea::show_virtual_rows measured rows of unexpected height");
+        }
+        ctx.data_mut(|d| d.insert_temp(rows_id, rows));
+
+        output
+    }
+
     /// This can be used to only paint the visible part of the contents.
     ///
     /// `add_contents` is given the viewport rectangle, which is the relative view of the content.
@@ -0,0 +1,334 @@
+use std::hash::Hash;
+
+use crate::{
+    Context, CursorIcon, Id, InnerResponse, Rangef, Rect, Response, Sense, Ui, UiBuilder,
+    emath::NumExt as _, vec2,
+};
+
+/// How the two panes of a [`Splitter`] or a [`crate::DockNode::Split`] are placed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SplitDirection {
+    /// Side by side, with a vertical divider between them.
+    Horizontal,
+
+    /// Above each other, with a horizontal divider between them.
+    Vertical,
+}
+
+/// Which pane of a [`Splitter`] is collapsed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+enum Collapsed {
+    First,
+    Second,
+}
+
+/// The divider position of a [`Splitter`], stored between frames.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct SplitterState {
+    /// How much of the space goes to the first pane, in `0..=1`.
+    fraction: f32,
+
+    collapsed: Option<Collapsed>,
+}
+
+impl SplitterState {
+    pub fn load(ctx: &Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    pub fn store(self, ctx: &Context, id: Id) {
+        ctx.data_mut(|d| d.insert_persisted(id, self));
+    }
+
+    /// How much of the space goes to the first pane, in `0..=1`, when nothing is collapsed.
+    pub fn fraction(&self) -> f32 {
+        self.fraction
+    }
+
+    pub fn set_fraction(&mut self, fraction: f32) {
+        self.fraction = fraction;
+        self.collapsed = None;
+    }
+
+    /// Is one of the panes collapsed?
+    pub fn is_collapsed(&self) -> bool {
+        self.collapsed.is_some()
+    }
+}
+
+/// Two panes, side by side or above each other, with a draggable divider between them.
+///
+/// The splitter fills the available space of the [`Ui`].
+/// Splitters can be nested, by adding another [`Splitter`] to a pane.
+///
+/// Double-clicking the divider collapses the smaller pane, and double-clicking again restores it.
+/// The divider position is stored in [`crate::Memory`], and persisted with the `persistence` feature.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::{SplitDirection, Splitter};
+///
+/// Splitter::new("sidebar", SplitDirection::Horizontal)
+///     .default_fraction(0.25)
+///     .first_size_range(100.0..=400.0)
+///     .show(
+///         ui,
+///         |ui| ui.label("Sidebar"),
+///         |ui| {
+///             Splitter::new("editor", SplitDirection::Vertical).show(
+///                 ui,
+///                 |ui| ui.label("Editor"),
+///                 |ui| ui.label("Terminal"),
+///             );
+///         },
+///     );
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct Splitter {
+    id_salt: Id,
+    direction: SplitDirection,
+    default_fraction: f32,
+    first_size_range: Rangef,
+    second_size_range: Rangef,
+    collapsible: bool,
+}
+
+impl Splitter {
+    pub fn new(id_salt: impl Hash, direction: SplitDirection) -> Self {
+        Self {
+            id_salt: Id::new(id_salt),
+            direction,
+            default_fraction: 0.5,
+            first_size_range: Rangef::new(0.0, f32::INFINITY),
+            second_size_range: Rangef::new(0.0, f32::INFINITY),
+            collapsible: true,
+        }
+    }
+
+    /// How much of the space goes to the first pane, before the user has moved the divider.
+    ///
+    /// Default: `0.5`.
+    #[inline]
+    pub fn default_fraction(mut self, default_fraction: f32) -> Self {
+        self.default_fraction = default_fraction;
+        self
+    }
+
+    /// The smallest and largest width (or height, for [`SplitDirection::Vertical`]) of the first pane.
+    #[inline]
+    pub fn first_size_range(mut self, size_range: impl Into<Rangef>) -> Self {
+        self.first_size_range = size_range.into();
+        self
+    }
+
+    /// The smallest and largest width (or height, for [`SplitDirection::Vertical`]) of the second pane.
+    ///
+    /// If both ranges can't be satisfied, the range of the first pane wins.
+    #[inline]
+    pub fn second_size_range(mut self, size_range: impl Into<Rangef>) -> Self {
+        self.second_size_range = size_range.into();
+        self
+    }
+
+    /// Can the user collapse a pane by double-clicking the divider?
+    ///
+    /// Default: `true`.
+    #[inline]
+    pub fn collapsible(mut self, collapsible: bool) -> Self {
+        self.collapsible = collapsible;
+        self
+    }
+
+    /// Show the two panes. A collapsed pane is not shown, so its result is `None`.
+    ///
+    /// The response is [`Response::changed`] when the user moved the divider.
+    pub fn show<R1, R2>(
+        self,
+        ui: &mut Ui,
+        add_first: impl FnOnce(&mut Ui) -> R1,
+        add_second: impl FnOnce(&mut Ui) -> R2,
+    ) -> InnerResponse<(Option<R1>, Option<R2>)> {
+        let id = ui.make_persistent_id(self.id_salt);
+        let mut state = SplitterState::load(ui.ctx(), id).unwrap_or(SplitterState {
+            fraction: self.default_fraction,
+            collapsed: None,
+        });
+
+        let rect = ui.available_rect_before_wrap();
+        let horizontal = self.direction == SplitDirection::Horizontal;
+        let (main_range, gap) = if horizontal {
+            (rect.x_range(), ui.spacing().item_spacing.x)
+        } else {
+            (rect.y_range(), ui.spacing().item_spacing.y)
+        };
+        let total = (main_range.span() - gap).max(0.0);
+        let first_size = match state.collapsed {
+            Some(Collapsed::First) => 0.0,
+            Some(Collapsed::Second) => total,
+            None => self.clamp_first_size(state.fraction * total, total),
+        };
+
+        let divider_pos = main_range.min + first_size + 0.5 * gap;
+        let (first_rect, second_rect) = if horizontal {
+            (
+                rect.with_max_x(divider_pos - 0.5 * gap),
+                rect.with_min_x(divider_pos + 0.5 * gap),
+            )
+        } else {
+            (
+                rect.with_max_y(divider_pos - 0.5 * gap),
+                rect.with_min_y(divider_pos + 0.5 * gap),
+            )
+        };
+
+        let mut add_pane = |pane_rect: Rect, salt: &str, collapsed: Collapsed| {
+            (state.collapsed != Some(collapsed)).then(|| {
+                let mut pane_ui = ui.new_child(UiBuilder::new().id_salt(salt).max_rect(pane_rect));
+                pane_ui.set_clip_rect(pane_rect.intersect(ui.clip_rect()));
+                pane_ui
+            })
+        };
+        let first =
+            add_pane(first_rect, "first", Collapsed::First).map(|mut pane| add_first(&mut pane));
+        let second = add_pane(second_rect, "second", Collapsed::Second)
+            .map(|mut pane| add_second(&mut pane));
+
+        // The divider is on top of the panes, like the resize handle of a panel:
+        let cross_range = if horizontal {
+            rect.y_range()
+        } else {
+            rect.x_range()
+        };
+        let divider = divider(
+            ui,
+            id.with("divider"),
+            self.direction,
+            divider_pos,
+            cross_range,
+        );
+
+        let mut response = ui.allocate_rect(rect, Sense::hover());
+        if divider.dragged()
+            && let Some(pointer) = divider.interact_pointer_pos()
+        {
+            let pointer = if horizontal { pointer.x } else { pointer.y };
+            let first_size = self.clamp_first_size(pointer - main_range.min - 0.5 * gap, total);
+            state.set_fraction(first_size / total.at_least(1.0));
+            response.mark_changed();
+        }
+        if self.collapsible && divider.double_clicked() {
+            state.collapsed = match state.collapsed {
+                Some(_) => None,
+                None if first_size <= 0.5 * total => Some(Collapsed::First),
+                None => Some(Collapsed::Second),
+            };
+            response.mark_changed();
+        }
+
+        state.store(ui.ctx(), id);
+        InnerResponse::new((first, second), response)
+    }
+
+    /// Clamp the size of the first pane so that both panes are in their size ranges.
+    fn clamp_first_size(&self, first_size: f32, total: f32) -> f32 {
+        let min = self
+            .first_size_range
+            .min
+            .max(total - self.second_size_range.max);
+        let max = self
+            .first_size_range
+            .max
+            .min(total - self.second_size_range.min);
+        let first_size = if min <= max {
+            first_size.clamp(min, max)
+        } else {
+            // The first pane wins, and leaves as much as possible to the second:
+            self.first_size_range.min
+        };
+        first_size.clamp(0.0, total)
+    }
+}
+
+/// The draggable line between two panes, at `pos` on the main axis of `direction`, across `cross_range`.
+///
+/// It is painted like the resize handle of a panel.
+pub(crate) fn divider(
+    ui: &Ui,
+    id: Id,
+    direction: SplitDirection,
+    pos: f32,
+    cross_range: Rangef,
+) -> Response {
+    let grab_radius = ui.style().interaction.resize_grab_radius_side;
+    let (rect, cursor_icon) = match direction {
+        SplitDirection::Horizontal => (
+            Rect::from_x_y_ranges(pos..=pos, cross_range).expand2(vec2(grab_radius, 0.0)),
+            CursorIcon::ResizeHorizontal,
+        ),
+        SplitDirection::Vertical => (
+            Rect::from_x_y_ranges(cross_range, pos..=pos).expand2(vec2(0.0, grab_radius)),
+            CursorIcon::ResizeVertical,
+        ),
+    };
+    let response = ui.interact(rect, id, Sense::click_and_drag());
+
+    if response.hovered() || response.dragged() {
+        ui.ctx().set_cursor_icon(cursor_icon);
+    }
+    let stroke = if response.dragged() {
+        ui.visuals().widgets.active.fg_stroke
+    } else if response.hovered() {
+        ui.visuals().widgets.hovered.fg_stroke
+    } else {
+        ui.visuals().widgets.noninteractive.bg_stroke
+    };
+    match direction {
+        SplitDirection::Horizontal => {
+            ui.painter().vline(pos, cross_range, stroke);
+        }
+        SplitDirection::Vertical => {
+            ui.painter().hline(cross_range, pos, stroke);
+        }
+    }
+
+    #[cfg(feature = "accesskit")]
+    ui.ctx().accesskit_node_builder(id, |builder| {
+        builder.set_role(accesskit::Role::Splitter);
+    });
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SplitDirection, Splitter};
+
+    #[test]
+    fn test_clamp_first_size() {
+        let splitter = Splitter::new("splitter", SplitDirection::Horizontal)
+            .first_size_range(100.0..=400.0)
+            .second_size_range(200.0..=f32::INFINITY);
+        assert_eq!(splitter.clamp_first_size(250.0, 1000.0), 250.0);
+        assert_eq!(splitter.clamp_first_size(50.0, 1000.0), 100.0);
+        assert_eq!(splitter.clamp_first_size(900.0, 1000.0), 400.0);
+        assert_eq!(
+            splitter.clamp_first_size(250.0, 400.0),
+            200.0,
+            "Leave room for the second pane"
+        );
+        assert_eq!(
+            splitter.clamp_first_size(250.0, 250.0),
+            100.0,
+            "The first pane wins"
+        );
+        assert_eq!(
+            splitter.clamp_first_size(250.0, 50.0),
+            50.0,
+            "Never more than there is"
+        );
+    }
+}
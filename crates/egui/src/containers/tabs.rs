@@ -0,0 +1,630 @@
+use crate::{
+    Align, CursorIcon, DragAndDrop, EventFilter, FocusDirection, Id, InnerResponse, Key, Modifiers,
+    Rect, Response, ScrollArea, Sense, TextStyle, TextWrapMode, Ui, UiBuilder, WidgetInfo,
+    WidgetText, WidgetType, containers::scroll_area::ScrollBarVisibility, epaint::CornerRadiusF32,
+    pos2, vec2,
+};
+
+/// The active tab of [`Tabs`], stored between frames.
+#[derive(Clone, Debug, Default)]
+pub struct TabsState {
+    /// The [`Id`] of the active tab, as given by `tab_id` in [`Tabs::show`].
+    active: Option<Id>,
+
+    /// The index of the active tab, found from [`Self::active`] each frame.
+    ///
+    /// If the active tab is removed, the tab that is now at this index becomes active.
+    active_index: usize,
+
+    /// Did the tabs not fit in the tab bar last frame?
+    overflowing: bool,
+
+    /// Scroll the tab bar to the active tab, e.g. after it was picked from the dropdown.
+    scroll_to_active: bool,
+
+    /// The tab bar and contents last frame, to tell if the focus is in them.
+    rect: Option<Rect>,
+}
+
+impl TabsState {
+    pub fn load(ctx: &crate::Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_temp(id))
+    }
+
+    pub fn store(self, ctx: &crate::Context, id: Id) {
+        ctx.data_mut(|d| d.insert_temp(id, self));
+    }
+
+    /// The [`Id`] of the active tab, as given by `tab_id` in [`Tabs::show`].
+    pub fn active(&self) -> Option<Id> {
+        self.active
+    }
+
+    /// Switch to the tab with this [`Id`], as given by `tab_id` in [`Tabs::show`].
+    pub fn set_active(&mut self, tab_id: Id) {
+        self.active = Some(tab_id);
+        self.scroll_to_active = true;
+    }
+
+    /// Switch to the tab at `index`.
+    fn activate(&mut self, index: usize) {
+        self.active_index = index;
+        self.scroll_to_active = true;
+    }
+
+    /// Find the index of the active tab,
+    /// or of its nearest neighbor if it is gone.
+    fn resolve_active(&mut self, tab_keys: &[Id]) {
+        if let Some(index) = self
+            .active
+            .and_then(|active| tab_keys.iter().position(|key| *key == active))
+        {
+            self.active_index = index;
+        }
+        self.active_index = self.active_index.min(tab_keys.len().saturating_sub(1));
+    }
+}
+
+/// What [`Tabs::show`] returns.
+pub struct TabsOutput<Tab, R> {
+    /// The response of the tab bar and the contents.
+    ///
+    /// It is [`Response::changed`] when the user switched, closed or moved tabs.
+    pub response: Response,
+
+    /// What `add_contents` returned, or `None` if there are no tabs.
+    pub inner: Option<R>,
+
+    /// The index of the active tab.
+    pub active: usize,
+
+    /// The tabs the user closed this frame, which have already been removed.
+    pub closed: Vec<Tab>,
+}
+
+/// A row of tabs above the contents of the active one.
+///
+/// The tabs are your own `Tab` type in a `Vec`, and [`Tabs`] remembers which one is active.
+/// Each tab needs an [`Id`] that stays the same when the tabs are reordered or closed,
+/// e.g. from the path of a document. The contents of each tab are shown with that [`Id`],
+/// so that widgets in different tabs don't share their state.
+///
+/// The user can switch tabs by clicking them, with the arrow keys on a focused tab,
+/// or with ctrl+tab and ctrl+shift+tab while the focus is on a tab or in the contents.
+///
+/// Tabs can be closed (see [`Self::closable`]) and reordered by dragging them (see [`Self::reorderable`]),
+/// which changes the `Vec`.
+/// When the tabs don't fit, the tab bar scrolls and a dropdown lists all of them.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut documents = vec![("a.txt".to_owned(), String::new())];
+/// let output = egui::Tabs::new(ui.make_persistent_id("documents"))
+///     .closable(true)
+///     .show(
+///         ui,
+///         &mut documents,
+///         |(name, _)| egui::Id::new(name),
+///         |(name, _)| name.into(),
+///         |ui, (_, text)| ui.text_edit_multiline(text),
+///     );
+///
+/// for (name, _) in output.closed {
+///     // ask to save the document
+/// }
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct Tabs {
+    id: Id,
+    closable: bool,
+    reorderable: bool,
+}
+
+/// Something the user did to a tab, done once the whole tab bar is shown.
+enum TabAction {
+    Close(usize),
+
+    /// Move the tab at `from` to before the tab at `to`, or to the end if `to` is the number of tabs.
+    Move {
+        from: usize,
+        to: usize,
+    },
+}
+
+/// The [`DragAndDrop`] payload while a tab is dragged.
+#[derive(Clone, Copy)]
+struct DraggedTab {
+    tabs_id: Id,
+    index: usize,
+}
+
+impl Tabs {
+    /// The `id` must be unique, e.g. from [`Ui::make_persistent_id`].
+    pub fn new(id: Id) -> Self {
+        Self {
+            id,
+            closable: false,
+            reorderable: true,
+        }
+    }
+
+    /// Show a close button on each tab. Middle-clicking a tab also closes it.
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn closable(mut self, closable: bool) -> Self {
+        self.closable = closable;
+        self
+    }
+
+    /// Can the user reorder the tabs by dragging them?
+    ///
+    /// Default: `true`.
+    #[inline]
+    pub fn reorderable(mut self, reorderable: bool) -> Self {
+        self.reorderable = reorderable;
+        self
+    }
+
+    /// Show the tab bar and the contents of the active tab.
+    ///
+    /// `tab_id` must give each tab a unique [`Id`] that does not change when the tabs are reordered.
+    pub fn show<Tab, R>(
+        self,
+        ui: &mut Ui,
+        tabs: &mut Vec<Tab>,
+        tab_id: impl Fn(&Tab) -> Id,
+        title: impl Fn(&Tab) -> WidgetText,
+        add_contents: impl FnOnce(&mut Ui, &mut Tab) -> R,
+    ) -> TabsOutput<Tab, R> {
+        let id = self.id;
+        let mut state = TabsState::load(ui.ctx(), id).unwrap_or_default();
+        let tab_keys: Vec<Id> = tabs.iter().map(&tab_id).collect();
+        state.resolve_active(&tab_keys);
+        let prev_active = tab_keys.get(state.active_index).copied();
+
+        let tab_ids: Vec<Id> = tab_keys.iter().map(|key| id.with(key)).collect();
+        if focus_is_inside(ui, &state, &tab_ids) {
+            ctrl_tab_navigation(ui, &mut state, &tab_ids);
+        }
+
+        let mut closed = Vec::new();
+        let mut moved = false;
+        let InnerResponse {
+            inner,
+            mut response,
+        } = ui.scope_builder(UiBuilder::new().id_salt(id), |ui| {
+            match self.tab_bar(ui, &mut state, tabs, &tab_ids, &title) {
+                Some(TabAction::Close(index)) => {
+                    closed.push(close_tab(tabs, &mut state.active_index, index));
+                }
+                Some(TabAction::Move { from, to }) => {
+                    moved = move_tab(tabs, &mut state.active_index, from, to);
+                }
+                None => {}
+            }
+
+            let tab = tabs.get_mut(state.active_index)?;
+            let panel_id = tab_id(tab);
+            let panel = ui.scope_builder(UiBuilder::new().id_salt(("panel", panel_id)), |ui| {
+                #[cfg(feature = "accesskit")]
+                ui.ctx().accesskit_node_builder(ui.unique_id(), |node| {
+                    node.set_role(accesskit::Role::TabPanel);
+                });
+                add_contents(ui, tab)
+            });
+            Some(panel.inner)
+        });
+
+        state.active = tabs.get(state.active_index).map(&tab_id);
+        if state.active != prev_active || !closed.is_empty() || moved {
+            response.mark_changed();
+        }
+        let active = state.active_index;
+        state.rect = Some(response.rect);
+        state.store(ui.ctx(), id);
+        TabsOutput {
+            response,
+            inner,
+            active,
+            closed,
+        }
+    }
+
+    /// The tab bar, with a dropdown of all tabs when they don't fit.
+    fn tab_bar<Tab>(
+        &self,
+        ui: &mut Ui,
+        state: &mut TabsState,
+        tabs: &[Tab],
+        tab_ids: &[Id],
+        title: &impl Fn(&Tab) -> WidgetText,
+    ) -> Option<TabAction> {
+        let mut action = None;
+        let bar = ui.horizontal(|ui| {
+            #[cfg(feature = "accesskit")]
+            ui.ctx().accesskit_node_builder(ui.unique_id(), |node| {
+                node.set_role(accesskit::Role::TabList);
+            });
+
+            ui.spacing_mut().item_spacing.x = 0.0;
+            let dropdown_width = if state.overflowing {
+                ui.spacing().interact_size.y + ui.spacing().button_padding.x
+            } else {
+                0.0
+            };
+            let scroll = ScrollArea::horizontal()
+                .id_salt("tab_bar")
+                .max_width(ui.available_width() - dropdown_width)
+                .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden)
+                .show(ui, |ui| {
+                    for (index, tab) in tabs.iter().enumerate() {
+                        if let Some(tab_action) = self.tab(ui, state, tab_ids, index, title(tab)) {
+                            action = Some(tab_action);
+                        }
+                    }
+                });
+            state.overflowing = scroll.inner_rect.width() < scroll.content_size.x;
+
+            if state.overflowing {
+                ui.add_space(ui.spacing().button_padding.x);
+                ui.menu_button("⏷", |ui| {
+                    for (index, tab) in tabs.iter().enumerate() {
+                        if ui
+                            .selectable_label(index == state.active_index, title(tab))
+                            .clicked()
+                        {
+                            state.activate(index);
+                            ui.ctx().request_repaint();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("All tabs");
+            }
+        });
+
+        let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
+        ui.painter()
+            .hline(ui.max_rect().x_range(), bar.response.rect.bottom(), stroke);
+
+        action
+    }
+
+    /// One tab, with its close button.
+    fn tab(
+        &self,
+        ui: &mut Ui,
+        state: &mut TabsState,
+        tab_ids: &[Id],
+        index: usize,
+        title: WidgetText,
+    ) -> Option<TabAction> {
+        let tab_id = tab_ids[index];
+        let is_active = index == state.active_index;
+        let text = title.text().to_owned();
+
+        let padding = ui.spacing().button_padding;
+        let close_size = ui.spacing().icon_width;
+        let close_space = if self.closable {
+            ui.spacing().icon_spacing + close_size
+        } else {
+            0.0
+        };
+        let galley = title.into_galley(
+            ui,
+            Some(TextWrapMode::Extend),
+            f32::INFINITY,
+            TextStyle::Button,
+        );
+        let size = vec2(
+            galley.size().x + close_space + 2.0 * padding.x,
+            (galley.size().y + 2.0 * padding.y).max(ui.spacing().interact_size.y),
+        );
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let sense = if self.reorderable {
+            Sense::click_and_drag()
+        } else {
+            Sense::click()
+        };
+        let response = ui.interact(rect, tab_id, sense);
+
+        // The close button is on top of the tab, so it gets the clicks:
+        let close_response = self.closable.then(|| {
+            let close_rect = Rect::from_center_size(
+                pos2(rect.right() - padding.x - 0.5 * close_size, rect.center().y),
+                vec2(close_size, close_size),
+            );
+            let close_response = ui.interact(close_rect, tab_id.with("close"), Sense::click());
+            close_response.widget_info(|| {
+                WidgetInfo::labeled(WidgetType::Button, ui.is_enabled(), "Close tab")
+            });
+            close_response
+        });
+
+        let mut action = None;
+        if response.clicked() || response.drag_started() {
+            state.active_index = index;
+        }
+        if response.clicked() {
+            response.request_focus();
+        }
+        if self.closable
+            && (response.middle_clicked() || close_response.as_ref().is_some_and(|c| c.clicked()))
+        {
+            action = Some(TabAction::Close(index));
+        }
+        if response.has_focus() {
+            keyboard_navigation(ui, state, tab_ids, index);
+        }
+        if is_active && state.scroll_to_active {
+            response.scroll_to_me(Some(Align::Center));
+            state.scroll_to_active = false;
+        }
+
+        if self.reorderable {
+            if response.drag_started() {
+                DragAndDrop::set_payload(
+                    ui.ctx(),
+                    DraggedTab {
+                        tabs_id: self.id,
+                        index,
+                    },
+                );
+            }
+            if response.dragged() {
+                ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+            }
+            if let Some(to) = self.drop_target(ui, &response, index) {
+                action = Some(to);
+            }
+        }
+
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact_selectable(&response, is_active);
+            let corner_radius = CornerRadiusF32 {
+                nw: visuals.corner_radius.nw.into(),
+                ne: visuals.corner_radius.ne.into(),
+                sw: 0.0,
+                se: 0.0,
+            };
+            if is_active || response.hovered() || response.has_focus() {
+                let stroke = if response.has_focus() {
+                    ui.visuals().selection.stroke
+                } else {
+                    visuals.bg_stroke
+                };
+                ui.painter().rect(
+                    rect,
+                    corner_radius,
+                    visuals.weak_bg_fill,
+                    stroke,
+                    crate::StrokeKind::Inside,
+                );
+            }
+            let text_pos = pos2(
+                rect.left() + padding.x,
+                rect.center().y - 0.5 * galley.size().y,
+            );
+            ui.painter().galley(text_pos, galley, visuals.text_color());
+
+            if let Some(close_response) = &close_response {
+                let visuals = ui.style().interact(close_response);
+                let close_rect = close_response.rect.shrink(2.0).expand(visuals.expansion);
+                let stroke = visuals.fg_stroke;
+                ui.painter()
+                    .line_segment([close_rect.left_top(), close_rect.right_bottom()], stroke);
+                ui.painter()
+                    .line_segment([close_rect.right_top(), close_rect.left_bottom()], stroke);
+            }
+        }
+
+        response.widget_info(|| {
+            WidgetInfo::selected(
+                WidgetType::SelectableLabel,
+                ui.is_enabled(),
+                is_active,
+                &text,
+            )
+        });
+        #[cfg(feature = "accesskit")]
+        ui.ctx().accesskit_node_builder(tab_id, |builder| {
+            builder.set_role(accesskit::Role::Tab);
+            builder.set_selected(is_active);
+        });
+
+        action
+    }
+
+    /// Show where a dragged tab would go, and report a [`TabAction::Move`] when it is dropped.
+    fn drop_target(&self, ui: &Ui, response: &Response, index: usize) -> Option<TabAction> {
+        let payload = response.dnd_hover_payload::<DraggedTab>()?;
+        if payload.tabs_id != self.id {
+            return None;
+        }
+        let pointer = ui.ctx().pointer_interact_pos()?;
+
+        let rect = response.rect;
+        let (to, x) = if pointer.x < rect.center().x {
+            (index, rect.left())
+        } else {
+            (index + 1, rect.right())
+        };
+        let stroke = ui.visuals().widgets.active.bg_stroke;
+        ui.painter().vline(x, rect.y_range(), stroke);
+
+        let payload = response.dnd_release_payload::<DraggedTab>()?;
+        Some(TabAction::Move {
+            from: payload.index,
+            to,
+        })
+    }
+}
+
+/// Is a tab, or a widget in the contents of the active tab, focused?
+fn focus_is_inside(ui: &Ui, state: &TabsState, tab_ids: &[Id]) -> bool {
+    let Some(focused) = ui.memory(|mem| mem.focused()) else {
+        return false;
+    };
+    tab_ids.contains(&focused)
+        || ui.ctx().read_response(focused).is_some_and(|response| {
+            response.layer_id == ui.layer_id()
+                && state
+                    .rect
+                    .is_some_and(|rect| rect.contains_rect(response.rect))
+        })
+}
+
+/// Switch to the previous or next tab with ctrl+shift+tab and ctrl+tab, and focus it.
+///
+/// This runs before any widget in the tabs, so that they don't get the key presses.
+fn ctrl_tab_navigation(ui: &Ui, state: &mut TabsState, tab_ids: &[Id]) {
+    let num_tabs = tab_ids.len();
+    if num_tabs == 0 {
+        return;
+    }
+    let index = state.active_index;
+    let new_index = ui.input_mut(|i| {
+        // Check with shift first, since `consume_key` ignores extra shift:
+        if i.consume_key(Modifiers::CTRL | Modifiers::SHIFT, Key::Tab) {
+            Some((index + num_tabs - 1) % num_tabs)
+        } else if i.consume_key(Modifiers::CTRL, Key::Tab) {
+            Some((index + 1) % num_tabs)
+        } else {
+            None
+        }
+    });
+    if let Some(new_index) = new_index {
+        state.activate(new_index);
+        ui.memory_mut(|mem| {
+            // Egui sees any tab key as moving the focus to the next widget, which ctrl+tab should not:
+            mem.move_focus(FocusDirection::None);
+            mem.request_focus(tab_ids[new_index]);
+        });
+        ui.ctx().request_repaint();
+    }
+}
+
+/// Switch to the previous or next tab with the arrow keys, and move the focus along.
+fn keyboard_navigation(ui: &Ui, state: &mut TabsState, tab_ids: &[Id], index: usize) {
+    // Keep the arrow keys from moving the focus to other widgets.
+    // Tab is not locked, so it moves the focus out of the tab bar as usual:
+    let event_filter = EventFilter {
+        horizontal_arrows: true,
+        ..Default::default()
+    };
+    ui.memory_mut(|mem| mem.set_focus_lock_filter(tab_ids[index], event_filter));
+
+    let new_index = ui.input_mut(|i| {
+        let modifiers = i.modifiers;
+        if i.consume_key(modifiers, Key::ArrowLeft) {
+            index.checked_sub(1)
+        } else if i.consume_key(modifiers, Key::ArrowRight) {
+            Some(index + 1).filter(|&next| next < tab_ids.len())
+        } else {
+            None
+        }
+    });
+    if let Some(new_index) = new_index {
+        state.activate(new_index);
+        ui.memory_mut(|mem| mem.request_focus(tab_ids[new_index]));
+        ui.ctx().request_repaint();
+    }
+}
+
+/// Remove the tab at `index`, and make its neighbor active if it was.
+fn close_tab<Tab>(tabs: &mut Vec<Tab>, active: &mut usize, index: usize) -> Tab {
+    let tab = tabs.remove(index);
+    if index < *active {
+        *active -= 1;
+    }
+    *active = (*active).min(tabs.len().saturating_sub(1));
+    tab
+}
+
+/// Move the tab at `from` to before the tab at `to`, keeping the same tab active.
+///
+/// Returns `false` if that leaves the tab where it was.
+fn move_tab<Tab>(tabs: &mut Vec<Tab>, active: &mut usize, from: usize, to: usize) -> bool {
+    if to == from || to == from + 1 {
+        return false;
+    }
+    let to = if from < to { to - 1 } else { to };
+    let tab = tabs.remove(from);
+    tabs.insert(to, tab);
+
+    if *active == from {
+        *active = to;
+    } else if from < *active && *active <= to {
+        *active -= 1;
+    } else if to <= *active && *active < from {
+        *active += 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TabsState, close_tab, move_tab};
+    use crate::Id;
+
+    #[test]
+    fn test_close_and_move_tabs() {
+        let mut tabs = vec!["a", "b", "c", "d"];
+        let mut active = 1;
+
+        assert!(move_tab(&mut tabs, &mut active, 0, 3));
+        assert_eq!(tabs, ["b", "c", "a", "d"]);
+        assert_eq!(tabs[active], "b");
+
+        assert!(move_tab(&mut tabs, &mut active, 3, 0));
+        assert_eq!(tabs, ["d", "b", "c", "a"]);
+        assert_eq!(tabs[active], "b");
+
+        assert!(move_tab(&mut tabs, &mut active, 1, 4));
+        assert_eq!(tabs, ["d", "c", "a", "b"]);
+        assert_eq!(tabs[active], "b");
+
+        // Dropping a tab next to itself doesn't move it:
+        assert!(!move_tab(&mut tabs, &mut active, 1, 1));
+        assert!(!move_tab(&mut tabs, &mut active, 1, 2));
+        assert_eq!(tabs, ["d", "c", "a", "b"]);
+
+        // Closing the active tab activates the next one, or the previous one at the end:
+        assert_eq!(close_tab(&mut tabs, &mut active, 3), "b");
+        assert_eq!(tabs[active], "a");
+        assert_eq!(close_tab(&mut tabs, &mut active, 0), "d");
+        assert_eq!(tabs[active], "a");
+        assert_eq!(close_tab(&mut tabs, &mut active, 1), "a");
+        assert_eq!(tabs[active], "c");
+        assert_eq!(close_tab(&mut tabs, &mut active, 0), "c");
+        assert_eq!(active, 0);
+        assert!(tabs.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_active() {
+        let keys = |names: &[&str]| names.iter().map(Id::new).collect::<Vec<_>>();
+        let mut state = TabsState::default();
+        state.resolve_active(&keys(&["a", "b", "c"]));
+        assert_eq!(state.active_index, 0);
+
+        state.set_active(Id::new("b"));
+        state.resolve_active(&keys(&["a", "b", "c"]));
+        assert_eq!(state.active_index, 1);
+
+        // Inserting or removing tabs before the active one keeps it active:
+        state.resolve_active(&keys(&["x", "a", "b", "c"]));
+        assert_eq!(state.active_index, 2);
+        state.resolve_active(&keys(&["b", "c"]));
+        assert_eq!(state.active_index, 0);
+
+        // When the active tab is gone, the one in its place becomes active:
+        state.resolve_active(&keys(&["c", "d", "e"]));
+        assert_eq!(state.active_index, 0);
+        state.active_index = 2;
+        state.resolve_active(&keys(&["c", "d"]));
+        assert_eq!(state.active_index, 1);
+    }
+}
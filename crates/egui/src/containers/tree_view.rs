@@ -0,0 +1,608 @@
+use std::hash::Hash;
+
+use crate::{
+    CursorIcon, DragAndDrop, EventFilter, Id, Key, Rect, Response, Sense, Stroke, StrokeKind,
+    TextStyle, TextWrapMode, Ui, UiBuilder, WidgetInfo, WidgetText, WidgetType,
+    collapsing_header::{CollapsingState, paint_default_icon},
+    pos2, vec2,
+};
+
+/// The selection and keyboard cursor of a [`TreeView`], stored between frames.
+#[derive(Clone, Debug)]
+pub struct TreeViewState<NodeId> {
+    selected: Vec<NodeId>,
+
+    /// The node that keyboard navigation moves from.
+    cursor: Option<NodeId>,
+
+    /// Where a shift-click or shift-arrow range selection starts.
+    anchor: Option<NodeId>,
+}
+
+impl<NodeId> Default for TreeViewState<NodeId> {
+    fn default() -> Self {
+        Self {
+            selected: Vec::new(),
+            cursor: None,
+            anchor: None,
+        }
+    }
+}
+
+impl<NodeId: Clone + PartialEq + Send + Sync + 'static> TreeViewState<NodeId> {
+    pub fn load(ctx: &crate::Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_temp(id))
+    }
+
+    pub fn store(self, ctx: &crate::Context, id: Id) {
+        ctx.data_mut(|d| d.insert_temp(id, self));
+    }
+
+    /// The selected nodes, in the order they were selected.
+    pub fn selected(&self) -> &[NodeId] {
+        &self.selected
+    }
+
+    pub fn is_selected(&self, node: &NodeId) -> bool {
+        self.selected.contains(node)
+    }
+
+    /// Select only these nodes.
+    pub fn set_selected(&mut self, selected: Vec<NodeId>) {
+        self.cursor = selected.last().cloned();
+        self.anchor = self.cursor.clone();
+        self.selected = selected;
+    }
+}
+
+/// Where [`TreeViewAction::Move`] wants the dragged nodes to go, relative to its target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DropPosition {
+    /// Just before the target, under the same parent.
+    Before,
+
+    /// Just after the target, under the same parent.
+    After,
+
+    /// Into the target, which is a directory.
+    Inside,
+}
+
+/// Something the user did in a [`TreeView`] that your code should handle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TreeViewAction<NodeId> {
+    /// The user double-clicked a node, or pressed enter with nodes selected.
+    Activate(Vec<NodeId>),
+
+    /// The user dragged `nodes` and dropped them at `target`.
+    ///
+    /// The tree never moves the nodes itself, since it does not own them.
+    Move {
+        nodes: Vec<NodeId>,
+        target: NodeId,
+        position: DropPosition,
+    },
+}
+
+/// What [`TreeView::show`] returns.
+pub struct TreeViewOutput<NodeId> {
+    /// The response of the whole tree.
+    ///
+    /// It is [`Response::changed`] when the selection changed.
+    pub response: Response,
+
+    /// The state, which has already been stored.
+    pub state: TreeViewState<NodeId>,
+
+    /// What the user did this frame.
+    pub actions: Vec<TreeViewAction<NodeId>>,
+}
+
+/// A tree of nodes, e.g. for a file browser or a scene outliner.
+///
+/// Nodes are identified by your own `NodeId` type.
+/// The user can select them with the pointer (shift and ctrl/cmd for multiple nodes) or the arrow keys,
+/// open and close directories, and drag nodes onto other nodes.
+///
+/// The children of a directory are only added while it is open, so they can be loaded lazily.
+/// Whether a directory is open is stored in a [`CollapsingState`].
+///
+/// While nodes are dragged, they are the [`DragAndDrop`] payload as a `Vec<NodeId>`,
+/// so you can also drop them onto your own widgets with [`Response::dnd_release_payload`].
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::{DropPosition, TreeView, TreeViewAction};
+///
+/// let output = TreeView::new(ui.make_persistent_id("files")).show(ui, |tree| {
+///     tree.dir("src", "src", |tree| {
+///         tree.leaf("lib.rs", "lib.rs");
+///         tree.leaf("main.rs", "main.rs");
+///     });
+///     tree.leaf("Cargo.toml", "Cargo.toml");
+/// });
+///
+/// for action in output.actions {
+///     match action {
+///         TreeViewAction::Activate(nodes) => { /* open the files */ }
+///         TreeViewAction::Move { nodes, target, position } => { /* move the files */ }
+///     }
+/// }
+/// # });
+/// ```
+#[must_use = "You should call .show()"]
+pub struct TreeView {
+    id: Id,
+    multi_select: bool,
+    default_open: bool,
+}
+
+impl TreeView {
+    /// The `id` must be unique, e.g. from [`Ui::make_persistent_id`].
+    pub fn new(id: Id) -> Self {
+        Self {
+            id,
+            multi_select: true,
+            default_open: false,
+        }
+    }
+
+    /// Can the user select more than one node?
+    ///
+    /// Default: `true`.
+    #[inline]
+    pub fn multi_select(mut self, multi_select: bool) -> Self {
+        self.multi_select = multi_select;
+        self
+    }
+
+    /// Are directories open the first time they are shown?
+    ///
+    /// Default: `false`.
+    #[inline]
+    pub fn default_open(mut self, default_open: bool) -> Self {
+        self.default_open = default_open;
+        self
+    }
+
+    pub fn show<NodeId: Clone + PartialEq + Hash + Send + Sync + 'static>(
+        self,
+        ui: &mut Ui,
+        add_nodes: impl FnOnce(&mut TreeViewBuilder<'_, NodeId>),
+    ) -> TreeViewOutput<NodeId> {
+        let Self {
+            id,
+            multi_select,
+            default_open,
+        } = self;
+
+        let state = TreeViewState::load(ui.ctx(), id).unwrap_or_default();
+        let prev_selected = state.selected.clone();
+        let mut shared = Shared {
+            tree_id: id,
+            multi_select,
+            default_open,
+            state,
+            rows: Vec::new(),
+            pending: None,
+            actions: Vec::new(),
+        };
+
+        let mut response = ui
+            .scope_builder(UiBuilder::new().id_salt(id), |ui| {
+                #[cfg(feature = "accesskit")]
+                ui.ctx().accesskit_node_builder(ui.unique_id(), |node| {
+                    node.set_role(accesskit::Role::Tree);
+                    if multi_select {
+                        node.set_multiselectable();
+                    }
+                });
+
+                ui.spacing_mut().item_spacing.y = 0.0;
+                add_nodes(&mut TreeViewBuilder {
+                    ui,
+                    shared: &mut shared,
+                    ancestors: Vec::new(),
+                });
+            })
+            .response;
+
+        shared.apply_pending(ui);
+        if shared.state.selected != prev_selected {
+            response.mark_changed();
+        }
+
+        let Shared { state, actions, .. } = shared;
+        state.clone().store(ui.ctx(), id);
+        TreeViewOutput {
+            response,
+            state,
+            actions,
+        }
+    }
+}
+
+/// A node as it was shown this frame.
+struct Row<NodeId> {
+    node: NodeId,
+    id: Id,
+    rect: Rect,
+}
+
+/// A change of the selection, done once all rows are known.
+enum Pending<NodeId> {
+    /// Select `node`. With `extend`, select everything from the anchor to it,
+    /// with `toggle`, add or remove it.
+    Select {
+        node: NodeId,
+        extend: bool,
+        toggle: bool,
+    },
+
+    /// Move the cursor this many rows, e.g. with the arrow keys.
+    Step { delta: isize, extend: bool },
+}
+
+struct Shared<NodeId> {
+    tree_id: Id,
+    multi_select: bool,
+    default_open: bool,
+    state: TreeViewState<NodeId>,
+
+    /// The visible rows, top to bottom.
+    rows: Vec<Row<NodeId>>,
+
+    pending: Option<Pending<NodeId>>,
+    actions: Vec<TreeViewAction<NodeId>>,
+}
+
+impl<NodeId: Clone + PartialEq> Shared<NodeId> {
+    fn apply_pending(&mut self, ui: &Ui) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        let index_of = |node: &NodeId| self.rows.iter().position(|row| row.node == *node);
+
+        let from_keyboard = matches!(pending, Pending::Step { .. });
+        let (node, extend, toggle) = match pending {
+            Pending::Select {
+                node,
+                extend,
+                toggle,
+            } => (node, extend, toggle),
+            Pending::Step { delta, extend } => {
+                let Some(last) = self.rows.len().checked_sub(1) else {
+                    return;
+                };
+                let index = match self.state.cursor.as_ref().and_then(index_of) {
+                    Some(cursor) => cursor.saturating_add_signed(delta).min(last),
+                    None => 0,
+                };
+                (self.rows[index].node.clone(), extend, false)
+            }
+        };
+
+        let anchor = self.state.anchor.as_ref().and_then(index_of);
+        let state = &mut self.state;
+        if toggle && self.multi_select {
+            if let Some(index) = state.selected.iter().position(|n| *n == node) {
+                state.selected.remove(index);
+            } else {
+                state.selected.push(node.clone());
+            }
+            state.anchor = Some(node.clone());
+        } else if extend
+            && self.multi_select
+            && let (Some(anchor), Some(index)) = (anchor, index_of(&node))
+        {
+            state.selected = self.rows[anchor.min(index)..=anchor.max(index)]
+                .iter()
+                .map(|row| row.node.clone())
+                .collect();
+        } else {
+            state.selected = vec![node.clone()];
+            state.anchor = Some(node.clone());
+        }
+        state.cursor = Some(node.clone());
+
+        if from_keyboard && let Some(row) = self.rows.iter().find(|row| row.node == node) {
+            ui.memory_mut(|mem| mem.request_focus(row.id));
+            ui.scroll_to_rect(row.rect, None);
+        }
+    }
+}
+
+/// Adds the nodes of a [`TreeView`], see [`TreeView::show`].
+pub struct TreeViewBuilder<'a, NodeId> {
+    ui: &'a mut Ui,
+    shared: &'a mut Shared<NodeId>,
+
+    /// The directories this builder adds nodes to, outermost first.
+    ancestors: Vec<NodeId>,
+}
+
+impl<NodeId: Clone + PartialEq + Hash + Send + Sync + 'static> TreeViewBuilder<'_, NodeId> {
+    /// Add a node without children.
+    pub fn leaf(&mut self, node: NodeId, label: impl Into<WidgetText>) -> Response {
+        self.row(node, label.into(), None)
+    }
+
+    /// Add a node with children.
+    ///
+    /// `add_children` is only called while the directory is open.
+    pub fn dir(
+        &mut self,
+        node: NodeId,
+        label: impl Into<WidgetText>,
+        add_children: impl FnOnce(&mut TreeViewBuilder<'_, NodeId>),
+    ) -> Response {
+        let collapsing_id = self.shared.tree_id.with((&node, "open"));
+        let mut collapsing = CollapsingState::load_with_default_open(
+            self.ui.ctx(),
+            collapsing_id,
+            self.shared.default_open,
+        );
+        let response = self.row(node.clone(), label.into(), Some(&mut collapsing));
+
+        let mut ancestors = self.ancestors.clone();
+        ancestors.push(node);
+        collapsing.show_body_unindented(self.ui, |ui| {
+            add_children(&mut TreeViewBuilder {
+                ui,
+                shared: self.shared,
+                ancestors,
+            });
+        });
+        response
+    }
+
+    fn row(
+        &mut self,
+        node: NodeId,
+        label: WidgetText,
+        mut collapsing: Option<&mut CollapsingState>,
+    ) -> Response {
+        let row_id = self.shared.tree_id.with(&node);
+        let level = self.ancestors.len();
+        let text = label.text().to_owned();
+
+        let ui = &mut *self.ui;
+        let size = vec2(ui.available_width(), ui.spacing().interact_size.y);
+        let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+        let response = ui.interact(rect, row_id, Sense::click_and_drag());
+        let indent = ui.spacing().indent;
+        let toggle_rect = Rect::from_min_size(
+            pos2(rect.left() + level as f32 * indent, rect.top()),
+            vec2(indent, rect.height()),
+        );
+
+        // The toggle button is on top of the row, so it gets the clicks:
+        if let Some(collapsing) = &mut collapsing {
+            let toggle = ui.interact(toggle_rect, collapsing.id(), Sense::click());
+            if toggle.clicked() || response.double_clicked() {
+                collapsing.toggle(ui);
+            }
+            let (mut icon_rect, _) = ui.spacing().icon_rectangles(toggle_rect);
+            icon_rect.set_center(toggle_rect.center());
+            let openness = collapsing.openness(ui.ctx());
+            paint_default_icon(ui, openness, &toggle.with_new_rect(icon_rect));
+        }
+
+        self.pointer_interaction(&node, &response, collapsing.is_none());
+        if response.has_focus() {
+            self.keyboard_navigation(&node, collapsing.as_deref_mut());
+        }
+        self.drop_target(&node, &response, collapsing.as_deref().map(|c| c.is_open()));
+
+        let ui = &mut *self.ui;
+        let selected = self.shared.state.is_selected(&node);
+        if ui.is_rect_visible(rect) {
+            let visuals = ui.style().interact_selectable(&response, selected);
+            if selected || response.hovered() || response.has_focus() {
+                let stroke = if response.has_focus() {
+                    ui.visuals().selection.stroke
+                } else {
+                    Stroke::NONE
+                };
+                ui.painter().rect(
+                    rect,
+                    visuals.corner_radius,
+                    visuals.weak_bg_fill,
+                    stroke,
+                    StrokeKind::Inside,
+                );
+            }
+            let text_left = toggle_rect.right() + ui.spacing().item_spacing.x;
+            let galley = label.into_galley(
+                ui,
+                Some(TextWrapMode::Truncate),
+                (rect.right() - text_left).max(0.0),
+                TextStyle::Button,
+            );
+            let text_pos = pos2(text_left, rect.center().y - 0.5 * galley.size().y);
+            ui.painter().galley(text_pos, galley, visuals.text_color());
+        }
+
+        if response.clicked() {
+            response.request_focus();
+        }
+        response.widget_info(|| {
+            WidgetInfo::selected(
+                WidgetType::SelectableLabel,
+                ui.is_enabled(),
+                selected,
+                &text,
+            )
+        });
+        #[cfg(feature = "accesskit")]
+        ui.ctx().accesskit_node_builder(row_id, |builder| {
+            builder.set_role(accesskit::Role::TreeItem);
+            builder.set_level(level + 1);
+            builder.set_selected(selected);
+            if let Some(collapsing) = &collapsing {
+                builder.set_expanded(collapsing.is_open());
+            }
+        });
+
+        self.shared.rows.push(Row {
+            node,
+            id: row_id,
+            rect,
+        });
+        response
+    }
+
+    /// Select with clicks, activate with double-clicks, and start dragging.
+    fn pointer_interaction(&mut self, node: &NodeId, response: &Response, is_leaf: bool) {
+        let shared = &mut *self.shared;
+        let modifiers = self.ui.input(|i| i.modifiers);
+        if response.clicked() {
+            shared.pending = Some(Pending::Select {
+                node: node.clone(),
+                extend: modifiers.shift,
+                toggle: modifiers.command,
+            });
+        }
+        if response.double_clicked() && is_leaf {
+            shared
+                .actions
+                .push(TreeViewAction::Activate(vec![node.clone()]));
+        }
+
+        if response.drag_started() {
+            if !shared.state.is_selected(node) {
+                shared.state.set_selected(vec![node.clone()]);
+            }
+            DragAndDrop::set_payload(self.ui.ctx(), shared.state.selected.clone());
+        }
+        if response.dragged() {
+            self.ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+        }
+    }
+
+    /// Move the cursor with the arrow keys, open and close directories, and activate with enter.
+    fn keyboard_navigation(&mut self, node: &NodeId, collapsing: Option<&mut CollapsingState>) {
+        let ui = &*self.ui;
+        let shared = &mut *self.shared;
+        shared.state.cursor = Some(node.clone());
+
+        // Keep the arrow keys from moving the focus to other widgets:
+        let event_filter = EventFilter {
+            horizontal_arrows: true,
+            vertical_arrows: true,
+            ..Default::default()
+        };
+        let row_id = shared.tree_id.with(node);
+        ui.memory_mut(|mem| mem.set_focus_lock_filter(row_id, event_filter));
+
+        let (modifiers, [down, up, right, left, home, end, enter]) = ui.input_mut(|i| {
+            let modifiers = i.modifiers;
+            let keys = [
+                Key::ArrowDown,
+                Key::ArrowUp,
+                Key::ArrowRight,
+                Key::ArrowLeft,
+                Key::Home,
+                Key::End,
+                Key::Enter,
+            ];
+            (modifiers, keys.map(|key| i.consume_key(modifiers, key)))
+        });
+        let extend = modifiers.shift;
+
+        // This row is not in `rows` yet, so it has index `rows.len()`:
+        let parent_delta = self.ancestors.last().and_then(|parent| {
+            let parent_index = shared.rows.iter().rposition(|row| row.node == *parent)?;
+            Some(parent_index as isize - shared.rows.len() as isize)
+        });
+
+        let delta = if down {
+            Some(1)
+        } else if up {
+            Some(-1)
+        } else if home {
+            Some(isize::MIN)
+        } else if end {
+            Some(isize::MAX)
+        } else if right {
+            match collapsing {
+                Some(collapsing) if !collapsing.is_open() => {
+                    collapsing.set_open(true);
+                    ui.ctx().request_repaint();
+                    None
+                }
+                Some(_) => Some(1), // to the first child
+                None => None,
+            }
+        } else if left {
+            match collapsing {
+                Some(collapsing) if collapsing.is_open() => {
+                    collapsing.set_open(false);
+                    ui.ctx().request_repaint();
+                    None
+                }
+                _ => parent_delta,
+            }
+        } else {
+            None
+        };
+        if let Some(delta) = delta {
+            shared.pending = Some(Pending::Step { delta, extend });
+        }
+
+        if enter && !shared.state.selected.is_empty() {
+            shared
+                .actions
+                .push(TreeViewAction::Activate(shared.state.selected.clone()));
+        }
+    }
+
+    /// Show where dragged nodes would go, and report a [`TreeViewAction::Move`] when they are dropped.
+    ///
+    /// `is_open` is `None` for leaves.
+    fn drop_target(&mut self, node: &NodeId, response: &Response, is_open: Option<bool>) {
+        let Some(payload) = response.dnd_hover_payload::<Vec<NodeId>>() else {
+            return;
+        };
+        // Nodes can't be dropped onto themselves or into their own children:
+        if payload.contains(node) || self.ancestors.iter().any(|a| payload.contains(a)) {
+            return;
+        }
+        let Some(pointer) = self.ui.ctx().pointer_interact_pos() else {
+            return;
+        };
+
+        let rect = response.rect;
+        let t = (pointer.y - rect.top()) / rect.height();
+        let position = match is_open {
+            Some(_) if t < 0.25 => DropPosition::Before,
+            // "After" an open directory would be confusing, since its children come next:
+            Some(false) if 0.75 < t => DropPosition::After,
+            Some(_) => DropPosition::Inside,
+            None if t < 0.5 => DropPosition::Before,
+            None => DropPosition::After,
+        };
+
+        let stroke = self.ui.visuals().widgets.active.bg_stroke;
+        let painter = self.ui.painter();
+        match position {
+            DropPosition::Before => {
+                painter.hline(rect.x_range(), rect.top(), stroke);
+            }
+            DropPosition::After => {
+                painter.hline(rect.x_range(), rect.bottom(), stroke);
+            }
+            DropPosition::Inside => {
+                painter.rect_stroke(rect, 2.0, stroke, StrokeKind::Inside);
+            }
+        }
+
+        if let Some(payload) = response.dnd_release_payload::<Vec<NodeId>>() {
+            self.shared.actions.push(TreeViewAction::Move {
+                nodes: (*payload).clone(),
+                target: node.clone(),
+                position,
+            });
+        }
+    }
+}
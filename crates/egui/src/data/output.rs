@@ -511,6 +511,9 @@ pub struct WidgetInfo {
 
     /// The hint text for text edit fields.
     pub hint_text: Option<String>,
+
+    /// Why the contents of a text edit field are invalid, if they are.
+    pub error: Option<String>,
 }
 
 impl std::fmt::Debug for WidgetInfo {
@@ -525,6 +528,7 @@ impl std::fmt::Debug for WidgetInfo {
             value,
             text_selection,
             hint_text,
+            error,
         } = self;
 
         let mut s = f.debug_struct("WidgetInfo");
@@ -556,6 +560,9 @@ impl std::fmt::Debug for WidgetInfo {
         if let Some(hint_text) = hint_text {
             s.field("hint_text", hint_text);
         }
+        if let Some(error) = error {
+            s.field("error", error);
+        }
 
         s.finish()
     }
@@ -573,6 +580,7 @@ impl WidgetInfo {
             value: None,
             text_selection: None,
             hint_text: None,
+            error: None,
         }
     }
 
@@ -665,6 +673,7 @@ impl WidgetInfo {
             value,
             text_selection: _,
             hint_text: _,
+            error,
         } = self;
 
         // TODO(emilk): localization
@@ -721,6 +730,10 @@ impl WidgetInfo {
             description += &value.to_string();
         }
 
+        if let Some(error) = error {
+            description = format!("{description}: invalid, {error}");
+        }
+
         if !enabled {
             description += ": disabled";
         }
@@ -1,8 +1,10 @@
 use emath::GuiRounding as _;
 
+use std::ops::Range;
+
 use crate::{
-    Align2, Color32, Context, Id, InnerResponse, NumExt as _, Painter, Rect, Region, Style, Ui,
-    UiBuilder, Vec2, vec2,
+    Align, Align2, Color32, Context, Id, InnerResponse, NumExt as _, Painter, Rect, Region, Style,
+    Ui, UiBuilder, Vec2, pos2, vec2,
 };
 
 #[cfg(debug_assertions)]
@@ -50,6 +52,119 @@ impl State {
         self.col_widths.iter().sum::<f32>()
             + (self.col_widths.len().at_least(1) - 1) as f32 * x_spacing
     }
+
+    /// Make the spanned columns at least as wide as `width`, by widening them all equally.
+    fn set_min_spanned_width(&mut self, cols: Range<usize>, width: f32, x_spacing: f32) {
+        Self::set_min_spanned_size(&mut self.col_widths, cols, width, x_spacing);
+    }
+
+    /// Make the spanned rows at least as high as `height`, by heightening them all equally.
+    fn set_min_spanned_height(&mut self, rows: Range<usize>, height: f32, y_spacing: f32) {
+        Self::set_min_spanned_size(&mut self.row_heights, rows, height, y_spacing);
+    }
+
+    fn set_min_spanned_size(sizes: &mut Vec<f32>, span: Range<usize>, size: f32, spacing: f32) {
+        sizes.resize(sizes.len().max(span.end), 0.0);
+        let num = span.len() as f32;
+        let spanned = sizes[span.clone()].iter().sum::<f32>() + (num - 1.0) * spacing;
+        if spanned < size {
+            let extra = (size - spanned) / num;
+            for size in &mut sizes[span] {
+                *size += extra;
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Where to put the next widget in a [`Grid`], and how many cells it covers.
+///
+/// Use with [`Ui::set_grid_cell`].
+///
+/// The rows and columns count from zero, at the first row and column of the grid.
+/// With [`Grid::start_row`], row zero is the start row.
+/// Cells that are added after this one continue to the right of it.
+/// Cells covered by a cell that spans several rows are skipped.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::GridCell;
+///
+/// egui::Grid::new("form").num_columns(2).show(ui, |ui| {
+///     ui.set_grid_cell(GridCell::new().col_span(2));
+///     ui.heading("Section header");
+///     ui.end_row();
+///
+///     ui.label("Name");
+///     ui.text_edit_singleline(&mut String::new());
+///     ui.end_row();
+///
+///     ui.set_grid_cell(GridCell::at(3, 1));
+///     ui.label("Bottom right");
+/// });
+/// # });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridCell {
+    row: Option<usize>,
+    col: Option<usize>,
+    col_span: usize,
+    row_span: usize,
+}
+
+impl Default for GridCell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridCell {
+    /// The next cell, covering a single row and column.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            row: None,
+            col: None,
+            col_span: 1,
+            row_span: 1,
+        }
+    }
+
+    /// The cell at the given row and column.
+    #[inline]
+    pub fn at(row: usize, col: usize) -> Self {
+        Self {
+            row: Some(row),
+            col: Some(col),
+            ..Self::new()
+        }
+    }
+
+    /// How many columns the cell covers.
+    ///
+    /// Default: `1`.
+    #[inline]
+    pub fn col_span(mut self, col_span: usize) -> Self {
+        self.col_span = col_span.at_least(1);
+        self
+    }
+
+    /// How many rows the cell covers.
+    ///
+    /// Default: `1`.
+    #[inline]
+    pub fn row_span(mut self, row_span: usize) -> Self {
+        self.row_span = row_span.at_least(1);
+        self
+    }
+}
+
+/// Options for a single column of a [`Grid`].
+#[derive(Clone, Copy, Debug, Default)]
+struct GridColumn {
+    align: Option<Align>,
+    width_fraction: Option<f32>,
 }
 
 // ----------------------------------------------------------------------------
@@ -79,10 +194,22 @@ pub(crate) struct GridLayout {
     min_cell_size: Vec2,
     max_cell_size: Vec2,
     color_picker: Option<ColorPickerFn>,
+    columns: Vec<GridColumn>,
+    start_row: usize,
 
     // Cursor:
     col: usize,
     row: usize,
+
+    /// How many columns and rows the next cell covers.
+    span: (usize, usize),
+
+    /// The rows and columns covered by cells spanning several rows.
+    covered: Vec<(Range<usize>, Range<usize>)>,
+
+    /// The sizes of cells spanning several columns or rows, applied to [`Self::curr_state`] at the end.
+    spanned_widths: Vec<(Range<usize>, f32)>,
+    spanned_heights: Vec<(Range<usize>, f32)>,
 }
 
 impl GridLayout {
@@ -114,9 +241,15 @@ impl GridLayout {
             min_cell_size: ui.spacing().interact_size,
             max_cell_size: Vec2::INFINITY,
             color_picker: None,
+            columns: Vec::new(),
+            start_row: 0,
 
             col: 0,
             row: 0,
+            span: (1, 1),
+            covered: Vec::new(),
+            spanned_widths: Vec::new(),
+            spanned_heights: Vec::new(),
         }
     }
 }
@@ -125,7 +258,110 @@ impl GridLayout {
     fn prev_col_width(&self, col: usize) -> f32 {
         self.prev_state
             .col_width(col)
-            .unwrap_or(self.min_cell_size.x)
+            .unwrap_or_else(|| self.min_col_width(col))
+    }
+
+    /// The width of the columns covered by the next cell, from the previous frame.
+    fn prev_spanned_width(&self) -> f32 {
+        (self.col..self.col + self.span.0)
+            .map(|col| self.prev_col_width(col))
+            .sum::<f32>()
+            + (self.span.0 - 1) as f32 * self.spacing.x
+    }
+
+    /// The height of the rows covered by the next cell, from the previous frame.
+    fn prev_spanned_height(&self) -> f32 {
+        (self.row..self.row + self.span.1)
+            .map(|row| self.prev_row_height(row))
+            .sum::<f32>()
+            + (self.span.1 - 1) as f32 * self.spacing.y
+    }
+
+    fn min_col_width(&self, col: usize) -> f32 {
+        self.fraction_col_width(col).unwrap_or(self.min_cell_size.x)
+    }
+
+    /// The width of a column with a [`Grid::col_width_fraction`].
+    fn fraction_col_width(&self, col: usize) -> Option<f32> {
+        let fraction = self.columns.get(col)?.width_fraction?;
+        let num_columns = self
+            .num_columns
+            .unwrap_or(self.prev_state.col_widths.len())
+            .max(col + 1);
+        let width = self.initial_available.width() - (num_columns - 1) as f32 * self.spacing.x;
+        width
+            .is_finite()
+            .then(|| (fraction * width).at_least(self.min_cell_size.x))
+    }
+
+    /// The width of the column of the next cell, if it has a [`Grid::col_width_fraction`].
+    fn fixed_cell_width(&self) -> Option<f32> {
+        if self.span.0 == 1 {
+            self.fraction_col_width(self.col)
+        } else {
+            None
+        }
+    }
+
+    fn col_left(&self, col: usize) -> f32 {
+        self.initial_available.min.x
+            + (0..col)
+                .map(|col| self.prev_col_width(col) + self.spacing.x)
+                .sum::<f32>()
+    }
+
+    fn row_top(&self, row: usize) -> f32 {
+        self.initial_available.min.y
+            + (self.start_row..row)
+                .map(|row| {
+                    let height = self
+                        .curr_state
+                        .row_height(row)
+                        .or_else(|| self.prev_state.row_height(row))
+                        .unwrap_or(self.min_cell_size.y);
+                    height + self.spacing.y
+                })
+                .sum::<f32>()
+    }
+
+    /// Move the cursor to the given cell, see [`crate::Ui::set_grid_cell`].
+    pub(crate) fn set_cell(&mut self, cell: GridCell, cursor: &mut Rect, painter: &Painter) {
+        if let Some(row) = cell.row.map(|row| self.start_row + row)
+            && row != self.row
+        {
+            // Paint the rows we jump over, and the one we jump to, like `end_row` would:
+            for passed_row in self.row + 1..=row {
+                self.paint_row(passed_row, self.row_top(passed_row), painter);
+            }
+            self.row = row;
+            cursor.min.y = self.row_top(self.row);
+            if cell.col.is_none() {
+                self.col = 0;
+                cursor.min.x = self.initial_available.min.x;
+            }
+        }
+        if let Some(col) = cell.col {
+            self.col = col;
+            cursor.min.x = self.col_left(col);
+        } else {
+            self.skip_covered_cells(cursor);
+        }
+        self.span = (cell.col_span, cell.row_span);
+    }
+
+    /// Move the cursor past the cells that are covered by a cell from a row above.
+    fn skip_covered_cells(&mut self, cursor: &mut Rect) {
+        while let Some((_, cols)) = self
+            .covered
+            .iter()
+            .find(|(rows, cols)| rows.contains(&self.row) && cols.contains(&self.col))
+        {
+            let end = cols.end;
+            for col in self.col..end {
+                cursor.min.x += self.prev_col_width(col) + self.spacing.x;
+            }
+            self.col = end;
+        }
     }
 
     fn prev_row_height(&self, row: usize) -> f32 {
@@ -135,19 +371,21 @@ impl GridLayout {
     }
 
     pub(crate) fn wrap_text(&self) -> bool {
-        self.max_cell_size.x.is_finite()
+        self.max_cell_size.x.is_finite() || self.fixed_cell_width().is_some()
     }
 
     pub(crate) fn available_rect(&self, region: &Region) -> Rect {
-        let is_last_column = Some(self.col + 1) == self.num_columns;
+        let is_last_column = Some(self.col + self.span.0) == self.num_columns;
 
-        let width = if is_last_column {
+        let width = if let Some(width) = self.fixed_cell_width() {
+            width
+        } else if is_last_column {
             // The first frame we don't really know the widths of the previous columns,
             // so returning a big available width here can cause trouble.
             if self.is_first_frame {
                 self.curr_state
                     .col_width(self.col)
-                    .unwrap_or(self.min_cell_size.x)
+                    .unwrap_or_else(|| self.min_col_width(self.col))
             } else {
                 (self.initial_available.right() - region.cursor.left())
                     .at_most(self.max_cell_size.x)
@@ -155,17 +393,21 @@ impl GridLayout {
         } else if self.max_cell_size.x.is_finite() {
             // TODO(emilk): should probably heed `prev_state` here too
             self.max_cell_size.x
+        } else if 1 < self.span.0 {
+            self.prev_spanned_width()
         } else {
             // If we want to allow width-filling widgets like [`Separator`] in one of the first cells
             // then we need to make sure they don't spill out of the first cell:
             self.prev_state
                 .col_width(self.col)
                 .or_else(|| self.curr_state.col_width(self.col))
-                .unwrap_or(self.min_cell_size.x)
+                .unwrap_or_else(|| self.min_col_width(self.col))
         };
 
-        // If something above was wider, we can be wider:
-        let width = width.max(self.curr_state.col_width(self.col).unwrap_or(0.0));
+        // If something above was wider, we can be wider (unless the column has a fixed width):
+        let width = self
+            .fixed_cell_width()
+            .unwrap_or_else(|| width.max(self.curr_state.col_width(self.col).unwrap_or(0.0)));
 
         let available = region.max_rect.intersect(region.cursor);
 
@@ -178,16 +420,19 @@ impl GridLayout {
     }
 
     pub(crate) fn next_cell(&self, cursor: Rect, child_size: Vec2) -> Rect {
-        let width = self.prev_state.col_width(self.col).unwrap_or(0.0);
-        let height = self.prev_row_height(self.row);
+        let width = if self.span.0 == 1 {
+            self.prev_state.col_width(self.col).unwrap_or(0.0)
+        } else {
+            self.prev_spanned_width()
+        };
+        let height = self.prev_spanned_height();
         let size = child_size.max(vec2(width, height));
         Rect::from_min_size(cursor.min, size).round_ui()
     }
 
-    #[expect(clippy::unused_self)]
     pub(crate) fn align_size_within_rect(&self, size: Vec2, frame: Rect) -> Rect {
-        // TODO(emilk): allow this alignment to be customized
-        Align2::LEFT_CENTER
+        let align = self.columns.get(self.col).and_then(|column| column.align);
+        Align2([align.unwrap_or(Align::LEFT), Align::Center])
             .align_size_within_rect(size, frame)
             .round_ui()
     }
@@ -203,8 +448,8 @@ impl GridLayout {
             let debug_expand_height = self.style.debug.show_expand_height;
             if debug_expand_width || debug_expand_height {
                 let rect = widget_rect;
-                let too_wide = rect.width() > self.prev_col_width(self.col);
-                let too_high = rect.height() > self.prev_row_height(self.row);
+                let too_wide = rect.width() > self.prev_spanned_width();
+                let too_high = rect.height() > self.prev_spanned_height();
 
                 if (debug_expand_width && too_wide) || (debug_expand_height && too_high) {
                     let painter = self.ctx.debug_painter();
@@ -227,29 +472,46 @@ impl GridLayout {
             }
         }
 
-        self.curr_state
-            .set_min_col_width(self.col, widget_rect.width().max(self.min_cell_size.x));
-        self.curr_state
-            .set_min_row_height(self.row, widget_rect.height().max(self.min_cell_size.y));
+        let (col_span, row_span) = std::mem::replace(&mut self.span, (1, 1));
+        let cols = self.col..self.col + col_span;
+        let rows = self.row..self.row + row_span;
+        let width = self
+            .fixed_cell_width()
+            .unwrap_or_else(|| widget_rect.width().max(self.min_col_width(self.col)));
+        let height = widget_rect.height().max(self.min_cell_size.y);
+        if col_span == 1 {
+            self.curr_state.set_min_col_width(self.col, width);
+        } else {
+            self.spanned_widths.push((cols.clone(), width));
+        }
+        if row_span == 1 {
+            self.curr_state.set_min_row_height(self.row, height);
+        } else {
+            self.spanned_heights.push((rows.clone(), height));
+            self.covered.push((rows.start + 1..rows.end, cols.clone()));
+        }
 
-        cursor.min.x += self.prev_col_width(self.col) + self.spacing.x;
-        self.col += 1;
+        for col in cols {
+            cursor.min.x += self.prev_col_width(col) + self.spacing.x;
+        }
+        self.col += col_span;
+        self.skip_covered_cells(cursor);
     }
 
-    fn paint_row(&self, cursor: &Rect, painter: &Painter) {
+    /// Paint the background of the row starting at `top`.
+    fn paint_row(&self, row: usize, top: f32, painter: &Painter) {
         // handle row color painting based on color-picker function
         let Some(color_picker) = self.color_picker.as_ref() else {
             return;
         };
-        let Some(row_color) = color_picker(self.row, &self.style) else {
+        let Some(row_color) = color_picker(row, &self.style) else {
             return;
         };
-        let Some(height) = self.prev_state.row_height(self.row) else {
+        let Some(height) = self.prev_state.row_height(row) else {
             return;
         };
-        // Paint background for coming row:
         let size = Vec2::new(self.prev_state.full_width(self.spacing.x), height);
-        let rect = Rect::from_min_size(cursor.min, size);
+        let rect = Rect::from_min_size(pos2(self.initial_available.min.x, top), size);
         let rect = rect.expand2(0.5 * self.spacing.y * Vec2::Y);
         let rect = rect.expand2(2.0 * Vec2::X); // HACK: just looks better with some spacing on the sides
 
@@ -266,11 +528,29 @@ impl GridLayout {
 
         self.col = 0;
         self.row += 1;
+        self.span = (1, 1);
+        self.skip_covered_cells(cursor);
 
-        self.paint_row(cursor, painter);
+        // Paint background for coming row:
+        self.paint_row(self.row, cursor.min.y, painter);
     }
 
-    pub(crate) fn save(&self) {
+    pub(crate) fn save(&mut self) {
+        for (cols, width) in std::mem::take(&mut self.spanned_widths) {
+            self.curr_state
+                .set_min_spanned_width(cols, width, self.spacing.x);
+        }
+        for (rows, height) in std::mem::take(&mut self.spanned_heights) {
+            self.curr_state
+                .set_min_spanned_height(rows, height, self.spacing.y);
+        }
+        // Cells spanning several columns never widen a column with a fixed fraction:
+        for col in 0..self.curr_state.col_widths.len() {
+            if let Some(width) = self.fraction_col_width(col) {
+                self.curr_state.col_widths[col] = width;
+            }
+        }
+
         // We need to always save state on the first frame, otherwise request_discard
         // would be called repeatedly (see #5132)
         if self.curr_state != self.prev_state || self.is_first_frame {
@@ -318,6 +598,7 @@ pub struct Grid {
     spacing: Option<Vec2>,
     start_row: usize,
     color_picker: Option<ColorPickerFn>,
+    columns: Vec<GridColumn>,
 }
 
 impl Grid {
@@ -332,6 +613,7 @@ impl Grid {
             spacing: None,
             start_row: 0,
             color_picker: None,
+            columns: Vec::new(),
         }
     }
 
@@ -405,6 +687,32 @@ impl Grid {
         self.start_row = start_row;
         self
     }
+
+    /// How to align the widgets of the given column horizontally.
+    /// Default: [`Align::LEFT`].
+    #[inline]
+    pub fn col_align(mut self, col: usize, align: Align) -> Self {
+        self.column_mut(col).align = Some(align);
+        self
+    }
+
+    /// Make the given column this fraction of the width available to the grid,
+    /// not counting the spacing between the columns.
+    ///
+    /// For instance, `0.25` for a column that takes up a quarter of the width.
+    /// Text in the column wraps to fit, and the column never grows wider than this.
+    #[inline]
+    pub fn col_width_fraction(mut self, col: usize, fraction: f32) -> Self {
+        self.column_mut(col).width_fraction = Some(fraction);
+        self
+    }
+
+    fn column_mut(&mut self, col: usize) -> &mut GridColumn {
+        if self.columns.len() <= col {
+            self.columns.resize_with(col + 1, Default::default);
+        }
+        &mut self.columns[col]
+    }
 }
 
 impl Grid {
@@ -426,6 +734,7 @@ impl Grid {
             spacing,
             start_row,
             mut color_picker,
+            columns,
         } = self;
         let min_col_width = min_col_width.unwrap_or_else(|| ui.spacing().interact_size.x);
         let min_row_height = min_row_height.unwrap_or_else(|| ui.spacing().interact_size.y);
@@ -465,6 +774,8 @@ impl Grid {
                     min_cell_size: vec2(min_col_width, min_row_height),
                     max_cell_size,
                     spacing,
+                    columns,
+                    start_row,
                     row: start_row,
                     ..GridLayout::new(ui, id, prev_state)
                 };
@@ -473,7 +784,7 @@ impl Grid {
                 if is_color {
                     let cursor = ui.cursor();
                     let painter = ui.painter();
-                    grid.paint_row(&cursor, painter);
+                    grid.paint_row(start_row, cursor.min.y, painter);
                 }
 
                 ui.set_grid(grid);
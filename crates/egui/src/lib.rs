@@ -475,8 +475,8 @@ pub use epaint::{
 pub mod text {
     pub use crate::text_selection::CCursorRange;
     pub use epaint::text::{
-        FontData, FontDefinitions, FontFamily, Fonts, Galley, LayoutJob, LayoutSection, TAB_SIZE,
-        TextFormat, TextWrapping, cursor::CCursor,
+        FontData, FontDefinitions, FontFamily, Fonts, Galley, InlineBox, LayoutJob, LayoutSection,
+        TAB_SIZE, TextFormat, TextWrapping, cursor::CCursor,
     };
 }
 
@@ -494,7 +494,7 @@ pub use self::{
     },
     drag_and_drop::DragAndDrop,
     epaint::text::TextWrapMode,
-    grid::Grid,
+    grid::{Grid, GridCell},
     id::{Id, IdMap},
     input_state::{InputOptions, InputState, MultiTouchInfo, PointerState, SurrenderFocusOn},
     layers::{LayerId, Order},
@@ -199,6 +199,14 @@ impl Placer {
         }
     }
 
+    /// Place the next widget in this cell, if in a grid layout.
+    /// Otherwise does nothing.
+    pub(crate) fn set_grid_cell(&mut self, cell: grid::GridCell, painter: &Painter) {
+        if let Some(grid) = &mut self.grid {
+            grid.set_cell(cell, &mut self.region.cursor, painter);
+        }
+    }
+
     /// Set row height in horizontal wrapping layout.
     pub(crate) fn set_row_height(&mut self, height: f32) {
         self.layout.set_row_height(&mut self.region, height);
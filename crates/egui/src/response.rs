@@ -892,6 +892,10 @@ impl Response {
         if let Some(hint_text) = info.hint_text {
             builder.set_placeholder(hint_text);
         }
+        if let Some(error) = info.error {
+            builder.set_invalid(accesskit::Invalid::True);
+            builder.set_description(error);
+        }
     }
 
     /// Associate a label with a control for accessibility.
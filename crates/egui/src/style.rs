@@ -984,6 +984,11 @@ pub struct Visuals {
     /// Defaults to [`Self::extreme_bg_color`].
     pub text_edit_bg_color: Option<Color32>,
 
+    /// The frame color of a [`crate::TextEdit`] with invalid contents, see [`crate::TextEdit::invalid`].
+    ///
+    /// Defaults to [`Self::error_fg_color`].
+    pub text_edit_invalid_color: Option<Color32>,
+
     /// Background color behind code-styled monospaced labels.
     pub code_bg_color: Color32,
 
@@ -1083,6 +1088,11 @@ impl Visuals {
         self.text_edit_bg_color.unwrap_or(self.extreme_bg_color)
     }
 
+    /// The frame color of a [`crate::TextEdit`] with invalid contents.
+    pub fn text_edit_invalid_color(&self) -> Color32 {
+        self.text_edit_invalid_color.unwrap_or(self.error_fg_color)
+    }
+
     /// Window background color.
     #[inline(always)]
     pub fn window_fill(&self) -> Color32 {
@@ -1399,6 +1409,7 @@ impl Visuals {
             faint_bg_color: Color32::from_additive_luminance(5), // visible, but barely so
             extreme_bg_color: Color32::from_gray(10),            // e.g. TextEdit background
             text_edit_bg_color: None, // use `extreme_bg_color` by default
+            text_edit_invalid_color: None, // use `error_fg_color` by default
             code_bg_color: Color32::from_gray(64),
             warn_fg_color: Color32::from_rgb(255, 143, 0), // orange
             error_fg_color: Color32::from_rgb(255, 0, 0),  // red
@@ -2095,6 +2106,7 @@ impl Visuals {
             faint_bg_color,
             extreme_bg_color,
             text_edit_bg_color,
+            text_edit_invalid_color,
             code_bg_color,
             warn_fg_color,
             error_fg_color,
@@ -2182,6 +2194,13 @@ impl Visuals {
 
                     ui_optional_color(ui, text_edit_bg_color, *extreme_bg_color, "TextEdit")
                         .on_hover_text("Background of TextEdit");
+                    ui_optional_color(
+                        ui,
+                        text_edit_invalid_color,
+                        *error_fg_color,
+                        "Invalid TextEdit",
+                    )
+                    .on_hover_text("Frame of a TextEdit with invalid contents");
                 });
         });
 
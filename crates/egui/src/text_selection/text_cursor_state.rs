@@ -3,7 +3,7 @@
 use epaint::text::{Galley, cursor::CCursor};
 use unicode_segmentation::UnicodeSegmentation as _;
 
-use crate::{NumExt as _, Rect, Response, Ui, epaint};
+use crate::{NumExt as _, Pos2, Rect, Response, Ui, epaint};
 
 use super::CCursorRange;
 
@@ -51,6 +51,132 @@ impl TextCursorState {
     }
 }
 
+/// Select a rectangular block of text, with one range per row.
+///
+/// `anchor` is where the drag started, and `pointer` is where it is now, both relative to the galley.
+/// The range on the row closest to `pointer` comes first, to become the main one.
+pub(crate) fn block_selection(galley: &Galley, anchor: Pos2, pointer: Pos2) -> Vec<CCursorRange> {
+    let (min_y, max_y) = (anchor.y.min(pointer.y), anchor.y.max(pointer.y));
+
+    let mut ranges = vec![];
+    let mut row_start = 0;
+    for row in &galley.rows {
+        if min_y <= row.max_y() && row.min_y() <= max_y {
+            let ccursor_at = |x: f32| {
+                let column = row.char_at(x - row.pos.x);
+                CCursor {
+                    index: row_start + column,
+                    prefer_next_row: column < row.char_count_excluding_newline(),
+                }
+            };
+            ranges.push(CCursorRange::two(
+                ccursor_at(anchor.x),
+                ccursor_at(pointer.x),
+            ));
+        }
+        row_start += row.char_count_including_newline();
+    }
+
+    if ranges.is_empty() {
+        // Dragging above or below the text:
+        ranges.push(CCursorRange::two(
+            galley.cursor_from_pos(anchor.to_vec2()),
+            galley.cursor_from_pos(pointer.to_vec2()),
+        ));
+    } else if anchor.y <= pointer.y {
+        ranges.reverse();
+    }
+
+    ranges
+}
+
+/// Merge any ranges that overlap.
+///
+/// Two selections that only touch are kept apart, but a cursor touching a selection is merged into it.
+///
+/// When two ranges are merged, the result takes the place (and direction) of the earlier one.
+pub(crate) fn merge_overlapping_ranges(ranges: &mut Vec<CCursorRange>) {
+    fn overlaps(a: &CCursorRange, b: &CCursorRange) -> bool {
+        let [a_min, a_max] = a.sorted_cursors();
+        let [b_min, b_max] = b.sorted_cursors();
+        if a.is_empty() || b.is_empty() {
+            a_min.index <= b_max.index && b_min.index <= a_max.index
+        } else {
+            a_min.index < b_max.index && b_min.index < a_max.index
+        }
+    }
+
+    loop {
+        let num_ranges = ranges.len();
+        let mut merged: Vec<CCursorRange> = Vec::with_capacity(num_ranges);
+        for range in ranges.drain(..) {
+            if let Some(existing) = merged.iter_mut().find(|r| overlaps(r, &range)) {
+                let [min, max] = existing.sorted_cursors();
+                let [other_min, other_max] = range.sorted_cursors();
+                let min = if other_min.index < min.index {
+                    other_min
+                } else {
+                    min
+                };
+                let max = if max.index < other_max.index {
+                    other_max
+                } else {
+                    max
+                };
+                *existing = if existing.is_sorted() && !existing.is_empty() {
+                    CCursorRange::two(max, min)
+                } else {
+                    CCursorRange::two(min, max)
+                };
+            } else {
+                merged.push(range);
+            }
+        }
+        *ranges = merged;
+        if ranges.len() == num_ranges {
+            return;
+        }
+    }
+}
+
+/// Add the next occurrence of the text selected by the main range (the first one) as a new main range.
+///
+/// The search starts after the main range, and wraps around to the start of the text.
+/// If the main range is empty, the word at it is selected instead.
+pub(crate) fn add_next_occurrence(text: &str, ranges: &mut Vec<CCursorRange>) {
+    let Some(main) = ranges.first().copied() else {
+        return;
+    };
+
+    if main.is_empty() {
+        ranges[0] = select_word_at(text, main.primary);
+        return;
+    }
+
+    let needle = main.slice_str(text);
+    let search_start = byte_index_from_char_index(text, main.as_sorted_char_range().end);
+    let (before, after) = text.split_at(search_start);
+    let candidates = after
+        .match_indices(needle)
+        .map(|(i, _)| search_start + i)
+        .chain(before.match_indices(needle).map(|(i, _)| i));
+
+    for byte_index in candidates {
+        let start = char_index_from_byte_index(text, byte_index);
+        let candidate = CCursorRange::two(
+            CCursor::new(start),
+            CCursor::new(start + needle.chars().count()),
+        );
+        let already_selected = ranges
+            .iter()
+            .any(|range| range.as_sorted_char_range() == candidate.as_sorted_char_range());
+        if !already_selected {
+            ranges.insert(0, candidate);
+            return;
+        }
+    }
+}
+
 impl TextCursorState {
     /// Handle clicking and/or dragging text.
     ///
@@ -321,7 +447,18 @@ pub fn cursor_rect(galley: &Galley, cursor: &CCursor, row_height: f32) -> Rect {
 
 #[cfg(test)]
 mod test {
-    use crate::text_selection::text_cursor_state::next_word_boundary_char_index;
+    use epaint::text::cursor::CCursor;
+
+    use crate::text_selection::{
+        CCursorRange,
+        text_cursor_state::{
+            add_next_occurrence, merge_overlapping_ranges, next_word_boundary_char_index,
+        },
+    };
+
+    fn range(min: usize, max: usize) -> CCursorRange {
+        CCursorRange::two(CCursor::new(min), CCursor::new(max))
+    }
 
     #[test]
     fn test_next_word_boundary_char_index() {
@@ -352,4 +489,54 @@ mod test {
         assert_eq!(next_word_boundary_char_index(text, 19), 20);
         assert_eq!(next_word_boundary_char_index(text, 20), 21);
     }
+
+    #[test]
+    fn test_merge_overlapping_ranges() {
+        let mut ranges = vec![range(2, 5), range(4, 8), range(0, 0), range(5, 5)];
+        merge_overlapping_ranges(&mut ranges);
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|r| r.as_sorted_char_range())
+                .collect::<Vec<_>>(),
+            vec![2..8, 0..0]
+        );
+
+        // Selections that only touch are kept apart:
+        let mut ranges = vec![range(0, 1), range(1, 2)];
+        merge_overlapping_ranges(&mut ranges);
+        assert_eq!(ranges.len(), 2);
+
+        // Cursors at the same place become one:
+        let mut ranges = vec![range(3, 3), range(3, 3)];
+        merge_overlapping_ranges(&mut ranges);
+        assert_eq!(ranges, vec![range(3, 3)]);
+    }
+
+    #[test]
+    fn test_add_next_occurrence() {
+        let text = "foo bar foo\nfoo";
+
+        // An empty range selects the word at it:
+        let mut ranges = vec![range(9, 9)];
+        add_next_occurrence(text, &mut ranges);
+        assert_eq!(ranges, vec![range(8, 11)]);
+
+        // Then the next occurrences are added, wrapping around:
+        add_next_occurrence(text, &mut ranges);
+        assert_eq!(ranges, vec![range(12, 15), range(8, 11)]);
+        add_next_occurrence(text, &mut ranges);
+        assert_eq!(ranges, vec![range(0, 3), range(12, 15), range(8, 11)]);
+
+        // Nothing more to add:
+        add_next_occurrence(text, &mut ranges);
+        assert_eq!(ranges.len(), 3);
+
+        // Adjacent occurrences:
+        let mut ranges = vec![range(0, 1)];
+        add_next_occurrence("aaa", &mut ranges);
+        add_next_occurrence("aaa", &mut ranges);
+        merge_overlapping_ranges(&mut ranges);
+        assert_eq!(ranges, vec![range(2, 3), range(1, 2), range(0, 1)]);
+    }
 }
@@ -2829,6 +2829,16 @@ impl Ui {
             .end_row(self.spacing().item_spacing, &self.painter().clone());
     }
 
+    /// Put the next widget in a specific cell of a [`crate::Grid`], possibly covering several cells.
+    ///
+    /// To put several widgets in the cell, group them with e.g. [`Self::horizontal`].
+    /// Outside of a grid this does nothing.
+    ///
+    /// See [`crate::GridCell`] for an example.
+    pub fn set_grid_cell(&mut self, cell: crate::GridCell) {
+        self.placer.set_grid_cell(cell, &self.painter().clone());
+    }
+
     /// Set row height in horizontal wrapping layout.
     pub fn set_row_height(&mut self, height: f32) {
         self.placer.set_row_height(height);
@@ -1,5 +1,5 @@
 use emath::GuiRounding as _;
-use epaint::text::TextFormat;
+use epaint::text::{FontFeature, TextFormat};
 use std::fmt::Formatter;
 use std::{borrow::Cow, sync::Arc};
 
@@ -8,6 +8,21 @@ use crate::{
     text::{LayoutJob, TextWrapping},
 };
 
+/// How far [`RichText::superscript`] raises the text, as a fraction of the font size.
+///
+/// Close to the `ySuperscriptYOffset` that common fonts have in their `OS/2` table.
+const SUPERSCRIPT_SHIFT: f32 = 0.35;
+
+/// How far [`RichText::subscript`] lowers the text, as a fraction of the font size.
+///
+/// Close to the `ySubscriptYOffset` that common fonts have in their `OS/2` table.
+const SUBSCRIPT_SHIFT: f32 = 0.15;
+
+/// The size of superscript and subscript text, as a fraction of the font size.
+///
+/// Close to the `ySuperscriptYSize` and `ySubscriptYSize` that common fonts have in their `OS/2` table.
+const SCRIPT_SIZE_FACTOR: f32 = 0.65;
+
 /// Text and optional style choices for it.
 ///
 /// The style choices (font, color) are applied to the entire text.
@@ -41,6 +56,10 @@ pub struct RichText {
     underline: bool,
     italics: bool,
     raised: bool,
+    superscript: bool,
+    subscript: bool,
+    baseline_shift: f32,
+    font_features: Vec<FontFeature>,
 }
 
 impl Default for RichText {
@@ -62,6 +81,10 @@ impl Default for RichText {
             underline: Default::default(),
             italics: Default::default(),
             raised: Default::default(),
+            superscript: Default::default(),
+            subscript: Default::default(),
+            baseline_shift: Default::default(),
+            font_features: Default::default(),
         }
     }
 }
@@ -287,6 +310,60 @@ impl RichText {
         self
     }
 
+    /// Smaller text above the baseline, e.g. the `2` in `m²`.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// let mut job = egui::text::LayoutJob::default();
+    /// let style = ui.style();
+    /// egui::RichText::new("m").append_to(&mut job, style, Default::default(), egui::Align::BOTTOM);
+    /// egui::RichText::new("2").superscript().append_to(&mut job, style, Default::default(), egui::Align::BOTTOM);
+    /// ui.label(job);
+    /// # });
+    /// ```
+    #[inline]
+    pub fn superscript(mut self) -> Self {
+        self.superscript = true;
+        self.subscript = false;
+        self
+    }
+
+    /// Smaller text below the baseline, e.g. the `2` in `H₂O`.
+    #[inline]
+    pub fn subscript(mut self) -> Self {
+        self.subscript = true;
+        self.superscript = false;
+        self
+    }
+
+    /// Move the text up (positive) or down (negative) from its baseline, in points.
+    ///
+    /// This is added to the shift of [`Self::superscript`] and [`Self::subscript`].
+    ///
+    /// See [`TextFormat::baseline_shift`].
+    #[inline]
+    pub fn baseline_shift(mut self, baseline_shift: f32) -> Self {
+        self.baseline_shift = baseline_shift;
+        self
+    }
+
+    /// Turn an OpenType feature of the font on or off.
+    ///
+    /// Requires the `text_shaping` feature of `epaint`, see [`TextFormat::font_features`].
+    #[inline]
+    pub fn font_feature(mut self, font_feature: FontFeature) -> Self {
+        self.font_features.push(font_feature);
+        self
+    }
+
+    /// Show lowercase letters as small capitals, if the font supports it.
+    ///
+    /// See [`Self::font_feature`].
+    #[inline]
+    pub fn small_caps(self) -> Self {
+        self.font_feature(FontFeature::SMALL_CAPS)
+    }
+
     /// Fill-color behind the text.
     #[inline]
     pub fn background_color(mut self, background_color: impl Into<Color32>) -> Self {
@@ -398,12 +475,16 @@ impl RichText {
             underline,
             italics,
             raised,
+            superscript,
+            subscript,
+            baseline_shift,
+            font_features,
         } = self;
 
         let line_color = text_color.unwrap_or_else(|| style.visuals.text_color());
         let text_color = text_color.unwrap_or(crate::Color32::PLACEHOLDER);
 
-        let font_id = {
+        let mut font_id = {
             let mut font_id = style.override_font_id.clone().unwrap_or_else(|| {
                 (text_style.as_ref().or(style.override_text_style.as_ref()))
                     .map(|text_style| text_style.resolve(style))
@@ -435,7 +516,17 @@ impl RichText {
             crate::Stroke::NONE
         };
 
-        let valign = if raised {
+        let mut baseline_shift = baseline_shift;
+        let valign = if superscript || subscript {
+            // Shift relative to the baseline of the row:
+            baseline_shift += if superscript {
+                SUPERSCRIPT_SHIFT
+            } else {
+                -SUBSCRIPT_SHIFT
+            } * font_id.size;
+            font_id.size *= SCRIPT_SIZE_FACTOR;
+            crate::Align::BOTTOM
+        } else if raised {
             crate::Align::TOP
         } else {
             default_valign
@@ -451,9 +542,12 @@ impl RichText {
                 background: background_color,
                 italics,
                 underline,
+                wavy_underline: crate::Stroke::NONE,
                 strikethrough,
                 valign,
                 expand_bg,
+                baseline_shift,
+                font_features,
             },
         )
     }
@@ -665,6 +759,24 @@ impl WidgetText {
         self.map_rich_text(|text| text.raised())
     }
 
+    /// Prefer using [`RichText`] directly!
+    #[inline]
+    pub fn superscript(self) -> Self {
+        self.map_rich_text(|text| text.superscript())
+    }
+
+    /// Prefer using [`RichText`] directly!
+    #[inline]
+    pub fn subscript(self) -> Self {
+        self.map_rich_text(|text| text.subscript())
+    }
+
+    /// Prefer using [`RichText`] directly!
+    #[inline]
+    pub fn small_caps(self) -> Self {
+        self.map_rich_text(|text| text.small_caps())
+    }
+
     /// Prefer using [`RichText`] directly!
     #[inline]
     pub fn background_color(self, background_color: impl Into<Color32>) -> Self {
@@ -21,6 +21,28 @@ use crate::{
 ///
 /// For full control of the text you can use [`crate::text::LayoutJob`]
 /// as argument to [`Self::new`].
+///
+/// To put images or widgets inside the text, reserve room for them with
+/// [`crate::text::LayoutJob::append_inline_box`], and place them where the text was wrapped to:
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui::text::{InlineBox, LayoutJob};
+///
+/// let style = ui.style();
+/// let mut job = LayoutJob::default();
+/// egui::RichText::new("Press ").append_to(&mut job, style, Default::default(), egui::Align::BOTTOM);
+/// let button = job.append_inline_box(InlineBox::new(egui::vec2(40.0, 16.0)), Default::default());
+/// egui::RichText::new(" to continue.").append_to(&mut job, style, Default::default(), egui::Align::BOTTOM);
+///
+/// let (pos, galley, response) = egui::Label::new(job).layout_in_ui(ui);
+/// ui.painter().galley(pos, galley.clone(), ui.visuals().text_color());
+/// for (section_index, rect) in galley.inline_box_rects() {
+///     if section_index == button {
+///         ui.put(rect.translate(pos.to_vec2()), egui::Button::new("OK"));
+///     }
+/// }
+/// # });
+/// ```
 #[must_use = "You should put this widget in a ui with `ui.add(widget);`"]
 pub struct Label {
     text: WidgetText,
@@ -14,6 +14,7 @@ mod hyperlink;
 mod image;
 mod image_button;
 mod label;
+mod number_input;
 mod progress_bar;
 mod radio_button;
 mod selected_label;
@@ -36,6 +37,7 @@ pub use self::{
     },
     image_button::ImageButton,
     label::Label,
+    number_input::NumberInput,
     progress_bar::ProgressBar,
     radio_button::RadioButton,
     separator::Separator,
@@ -0,0 +1,395 @@
+#![allow(clippy::needless_pass_by_value)] // False positives with `impl ToString`
+
+use std::ops::RangeInclusive;
+
+use crate::{Button, DragValue, MINUS_CHAR_STR, Response, Ui, Widget, emath, vec2};
+
+use super::drag_value::clamp_value_to_range;
+
+// ----------------------------------------------------------------------------
+
+/// Combined into one function (rather than two) to make it easier
+/// for the borrow checker.
+type GetSetValue<'a> = Box<dyn 'a + FnMut(Option<f64>) -> f64>;
+
+fn get(get_set_value: &mut GetSetValue<'_>) -> f64 {
+    (get_set_value)(None)
+}
+
+fn set(get_set_value: &mut GetSetValue<'_>, value: f64) {
+    (get_set_value)(Some(value));
+}
+
+/// A [`DragValue`] with `−` and `+` buttons on the sides, a unit, and arithmetic in the text input.
+///
+/// When editing the value as text, you can type:
+/// * a number, e.g. `12.5`
+/// * a number in another unit, e.g. `2 cm` into a field in `mm` (see [`Self::accept_unit`])
+/// * arithmetic with `+ - * /` and parentheses, e.g. `=3*4` or `(2 + 3) cm + 5 mm`.
+///   The leading `=` is optional.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut length_mm: f64 = 0.0;
+/// ui.add(
+///     egui::NumberInput::new(&mut length_mm)
+///         .range(0.0..=1000.0)
+///         .step(0.5)
+///         .unit("mm")
+///         .accept_unit("cm", 10.0)
+///         .accept_unit("m", 1000.0),
+/// );
+/// # });
+/// ```
+#[must_use = "You should put this widget in a ui with `ui.add(widget);`"]
+pub struct NumberInput<'a> {
+    get_set_value: GetSetValue<'a>,
+    step: f64,
+    speed: f64,
+    range: RangeInclusive<f64>,
+    min_decimals: usize,
+    max_decimals: Option<usize>,
+    unit: String,
+    units: Vec<(String, f64)>,
+}
+
+impl<'a> NumberInput<'a> {
+    pub fn new<Num: emath::Numeric>(value: &'a mut Num) -> Self {
+        let slf = Self::from_get_set(move |v: Option<f64>| {
+            if let Some(v) = v {
+                *value = Num::from_f64(v);
+            }
+            value.to_f64()
+        });
+
+        if Num::INTEGRAL {
+            slf.max_decimals(0).range(Num::MIN..=Num::MAX).speed(0.25)
+        } else {
+            slf
+        }
+    }
+
+    pub fn from_get_set(get_set_value: impl 'a + FnMut(Option<f64>) -> f64) -> Self {
+        Self {
+            get_set_value: Box::new(get_set_value),
+            step: 1.0,
+            speed: 1.0,
+            range: f64::NEG_INFINITY..=f64::INFINITY,
+            min_decimals: 0,
+            max_decimals: None,
+            unit: Default::default(),
+            units: Vec::new(),
+        }
+    }
+
+    /// How much the `−` and `+` buttons change the value.
+    ///
+    /// Default: `1.0`.
+    #[inline]
+    pub fn step(mut self, step: impl Into<f64>) -> Self {
+        self.step = step.into();
+        self
+    }
+
+    /// How much the value changes when dragged one point (logical pixel), see [`DragValue::speed`].
+    #[inline]
+    pub fn speed(mut self, speed: impl Into<f64>) -> Self {
+        self.speed = speed.into();
+        self
+    }
+
+    /// Sets valid range for the value.
+    ///
+    /// Values are clamped to this range, and the `−` and `+` buttons are disabled at its ends.
+    #[inline]
+    pub fn range<Num: emath::Numeric>(mut self, range: RangeInclusive<Num>) -> Self {
+        self.range = range.start().to_f64()..=range.end().to_f64();
+        self
+    }
+
+    /// See [`DragValue::min_decimals`].
+    #[inline]
+    pub fn min_decimals(mut self, min_decimals: usize) -> Self {
+        self.min_decimals = min_decimals;
+        self
+    }
+
+    /// See [`DragValue::max_decimals`].
+    ///
+    /// Stepping with the `−` and `+` buttons also rounds to this number of decimals.
+    #[inline]
+    pub fn max_decimals(mut self, max_decimals: usize) -> Self {
+        self.max_decimals = Some(max_decimals);
+        self
+    }
+
+    /// See [`DragValue::fixed_decimals`].
+    #[inline]
+    pub fn fixed_decimals(mut self, num_decimals: usize) -> Self {
+        self.min_decimals = num_decimals;
+        self.max_decimals = Some(num_decimals);
+        self
+    }
+
+    /// The unit of the value, e.g. `"mm"`.
+    ///
+    /// It is shown after the value, and may be typed after numbers.
+    #[inline]
+    pub fn unit(mut self, unit: impl ToString) -> Self {
+        self.unit = unit.to_string();
+        self
+    }
+
+    /// Also accept numbers typed in another unit.
+    ///
+    /// One `unit` is `factor` of the unit of the value,
+    /// e.g. `.unit("mm").accept_unit("cm", 10.0)` turns `2 cm` into `20`.
+    #[inline]
+    pub fn accept_unit(mut self, unit: impl ToString, factor: f64) -> Self {
+        self.units.push((unit.to_string(), factor));
+        self
+    }
+}
+
+impl Widget for NumberInput<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let Self {
+            mut get_set_value,
+            step,
+            speed,
+            range,
+            min_decimals,
+            max_decimals,
+            unit,
+            mut units,
+        } = self;
+
+        if !unit.is_empty() {
+            units.push((unit.clone(), 1.0));
+        }
+
+        let step_by = |ui: &mut Ui, get_set_value: &mut GetSetValue<'_>, sign: f64| {
+            let value = get(get_set_value);
+            let (text, enabled) = if sign < 0.0 {
+                (MINUS_CHAR_STR, *range.start() < value)
+            } else {
+                ("+", value < *range.end())
+            };
+            let button_size = vec2(ui.spacing().interact_size.y, ui.spacing().interact_size.y);
+            let mut response = ui.add_enabled(enabled, Button::new(text).min_size(button_size));
+            if response.clicked() {
+                let mut new_value = clamp_value_to_range(value + sign * step, range.clone());
+                if let Some(max_decimals) = max_decimals {
+                    new_value = emath::round_to_decimals(new_value, max_decimals);
+                }
+                set(get_set_value, new_value);
+                response.mark_changed();
+            }
+            response
+        };
+
+        ui.horizontal(|ui| {
+            let decrement = step_by(ui, &mut get_set_value, -1.0);
+
+            let suffix = if unit.is_empty() {
+                String::new()
+            } else {
+                format!(" {unit}")
+            };
+            let drag_value = ui.add(
+                DragValue::from_get_set(&mut get_set_value)
+                    .speed(speed)
+                    .range(range.clone())
+                    .min_decimals(min_decimals)
+                    .max_decimals_opt(max_decimals)
+                    .suffix(suffix)
+                    .custom_parser(|text| parse_expression(text, &units)),
+            );
+
+            let increment = step_by(ui, &mut get_set_value, 1.0);
+
+            drag_value.union(decrement).union(increment)
+        })
+        .inner
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Evaluate arithmetic like `=3*4` or `(2 + 3) cm + 5 mm`.
+///
+/// Each number or parenthesis may be followed by one of the `units`, which multiplies it by its factor.
+/// Like the default parser of [`DragValue`], this ignores whitespace and treats `−` as a normal minus.
+fn parse_expression(text: &str, units: &[(String, f64)]) -> Option<f64> {
+    let chars: Vec<char> = text
+        .trim()
+        .trim_start_matches('=')
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == '−' { '-' } else { c })
+        .collect();
+    let mut parser = ExpressionParser {
+        chars: &chars,
+        pos: 0,
+        depth: 0,
+        units,
+    };
+    let value = parser.sum()?;
+    (parser.pos == chars.len()).then_some(value)
+}
+
+struct ExpressionParser<'a> {
+    chars: &'a [char],
+    pos: usize,
+
+    /// How many [`Self::factor`] we are nested in, to not overflow the stack on e.g. a pasted `((((…`.
+    depth: usize,
+
+    units: &'a [(String, f64)],
+}
+
+impl ExpressionParser<'_> {
+    const MAX_DEPTH: usize = 64;
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        self.pos += found as usize;
+        found
+    }
+
+    /// `product (('+' | '-') product)*`
+    fn sum(&mut self) -> Option<f64> {
+        let mut value = self.product()?;
+        loop {
+            if self.eat('+') {
+                value += self.product()?;
+            } else if self.eat('-') {
+                value -= self.product()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// `factor (('*' | '/') factor)*`
+    fn product(&mut self) -> Option<f64> {
+        let mut value = self.factor()?;
+        loop {
+            if self.eat('*') {
+                value *= self.factor()?;
+            } else if self.eat('/') {
+                value /= self.factor()?;
+            } else {
+                return Some(value);
+            }
+        }
+    }
+
+    /// `('+' | '-') factor | (number | '(' sum ')') unit?`
+    ///
+    /// Fails if nested deeper than [`Self::MAX_DEPTH`].
+    fn factor(&mut self) -> Option<f64> {
+        if Self::MAX_DEPTH <= self.depth {
+            return None;
+        }
+        self.depth += 1;
+        let value = self.nested_factor();
+        self.depth -= 1;
+        value
+    }
+
+    fn nested_factor(&mut self) -> Option<f64> {
+        if self.eat('-') {
+            return Some(-self.factor()?);
+        }
+        if self.eat('+') {
+            return self.factor();
+        }
+
+        let value = if self.eat('(') {
+            let value = self.sum()?;
+            self.eat(')').then_some(value)?
+        } else {
+            self.number()?
+        };
+        Some(value * self.unit()?)
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.pos += 1;
+        }
+        // An exponent, as in `1.5e-3`, but not a unit starting with `e`:
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let sign = matches!(self.chars.get(self.pos + 1), Some('+' | '-')) as usize;
+            if self
+                .chars
+                .get(self.pos + 1 + sign)
+                .is_some_and(char::is_ascii_digit)
+            {
+                self.pos += 1 + sign;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+            }
+        }
+        let number: String = self.chars[start..self.pos].iter().collect();
+        number.parse().ok()
+    }
+
+    /// The factor of the unit at the current position, or `1.0` if there is none.
+    fn unit(&mut self) -> Option<f64> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_ascii_digit() && !"+-*/().".contains(c))
+        {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Some(1.0);
+        }
+        let unit: String = self.chars[start..self.pos].iter().collect();
+        self.units
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, factor)| *factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_expression;
+
+    #[test]
+    fn test_parse_expression() {
+        let units = [("mm".to_owned(), 1.0), ("cm".to_owned(), 10.0)];
+        let parse = |text| parse_expression(text, &units);
+
+        assert_eq!(parse("12.5"), Some(12.5));
+        assert_eq!(parse(" −1 234 "), Some(-1234.0));
+        assert_eq!(parse("1.5e-3"), Some(0.0015));
+        assert_eq!(parse("=3*4"), Some(12.0));
+        assert_eq!(parse("1 + 2 * 3"), Some(7.0));
+        assert_eq!(parse("-(1 + 2) * 3"), Some(-9.0));
+        assert_eq!(parse("2 cm"), Some(20.0));
+        assert_eq!(parse("2mm"), Some(2.0));
+        assert_eq!(parse("=(2 + 3) cm + 5 mm"), Some(55.0));
+        assert_eq!(parse("1 / 4"), Some(0.25));
+
+        assert_eq!(parse("2 km"), None, "Unknown unit");
+        assert_eq!(parse("(1 + 2"), None);
+        assert_eq!(parse("1 +"), None);
+        assert_eq!(parse(""), None);
+
+        assert_eq!(parse("((((--1))))"), Some(1.0));
+        let deep_parens = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert_eq!(parse(&deep_parens), None, "Too deeply nested");
+        assert_eq!(parse(&format!("{}1", "-".repeat(100_000))), None);
+    }
+}
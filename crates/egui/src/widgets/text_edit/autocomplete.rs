@@ -0,0 +1,68 @@
+use std::ops::Range;
+
+use super::TextBuffer;
+
+/// The suggestions shown below the cursor of a [`crate::TextEdit::autocomplete`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AutocompleteState {
+    /// The character range of the text that a suggestion replaces.
+    pub query: Range<usize>,
+
+    pub suggestions: Vec<String>,
+
+    /// Index into [`Self::suggestions`].
+    pub selected: usize,
+
+    /// Replace the query with the selected suggestion the next frame, e.g. after it was clicked.
+    pub accept: bool,
+}
+
+impl AutocompleteState {
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.suggestions.len();
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self
+            .selected
+            .checked_sub(1)
+            .unwrap_or(self.suggestions.len() - 1);
+    }
+}
+
+/// The character range of the text before `cursor` that the user wants to complete.
+///
+/// That is the word before the cursor, or with `whole_line` everything from the start of the line.
+pub(crate) fn query_before_cursor(
+    text: &dyn TextBuffer,
+    cursor: usize,
+    whole_line: bool,
+) -> Range<usize> {
+    let line_start = text.char_index_from_line(text.line_from_char_index(cursor));
+    if whole_line {
+        return line_start..cursor;
+    }
+    let before_cursor = text.char_range_text(line_start..cursor);
+    let word_len = before_cursor
+        .chars()
+        .rev()
+        .take_while(|c| !c.is_whitespace())
+        .count();
+    cursor - word_len..cursor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::query_before_cursor;
+
+    #[test]
+    fn test_query_before_cursor() {
+        let text = "first line\nlet föo = ba";
+        assert_eq!(query_before_cursor(&text, 23, false), 21..23);
+        assert_eq!(query_before_cursor(&text, 18, false), 15..18);
+        assert_eq!(query_before_cursor(&text, 14, false), 11..14);
+        assert_eq!(query_before_cursor(&text, 15, false), 15..15);
+        assert_eq!(query_before_cursor(&text, 23, true), 11..23);
+        assert_eq!(query_before_cursor(&text, 5, true), 0..5);
+    }
+}
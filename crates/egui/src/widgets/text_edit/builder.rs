@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{borrow::Cow, ops::Range, sync::Arc};
 
 use emath::{Rect, TSTransform};
 use epaint::{
@@ -8,18 +8,33 @@ use epaint::{
 
 use crate::{
     Align, Align2, Color32, Context, CursorIcon, Event, EventFilter, FontSelection, Id, ImeEvent,
-    Key, KeyboardShortcut, Margin, Modifiers, NumExt as _, Response, Sense, Shape, TextBuffer,
-    TextStyle, TextWrapMode, Ui, Vec2, Widget, WidgetInfo, WidgetText, WidgetWithState, epaint,
+    Key, KeyboardShortcut, Margin, Modifiers, NumExt as _, Popup, Pos2, Response, ScrollArea,
+    Sense, Shape, TextBuffer, TextStyle, TextWrapMode, Ui, Vec2, Widget, WidgetInfo, WidgetText,
+    WidgetWithState, epaint,
     os::OperatingSystem,
     output::OutputEvent,
     response, text_selection,
-    text_selection::{CCursorRange, text_cursor_state::cursor_rect, visuals::paint_text_selection},
+    text_selection::{
+        CCursorRange,
+        text_cursor_state::{
+            add_next_occurrence, block_selection, cursor_rect, merge_overlapping_ranges,
+        },
+        visuals::{paint_text_highlight, paint_text_selection},
+    },
     vec2,
 };
 
-use super::{TextEditOutput, TextEditState};
+use super::{
+    InputMask, TextEditDiagnostic, TextEditOutput, TextEditSearch, TextEditState,
+    autocomplete::{AutocompleteState, query_before_cursor},
+    diagnostic::{diagnostic_at, underline_diagnostics},
+    recording::{RecordingTextBuffer, TextChange},
+    search::SearchCommand,
+};
 
 type LayouterFn<'t> = &'t mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley>;
+type AutocompleteFn<'t> = &'t mut dyn FnMut(&str) -> Vec<String>;
+type InputFilterFn<'t> = &'t mut dyn FnMut(&str) -> bool;
 
 /// A text region that the user can edit the contents of.
 ///
@@ -59,6 +74,13 @@ type LayouterFn<'t> = &'t mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley
 /// }
 /// ```
 ///
+/// ## Multiple cursors
+/// In a multiline [`TextEdit`] you can Alt-drag to select a rectangular block of text, with one cursor per row,
+/// or press Ctrl-D (Cmd-D on Mac) to also select the next occurrence of the selected text.
+/// Typing, pasting and deleting is then done at each cursor. Press escape to go back to a single cursor.
+///
+/// The cursors are stored in [`TextEditState::char_ranges`](crate::text_edit::TextEditState::char_ranges).
+///
 /// ## Advanced usage
 /// See [`TextEdit::show`].
 ///
@@ -89,6 +111,12 @@ pub struct TextEdit<'t> {
     char_limit: usize,
     return_key: Option<KeyboardShortcut>,
     background_color: Option<Color32>,
+    search: Option<&'t mut TextEditSearch>,
+    autocomplete: Option<AutocompleteFn<'t>>,
+    input_filter: Option<InputFilterFn<'t>>,
+    mask: Option<InputMask>,
+    invalid: Option<String>,
+    diagnostics: &'t [TextEditDiagnostic],
 }
 
 impl WidgetWithState for TextEdit<'_> {
@@ -148,6 +176,12 @@ impl<'t> TextEdit<'t> {
             char_limit: usize::MAX,
             return_key: Some(KeyboardShortcut::new(Modifiers::NONE, Key::Enter)),
             background_color: None,
+            search: None,
+            autocomplete: None,
+            input_filter: None,
+            mask: None,
+            invalid: None,
+            diagnostics: &[],
         }
     }
 
@@ -264,7 +298,7 @@ impl<'t> TextEdit<'t> {
     /// # let mut my_code = String::new();
     /// # fn my_memoized_highlighter(s: &str) -> egui::text::LayoutJob { Default::default() }
     /// let mut layouter = |ui: &egui::Ui, buf: &dyn egui::TextBuffer, wrap_width: f32| {
-    ///     let mut layout_job: egui::text::LayoutJob = my_memoized_highlighter(buf.as_str());
+    ///     let mut layout_job: egui::text::LayoutJob = my_memoized_highlighter(&buf.text());
     ///     layout_job.wrap.max_width = wrap_width;
     ///     ui.fonts_mut(|f| f.layout_job(layout_job))
     /// };
@@ -396,6 +430,142 @@ impl<'t> TextEdit<'t> {
         self.return_key = return_key.into();
         self
     }
+
+    /// Highlight all matches of a search, and do its pending find or replace commands.
+    ///
+    /// See [`TextEditSearch`] for an example.
+    #[inline]
+    pub fn search(mut self, search: &'t mut TextEditSearch) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    /// Show a popup with suggestions for completing the text being typed.
+    ///
+    /// Each time the user types, `suggest` is called with the word before the cursor,
+    /// and the suggestions it returns are shown below the cursor.
+    /// In a singleline [`TextEdit`] it is instead called with all text before the cursor,
+    /// so that you can complete e.g. commands with spaces in them.
+    ///
+    /// The user picks a suggestion with the arrow keys and enter or tab, or by clicking it.
+    /// It then replaces the word (or text) before the cursor, as one undo step.
+    /// Escape closes the popup.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut command = String::new();
+    /// const COMMANDS: [&str; 3] = ["open file", "open folder", "quit"];
+    /// let mut suggest = |query: &str| {
+    ///     COMMANDS
+    ///         .iter()
+    ///         .filter(|command| command.starts_with(query))
+    ///         .map(|command| command.to_string())
+    ///         .collect()
+    /// };
+    /// ui.add(egui::TextEdit::singleline(&mut command).autocomplete(&mut suggest));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn autocomplete(mut self, suggest: &'t mut dyn FnMut(&str) -> Vec<String>) -> Self {
+        self.autocomplete = Some(suggest);
+        self
+    }
+
+    /// Decide which edits the user may make.
+    ///
+    /// Before each edit (typing, pasting, deleting, undo, accepting a suggestion, replacing a search match, …),
+    /// `filter` is called with what the text would be after it.
+    /// If it returns `false`, the edit is not made.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut digits = String::new();
+    /// let mut only_digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+    /// ui.add(egui::TextEdit::singleline(&mut digits).input_filter(&mut only_digits));
+    /// # });
+    /// ```
+    ///
+    /// Note that this has to make a copy of the text for each edit.
+    #[inline]
+    pub fn input_filter(mut self, filter: &'t mut dyn FnMut(&str) -> bool) -> Self {
+        self.input_filter = Some(filter);
+        self
+    }
+
+    /// Only allow text that follows the given [`InputMask`], filling in its literals while typing.
+    ///
+    /// Use [`InputMask::is_complete`] to check if the user is done,
+    /// and e.g. [`Self::invalid`] to show if they are not.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut date = String::new();
+    /// let mask = egui::text_edit::InputMask::new("9999-99-99");
+    /// let complete = mask.is_complete(&date);
+    /// ui.add(
+    ///     egui::TextEdit::singleline(&mut date)
+    ///         .hint_text("YYYY-MM-DD")
+    ///         .mask(mask)
+    ///         .invalid_opt((!complete).then(|| "Enter a date".to_owned())),
+    /// );
+    /// # });
+    /// ```
+    #[inline]
+    pub fn mask(mut self, mask: InputMask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Show that the text is invalid, e.g. not a number.
+    ///
+    /// The frame is then painted with [`crate::Visuals::text_edit_invalid_color`],
+    /// and the reason is given to screen readers via [`crate::WidgetInfo::error`].
+    #[inline]
+    pub fn invalid(mut self, reason: impl Into<String>) -> Self {
+        self.invalid = Some(reason.into());
+        self
+    }
+
+    /// Show that the text is invalid if `reason` is `Some`, see [`Self::invalid`].
+    #[inline]
+    pub fn invalid_opt(mut self, reason: Option<String>) -> Self {
+        self.invalid = reason;
+        self
+    }
+
+    /// Underline parts of the text with a wavy line, e.g. misspelled words or lint errors.
+    ///
+    /// The message of a [`TextEditDiagnostic`] is shown when hovering it,
+    /// and right-clicking it opens a menu with its suggestions.
+    /// Picking one replaces the text of the diagnostic, as one undo step.
+    ///
+    /// The underlines are added to the galley of the [`Self::layouter`], if any.
+    ///
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// # let mut text = String::new();
+    /// use egui::text_edit::TextEditDiagnostic;
+    ///
+    /// let mut diagnostics = vec![];
+    /// let mut char_index = 0;
+    /// for word in text.split(' ') {
+    ///     let len = word.chars().count();
+    ///     if word == "teh" {
+    ///         let range = char_index..char_index + len;
+    ///         diagnostics.push(
+    ///             TextEditDiagnostic::new(range, "Unknown word").suggestions(vec!["the".to_owned()]),
+    ///         );
+    ///     }
+    ///     char_index += len + 1;
+    /// }
+    /// ui.add(egui::TextEdit::multiline(&mut text).diagnostics(&diagnostics));
+    /// # });
+    /// ```
+    #[inline]
+    pub fn diagnostics(mut self, diagnostics: &'t [TextEditDiagnostic]) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -429,12 +599,29 @@ impl TextEdit<'_> {
         let background_color = self
             .background_color
             .unwrap_or_else(|| ui.visuals().text_edit_bg_color());
+        let is_invalid = self.invalid.is_some();
         let output = self.show_content(ui);
 
         if frame {
             let visuals = ui.style().interact(&output.response);
             let frame_rect = output.response.rect.expand(visuals.expansion);
-            let shape = if is_mutable {
+            let shape = if is_invalid {
+                let stroke_width = if output.response.has_focus() {
+                    ui.visuals().selection.stroke.width
+                } else {
+                    visuals.bg_stroke.width
+                };
+                epaint::RectShape::new(
+                    frame_rect,
+                    visuals.corner_radius,
+                    background_color,
+                    (
+                        stroke_width.at_least(1.0),
+                        ui.visuals().text_edit_invalid_color(),
+                    ),
+                    StrokeKind::Inside,
+                )
+            } else if is_mutable {
                 if output.response.has_focus() {
                     epaint::RectShape::new(
                         frame_rect,
@@ -493,6 +680,12 @@ impl TextEdit<'_> {
             char_limit,
             return_key,
             background_color: _,
+            search,
+            mut autocomplete,
+            input_filter,
+            mask,
+            invalid,
+            diagnostics,
         } = self;
 
         let text_color = text_color
@@ -500,7 +693,6 @@ impl TextEdit<'_> {
             // .unwrap_or_else(|| ui.style().interact(&response).text_color()); // too bright
             .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
 
-        let prev_text = text.as_str().to_owned();
         let hint_text_str = hint_text.text().to_owned();
 
         let font_id = font_selection.resolve(ui.style());
@@ -516,16 +708,54 @@ impl TextEdit<'_> {
 
         let font_id_clone = font_id.clone();
         let mut default_layouter = move |ui: &Ui, text: &dyn TextBuffer, wrap_width: f32| {
-            let text = mask_if_password(password, text.as_str());
-            let layout_job = if multiline {
-                LayoutJob::simple(text, font_id_clone.clone(), text_color, wrap_width)
-            } else {
-                LayoutJob::simple_singleline(text, font_id_clone.clone(), text_color)
+            let layout_job = || {
+                let mut job_text = String::new();
+                for chunk in text.chunks() {
+                    if password {
+                        job_text.extend(std::iter::repeat_n(
+                            epaint::text::PASSWORD_REPLACEMENT_CHAR,
+                            chunk.chars().count(),
+                        ));
+                    } else {
+                        job_text.push_str(&chunk);
+                    }
+                }
+                let text = job_text;
+                if multiline {
+                    LayoutJob::simple(text, font_id_clone.clone(), text_color, wrap_width)
+                } else {
+                    LayoutJob::simple_singleline(text, font_id_clone.clone(), text_color)
+                }
             };
-            ui.fonts_mut(|f| f.layout_job(layout_job))
+            if let Some(revision) = text.revision() {
+                // Don't get the whole text unless it changed since last frame:
+                let key = crate::util::hash((
+                    revision,
+                    password,
+                    multiline,
+                    &font_id_clone,
+                    text_color,
+                    emath::OrderedFloat(wrap_width),
+                ));
+                ui.fonts_mut(|f| f.layout_job_with_key(key, layout_job))
+            } else {
+                ui.fonts_mut(|f| f.layout_job(layout_job()))
+            }
         };
 
         let layouter = layouter.unwrap_or(&mut default_layouter);
+        let diagnostic_color = ui.visuals().error_fg_color;
+        let mut layouter_with_diagnostics = |ui: &Ui, text: &dyn TextBuffer, wrap_width: f32| {
+            let galley = layouter(ui, text, wrap_width);
+            if diagnostics.is_empty() {
+                galley
+            } else {
+                let job = underline_diagnostics(&galley.job, diagnostics, diagnostic_color);
+                ui.fonts_mut(|f| f.layout_job(job))
+            }
+        };
+        let layouter: &mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley> =
+            &mut layouter_with_diagnostics;
 
         let mut galley = layouter(ui, text, wrap_width);
 
@@ -594,13 +824,33 @@ impl TextEdit<'_> {
             }
 
             let is_being_dragged = ui.ctx().is_being_dragged(response.id);
-            let did_interact = state.cursor.pointer_interaction(
-                ui,
-                &response,
-                cursor_at_pointer,
-                &galley,
-                is_being_dragged,
-            );
+            let pointer_pos_in_galley = pointer_pos - rect.min.to_vec2() + state.text_offset;
+            let did_interact = if multiline
+                && response.sense.senses_drag()
+                && response.hovered()
+                && ui.input(|i| i.pointer.any_pressed() && i.modifiers.alt)
+            {
+                // The start of an Alt-drag block selection:
+                state.block_selection_anchor = Some(pointer_pos_in_galley);
+                state.set_char_ranges(block_selection(
+                    &galley,
+                    pointer_pos_in_galley,
+                    pointer_pos_in_galley,
+                ));
+                true
+            } else if is_being_dragged && let Some(anchor) = state.block_selection_anchor {
+                state.set_char_ranges(block_selection(&galley, anchor, pointer_pos_in_galley));
+                true
+            } else {
+                state.block_selection_anchor = None;
+                state.cursor.pointer_interaction(
+                    ui,
+                    &response,
+                    cursor_at_pointer,
+                    &galley,
+                    is_being_dragged,
+                )
+            };
 
             if did_interact || response.clicked() {
                 ui.memory_mut(|mem| mem.request_focus(response.id));
@@ -613,11 +863,54 @@ impl TextEdit<'_> {
             ui.ctx().set_cursor_icon(CursorIcon::Text);
         }
 
+        let mut editor = Editor::new(
+            ui.input(|i| i.time),
+            mask.as_ref(),
+            input_filter.map(|filter| filter as InputFilterFn<'_>),
+        );
+
         let mut cursor_range = None;
         let prev_cursor_range = state.cursor.range(&galley);
+        let mut suggestion_moved = false;
         if interactive && ui.memory(|mem| mem.has_focus(id)) {
             ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
 
+            if let Some(autocomplete_state) = &mut state.autocomplete {
+                // The popup takes the keys for picking a suggestion:
+                ui.input_mut(|i| {
+                    if i.consume_key(Modifiers::NONE, Key::ArrowDown) {
+                        autocomplete_state.select_next();
+                        suggestion_moved = true;
+                    }
+                    if i.consume_key(Modifiers::NONE, Key::ArrowUp) {
+                        autocomplete_state.select_previous();
+                        suggestion_moved = true;
+                    }
+                    if i.consume_key(Modifiers::NONE, Key::Enter)
+                        | i.consume_key(Modifiers::NONE, Key::Tab)
+                    {
+                        autocomplete_state.accept = true;
+                    }
+                });
+                if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape)) {
+                    state.autocomplete = None;
+                }
+            }
+            if let Some(autocomplete_state) = state.autocomplete.take_if(|a| a.accept)
+                && accept_suggestion(
+                    ui,
+                    &mut state,
+                    &mut editor,
+                    &autocomplete_state,
+                    text,
+                    &mut galley,
+                    layouter,
+                    wrap_width,
+                )
+            {
+                response.mark_changed();
+            }
+
             let default_cursor_range = if cursor_at_end {
                 CCursorRange::one(galley.end())
             } else {
@@ -627,11 +920,15 @@ impl TextEdit<'_> {
             let (changed, new_cursor_range) = events(
                 ui,
                 &mut state,
+                &mut editor,
                 text,
-                &mut galley,
-                layouter,
+                &mut GalleyLayout {
+                    galley: &mut galley,
+                    layouter: &mut *layouter,
+                    wrap_width,
+                    id,
+                },
                 id,
-                wrap_width,
                 multiline,
                 password,
                 default_cursor_range,
@@ -644,7 +941,112 @@ impl TextEdit<'_> {
                 response.mark_changed();
             }
             cursor_range = Some(new_cursor_range);
+
+            if let Some(suggest) = &mut autocomplete {
+                if changed {
+                    state.autocomplete = suggestions_at_cursor(&state, text, multiline, suggest);
+                } else if prev_cursor_range != cursor_range {
+                    state.autocomplete = None;
+                }
+            }
+            if state.autocomplete.is_some() {
+                // Keep tab and escape from moving the focus next frame:
+                let event_filter = EventFilter {
+                    tab: true,
+                    escape: true,
+                    ..event_filter
+                };
+                ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
+            } else if !state.extra_char_ranges().is_empty() {
+                // Keep escape from moving the focus next frame, so it can remove the extra cursors:
+                let event_filter = EventFilter {
+                    escape: true,
+                    ..event_filter
+                };
+                ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            let diagnostic_at_pointer = |pos: Pos2| {
+                let cursor = galley.cursor_from_pos(pos - rect.min + state.text_offset);
+                diagnostic_at(diagnostics, cursor.index).cloned()
+            };
+            if response.secondary_clicked() {
+                state.diagnostic_menu = response
+                    .interact_pointer_pos()
+                    .and_then(diagnostic_at_pointer);
+            } else if response.hovered()
+                && state.diagnostic_menu.is_none()
+                && let Some(diagnostic) = response.hover_pos().and_then(diagnostic_at_pointer)
+                && !diagnostic.message.is_empty()
+            {
+                response = response.on_hover_text_at_pointer(diagnostic.message);
+            }
+        } else {
+            state.diagnostic_menu = None;
+        }
+        if let Some(diagnostic) = state.diagnostic_menu.clone() {
+            let mut picked = None;
+            let menu = Popup::context_menu(&response).show(|ui| {
+                if !diagnostic.message.is_empty() {
+                    ui.label(&diagnostic.message);
+                    ui.separator();
+                }
+                if diagnostic.suggestions.is_empty() {
+                    ui.weak("No suggestions");
+                }
+                for suggestion in &diagnostic.suggestions {
+                    if ui.button(suggestion).clicked() {
+                        picked = Some(suggestion);
+                    }
+                }
+            });
+            if let Some(suggestion) = picked {
+                if replace_char_range(
+                    ui,
+                    &mut state,
+                    &mut editor,
+                    diagnostic.range.clone(),
+                    suggestion,
+                    text,
+                    &mut galley,
+                    layouter,
+                    wrap_width,
+                ) {
+                    response.mark_changed();
+                }
+                state.diagnostic_menu = None;
+            } else if menu.is_none() {
+                state.diagnostic_menu = None;
+            }
+        }
+
+        let mut search_matches = Arc::default();
+        let mut scroll_to_search_match = false;
+        if let Some(search) = search {
+            if let Some(command) = search.command.take() {
+                if run_search_command(
+                    ui,
+                    &mut state,
+                    &mut editor,
+                    search,
+                    command,
+                    text,
+                    &mut galley,
+                    layouter,
+                    wrap_width,
+                ) {
+                    response.mark_changed();
+                }
+                scroll_to_search_match = true;
+            }
+            search_matches = state.search_matches(search, text);
         }
+        let current_search_match = state.cursor.range(&galley).and_then(|cursor_range| {
+            let selection = cursor_range.as_sorted_char_range();
+            search_matches.iter().position(|m| *m == selection)
+        });
 
         let mut galley_pos = align
             .align_size_within_rect(galley.size(), rect)
@@ -689,7 +1091,7 @@ impl TextEdit<'_> {
         };
 
         if ui.is_rect_visible(rect) {
-            if text.as_str().is_empty() && !hint_text.is_empty() {
+            if text.is_empty() && !hint_text.is_empty() {
                 let hint_text_color = ui.visuals().weak_text_color();
                 let hint_text_font_id = hint_text_font.unwrap_or(font_id.into());
                 let galley = if multiline {
@@ -716,9 +1118,26 @@ impl TextEdit<'_> {
 
             let has_focus = ui.memory(|mem| mem.has_focus(id));
 
-            if has_focus && let Some(cursor_range) = state.cursor.range(&galley) {
+            if has_focus {
                 // Add text selection rectangles to the galley:
-                paint_text_selection(&mut galley, ui.visuals(), &cursor_range, None);
+                for cursor_range in state.ranges(&galley) {
+                    paint_text_selection(&mut galley, ui.visuals(), &cursor_range, None);
+                }
+            }
+
+            // Highlight search matches (behind the selection), and the selected one even when we don't have focus,
+            // since the focus is then usually in the search field:
+            for (i, search_match) in search_matches.iter().enumerate() {
+                let color = if current_search_match == Some(i) && !has_focus {
+                    ui.visuals().selection.bg_fill
+                } else {
+                    ui.visuals().warn_fg_color.gamma_multiply(0.3)
+                };
+                let range = CCursorRange::two(
+                    CCursor::new(search_match.start),
+                    CCursor::new(search_match.end),
+                );
+                paint_text_highlight(&mut galley, &range, color);
             }
 
             if !clip_text {
@@ -779,10 +1198,18 @@ impl TextEdit<'_> {
                     // * Don't repaint the ui because of a blinking cursor in an app that is not in focus
                     let viewport_has_focus = ui.ctx().input(|i| i.focused);
                     if viewport_has_focus {
-                        text_selection::visuals::paint_text_cursor(
+                        let cursor_rects: Vec<Rect> = state
+                            .ranges(&galley)
+                            .iter()
+                            .map(|range| {
+                                cursor_rect(&galley, &range.primary, row_height)
+                                    .translate(galley_pos.to_vec2())
+                            })
+                            .collect();
+                        text_selection::visuals::paint_text_cursors(
                             ui,
                             &painter,
-                            primary_cursor_rect,
+                            &cursor_rects,
                             now - state.last_interaction_time,
                         );
                     }
@@ -803,6 +1230,67 @@ impl TextEdit<'_> {
             }
         }
 
+        if scroll_to_search_match && let Some(cursor_range) = state.cursor.range(&galley) {
+            let match_rect = cursor_rect(&galley, &cursor_range.primary, row_height)
+                .translate(galley_pos.to_vec2());
+            ui.scroll_to_rect(match_rect + margin, None);
+        }
+
+        if let Some(autocomplete_state) = &mut state.autocomplete {
+            let has_focus = ui.memory(|mem| mem.has_focus(id));
+            let mut clicked_suggestion = None;
+
+            // Clicking the popup takes the focus from us, but we still want to see the click:
+            if has_focus || ui.input(|i| i.pointer.any_down() || i.pointer.any_released()) {
+                let cursor = CCursor::new(autocomplete_state.query.end);
+                let to_global = ui
+                    .ctx()
+                    .layer_transform_to_global(ui.layer_id())
+                    .unwrap_or_default();
+                let anchor_rect = to_global
+                    * cursor_rect(&galley, &cursor, row_height).translate(galley_pos.to_vec2());
+
+                Popup::new(
+                    id.with("autocomplete"),
+                    ui.ctx().clone(),
+                    anchor_rect,
+                    ui.layer_id(),
+                )
+                .show(|ui| {
+                    ScrollArea::vertical()
+                        .max_height(ui.spacing().combo_height)
+                        .show(ui, |ui| {
+                            for (i, suggestion) in autocomplete_state.suggestions.iter().enumerate()
+                            {
+                                let selected = i == autocomplete_state.selected;
+                                let response = ui.selectable_label(selected, suggestion);
+                                if selected && suggestion_moved {
+                                    response.scroll_to_me(None);
+                                }
+                                if response.clicked() {
+                                    clicked_suggestion = Some(i);
+                                }
+                            }
+                        });
+                });
+            }
+
+            if let Some(i) = clicked_suggestion {
+                // Replace the query next frame, when we have the focus back:
+                autocomplete_state.selected = i;
+                autocomplete_state.accept = true;
+                ui.memory_mut(|mem| mem.request_focus(id));
+                ui.ctx().request_repaint();
+            } else if !has_focus && !ui.input(|i| i.pointer.any_down()) {
+                state.autocomplete = None;
+            }
+        }
+
+        if response.lost_focus() {
+            // Leave multi-cursor editing:
+            state.set_char_ranges(state.cursor.char_range().into_iter().collect());
+        }
+
         // Ensures correct IME behavior when the text input area gains or loses focus.
         if state.ime_enabled && (response.gained_focus() || response.lost_focus()) {
             state.ime_enabled = false;
@@ -815,33 +1303,47 @@ impl TextEdit<'_> {
 
         state.clone().store(ui.ctx(), id);
 
-        if response.changed() {
-            response.widget_info(|| {
-                WidgetInfo::text_edit(
+        let text_edit_info = || {
+            let current_text = text.text();
+            let prev_text = (!editor.changes.is_empty()).then(|| {
+                let mut prev_text = current_text.clone().into_owned();
+                for change in editor.changes.iter().rev() {
+                    change.revert(&mut prev_text);
+                }
+                prev_text
+            });
+            let current_text = mask_if_password(password, &current_text);
+            let prev_text = prev_text.map_or_else(
+                || current_text.clone(),
+                |prev_text| mask_if_password(password, &prev_text),
+            );
+            WidgetInfo {
+                error: invalid.clone(),
+                ..WidgetInfo::text_edit(
                     ui.is_enabled(),
-                    mask_if_password(password, prev_text.as_str()),
-                    mask_if_password(password, text.as_str()),
+                    prev_text,
+                    current_text,
                     hint_text_str.as_str(),
                 )
-            });
+            }
+        };
+
+        if response.changed() {
+            response.widget_info(text_edit_info);
         } else if selection_changed {
             let cursor_range = cursor_range.unwrap();
             let char_range = cursor_range.primary.index..=cursor_range.secondary.index;
-            let info = WidgetInfo::text_selection_changed(
-                ui.is_enabled(),
-                char_range,
-                mask_if_password(password, text.as_str()),
-            );
-            response.output_event(OutputEvent::TextSelectionChanged(info));
-        } else {
-            response.widget_info(|| {
-                WidgetInfo::text_edit(
+            let info = WidgetInfo {
+                error: invalid.clone(),
+                ..WidgetInfo::text_selection_changed(
                     ui.is_enabled(),
-                    mask_if_password(password, prev_text.as_str()),
-                    mask_if_password(password, text.as_str()),
-                    hint_text_str.as_str(),
+                    char_range,
+                    mask_if_password(password, &text.text()),
                 )
-            });
+            };
+            response.output_event(OutputEvent::TextSelectionChanged(info));
+        } else {
+            response.widget_info(text_edit_info);
         }
 
         #[cfg(feature = "accesskit")]
@@ -871,6 +1373,8 @@ impl TextEdit<'_> {
             text_clip_rect,
             state,
             cursor_range,
+            search_matches,
+            current_search_match,
         }
     }
 }
@@ -894,15 +1398,16 @@ fn mask_if_password(is_password: bool, text: &str) -> String {
 // ----------------------------------------------------------------------------
 
 /// Check for (keyboard) events to edit the cursor and/or text.
+///
+/// Edits are applied at every cursor. Returns the new main cursor range.
 #[expect(clippy::too_many_arguments)]
-fn events(
+pub(super) fn events(
     ui: &crate::Ui,
     state: &mut TextEditState,
+    editor: &mut Editor<'_>,
     text: &mut dyn TextBuffer,
-    galley: &mut Arc<Galley>,
-    layouter: &mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley>,
+    layout: &mut dyn EditLayout,
     id: Id,
-    wrap_width: f32,
     multiline: bool,
     password: bool,
     default_cursor_range: CCursorRange,
@@ -911,15 +1416,32 @@ fn events(
     return_key: Option<KeyboardShortcut>,
 ) -> (bool, CCursorRange) {
     let os = ui.ctx().os();
+    let mask = editor.mask;
 
-    let mut cursor_range = state.cursor.range(galley).unwrap_or(default_cursor_range);
+    if !state.extra_char_ranges().is_empty()
+        && ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape))
+    {
+        // Go back to a single cursor:
+        state.set_char_ranges(state.cursor.char_range().into_iter().collect());
+    }
+
+    // The main cursor range comes first:
+    let mut cursor_ranges: Vec<CCursorRange> = state
+        .char_ranges()
+        .into_iter()
+        .map(|mut range| {
+            range.primary = layout.clamp_cursor(text, range.primary);
+            range.secondary = layout.clamp_cursor(text, range.secondary);
+            range
+        })
+        .collect();
+    if cursor_ranges.is_empty() {
+        cursor_ranges.push(default_cursor_range);
+    }
 
     // We feed state to the undoer both before and after handling input
     // so that the undoer creates automatic saves even when there are no events for a while.
-    state.undoer.lock().feed_state(
-        ui.input(|i| i.time),
-        &(cursor_range, text.as_str().to_owned()),
-    );
+    state.feed_undoer(editor.time, cursor_ranges[0], text);
 
     let copy_if_not_password = |ui: &Ui, text: String| {
         if !password {
@@ -938,35 +1460,58 @@ fn events(
     }
 
     for event in &events {
+        let cursor_range = cursor_ranges[0];
+        let num_cursors = cursor_ranges.len();
+
+        // First handle events that only changes the selection cursor, not the text:
+        let moved_cursors = move_cursors(&mut cursor_ranges, os, event, text, layout);
+
         let did_mutate_text = match event {
-            // First handle events that only changes the selection cursor, not the text:
-            event if cursor_range.on_event(os, event, galley, id) => None,
+            _ if moved_cursors => None,
 
             Event::Copy => {
-                if !cursor_range.is_empty() {
-                    copy_if_not_password(ui, cursor_range.slice_str(text.as_str()).to_owned());
+                if let Some(selected_text) = selected_text(text, &cursor_ranges) {
+                    copy_if_not_password(ui, selected_text);
                 }
                 None
             }
             Event::Cut => {
-                if cursor_range.is_empty() {
-                    None
+                if let Some(selected_text) = selected_text(text, &cursor_ranges) {
+                    copy_if_not_password(ui, selected_text);
+                    editor.edit(state, text, cursor_range, false, |text| {
+                        edit_at_each_cursor(text, &cursor_ranges, |text, _, cursor_range| {
+                            Some(CCursorRange::one(text.delete_selected(cursor_range)))
+                        })
+                    })
                 } else {
-                    copy_if_not_password(ui, cursor_range.slice_str(text.as_str()).to_owned());
-                    Some(CCursorRange::one(text.delete_selected(&cursor_range)))
+                    None
                 }
             }
             Event::Paste(text_to_insert) => {
                 if !text_to_insert.is_empty() {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-                    if multiline {
-                        text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
-                    } else {
-                        let single_line = text_to_insert.replace(['\r', '\n'], " ");
-                        text.insert_text_at(&mut ccursor, &single_line, char_limit);
-                    }
+                    // Pasting one line per cursor, e.g. after copying from the same number of cursors:
+                    let lines: Vec<&str> = text_to_insert.lines().collect();
+                    let one_line_per_cursor = 1 < num_cursors && lines.len() == num_cursors;
+
+                    editor.edit(state, text, cursor_range, false, |text| {
+                        edit_at_each_cursor(text, &cursor_ranges, |text, i, cursor_range| {
+                            let text_to_insert = if one_line_per_cursor {
+                                lines[i]
+                            } else {
+                                text_to_insert.as_str()
+                            };
+                            let mut ccursor = text.delete_selected(cursor_range);
+                            let text_to_insert = with_mask_literals(mask, ccursor, text_to_insert);
+                            if multiline {
+                                text.insert_text_at(&mut ccursor, &text_to_insert, char_limit);
+                            } else {
+                                let single_line = text_to_insert.replace(['\r', '\n'], " ");
+                                text.insert_text_at(&mut ccursor, &single_line, char_limit);
+                            }
 
-                    Some(CCursorRange::one(ccursor))
+                            Some(CCursorRange::one(ccursor))
+                        })
+                    })
                 } else {
                     None
                 }
@@ -974,11 +1519,15 @@ fn events(
             Event::Text(text_to_insert) => {
                 // Newlines are handled by `Key::Enter`.
                 if !text_to_insert.is_empty() && text_to_insert != "\n" && text_to_insert != "\r" {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-
-                    text.insert_text_at(&mut ccursor, text_to_insert, char_limit);
+                    editor.edit(state, text, cursor_range, false, |text| {
+                        edit_at_each_cursor(text, &cursor_ranges, |text, _, cursor_range| {
+                            let mut ccursor = text.delete_selected(cursor_range);
+                            let text_to_insert = with_mask_literals(mask, ccursor, text_to_insert);
+                            text.insert_text_at(&mut ccursor, &text_to_insert, char_limit);
 
-                    Some(CCursorRange::one(ccursor))
+                            Some(CCursorRange::one(ccursor))
+                        })
+                    })
                 } else {
                     None
                 }
@@ -988,16 +1537,18 @@ fn events(
                 pressed: true,
                 modifiers,
                 ..
-            } if multiline => {
-                let mut ccursor = text.delete_selected(&cursor_range);
-                if modifiers.shift {
-                    // TODO(emilk): support removing indentation over a selection?
-                    text.decrease_indentation(&mut ccursor);
-                } else {
-                    text.insert_text_at(&mut ccursor, "\t", char_limit);
-                }
-                Some(CCursorRange::one(ccursor))
-            }
+            } if multiline => editor.edit(state, text, cursor_range, false, |text| {
+                edit_at_each_cursor(text, &cursor_ranges, |text, _, cursor_range| {
+                    let mut ccursor = text.delete_selected(cursor_range);
+                    if modifiers.shift {
+                        // TODO(emilk): support removing indentation over a selection?
+                        text.decrease_indentation(&mut ccursor);
+                    } else {
+                        text.insert_text_at(&mut ccursor, "\t", char_limit);
+                    }
+                    Some(CCursorRange::one(ccursor))
+                })
+            }),
             Event::Key {
                 key,
                 pressed: true,
@@ -1008,10 +1559,14 @@ fn events(
             }) =>
             {
                 if multiline {
-                    let mut ccursor = text.delete_selected(&cursor_range);
-                    text.insert_text_at(&mut ccursor, "\n", char_limit);
-                    // TODO(emilk): if code editor, auto-indent by same leading tabs, + one if the lines end on an opening bracket
-                    Some(CCursorRange::one(ccursor))
+                    editor.edit(state, text, cursor_range, false, |text| {
+                        edit_at_each_cursor(text, &cursor_ranges, |text, _, cursor_range| {
+                            let mut ccursor = text.delete_selected(cursor_range);
+                            text.insert_text_at(&mut ccursor, "\n", char_limit);
+                            // TODO(emilk): if code editor, auto-indent by same leading tabs, + one if the lines end on an opening bracket
+                            Some(CCursorRange::one(ccursor))
+                        })
+                    })
                 } else {
                     ui.memory_mut(|mem| mem.surrender_focus(id)); // End input with enter
                     break;
@@ -1027,16 +1582,7 @@ fn events(
                 || (modifiers.matches_logically(Modifiers::SHIFT | Modifiers::COMMAND)
                     && *key == Key::Z) =>
             {
-                if let Some((redo_ccursor_range, redo_txt)) = state
-                    .undoer
-                    .lock()
-                    .redo(&(cursor_range, text.as_str().to_owned()))
-                {
-                    text.replace_with(redo_txt);
-                    Some(*redo_ccursor_range)
-                } else {
-                    None
-                }
+                editor.undo(state, text, cursor_range, true)
             }
 
             Event::Key {
@@ -1045,16 +1591,17 @@ fn events(
                 modifiers,
                 ..
             } if modifiers.matches_logically(Modifiers::COMMAND) => {
-                if let Some((undo_ccursor_range, undo_txt)) = state
-                    .undoer
-                    .lock()
-                    .undo(&(cursor_range, text.as_str().to_owned()))
-                {
-                    text.replace_with(undo_txt);
-                    Some(*undo_ccursor_range)
-                } else {
-                    None
-                }
+                editor.undo(state, text, cursor_range, false)
+            }
+
+            Event::Key {
+                key: Key::D,
+                pressed: true,
+                modifiers,
+                ..
+            } if multiline && modifiers.matches_logically(Modifiers::COMMAND) => {
+                add_next_occurrence(&text.text(), &mut cursor_ranges);
+                None
             }
 
             Event::Key {
@@ -1062,10 +1609,16 @@ fn events(
                 key,
                 pressed: true,
                 ..
-            } => check_for_mutating_key_press(os, &cursor_range, text, galley, modifiers, *key),
+            } => editor.edit(state, text, cursor_range, false, |text| {
+                edit_at_each_cursor(text, &cursor_ranges, |text, _, cursor_range| {
+                    check_for_mutating_key_press(os, cursor_range, text, layout, modifiers, *key)
+                })
+            }),
 
             Event::Ime(ime_event) => match ime_event {
                 ImeEvent::Enabled => {
+                    // IME composes text at the main cursor only:
+                    cursor_ranges.truncate(1);
                     state.ime_enabled = true;
                     state.ime_cursor_range = cursor_range;
                     None
@@ -1074,15 +1627,17 @@ fn events(
                     if text_mark == "\n" || text_mark == "\r" {
                         None
                     } else {
-                        // Empty prediction can be produced when user press backspace
-                        // or escape during IME, so we clear current text.
-                        let mut ccursor = text.delete_selected(&cursor_range);
-                        let start_cursor = ccursor;
-                        if !text_mark.is_empty() {
-                            text.insert_text_at(&mut ccursor, text_mark, char_limit);
-                        }
                         state.ime_cursor_range = cursor_range;
-                        Some(CCursorRange::two(start_cursor, ccursor))
+                        editor.edit(state, text, cursor_range, false, |text| {
+                            // Empty prediction can be produced when user press backspace
+                            // or escape during IME, so we clear current text.
+                            let mut ccursor = text.delete_selected(&cursor_range);
+                            let start_cursor = ccursor;
+                            if !text_mark.is_empty() {
+                                text.insert_text_at(&mut ccursor, text_mark, char_limit);
+                            }
+                            Some(vec![CCursorRange::two(start_cursor, ccursor)])
+                        })
                     }
                 }
                 ImeEvent::Commit(prediction) => {
@@ -1095,12 +1650,14 @@ fn events(
                             && cursor_range.secondary.index
                                 == state.ime_cursor_range.secondary.index
                         {
-                            let mut ccursor = text.delete_selected(&cursor_range);
-                            text.insert_text_at(&mut ccursor, prediction, char_limit);
-                            Some(CCursorRange::one(ccursor))
+                            editor.edit(state, text, cursor_range, false, |text| {
+                                let mut ccursor = text.delete_selected(&cursor_range);
+                                text.insert_text_at(&mut ccursor, prediction, char_limit);
+                                Some(vec![CCursorRange::one(ccursor)])
+                            })
                         } else {
                             let ccursor = cursor_range.primary;
-                            Some(CCursorRange::one(ccursor))
+                            Some(vec![CCursorRange::one(ccursor)])
                         }
                     }
                 }
@@ -1113,30 +1670,507 @@ fn events(
             _ => None,
         };
 
-        if let Some(new_ccursor_range) = did_mutate_text {
+        if let Some(new_cursor_ranges) = did_mutate_text {
             any_change = true;
 
             // Layout again to avoid frame delay, and to keep `text` and `galley` in sync.
-            *galley = layouter(ui, text, wrap_width);
+            layout.text_changed(ui, text);
 
-            // Set cursor_range using new galley:
-            cursor_range = new_ccursor_range;
+            // Set cursor_ranges using new galley:
+            cursor_ranges = new_cursor_ranges;
         }
+
+        // Cursors that ran into each other become one:
+        merge_overlapping_ranges(&mut cursor_ranges);
     }
 
-    state.cursor.set_char_range(Some(cursor_range));
+    let cursor_range = cursor_ranges[0];
+    state.set_char_ranges(cursor_ranges);
 
-    state.undoer.lock().feed_state(
-        ui.input(|i| i.time),
-        &(cursor_range, text.as_str().to_owned()),
-    );
+    state.feed_undoer(editor.time, cursor_range, text);
 
     (any_change, cursor_range)
 }
 
+/// Makes every change to the text of a [`TextEdit`], so that it is checked and can be undone.
+pub(super) struct Editor<'t> {
+    /// The time of this frame, for grouping edits into undo steps.
+    time: f64,
+
+    mask: Option<&'t InputMask>,
+    input_filter: Option<InputFilterFn<'t>>,
+
+    /// The changes made this frame, to tell screen readers what the text was before them.
+    changes: Vec<TextChange>,
+}
+
+impl<'t> Editor<'t> {
+    pub(super) fn new(
+        time: f64,
+        mask: Option<&'t InputMask>,
+        input_filter: Option<InputFilterFn<'t>>,
+    ) -> Self {
+        Self {
+            time,
+            mask,
+            input_filter,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Change `text` with `edit`, which returns the new cursor ranges.
+    ///
+    /// Returns `None` if the text did not change. The edit is only kept if the mask and input filter accept the text after it.
+    /// If `own_undo_step`, the text before and after the edit are added as undo points,
+    /// so that it is undone on its own. Other edits are grouped by the undoer as the text is fed to it.
+    fn edit(
+        &mut self,
+        state: &TextEditState,
+        text: &mut dyn TextBuffer,
+        cursor_range: CCursorRange,
+        own_undo_step: bool,
+        edit: impl FnOnce(&mut RecordingTextBuffer<'_>) -> Option<Vec<CCursorRange>>,
+    ) -> Option<Vec<CCursorRange>> {
+        let text_before = own_undo_step.then(|| text.text().into_owned());
+        let (new_cursor_ranges, changes) = self.apply(text, edit)?;
+        if let Some(text_before) = text_before {
+            let cursor_after = new_cursor_ranges.first().copied().unwrap_or(cursor_range);
+            let mut undoer = state.undoer.lock();
+            undoer.add_undo(&(cursor_range, text_before));
+            undoer.add_undo(&(cursor_after, text.text().into_owned()));
+        }
+        self.changes.extend(changes);
+        Some(new_cursor_ranges)
+    }
+
+    /// Undo (or redo) the last undo step, if the mask and input filter accept the text after it.
+    ///
+    /// Undo points that only differ in the cursor are skipped, so that the text changes if it can.
+    ///
+    /// Returns the new cursor ranges.
+    fn undo(
+        &mut self,
+        state: &TextEditState,
+        text: &mut dyn TextBuffer,
+        cursor_range: CCursorRange,
+        redo: bool,
+    ) -> Option<Vec<CCursorRange>> {
+        // Undo on a copy of the undoer, so that nothing is undone if the text is rejected:
+        let mut undoer = state.undoer.lock().clone();
+        let current_text = text.text().into_owned();
+        let mut undone = (cursor_range, current_text.clone());
+        let mut any_undone = false;
+        loop {
+            let next = if redo {
+                undoer.redo(&undone)
+            } else {
+                undoer.undo(&undone)
+            };
+            let Some(next) = next.cloned() else {
+                break;
+            };
+            undone = next;
+            any_undone = true;
+            if undone.1 != current_text {
+                break;
+            }
+        }
+        if !any_undone {
+            return None;
+        }
+        let (undo_cursor_range, undo_text) = undone;
+
+        if undo_text != current_text {
+            let (_, changes) = self.apply(text, |text| {
+                text.replace_with(&undo_text);
+                Some(vec![undo_cursor_range])
+            })?;
+            self.changes.extend(changes);
+        }
+        *state.undoer.lock() = undoer;
+        Some(vec![undo_cursor_range])
+    }
+
+    /// Make the edit to `text`, recording its changes.
+    ///
+    /// Returns `None` if nothing changed. With an input filter or mask, the edit is made to a copy of the text,
+    /// and only made to `text` if the copy passes them.
+    fn apply(
+        &mut self,
+        text: &mut dyn TextBuffer,
+        edit: impl FnOnce(&mut RecordingTextBuffer<'_>) -> Option<Vec<CCursorRange>>,
+    ) -> Option<(Vec<CCursorRange>, Vec<TextChange>)> {
+        if self.mask.is_none() && self.input_filter.is_none() {
+            let mut recording = RecordingTextBuffer::new(text);
+            let new_cursor_ranges = edit(&mut recording)?;
+            let changes = recording.changes;
+            return (!changes.is_empty()).then_some((new_cursor_ranges, changes));
+        }
+
+        let mut edited_text = text.text().into_owned();
+        let mut recording = RecordingTextBuffer::new(&mut edited_text);
+        let new_cursor_ranges = edit(&mut recording)?;
+        let changes = recording.changes;
+        if changes.is_empty() {
+            return None;
+        }
+
+        let accepted = self.mask.is_none_or(|mask| mask.accepts(&edited_text))
+            && (self.input_filter.as_mut()).is_none_or(|filter| filter(&edited_text));
+        if !accepted {
+            return None;
+        }
+        for change in &changes {
+            change.apply(text);
+        }
+        Some((new_cursor_ranges, changes))
+    }
+}
+
+/// What to insert at `ccursor` when the user types `typed`.
+fn with_mask_literals<'a>(
+    mask: Option<&InputMask>,
+    ccursor: CCursor,
+    typed: &'a str,
+) -> Cow<'a, str> {
+    match mask {
+        Some(mask) => Cow::Owned(mask.fill_literals(ccursor.index, typed)),
+        None => Cow::Borrowed(typed),
+    }
+}
+
+/// Let each cursor handle an event that only moves it, without changing the text.
+///
+/// Returns `true` if the event was handled.
+fn move_cursors(
+    cursor_ranges: &mut [CCursorRange],
+    os: OperatingSystem,
+    event: &Event,
+    text: &dyn TextBuffer,
+    layout: &dyn EditLayout,
+) -> bool {
+    let mut handled = false;
+    for cursor_range in cursor_ranges {
+        handled |= layout.move_cursor(text, os, event, cursor_range);
+    }
+    handled
+}
+
+/// How [`events`] finds where the cursors go, for the layout of a text edit.
+pub(super) trait EditLayout {
+    /// Clamp a cursor restored from the state to the text.
+    fn clamp_cursor(&self, text: &dyn TextBuffer, ccursor: CCursor) -> CCursor;
+
+    /// Move the cursor for a key press (or accessibility request) that does not change the text.
+    ///
+    /// Returns `true` if the event was handled.
+    fn move_cursor(
+        &self,
+        text: &dyn TextBuffer,
+        os: OperatingSystem,
+        event: &Event,
+        cursor_range: &mut CCursorRange,
+    ) -> bool;
+
+    fn begin_of_paragraph(&self, text: &dyn TextBuffer, ccursor: CCursor) -> CCursor;
+
+    fn end_of_paragraph(&self, text: &dyn TextBuffer, ccursor: CCursor) -> CCursor;
+
+    /// Called after each edit, so that the layout can follow the text.
+    fn text_changed(&mut self, ui: &Ui, text: &dyn TextBuffer);
+}
+
+/// The layout of a [`TextEdit`]: one galley of the whole text.
+struct GalleyLayout<'a> {
+    galley: &'a mut Arc<Galley>,
+    layouter: &'a mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley>,
+    wrap_width: f32,
+    id: Id,
+}
+
+impl EditLayout for GalleyLayout<'_> {
+    fn clamp_cursor(&self, _text: &dyn TextBuffer, ccursor: CCursor) -> CCursor {
+        self.galley.clamp_cursor(&ccursor)
+    }
+
+    fn move_cursor(
+        &self,
+        _text: &dyn TextBuffer,
+        os: OperatingSystem,
+        event: &Event,
+        cursor_range: &mut CCursorRange,
+    ) -> bool {
+        cursor_range.on_event(os, event, self.galley, self.id)
+    }
+
+    fn begin_of_paragraph(&self, _text: &dyn TextBuffer, ccursor: CCursor) -> CCursor {
+        self.galley.cursor_begin_of_paragraph(&ccursor)
+    }
+
+    fn end_of_paragraph(&self, _text: &dyn TextBuffer, ccursor: CCursor) -> CCursor {
+        self.galley.cursor_end_of_paragraph(&ccursor)
+    }
+
+    fn text_changed(&mut self, ui: &Ui, text: &dyn TextBuffer) {
+        *self.galley = (self.layouter)(ui, text, self.wrap_width);
+    }
+}
+
+/// The selected text of all cursors, in text order and one per line.
+///
+/// Returns `None` if nothing is selected.
+fn selected_text(text: &dyn TextBuffer, cursor_ranges: &[CCursorRange]) -> Option<String> {
+    if cursor_ranges.iter().all(|range| range.is_empty()) {
+        return None;
+    }
+
+    let mut sorted_ranges = cursor_ranges.to_vec();
+    sorted_ranges.sort_by_key(|range| range.as_sorted_char_range().start);
+    let selections: Vec<Cow<'_, str>> = sorted_ranges
+        .iter()
+        .map(|range| text.char_range_text(range.as_sorted_char_range()))
+        .collect();
+    Some(selections.join("\n"))
+}
+
+/// Apply `edit` at each cursor, returning the new cursor ranges if any of them did mutate `text`.
+///
+/// The cursors are edited back to front, so that the text (and galley) before the cursor
+/// that is being edited is still unchanged. `edit` is also given the index of the cursor in text order.
+fn edit_at_each_cursor(
+    text: &mut RecordingTextBuffer<'_>,
+    cursor_ranges: &[CCursorRange],
+    mut edit: impl FnMut(&mut dyn TextBuffer, usize, &CCursorRange) -> Option<CCursorRange>,
+) -> Option<Vec<CCursorRange>> {
+    let mut text_order: Vec<usize> = (0..cursor_ranges.len()).collect();
+    text_order.sort_by_key(|&i| cursor_ranges[i].as_sorted_char_range().start);
+
+    let mut new_ranges = cursor_ranges.to_vec();
+    let mut char_deltas = vec![0; cursor_ranges.len()];
+    let mut did_mutate = false;
+
+    for (order, &i) in text_order.iter().enumerate().rev() {
+        let first_change = text.changes.len();
+        let Some(new_range) = edit(text, order, &cursor_ranges[i]) else {
+            continue;
+        };
+        did_mutate = true;
+        new_ranges[i] = new_range;
+        char_deltas[order] = text.char_delta_since(first_change);
+    }
+
+    // Each cursor moves with the edits at the cursors before it:
+    let mut offset = 0;
+    for (order, &i) in text_order.iter().enumerate() {
+        let range = &mut new_ranges[i];
+        for ccursor in [&mut range.primary, &mut range.secondary] {
+            ccursor.index = ccursor.index.saturating_add_signed(offset);
+        }
+        offset += char_deltas[order];
+    }
+
+    did_mutate.then_some(new_ranges)
+}
+
+/// Ask `suggest` for completions of the text before the (single) cursor.
+fn suggestions_at_cursor(
+    state: &TextEditState,
+    text: &dyn TextBuffer,
+    multiline: bool,
+    suggest: &mut dyn FnMut(&str) -> Vec<String>,
+) -> Option<AutocompleteState> {
+    let cursor_range = state.cursor.char_range()?;
+    if !cursor_range.is_empty() || !state.extra_char_ranges().is_empty() {
+        return None;
+    }
+
+    let query = query_before_cursor(text, cursor_range.primary.index, !multiline);
+    if query.is_empty() {
+        return None;
+    }
+    let suggestions = suggest(&text.char_range_text(query.clone()));
+    (!suggestions.is_empty()).then_some(AutocompleteState {
+        query,
+        suggestions,
+        selected: 0,
+        accept: false,
+    })
+}
+
+/// Replace the query with the selected suggestion, as one undo step.
+///
+/// Returns `true` if the text changed.
+#[expect(clippy::too_many_arguments)]
+fn accept_suggestion(
+    ui: &Ui,
+    state: &mut TextEditState,
+    editor: &mut Editor<'_>,
+    autocomplete_state: &AutocompleteState,
+    text: &mut dyn TextBuffer,
+    galley: &mut Arc<Galley>,
+    layouter: &mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley>,
+    wrap_width: f32,
+) -> bool {
+    let Some(suggestion) = autocomplete_state
+        .suggestions
+        .get(autocomplete_state.selected)
+    else {
+        return false;
+    };
+    replace_char_range(
+        ui,
+        state,
+        editor,
+        autocomplete_state.query.clone(),
+        suggestion,
+        text,
+        galley,
+        layouter,
+        wrap_width,
+    )
+}
+
+/// Replace a character range of the text, as one undo step, and put the cursor after the replacement.
+///
+/// Returns `true` if the text changed, which the mask and input filter may prevent.
+#[expect(clippy::too_many_arguments)]
+fn replace_char_range(
+    ui: &Ui,
+    state: &mut TextEditState,
+    editor: &mut Editor<'_>,
+    range: Range<usize>,
+    replacement: &str,
+    text: &mut dyn TextBuffer,
+    galley: &mut Arc<Galley>,
+    layouter: &mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley>,
+    wrap_width: f32,
+) -> bool {
+    if !text.is_mutable() || text.char_count() < range.end {
+        return false;
+    }
+
+    let cursor_range = state.cursor.range(galley).unwrap_or_default();
+    let Some(new_cursor_ranges) = editor.edit(state, text, cursor_range, true, |text| {
+        text.delete_char_range(range.clone());
+        let end = range.start + text.insert_text(replacement, range.start);
+        Some(vec![CCursorRange::one(CCursor::new(end))])
+    }) else {
+        return false;
+    };
+
+    state.set_char_ranges(new_cursor_ranges);
+    *galley = layouter(ui, text, wrap_width);
+    true
+}
+
+/// Select a match of the search, or replace matches.
+///
+/// Returns `true` if the text was changed.
+#[expect(clippy::too_many_arguments)]
+fn run_search_command(
+    ui: &Ui,
+    state: &mut TextEditState,
+    editor: &mut Editor<'_>,
+    search: &mut TextEditSearch,
+    command: SearchCommand,
+    text: &mut dyn TextBuffer,
+    galley: &mut Arc<Galley>,
+    layouter: &mut dyn FnMut(&Ui, &dyn TextBuffer, f32) -> Arc<Galley>,
+    wrap_width: f32,
+) -> bool {
+    let select = |state: &mut TextEditState, m: &std::ops::Range<usize>| {
+        let range = CCursorRange::two(CCursor::new(m.start), CCursor::new(m.end));
+        state.cursor.set_char_range(Some(range));
+    };
+    let next_match = |matches: &[std::ops::Range<usize>], from: usize| {
+        matches
+            .iter()
+            .find(|m| from <= m.start)
+            .or_else(|| matches.first())
+            .cloned()
+    };
+
+    let matches = state.search_matches(search, text);
+    let cursor_range = state.cursor.range(galley).unwrap_or_default();
+    let selection = cursor_range.as_sorted_char_range();
+
+    match command {
+        SearchCommand::FindNext => {
+            if let Some(m) = next_match(&matches, selection.end) {
+                select(state, &m);
+            }
+            false
+        }
+
+        SearchCommand::FindPrevious => {
+            let previous_match = matches
+                .iter()
+                .rev()
+                .find(|m| m.end <= selection.start)
+                .or_else(|| matches.last());
+            if let Some(m) = previous_match {
+                select(state, m);
+            }
+            false
+        }
+
+        SearchCommand::Replace => {
+            if !text.is_mutable() || !matches.contains(&selection) {
+                if let Some(m) = next_match(&matches, selection.end) {
+                    select(state, &m);
+                }
+                return false;
+            }
+
+            let replacement = search.replacement_for(&text.text(), &selection);
+            let Some(new_cursor_ranges) = editor.edit(state, text, cursor_range, true, |text| {
+                text.delete_char_range(selection.clone());
+                let end = selection.start + text.insert_text(&replacement, selection.start);
+                Some(vec![CCursorRange::one(CCursor::new(end))])
+            }) else {
+                return false;
+            };
+
+            let replaced_end = new_cursor_ranges[0].primary;
+            let new_cursor_range =
+                next_match(&state.search_matches(search, text), replaced_end.index)
+                    .map_or(CCursorRange::one(replaced_end), |m| {
+                        CCursorRange::two(CCursor::new(m.start), CCursor::new(m.end))
+                    });
+            state.cursor.set_char_range(Some(new_cursor_range));
+            *galley = layouter(ui, text, wrap_width);
+            true
+        }
+
+        SearchCommand::ReplaceAll => {
+            if !text.is_mutable() {
+                return false;
+            }
+            let replacements = search.replacements_in(&text.text());
+            let Some(first_match) = replacements.first().map(|(m, _)| m.start) else {
+                return false;
+            };
+            let Some(new_cursor_ranges) = editor.edit(state, text, cursor_range, true, |text| {
+                // Back to front, so the ranges of the matches before stay the same:
+                for (m, replacement) in replacements.iter().rev() {
+                    text.delete_char_range(m.clone());
+                    text.insert_text(replacement, m.start);
+                }
+                Some(vec![CCursorRange::one(CCursor::new(first_match))])
+            }) else {
+                return false;
+            };
+
+            state.set_char_ranges(new_cursor_ranges);
+            *galley = layouter(ui, text, wrap_width);
+            true
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 
-fn remove_ime_incompatible_events(events: &mut Vec<Event>) {
+pub(super) fn remove_ime_incompatible_events(events: &mut Vec<Event>) {
     // Remove key events which cause problems while 'IME' is being used.
     // See https://github.com/emilk/egui/pull/4509
     events.retain(|event| {
@@ -1162,14 +2196,14 @@ fn check_for_mutating_key_press(
     os: OperatingSystem,
     cursor_range: &CCursorRange,
     text: &mut dyn TextBuffer,
-    galley: &Galley,
+    layout: &dyn EditLayout,
     modifiers: &Modifiers,
     key: Key,
 ) -> Option<CCursorRange> {
     match key {
         Key::Backspace => {
             let ccursor = if modifiers.mac_cmd {
-                text.delete_paragraph_before_cursor(galley, cursor_range)
+                delete_paragraph_before_cursor(text, layout, cursor_range)
             } else if let Some(cursor) = cursor_range.single() {
                 if modifiers.alt || modifiers.ctrl {
                     // alt on mac, ctrl on windows
@@ -1185,7 +2219,7 @@ fn check_for_mutating_key_press(
 
         Key::Delete if !modifiers.shift || os != OperatingSystem::Windows => {
             let ccursor = if modifiers.mac_cmd {
-                text.delete_paragraph_after_cursor(galley, cursor_range)
+                delete_paragraph_after_cursor(text, layout, cursor_range)
             } else if let Some(cursor) = cursor_range.single() {
                 if modifiers.alt || modifiers.ctrl {
                     // alt on mac, ctrl on windows
@@ -1209,12 +2243,12 @@ fn check_for_mutating_key_press(
         }
 
         Key::K if modifiers.ctrl => {
-            let ccursor = text.delete_paragraph_after_cursor(galley, cursor_range);
+            let ccursor = delete_paragraph_after_cursor(text, layout, cursor_range);
             Some(CCursorRange::one(ccursor))
         }
 
         Key::U if modifiers.ctrl => {
-            let ccursor = text.delete_paragraph_before_cursor(galley, cursor_range);
+            let ccursor = delete_paragraph_before_cursor(text, layout, cursor_range);
             Some(CCursorRange::one(ccursor))
         }
 
@@ -1230,3 +2264,33 @@ fn check_for_mutating_key_press(
         _ => None,
     }
 }
+
+/// Like [`TextBuffer::delete_paragraph_before_cursor`], with the paragraph from `layout`.
+fn delete_paragraph_before_cursor(
+    text: &mut dyn TextBuffer,
+    layout: &dyn EditLayout,
+    cursor_range: &CCursorRange,
+) -> CCursor {
+    let [min, max] = cursor_range.sorted_cursors();
+    let min = layout.begin_of_paragraph(text, min);
+    if min == max {
+        text.delete_previous_char(min)
+    } else {
+        text.delete_selected(&CCursorRange::two(min, max))
+    }
+}
+
+/// Like [`TextBuffer::delete_paragraph_after_cursor`], with the paragraph from `layout`.
+fn delete_paragraph_after_cursor(
+    text: &mut dyn TextBuffer,
+    layout: &dyn EditLayout,
+    cursor_range: &CCursorRange,
+) -> CCursor {
+    let [min, max] = cursor_range.sorted_cursors();
+    let max = layout.end_of_paragraph(text, max);
+    if min == max {
+        text.delete_next_char(min)
+    } else {
+        text.delete_selected(&CCursorRange::two(min, max))
+    }
+}
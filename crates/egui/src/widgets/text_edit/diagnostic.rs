@@ -0,0 +1,169 @@
+use std::ops::Range;
+
+use epaint::{Color32, Stroke, text::LayoutJob};
+
+/// A marked range of the text in a [`crate::TextEdit`], e.g. a misspelled word or a lint error.
+///
+/// It is underlined with a wavy line, its message is shown on hover,
+/// and its suggestions are offered in the right-click menu.
+///
+/// Pass them to [`crate::TextEdit::diagnostics`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TextEditDiagnostic {
+    /// The character range (not byte range) of the text this is about.
+    pub range: Range<usize>,
+
+    /// What is wrong, e.g. "Unknown word" or "Unused variable".
+    pub message: String,
+
+    /// Replacements for the text in [`Self::range`], offered in the right-click menu.
+    pub suggestions: Vec<String>,
+
+    /// The color of the wavy underline.
+    ///
+    /// If `None` (the default), [`crate::Visuals::error_fg_color`] is used.
+    pub color: Option<Color32>,
+}
+
+impl TextEditDiagnostic {
+    pub fn new(range: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+            suggestions: Vec::new(),
+            color: None,
+        }
+    }
+
+    /// Replacements for the text, offered in the right-click menu.
+    #[inline]
+    pub fn suggestions(mut self, suggestions: Vec<String>) -> Self {
+        self.suggestions = suggestions;
+        self
+    }
+
+    /// The color of the wavy underline, e.g. [`crate::Visuals::warn_fg_color`] for warnings.
+    #[inline]
+    pub fn color(mut self, color: Color32) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// The diagnostic at the cursor `char_index`, if any.
+///
+/// A cursor right before or after a diagnostic counts as on it.
+pub(crate) fn diagnostic_at(
+    diagnostics: &[TextEditDiagnostic],
+    char_index: usize,
+) -> Option<&TextEditDiagnostic> {
+    diagnostics.iter().find(|diagnostic| {
+        diagnostic.range.contains(&char_index) || diagnostic.range.end == char_index
+    })
+}
+
+/// Split the sections of `job` so that the text of each diagnostic gets a wavy underline.
+pub(crate) fn underline_diagnostics(
+    job: &LayoutJob,
+    diagnostics: &[TextEditDiagnostic],
+    default_color: Color32,
+) -> LayoutJob {
+    let byte_offsets: Vec<usize> = job
+        .text
+        .char_indices()
+        .map(|(byte_index, _)| byte_index)
+        .chain([job.text.len()])
+        .collect();
+    let underlines: Vec<(Range<usize>, Stroke)> = diagnostics
+        .iter()
+        .filter(|diagnostic| {
+            !diagnostic.range.is_empty() && diagnostic.range.end < byte_offsets.len()
+        })
+        .map(|diagnostic| {
+            let byte_range =
+                byte_offsets[diagnostic.range.start]..byte_offsets[diagnostic.range.end];
+            let stroke = Stroke::new(1.0, diagnostic.color.unwrap_or(default_color));
+            (byte_range, stroke)
+        })
+        .collect();
+
+    let mut sections = Vec::with_capacity(job.sections.len() + 2 * underlines.len());
+    for section in &job.sections {
+        let section_range = section.byte_range.clone();
+        let mut cuts: Vec<usize> = underlines
+            .iter()
+            .flat_map(|(range, _)| [range.start, range.end])
+            .filter(|&cut| section_range.start < cut && cut < section_range.end)
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        cuts.push(section_range.end);
+
+        let mut start = section_range.start;
+        for end in cuts {
+            let mut piece = section.clone();
+            piece.byte_range = start..end;
+            if start != section_range.start {
+                piece.leading_space = 0.0;
+            }
+            if let Some((_, stroke)) = underlines
+                .iter()
+                .find(|(range, _)| range.start <= start && end <= range.end)
+            {
+                piece.format.wavy_underline = *stroke;
+            }
+            sections.push(piece);
+            start = end;
+        }
+    }
+
+    LayoutJob {
+        sections,
+        ..job.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use epaint::{Color32, FontId, Stroke, text::LayoutJob};
+
+    use super::{TextEditDiagnostic, diagnostic_at, underline_diagnostics};
+
+    #[test]
+    fn test_underline_diagnostics() {
+        let job = LayoutJob::simple_singleline(
+            "Thiss is wröng".into(),
+            FontId::default(),
+            Color32::WHITE,
+        );
+        let diagnostics = [
+            TextEditDiagnostic::new(0..5, "Unknown word"),
+            TextEditDiagnostic::new(9..14, "Unknown word").color(Color32::YELLOW),
+        ];
+        let underlined = underline_diagnostics(&job, &diagnostics, Color32::RED);
+
+        let pieces: Vec<(&str, Stroke)> = underlined
+            .sections
+            .iter()
+            .map(|section| {
+                (
+                    &underlined.text[section.byte_range.clone()],
+                    section.format.wavy_underline,
+                )
+            })
+            .collect();
+        assert_eq!(
+            pieces,
+            [
+                ("Thiss", Stroke::new(1.0, Color32::RED)),
+                (" is ", Stroke::NONE),
+                ("wröng", Stroke::new(1.0, Color32::YELLOW)),
+            ]
+        );
+
+        assert_eq!(diagnostic_at(&diagnostics, 5), Some(&diagnostics[0]));
+        assert_eq!(diagnostic_at(&diagnostics, 7), None);
+        assert_eq!(diagnostic_at(&diagnostics, 9), Some(&diagnostics[1]));
+    }
+}
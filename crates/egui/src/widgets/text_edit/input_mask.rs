@@ -0,0 +1,92 @@
+/// A pattern that the text of a [`crate::TextEdit`] has to follow, e.g. for dates, phone numbers or IP addresses.
+///
+/// In the pattern, `9` stands for a digit, `a` for a letter, and `*` for a letter or digit.
+/// Any other character is a literal, which is filled in automatically while typing.
+///
+/// Use it with [`crate::TextEdit::mask`].
+///
+/// ```
+/// let date = egui::text_edit::InputMask::new("9999-99-99");
+/// assert!(date.accepts("2024-0"));
+/// assert!(!date.accepts("2024-x"));
+/// assert!(!date.is_complete("2024-0"));
+/// assert!(date.is_complete("2024-01-31"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct InputMask {
+    pattern: Vec<char>,
+}
+
+impl InputMask {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.chars().collect(),
+        }
+    }
+
+    /// Does `text` match the start of the pattern, so that typing more can complete it?
+    pub fn accepts(&self, text: &str) -> bool {
+        text.chars().count() <= self.pattern.len()
+            && text
+                .chars()
+                .zip(&self.pattern)
+                .all(|(c, &pattern)| Self::matches(pattern, c))
+    }
+
+    /// Does `text` match the whole pattern?
+    pub fn is_complete(&self, text: &str) -> bool {
+        text.chars().count() == self.pattern.len() && self.accepts(text)
+    }
+
+    /// What to insert at `char_index` when the user types `typed`,
+    /// with the literals of the pattern filled in before the typed characters that need them.
+    pub(crate) fn fill_literals(&self, char_index: usize, typed: &str) -> String {
+        let mut filled = String::with_capacity(typed.len());
+        let mut index = char_index;
+        for c in typed.chars() {
+            while let Some(&pattern) = self.pattern.get(index)
+                && Self::is_literal(pattern)
+                && pattern != c
+            {
+                filled.push(pattern);
+                index += 1;
+            }
+            filled.push(c);
+            index += 1;
+        }
+        filled
+    }
+
+    fn is_literal(pattern: char) -> bool {
+        !matches!(pattern, '9' | 'a' | '*')
+    }
+
+    fn matches(pattern: char, c: char) -> bool {
+        match pattern {
+            '9' => c.is_ascii_digit(),
+            'a' => c.is_alphabetic(),
+            '*' => c.is_alphanumeric(),
+            literal => literal == c,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InputMask;
+
+    #[test]
+    fn test_fill_literals() {
+        let phone = InputMask::new("(999) 999-9999");
+        assert_eq!(phone.fill_literals(0, "5"), "(5");
+        assert_eq!(phone.fill_literals(4, "1"), ") 1");
+        assert_eq!(phone.fill_literals(4, ")"), ")");
+        assert_eq!(phone.fill_literals(0, "5551234567"), "(555) 123-4567");
+        assert!(phone.is_complete("(555) 123-4567"));
+
+        // Past the end of the pattern nothing is filled in, and the filter rejects it:
+        assert_eq!(phone.fill_literals(14, "1"), "1");
+        assert!(!phone.accepts("(555) 123-45678"));
+    }
+}
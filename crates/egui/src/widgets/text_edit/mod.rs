@@ -1,9 +1,27 @@
+mod autocomplete;
 mod builder;
+mod diagnostic;
+mod input_mask;
 mod output;
+mod recording;
+#[cfg(feature = "rope")]
+mod rope_buffer;
+mod search;
 mod state;
 mod text_buffer;
+mod virtual_text_edit;
 
 pub use {
-    crate::text_selection::TextCursorState, builder::TextEdit, output::TextEditOutput,
-    state::TextEditState, text_buffer::TextBuffer,
+    crate::text_selection::TextCursorState,
+    builder::TextEdit,
+    diagnostic::TextEditDiagnostic,
+    input_mask::InputMask,
+    output::TextEditOutput,
+    search::TextEditSearch,
+    state::TextEditState,
+    text_buffer::TextBuffer,
+    virtual_text_edit::{VirtualTextEdit, VirtualTextEditState},
 };
+
+#[cfg(feature = "rope")]
+pub use rope_buffer::RopeBuffer;
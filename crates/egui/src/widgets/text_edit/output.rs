@@ -21,6 +21,12 @@ pub struct TextEditOutput {
 
     /// Where the text cursor is.
     pub cursor_range: Option<CCursorRange>,
+
+    /// The character ranges of all matches of the [`crate::TextEdit::search`], if any.
+    pub search_matches: Arc<Vec<std::ops::Range<usize>>>,
+
+    /// The index into [`Self::search_matches`] of the match that is selected, if any.
+    pub current_search_match: Option<usize>,
 }
 
 // TODO(emilk): add `output.paint` and `output.store` and split out that code from `TextEdit::show`.
@@ -0,0 +1,188 @@
+use std::{borrow::Cow, ops::Range};
+
+use super::TextBuffer;
+
+/// One change of a text: the characters `removed` at `start`, and what was `inserted` instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TextChange {
+    /// Character index of the change.
+    pub start: usize,
+    pub removed: String,
+    pub inserted: String,
+}
+
+impl TextChange {
+    /// Make this change to `text`.
+    pub fn apply(&self, text: &mut dyn TextBuffer) {
+        text.delete_char_range(self.start..self.start + self.removed.chars().count());
+        text.insert_text(&self.inserted, self.start);
+    }
+
+    /// Undo this change of `text`.
+    pub fn revert(&self, text: &mut dyn TextBuffer) {
+        text.delete_char_range(self.start..self.start + self.inserted.chars().count());
+        text.insert_text(&self.removed, self.start);
+    }
+
+    /// How many characters longer the text got.
+    fn char_delta(&self) -> isize {
+        self.inserted.chars().count() as isize - self.removed.chars().count() as isize
+    }
+}
+
+/// Forwards to another [`TextBuffer`], recording every change made to it.
+///
+/// Clearing and replacing the text are not forwarded, so they are recorded as a deletion and an insertion.
+pub(crate) struct RecordingTextBuffer<'a> {
+    text: &'a mut dyn TextBuffer,
+    pub changes: Vec<TextChange>,
+}
+
+impl<'a> RecordingTextBuffer<'a> {
+    pub fn new(text: &'a mut dyn TextBuffer) -> Self {
+        Self {
+            text,
+            changes: Vec::new(),
+        }
+    }
+
+    /// How many characters were inserted minus deleted, by the changes from `first_change` on.
+    ///
+    /// Used for editing at many cursors without counting all the characters after each edit.
+    pub fn char_delta_since(&self, first_change: usize) -> isize {
+        self.changes[first_change..]
+            .iter()
+            .map(TextChange::char_delta)
+            .sum()
+    }
+}
+
+impl TextBuffer for RecordingTextBuffer<'_> {
+    fn is_mutable(&self) -> bool {
+        self.text.is_mutable()
+    }
+
+    fn as_str(&self) -> &str {
+        self.text.as_str()
+    }
+
+    fn text(&self) -> Cow<'_, str> {
+        self.text.text()
+    }
+
+    fn chunks(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        self.text.chunks()
+    }
+
+    fn revision(&self) -> Option<u64> {
+        self.text.revision()
+    }
+
+    fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+        let num_inserted = self.text.insert_text(text, char_index);
+        if 0 < num_inserted {
+            let inserted = match text.char_indices().nth(num_inserted) {
+                Some((byte_index, _)) => &text[..byte_index],
+                None => text,
+            };
+            self.changes.push(TextChange {
+                start: char_index,
+                removed: String::new(),
+                inserted: inserted.to_owned(),
+            });
+        }
+        num_inserted
+    }
+
+    fn delete_char_range(&mut self, char_range: Range<usize>) {
+        // Deleting past the end, e.g. with the delete key, changes nothing:
+        let end = char_range.end.min(self.text.char_count());
+        let char_range = char_range.start.min(end)..end;
+        if char_range.is_empty() {
+            return;
+        }
+        let removed = self.text.char_range_text(char_range.clone()).into_owned();
+        self.text.delete_char_range(char_range.clone());
+        self.changes.push(TextChange {
+            start: char_range.start,
+            removed,
+            inserted: String::new(),
+        });
+    }
+
+    fn char_range(&self, char_range: Range<usize>) -> &str {
+        self.text.char_range(char_range)
+    }
+
+    fn char_range_text(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        self.text.char_range_text(char_range)
+    }
+
+    fn char_count(&self) -> usize {
+        self.text.char_count()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    fn line_count(&self) -> usize {
+        self.text.line_count()
+    }
+
+    fn line(&self, line: usize) -> Cow<'_, str> {
+        self.text.line(line)
+    }
+
+    fn line_from_char_index(&self, char_index: usize) -> usize {
+        self.text.line_from_char_index(char_index)
+    }
+
+    fn char_index_from_line(&self, line: usize) -> usize {
+        self.text.char_index_from_line(line)
+    }
+
+    fn byte_index_from_char_index(&self, char_index: usize) -> usize {
+        self.text.byte_index_from_char_index(char_index)
+    }
+
+    fn char_index_from_byte_index(&self, byte_index: usize) -> usize {
+        self.text.char_index_from_byte_index(byte_index)
+    }
+
+    fn type_id(&self) -> std::any::TypeId {
+        TextBuffer::type_id(self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecordingTextBuffer, TextBuffer as _};
+
+    #[test]
+    fn test_recorded_changes_apply_to_a_copy() {
+        let mut text = String::from("Hello world");
+        let mut copy = text.clone();
+
+        let mut recording = RecordingTextBuffer::new(&mut text);
+        recording.delete_char_range(5..11);
+        recording.insert_text(", wörld", 5);
+        recording.delete_char_range(20..30);
+        let changes = recording.changes;
+        assert_eq!(changes.len(), 2, "Deleting past the end changes nothing");
+
+        for change in &changes {
+            change.apply(&mut copy);
+        }
+        assert_eq!(copy, "Hello, wörld");
+        assert_eq!(text, copy);
+
+        let mut recording = RecordingTextBuffer::new(&mut text);
+        recording.replace_with("Bye");
+        assert_eq!(
+            recording.changes.len(),
+            2,
+            "Replacing is recorded as a delete and an insert"
+        );
+    }
+}
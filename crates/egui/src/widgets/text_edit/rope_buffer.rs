@@ -0,0 +1,451 @@
+use std::{
+    borrow::Cow,
+    ops::Range,
+    sync::{
+        OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use ropey::Rope;
+
+use super::TextBuffer;
+
+/// A [`TextBuffer`] backed by a rope, for editing large texts.
+///
+/// Inserting and deleting is `O(log N)` in the size of the text, instead of `O(N)` as for a [`String`].
+/// Use it with [`super::VirtualTextEdit`] to edit texts of many megabytes.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut text = egui::text_edit::RopeBuffer::from("Hello\nworld");
+/// egui::ScrollArea::both().show(ui, |ui| {
+///     ui.add(egui::text_edit::VirtualTextEdit::new(&mut text));
+/// });
+/// # });
+/// ```
+///
+/// Lines are only separated by `\n`.
+///
+/// The text is not stored as one contiguous `str`, so [`TextBuffer::as_str`] and [`TextBuffer::char_range`]
+/// copy the whole text into one, once after each change. [`crate::TextEdit`] and [`super::VirtualTextEdit`]
+/// only use [`TextBuffer::chunks`] and [`TextBuffer::char_range_text`], which don't.
+///
+/// With the `serde` feature, this is serialized as a string.
+#[derive(Clone, Debug, Default)]
+pub struct RopeBuffer {
+    rope: Rope,
+
+    /// See [`TextBuffer::revision`]. Zero for the empty buffer from [`Default`].
+    revision: u64,
+
+    /// The whole text as one `str`, built by [`TextBuffer::as_str`].
+    contiguous: OnceLock<String>,
+}
+
+/// A revision that no [`RopeBuffer`] had before.
+fn new_revision() -> u64 {
+    static NEXT_REVISION: AtomicU64 = AtomicU64::new(1);
+    NEXT_REVISION.fetch_add(1, Ordering::Relaxed)
+}
+
+impl RopeBuffer {
+    pub fn new(rope: Rope) -> Self {
+        Self {
+            rope,
+            revision: new_revision(),
+            contiguous: OnceLock::new(),
+        }
+    }
+
+    /// The underlying rope.
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    pub fn into_rope(self) -> Rope {
+        self.rope
+    }
+
+    fn changed(&mut self) {
+        self.revision = new_revision();
+        self.contiguous = OnceLock::new();
+    }
+
+    /// Clamp `char_range` to the text, like the byte conversions for a `String` do.
+    fn clamp_char_range(&self, char_range: Range<usize>) -> Range<usize> {
+        let len = self.rope.len_chars();
+        char_range.start.min(len)..char_range.end.min(len)
+    }
+}
+
+impl From<Rope> for RopeBuffer {
+    fn from(rope: Rope) -> Self {
+        Self::new(rope)
+    }
+}
+
+impl From<&str> for RopeBuffer {
+    fn from(text: &str) -> Self {
+        Self::new(Rope::from_str(text))
+    }
+}
+
+impl From<String> for RopeBuffer {
+    fn from(text: String) -> Self {
+        Self::new(Rope::from(text))
+    }
+}
+
+impl std::fmt::Display for RopeBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.rope, f)
+    }
+}
+
+impl PartialEq for RopeBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        self.rope == other.rope
+    }
+}
+
+impl Eq for RopeBuffer {}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RopeBuffer {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.rope)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RopeBuffer {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl TextBuffer for RopeBuffer {
+    fn is_mutable(&self) -> bool {
+        true
+    }
+
+    fn as_str(&self) -> &str {
+        self.contiguous.get_or_init(|| self.rope.to_string())
+    }
+
+    fn text(&self) -> Cow<'_, str> {
+        self.char_range_text(0..self.rope.len_chars())
+    }
+
+    fn chunks(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        Box::new(self.rope.chunks().map(Cow::Borrowed))
+    }
+
+    fn revision(&self) -> Option<u64> {
+        Some(self.revision)
+    }
+
+    fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+        self.rope
+            .insert(char_index.min(self.rope.len_chars()), text);
+        self.changed();
+        text.chars().count()
+    }
+
+    fn delete_char_range(&mut self, char_range: Range<usize>) {
+        assert!(
+            char_range.start <= char_range.end,
+            "start must be <= end, but got {char_range:?}"
+        );
+        self.rope.remove(self.clamp_char_range(char_range));
+        self.changed();
+    }
+
+    fn char_range(&self, char_range: Range<usize>) -> &str {
+        let char_range = self.clamp_char_range(char_range);
+        let start = char_range.start;
+        let slice = self.rope.slice(char_range);
+        slice.as_str().unwrap_or_else(|| {
+            let start = self.rope.char_to_byte(start);
+            &self.as_str()[start..start + slice.len_bytes()]
+        })
+    }
+
+    fn char_range_text(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        let slice = self.rope.slice(self.clamp_char_range(char_range));
+        match slice.as_str() {
+            Some(text) => Cow::Borrowed(text),
+            None => Cow::Owned(slice.to_string()),
+        }
+    }
+
+    fn char_count(&self) -> usize {
+        self.rope.len_chars()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rope.len_bytes() == 0
+    }
+
+    fn line_count(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    fn line(&self, line: usize) -> Cow<'_, str> {
+        if self.rope.len_lines() <= line {
+            return Cow::Borrowed("");
+        }
+        let start = self.rope.line_to_char(line);
+        let mut end = self.rope.line_to_char(line + 1);
+        if start < end && self.rope.char(end - 1) == '\n' {
+            end -= 1;
+        }
+        self.char_range_text(start..end)
+    }
+
+    fn line_from_char_index(&self, char_index: usize) -> usize {
+        self.rope
+            .char_to_line(char_index.min(self.rope.len_chars()))
+    }
+
+    fn char_index_from_line(&self, line: usize) -> usize {
+        self.rope.line_to_char(line.min(self.rope.len_lines()))
+    }
+
+    fn byte_index_from_char_index(&self, char_index: usize) -> usize {
+        self.rope
+            .char_to_byte(char_index.min(self.rope.len_chars()))
+    }
+
+    fn char_index_from_byte_index(&self, byte_index: usize) -> usize {
+        self.rope
+            .byte_to_char(byte_index.min(self.rope.len_bytes()))
+    }
+
+    fn clear(&mut self) {
+        self.rope = Rope::new();
+        self.changed();
+    }
+
+    fn replace_with(&mut self, text: &str) {
+        self.rope = Rope::from_str(text);
+        self.changed();
+    }
+
+    fn take(&mut self) -> String {
+        let text = self.rope.to_string();
+        self.clear();
+        text
+    }
+
+    fn type_id(&self) -> std::any::TypeId {
+        std::any::TypeId::of::<Self>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, cell::Cell, ops::Range};
+
+    use super::{RopeBuffer, TextBuffer};
+
+    #[test]
+    fn test_rope_buffer_matches_string() {
+        let text = "föö\nbär\n\nbaz\n";
+        let rope = RopeBuffer::from(text);
+
+        assert_eq!(rope.char_count(), text.char_count());
+        assert!(!rope.is_empty());
+        assert!(RopeBuffer::default().is_empty());
+        assert_eq!(rope.line_count(), text.line_count());
+        for line in 0..=text.line_count() {
+            assert_eq!(rope.line(line), text.line(line), "line {line}");
+            assert_eq!(
+                rope.char_index_from_line(line),
+                text.char_index_from_line(line),
+                "line {line}"
+            );
+        }
+        for char_index in 0..=text.char_count() {
+            assert_eq!(
+                rope.line_from_char_index(char_index),
+                text.line_from_char_index(char_index),
+                "char {char_index}"
+            );
+        }
+        assert_eq!(rope.char_range(2..6), text.char_range(2..6));
+        assert_eq!(rope.char_range_text(2..6), text.char_range_text(2..6));
+        assert_eq!(rope.text(), text);
+        assert_eq!(rope.as_str(), text);
+
+        // Out of range, like for a `String`:
+        let end = text.char_count();
+        assert_eq!(rope.char_range(2..end + 5), text.char_range(2..end + 5));
+        assert_eq!(rope.char_range_text(end + 1..end + 5), "");
+        assert_eq!(rope.char_range(end + 1..end + 5), "");
+
+        // Ranges over several chunks of the rope:
+        let text = "0123456789\n".repeat(1000);
+        let rope = RopeBuffer::from(text.as_str());
+        assert!(1 < rope.chunks().count());
+        assert_eq!(rope.char_range(100..9000), text.char_range(100..9000));
+        assert_eq!(rope.char_range_text(100..9000), text.char_range(100..9000));
+    }
+
+    #[test]
+    fn test_rope_buffer_edits() {
+        let mut rope = RopeBuffer::from("Hello world");
+        assert_eq!(rope.text(), "Hello world");
+        let revision = rope.revision();
+        assert_eq!(rope.clone().revision(), revision);
+
+        rope.delete_char_range(5..11);
+        assert_eq!(rope.insert_text(", wörld", 5), 7);
+        assert_eq!(rope.text(), "Hello, wörld");
+        assert_ne!(rope.revision(), revision);
+        assert_ne!(RopeBuffer::from("Hello, wörld").revision(), rope.revision());
+
+        // Out of range, like for a `String`:
+        assert_eq!(rope.insert_text("!", 100), 1);
+        assert_eq!(rope.text(), "Hello, wörld!");
+        rope.delete_char_range(12..100);
+        rope.delete_char_range(50..100);
+        assert_eq!(rope.text(), "Hello, wörld");
+
+        assert_eq!(rope.take(), "Hello, wörld");
+        assert_eq!(rope.text(), "");
+    }
+
+    /// A [`RopeBuffer`] that counts how often the whole text is read from it.
+    struct CountingBuffer {
+        rope: RopeBuffer,
+        whole_text_reads: Cell<usize>,
+    }
+
+    impl TextBuffer for CountingBuffer {
+        fn is_mutable(&self) -> bool {
+            true
+        }
+
+        fn as_str(&self) -> &str {
+            self.whole_text_reads.set(self.whole_text_reads.get() + 1);
+            self.rope.as_str()
+        }
+
+        fn text(&self) -> Cow<'_, str> {
+            self.whole_text_reads.set(self.whole_text_reads.get() + 1);
+            self.rope.text()
+        }
+
+        fn chunks(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+            self.whole_text_reads.set(self.whole_text_reads.get() + 1);
+            self.rope.chunks()
+        }
+
+        fn revision(&self) -> Option<u64> {
+            self.rope.revision()
+        }
+
+        fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
+            self.rope.insert_text(text, char_index)
+        }
+
+        fn delete_char_range(&mut self, char_range: Range<usize>) {
+            self.rope.delete_char_range(char_range);
+        }
+
+        fn char_range(&self, char_range: Range<usize>) -> &str {
+            if char_range == (0..self.char_count()) {
+                self.whole_text_reads.set(self.whole_text_reads.get() + 1);
+            }
+            self.rope.char_range(char_range)
+        }
+
+        fn char_range_text(&self, char_range: Range<usize>) -> Cow<'_, str> {
+            if char_range == (0..self.char_count()) {
+                self.whole_text_reads.set(self.whole_text_reads.get() + 1);
+            }
+            self.rope.char_range_text(char_range)
+        }
+
+        fn char_count(&self) -> usize {
+            self.rope.char_count()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.rope.is_empty()
+        }
+
+        fn line_count(&self) -> usize {
+            self.rope.line_count()
+        }
+
+        fn line(&self, line: usize) -> Cow<'_, str> {
+            self.rope.line(line)
+        }
+
+        fn line_from_char_index(&self, char_index: usize) -> usize {
+            self.rope.line_from_char_index(char_index)
+        }
+
+        fn char_index_from_line(&self, line: usize) -> usize {
+            self.rope.char_index_from_line(line)
+        }
+
+        fn byte_index_from_char_index(&self, char_index: usize) -> usize {
+            self.rope.byte_index_from_char_index(char_index)
+        }
+
+        fn char_index_from_byte_index(&self, byte_index: usize) -> usize {
+            self.rope.char_index_from_byte_index(byte_index)
+        }
+
+        fn type_id(&self) -> std::any::TypeId {
+            std::any::TypeId::of::<Self>()
+        }
+    }
+
+    #[test]
+    fn test_text_edit_reads_text_only_for_edits() {
+        let mut text = CountingBuffer {
+            rope: RopeBuffer::from("Hello\nworld"),
+            whole_text_reads: Cell::new(0),
+        };
+        let mut search = crate::text_edit::TextEditSearch::new("o");
+        let ctx = crate::Context::default();
+        let id = crate::Id::new("text_edit");
+        let mut run = |text: &mut CountingBuffer| {
+            let _ = ctx.run(Default::default(), |ctx| {
+                crate::CentralPanel::default().show(ctx, |ui| {
+                    ui.add(crate::TextEdit::multiline(text).id(id).search(&mut search));
+                });
+            });
+        };
+
+        run(&mut text);
+        ctx.memory_mut(|mem| mem.request_focus(id));
+        run(&mut text);
+        run(&mut text);
+
+        // Without edits, the layout and search matches from the last frame are used:
+        text.whole_text_reads.set(0);
+        for _ in 0..3 {
+            run(&mut text);
+        }
+        assert_eq!(text.whole_text_reads.get(), 0);
+
+        text.insert_text("!", 5);
+        run(&mut text);
+        assert!(0 < text.whole_text_reads.get(), "Laid out again");
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn test_rope_buffer_serde() {
+        let rope = RopeBuffer::from("Hello\nwörld");
+        let serialized = ron::to_string(&rope).unwrap();
+        assert_eq!(serialized, r#""Hello\nwörld""#);
+        assert_eq!(ron::from_str::<RopeBuffer>(&serialized).unwrap(), rope);
+    }
+}
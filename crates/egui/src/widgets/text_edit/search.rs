@@ -0,0 +1,347 @@
+use std::ops::Range;
+
+/// Search (and replace) in a [`crate::TextEdit`], e.g. for a Ctrl+F bar.
+///
+/// Keep this around between frames, let the user edit the [`Self::query`],
+/// and pass it to [`crate::TextEdit::search`].
+/// All matches are highlighted in the [`crate::TextEdit`].
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// # let mut code = String::new();
+/// # let mut search = egui::text_edit::TextEditSearch::default();
+/// ui.horizontal(|ui| {
+///     ui.text_edit_singleline(&mut search.query);
+///     ui.checkbox(&mut search.case_sensitive, "Aa");
+///     if ui.button("⏶").clicked() {
+///         search.find_previous();
+///     }
+///     if ui.button("⏷").clicked() {
+///         search.find_next();
+///     }
+/// });
+/// ui.horizontal(|ui| {
+///     ui.text_edit_singleline(&mut search.replacement);
+///     if ui.button("Replace").clicked() {
+///         search.replace();
+///     }
+///     if ui.button("Replace all").clicked() {
+///         search.replace_all();
+///     }
+/// });
+///
+/// let output = egui::TextEdit::multiline(&mut code)
+///     .search(&mut search)
+///     .show(ui);
+/// ui.label(format!("{} matches", output.search_matches.len()));
+/// # });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TextEditSearch {
+    /// What to search for. Nothing matches an empty query.
+    pub query: String,
+
+    /// What to replace matches with.
+    ///
+    /// When searching with a [`Self::regex`], `$1`, `$name` etc. refer to the capture groups of the match.
+    pub replacement: String,
+
+    /// Only match text with the same case as the query.
+    pub case_sensitive: bool,
+
+    /// Interpret the [`Self::query`] as a regular expression.
+    ///
+    /// Requires the `regex` feature. Without it, the query is always matched as plain text.
+    /// An invalid regular expression matches nothing.
+    pub regex: bool,
+
+    /// Done by the next [`crate::TextEdit`] this is passed to.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) command: Option<SearchCommand>,
+
+    /// The last compiled [`Self::regex`], so it is only compiled again when the query changes.
+    #[cfg(feature = "regex")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    compiled_regex: Option<CompiledRegex>,
+}
+
+/// A [`TextEditSearch::query`] compiled to a regular expression, and what it was compiled from.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+struct CompiledRegex {
+    query: String,
+    case_sensitive: bool,
+
+    /// `None` if the query is not a valid regular expression.
+    regex: Option<regex::Regex>,
+}
+
+#[cfg(feature = "regex")]
+impl PartialEq for CompiledRegex {
+    fn eq(&self, _other: &Self) -> bool {
+        true // Only a cache, which doesn't change what the search does.
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Eq for CompiledRegex {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SearchCommand {
+    FindNext,
+    FindPrevious,
+    Replace,
+    ReplaceAll,
+}
+
+impl TextEditSearch {
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Select the next match after the cursor, and scroll to it.
+    ///
+    /// Wraps around to the first match.
+    pub fn find_next(&mut self) {
+        self.command = Some(SearchCommand::FindNext);
+    }
+
+    /// Select the previous match before the cursor, and scroll to it.
+    ///
+    /// Wraps around to the last match.
+    pub fn find_previous(&mut self) {
+        self.command = Some(SearchCommand::FindPrevious);
+    }
+
+    /// Replace the selected match with [`Self::replacement`], then select the next match.
+    ///
+    /// If the selection is not a match, this just selects the next one.
+    pub fn replace(&mut self) {
+        self.command = Some(SearchCommand::Replace);
+    }
+
+    /// Replace all matches with [`Self::replacement`].
+    pub fn replace_all(&mut self) {
+        self.command = Some(SearchCommand::ReplaceAll);
+    }
+
+    /// The character ranges (NOT byte ranges!) of all non-empty matches in `text`.
+    ///
+    /// Takes `&mut self` to remember the compiled [`Self::regex`] for the next call.
+    #[cfg_attr(not(feature = "regex"), expect(clippy::needless_pass_by_ref_mut))]
+    pub fn matches(&mut self, text: &str) -> Vec<Range<usize>> {
+        if self.query.is_empty() {
+            return vec![];
+        }
+
+        #[cfg(feature = "regex")]
+        if self.regex {
+            self.compile_regex();
+            let Some(regex) = self.compiled() else {
+                return vec![];
+            };
+
+            let mut char_index = 0;
+            let mut byte_index = 0;
+            let mut char_index_of = |byte: usize| {
+                char_index += text[byte_index..byte].chars().count();
+                byte_index = byte;
+                char_index
+            };
+            return regex
+                .find_iter(text)
+                .filter(|m| !m.is_empty())
+                .map(|m| char_index_of(m.start())..char_index_of(m.end()))
+                .collect();
+        }
+
+        let chars_match = |a: char, b: char| {
+            if self.case_sensitive {
+                a == b
+            } else {
+                a == b || a.to_lowercase().eq(b.to_lowercase())
+            }
+        };
+        let query_len = self.query.chars().count();
+
+        let mut matches = vec![];
+        let mut chars = text.chars();
+        let mut start = 0;
+        loop {
+            let mut candidate = chars.clone();
+            let is_match =
+                (self.query.chars()).all(|q| candidate.next().is_some_and(|c| chars_match(c, q)));
+            if is_match {
+                matches.push(start..start + query_len);
+                chars = candidate;
+                start += query_len;
+            } else if chars.next().is_some() {
+                start += 1;
+            } else {
+                break;
+            }
+        }
+        matches
+    }
+
+    /// The text to replace the match at `char_range` in `text` with.
+    #[cfg_attr(not(feature = "regex"), expect(clippy::needless_pass_by_ref_mut))]
+    pub(crate) fn replacement_for(&mut self, text: &str, char_range: &Range<usize>) -> String {
+        #[cfg(feature = "regex")]
+        if self.regex {
+            use crate::text_selection::text_cursor_state::byte_index_from_char_index;
+
+            self.compile_regex();
+            let start = byte_index_from_char_index(text, char_range.start);
+            if let Some(captures) = self
+                .compiled()
+                .and_then(|regex| regex.captures_at(text, start))
+            {
+                let mut replacement = String::new();
+                captures.expand(&self.replacement, &mut replacement);
+                return replacement;
+            }
+        }
+
+        let _ = (text, char_range); // only used for regex replacements
+        self.replacement.clone()
+    }
+
+    /// The character ranges of all matches in `text`, each with the text to replace it with.
+    pub(crate) fn replacements_in(&mut self, text: &str) -> Vec<(Range<usize>, String)> {
+        #[cfg(feature = "regex")]
+        if self.regex {
+            self.compile_regex();
+            let Some(regex) = self.compiled() else {
+                return vec![];
+            };
+
+            let mut char_index = 0;
+            let mut byte_index = 0;
+            let mut char_index_of = |byte: usize| {
+                char_index += text[byte_index..byte].chars().count();
+                byte_index = byte;
+                char_index
+            };
+            return regex
+                .captures_iter(text)
+                .filter_map(|captures| {
+                    let m = captures.get(0).expect("Group 0 is the whole match");
+                    if m.is_empty() {
+                        return None;
+                    }
+                    let mut replacement = String::new();
+                    captures.expand(&self.replacement, &mut replacement);
+                    Some((
+                        char_index_of(m.start())..char_index_of(m.end()),
+                        replacement,
+                    ))
+                })
+                .collect();
+        }
+
+        self.matches(text)
+            .into_iter()
+            .map(|m| (m, self.replacement.clone()))
+            .collect()
+    }
+
+    /// Compile the [`Self::query`] to a regular expression, unless it already was.
+    #[cfg(feature = "regex")]
+    fn compile_regex(&mut self) {
+        let is_outdated = self.compiled_regex.as_ref().is_none_or(|compiled| {
+            compiled.query != self.query || compiled.case_sensitive != self.case_sensitive
+        });
+        if is_outdated {
+            let regex = regex::RegexBuilder::new(&self.query)
+                .case_insensitive(!self.case_sensitive)
+                .build()
+                .ok();
+            self.compiled_regex = Some(CompiledRegex {
+                query: self.query.clone(),
+                case_sensitive: self.case_sensitive,
+                regex,
+            });
+        }
+    }
+
+    /// The regular expression compiled by [`Self::compile_regex`].
+    ///
+    /// `None` if the query is not a valid regular expression.
+    #[cfg(feature = "regex")]
+    fn compiled(&self) -> Option<&regex::Regex> {
+        self.compiled_regex.as_ref()?.regex.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextEditSearch;
+
+    #[test]
+    fn test_matches() {
+        let mut search = TextEditSearch::new("ab");
+        assert_eq!(
+            search.matches("ab Ab aab ababab"),
+            vec![0..2, 3..5, 7..9, 10..12, 12..14, 14..16]
+        );
+
+        search.case_sensitive = true;
+        assert_eq!(search.matches("ab Ab aab"), vec![0..2, 7..9]);
+
+        // Character indices, not bytes:
+        let mut search = TextEditSearch::new("ö");
+        assert_eq!(search.matches("Öl ö"), vec![0..1, 3..4]);
+
+        assert!(TextEditSearch::new("").matches("abc").is_empty());
+    }
+
+    #[test]
+    fn test_replacements_in() {
+        let mut search = TextEditSearch::new("ö");
+        search.replacement = "oe".to_owned();
+        let oe = || "oe".to_owned();
+        assert_eq!(
+            search.replacements_in("Öl öö x"),
+            vec![(0..1, oe()), (3..4, oe()), (4..5, oe())]
+        );
+        assert!(search.replacements_in("xyz").is_empty());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_regex_matches() {
+        let mut search = TextEditSearch::new(r"(\w)ö(\d)");
+        search.regex = true;
+        search.replacement = "$2ö$1".to_owned();
+
+        let text = "äö1 Xö2 ö";
+        let matches = search.matches(text);
+        assert_eq!(matches, vec![0..3, 4..7]);
+        assert_eq!(search.replacement_for(text, &matches[1]), "2öX");
+        assert_eq!(
+            search.replacements_in(text),
+            vec![(0..3, "1öä".to_owned()), (4..7, "2öX".to_owned())]
+        );
+
+        // The compiled expression follows changes of the query and flags:
+        search.query = "a".to_owned();
+        assert_eq!(search.matches("aA"), vec![0..1, 1..2]);
+        search.case_sensitive = true;
+        assert_eq!(search.matches("aA"), vec![0..1]);
+        search.case_sensitive = false;
+
+        // Empty matches are skipped, and invalid expressions match nothing:
+        search.query = "x*".to_owned();
+        assert_eq!(search.matches("axxb"), vec![1..3]);
+        search.replacement = "y".to_owned();
+        assert_eq!(search.replacements_in("axxb"), vec![(1..3, "y".to_owned())]);
+        search.query = "(".to_owned();
+        assert!(search.matches("(").is_empty());
+    }
+}
@@ -1,10 +1,11 @@
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc};
 
 use crate::mutex::Mutex;
 
+use super::{TextBuffer, TextEditDiagnostic, TextEditSearch, autocomplete::AutocompleteState};
 use crate::{
-    Context, Id, Vec2,
-    text_selection::{CCursorRange, TextCursorState},
+    Context, Galley, Id, Pos2, Vec2,
+    text_selection::{CCursorRange, TextCursorState, text_cursor_state::merge_overlapping_ranges},
 };
 
 pub type TextEditUndoer = crate::util::undoer::Undoer<(CCursorRange, String)>;
@@ -35,12 +36,27 @@ pub type TextEditUndoer = crate::util::undoer::Undoer<(CCursorRange, String)>;
 #[cfg_attr(feature = "serde", serde(default))]
 pub struct TextEditState {
     /// Controls the text selection.
+    ///
+    /// This is the main cursor. Changing it removes any [`Self::extra_char_ranges`].
     pub cursor: TextCursorState,
 
+    // More cursors besides `cursor`, never overlapping each other or it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    extra_ccursor_ranges: Vec<CCursorRange>,
+
+    // What `cursor` was when `extra_ccursor_ranges` were set.
+    // They are dropped once `cursor` is changed, e.g. by the user of the `TextEdit`.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    extra_ccursor_ranges_main: Option<CCursorRange>,
+
     /// Wrapped in Arc for cheaper clones.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) undoer: Arc<Mutex<TextEditUndoer>>,
 
+    // The cursor range and the revision of the text (see `text_revision`) last given to the undoer.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    undoer_fed: Option<(CCursorRange, u64)>,
+
     // If IME candidate window is shown on this text edit.
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) ime_enabled: bool,
@@ -54,6 +70,22 @@ pub struct TextEditState {
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) text_offset: Vec2,
 
+    // Where an Alt-drag block selection started, relative to the galley.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) block_selection_anchor: Option<Pos2>,
+
+    // The open autocomplete popup, if any.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) autocomplete: Option<AutocompleteState>,
+
+    // The last matches of the search, and a hash of the text and search they were found with.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    search_matches: Option<(u64, Arc<Vec<Range<usize>>>)>,
+
+    // The diagnostic whose right-click menu is open, if any.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) diagnostic_menu: Option<TextEditDiagnostic>,
+
     /// When did the user last press a key or click on the `TextEdit`.
     /// Used to pause the cursor animation when typing.
     #[cfg_attr(feature = "serde", serde(skip))]
@@ -73,12 +105,109 @@ impl TextEditState {
         self.undoer.lock().clone()
     }
 
-    #[expect(clippy::needless_pass_by_ref_mut)] // Intentionally hide interiority of mutability
     pub fn set_undoer(&mut self, undoer: TextEditUndoer) {
         *self.undoer.lock() = undoer;
+        self.undoer_fed = None;
     }
 
     pub fn clear_undoer(&mut self) {
         self.set_undoer(TextEditUndoer::default());
     }
+
+    /// Give the current state to the undoer, without copying the text if neither it nor the cursor changed since last time.
+    pub(crate) fn feed_undoer(
+        &mut self,
+        time: f64,
+        cursor_range: CCursorRange,
+        text: &dyn TextBuffer,
+    ) {
+        let fed = (cursor_range, text_revision(text));
+        let mut undoer = self.undoer.lock();
+        if undoer.is_in_flux() || self.undoer_fed != Some(fed) {
+            undoer.feed_state(time, &(cursor_range, text.text().into_owned()));
+            self.undoer_fed = Some(fed);
+        }
+    }
+
+    /// The matches of `search` in `text`, only searched for again when the text or the search changed.
+    pub(crate) fn search_matches(
+        &mut self,
+        search: &mut TextEditSearch,
+        text: &dyn TextBuffer,
+    ) -> Arc<Vec<Range<usize>>> {
+        let key = crate::util::hash((
+            text_revision(text),
+            &search.query,
+            search.case_sensitive,
+            search.regex,
+        ));
+        match &self.search_matches {
+            Some((cached_key, matches)) if *cached_key == key => matches.clone(),
+            _ => {
+                let matches = Arc::new(search.matches(&text.text()));
+                self.search_matches = Some((key, matches.clone()));
+                matches
+            }
+        }
+    }
+
+    /// The ranges of the extra cursors, in addition to the main one in [`Self::cursor`].
+    ///
+    /// A [`crate::TextEdit`] can have more than one cursor (e.g. after a block selection with Alt-drag,
+    /// or after adding the next occurrence of the selection with Ctrl-D / Cmd-D).
+    pub fn extra_char_ranges(&self) -> &[CCursorRange] {
+        if self.cursor.char_range() == self.extra_ccursor_ranges_main {
+            &self.extra_ccursor_ranges
+        } else {
+            &[]
+        }
+    }
+
+    /// All selected ranges of characters: the main range first, followed by the extra cursors.
+    pub fn char_ranges(&self) -> Vec<CCursorRange> {
+        self.cursor
+            .char_range()
+            .into_iter()
+            .chain(self.extra_char_ranges().iter().copied())
+            .collect()
+    }
+
+    /// Sets all the selected ranges of characters.
+    ///
+    /// The first range becomes the main one in [`Self::cursor`], the rest become extra cursors.
+    /// Overlapping ranges are merged.
+    pub fn set_char_ranges(&mut self, mut ccursor_ranges: Vec<CCursorRange>) {
+        merge_overlapping_ranges(&mut ccursor_ranges);
+        let mut ranges = ccursor_ranges.into_iter();
+        self.cursor.set_char_range(ranges.next());
+        self.extra_ccursor_ranges = ranges.collect();
+        self.extra_ccursor_ranges_main = self.cursor.char_range();
+    }
+
+    /// All selected ranges of characters, clamped within the character range of the given [`Galley`].
+    ///
+    /// The main range comes first, followed by the extra cursors.
+    pub(crate) fn ranges(&self, galley: &Galley) -> Vec<CCursorRange> {
+        self.char_ranges()
+            .into_iter()
+            .map(|mut range| {
+                range.primary = galley.clamp_cursor(&range.primary);
+                range.secondary = galley.clamp_cursor(&range.secondary);
+                range
+            })
+            .collect()
+    }
+}
+
+/// The [`TextBuffer::revision`] of the text, or else a hash of it.
+fn text_revision(text: &dyn TextBuffer) -> u64 {
+    text.revision().unwrap_or_else(|| {
+        use std::hash::{BuildHasher as _, Hasher as _};
+
+        let mut hasher = ahash::RandomState::with_seeds(1, 2, 3, 4).build_hasher();
+        for chunk in text.chunks() {
+            hasher.write(chunk.as_bytes());
+        }
+        hasher.finish()
+    })
 }
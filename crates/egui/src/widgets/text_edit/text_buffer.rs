@@ -9,7 +9,7 @@ use crate::{
     text::CCursorRange,
     text_selection::text_cursor_state::{
         byte_index_from_char_index, ccursor_next_word, ccursor_previous_word,
-        char_index_from_byte_index, find_line_start, slice_char_range,
+        char_index_from_byte_index, slice_char_range,
     },
 };
 
@@ -17,13 +17,48 @@ use crate::{
 /// an underlying buffer.
 ///
 /// Most likely you will use a [`String`] which implements [`TextBuffer`].
+///
+/// The text does not have to be stored as one contiguous `str`, e.g. for a rope.
+/// Such buffers should override [`Self::text`], [`Self::chunks`] and [`Self::char_range_text`],
+/// so that [`crate::TextEdit`] never needs [`Self::as_str`] for them.
+/// They should also override [`Self::char_count`] and the line-based methods,
+/// which by default scan the whole text, so each call is `O(N)` in the size of the text.
+///
+/// [`crate::TextEdit`] lays out the whole text, so it reads it with [`Self::chunks`] each time it changes,
+/// unless the [`Self::revision`] shows that it did not.
+/// For very large texts, use [`crate::text_edit::VirtualTextEdit`] instead.
 pub trait TextBuffer {
     /// Can this text be edited?
     fn is_mutable(&self) -> bool;
 
     /// Returns this buffer as a `str`.
+    ///
+    /// Buffers that are not stored as one contiguous `str` have to build one for this.
     fn as_str(&self) -> &str;
 
+    /// The whole text.
+    ///
+    /// By default this borrows [`Self::as_str`].
+    /// Buffers that are not stored as one contiguous `str` can return an owned copy instead.
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.as_str())
+    }
+
+    /// The whole text in pieces, e.g. the chunks of a rope, without copying it.
+    ///
+    /// By default this is just [`Self::text`].
+    fn chunks(&self) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        Box::new(std::iter::once(self.text()))
+    }
+
+    /// A number that changes whenever the text changes, and that two buffers only share if they have the same text.
+    ///
+    /// This lets [`crate::TextEdit`] reuse the layout of the last frame without getting the whole [`Self::text`].
+    /// `None` (the default) if the buffer doesn't keep track of this.
+    fn revision(&self) -> Option<u64> {
+        None
+    }
+
     /// Inserts text `text` into this buffer at character index `char_index`.
     ///
     /// # Notes
@@ -44,17 +79,79 @@ pub trait TextBuffer {
         slice_char_range(self.as_str(), char_range)
     }
 
+    /// Reads the given character range.
+    ///
+    /// Unlike [`Self::char_range`], this does not need the text as one contiguous `str`,
+    /// so buffers that are not stored as one can return an owned copy of just this range.
+    fn char_range_text(&self, char_range: Range<usize>) -> Cow<'_, str> {
+        Cow::Borrowed(self.char_range(char_range))
+    }
+
+    /// The number of characters in this buffer.
+    fn char_count(&self) -> usize {
+        self.text().chars().count()
+    }
+
+    /// Is this buffer empty?
+    ///
+    /// Unlike comparing [`Self::char_count`] to zero, this doesn't count the characters.
+    fn is_empty(&self) -> bool {
+        self.chunks().all(|chunk| chunk.is_empty())
+    }
+
+    /// The number of lines, separated by `\n`.
+    ///
+    /// An empty buffer has one (empty) line, and so does a buffer ending with `\n`.
+    fn line_count(&self) -> usize {
+        self.text().split('\n').count()
+    }
+
+    /// The text of the given line, excluding the `\n`.
+    fn line(&self, line: usize) -> Cow<'_, str> {
+        map_text(self.text(), |text| {
+            text.split('\n').nth(line).unwrap_or_default()
+        })
+    }
+
+    /// The line the character at `char_index` is on.
+    fn line_from_char_index(&self, char_index: usize) -> usize {
+        self.text()
+            .chars()
+            .take(char_index)
+            .filter(|&c| c == '\n')
+            .count()
+    }
+
+    /// The character index where the given line starts.
+    ///
+    /// Past the last line, this is the end of the buffer.
+    fn char_index_from_line(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+        let mut line_start = 0;
+        for (i, c) in self.text().chars().enumerate() {
+            if c == '\n' {
+                line_start += 1;
+                if line_start == line {
+                    return i + 1;
+                }
+            }
+        }
+        self.char_count()
+    }
+
     fn byte_index_from_char_index(&self, char_index: usize) -> usize {
-        byte_index_from_char_index(self.as_str(), char_index)
+        byte_index_from_char_index(&self.text(), char_index)
     }
 
     fn char_index_from_byte_index(&self, char_index: usize) -> usize {
-        char_index_from_byte_index(self.as_str(), char_index)
+        char_index_from_byte_index(&self.text(), char_index)
     }
 
     /// Clears all characters in this buffer
     fn clear(&mut self) {
-        self.delete_char_range(0..self.as_str().len());
+        self.delete_char_range(0..self.char_count());
     }
 
     /// Replaces all contents of this string with `text`
@@ -65,7 +162,7 @@ pub trait TextBuffer {
 
     /// Clears all characters in this buffer and returns a string of the contents.
     fn take(&mut self) -> String {
-        let s = self.as_str().to_owned();
+        let s = self.text().into_owned();
         self.clear();
         s
     }
@@ -74,7 +171,7 @@ pub trait TextBuffer {
         if char_limit < usize::MAX {
             let mut new_string = text_to_insert;
             // Avoid subtract with overflow panic
-            let cutoff = char_limit.saturating_sub(self.as_str().chars().count());
+            let cutoff = char_limit.saturating_sub(self.char_count());
 
             new_string = match new_string.char_indices().nth(cutoff) {
                 None => new_string,
@@ -88,16 +185,18 @@ pub trait TextBuffer {
     }
 
     fn decrease_indentation(&mut self, ccursor: &mut CCursor) {
-        let line_start = find_line_start(self.as_str(), *ccursor);
+        let line = self.line_from_char_index(ccursor.index);
+        let line_start = CCursor::new(self.char_index_from_line(line));
+        let line_text = self.line(line);
 
-        let remove_len = if self.as_str().chars().nth(line_start.index) == Some('\t') {
+        let remove_len = if line_text.starts_with('\t') {
             Some(1)
-        } else if self
-            .as_str()
+        } else if line_text
             .chars()
-            .skip(line_start.index)
             .take(TAB_SIZE)
-            .all(|c| c == ' ')
+            .filter(|&c| c == ' ')
+            .count()
+            == TAB_SIZE
         {
             Some(TAB_SIZE)
         } else {
@@ -140,12 +239,12 @@ pub trait TextBuffer {
     }
 
     fn delete_previous_word(&mut self, max_ccursor: CCursor) -> CCursor {
-        let min_ccursor = ccursor_previous_word(self.as_str(), max_ccursor);
+        let min_ccursor = ccursor_previous_word(&self.text(), max_ccursor);
         self.delete_selected_ccursor_range([min_ccursor, max_ccursor])
     }
 
     fn delete_next_word(&mut self, min_ccursor: CCursor) -> CCursor {
-        let max_ccursor = ccursor_next_word(self.as_str(), min_ccursor);
+        let max_ccursor = ccursor_next_word(&self.text(), min_ccursor);
         self.delete_selected_ccursor_range([min_ccursor, max_ccursor])
     }
 
@@ -211,6 +310,14 @@ pub trait TextBuffer {
     fn type_id(&self) -> std::any::TypeId;
 }
 
+/// The part of `text` that `f` picks, borrowed if `text` is.
+fn map_text(text: Cow<'_, str>, f: impl FnOnce(&str) -> &str) -> Cow<'_, str> {
+    match text {
+        Cow::Borrowed(text) => Cow::Borrowed(f(text)),
+        Cow::Owned(text) => Cow::Owned(f(&text).to_owned()),
+    }
+}
+
 impl TextBuffer for String {
     fn is_mutable(&self) -> bool {
         true
@@ -220,6 +327,10 @@ impl TextBuffer for String {
         self.as_ref()
     }
 
+    fn is_empty(&self) -> bool {
+        Self::is_empty(self)
+    }
+
     fn insert_text(&mut self, text: &str, char_index: usize) -> usize {
         // Get the byte index from the character index
         let byte_idx = byte_index_from_char_index(self.as_str(), char_index);
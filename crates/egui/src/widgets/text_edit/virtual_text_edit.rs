@@ -0,0 +1,662 @@
+use std::sync::Arc;
+
+use epaint::text::{Galley, LayoutJob, cursor::CCursor};
+
+use crate::{
+    Color32, Context, CursorIcon, Event, EventFilter, FontId, FontSelection, Id, Key,
+    KeyboardShortcut, Modifiers, NumExt as _, Rect, Response, Sense, Ui, Widget, WidgetInfo,
+    os::OperatingSystem,
+    text_selection::{
+        CCursorRange,
+        text_cursor_state::{ccursor_next_word, ccursor_previous_word},
+        visuals::{paint_cursor_end, paint_text_cursors},
+    },
+    vec2,
+};
+
+use super::{
+    TextBuffer, TextEditState,
+    builder::{EditLayout, Editor, events},
+};
+
+/// A multiline text editor for very large texts, e.g. a log file of many megabytes.
+///
+/// Unlike [`super::TextEdit`], which lays out the whole text every time it changes,
+/// this only lays out the lines that are visible, one [`Galley`] per line
+/// (which the font system caches for as long as the line is shown).
+/// The visible lines are decided by the clip rectangle, so put it in a [`crate::ScrollArea`].
+///
+/// Lines are never wrapped, and all of them have the height of one row of the font.
+///
+/// The text is edited by the same code as a [`super::TextEdit`]:
+/// the keyboard shortcuts, multiple cursors (Ctrl+D), undo and IME all work the same.
+/// Moving the cursor goes by lines instead of laid out rows.
+/// What [`super::TextEdit`] has on top of that is not supported:
+/// wrapping, search, autocomplete, input masks and filters, diagnostics, block selection with Alt-drag,
+/// hint text and passwords.
+///
+/// Laying out and painting goes through the line-based methods of [`TextBuffer`],
+/// so use it with a `RopeBuffer` (`rope` feature), for which those are `O(log N)`.
+/// The default implementations of those methods, used by e.g. [`String`], scan the text from the start,
+/// which costs `O(N)` for every visible line, every frame.
+///
+/// Screen readers are given the text of the visible lines rather than the whole text.
+///
+/// ```
+/// # #[cfg(feature = "rope")]
+/// # egui::__run_test_ui(|ui| {
+/// # let huge_log = "first line\nsecond line";
+/// let mut log = egui::text_edit::RopeBuffer::from(huge_log);
+/// egui::ScrollArea::both().show(ui, |ui| {
+///     ui.add(egui::text_edit::VirtualTextEdit::new(&mut log));
+/// });
+/// # });
+/// ```
+#[must_use = "You should put this widget in a ui with `ui.add(widget);`"]
+pub struct VirtualTextEdit<'t> {
+    text: &'t mut dyn TextBuffer,
+    id: Option<Id>,
+    id_salt: Option<Id>,
+    font_selection: FontSelection,
+    text_color: Option<Color32>,
+    desired_width: Option<f32>,
+    interactive: bool,
+}
+
+impl<'t> VirtualTextEdit<'t> {
+    pub fn new(text: &'t mut dyn TextBuffer) -> Self {
+        Self {
+            text,
+            id: None,
+            id_salt: None,
+            font_selection: crate::TextStyle::Monospace.into(),
+            text_color: None,
+            desired_width: None,
+            interactive: true,
+        }
+    }
+
+    /// Use if you want to set an explicit [`Id`] for this widget.
+    #[inline]
+    pub fn id(mut self, id: Id) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// A source for the unique [`Id`], e.g. `.id_salt("log_viewer")`.
+    #[inline]
+    pub fn id_salt(mut self, id_salt: impl std::hash::Hash) -> Self {
+        self.id_salt = Some(Id::new(id_salt));
+        self
+    }
+
+    /// Pick a [`crate::FontId`] or [`crate::TextStyle`]. The default is [`crate::TextStyle::Monospace`].
+    #[inline]
+    pub fn font(mut self, font_selection: impl Into<FontSelection>) -> Self {
+        self.font_selection = font_selection.into();
+        self
+    }
+
+    #[inline]
+    pub fn text_color(mut self, text_color: Color32) -> Self {
+        self.text_color = Some(text_color);
+        self
+    }
+
+    /// The minimum width. The widget grows to fit the widest line laid out so far.
+    ///
+    /// Default: the available width.
+    #[inline]
+    pub fn desired_width(mut self, desired_width: f32) -> Self {
+        self.desired_width = Some(desired_width);
+        self
+    }
+
+    /// Default is `true`. If set to `false` then you cannot interact with the text (neither edit or select it).
+    #[inline]
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// The state of a [`VirtualTextEdit`], stored between frames.
+///
+/// Unlike [`TextEditState`] this is not persisted, since the undo history can be large.
+#[derive(Clone, Default)]
+pub struct VirtualTextEditState {
+    /// The cursors and undo history, which are edited the same way as those of a [`super::TextEdit`].
+    ///
+    /// The character indices are into the whole text.
+    pub text_edit: TextEditState,
+
+    /// The width of [`Self::widest_line`], so that the enclosing [`crate::ScrollArea`] can scroll to it.
+    max_line_width: f32,
+
+    /// The widest line laid out so far.
+    ///
+    /// When it is laid out again, [`Self::max_line_width`] is updated, so it can also shrink.
+    widest_line: usize,
+}
+
+impl VirtualTextEditState {
+    pub fn load(ctx: &Context, id: Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_temp(id))
+    }
+
+    pub fn store(self, ctx: &Context, id: Id) {
+        ctx.data_mut(|d| d.insert_temp(id, self));
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Lays out single lines of the text.
+struct LineLayouter {
+    ctx: Context,
+    font_id: FontId,
+    text_color: Color32,
+}
+
+impl LineLayouter {
+    fn layout(&self, text: &dyn TextBuffer, line: usize) -> Arc<Galley> {
+        let job = LayoutJob::simple_singleline(
+            text.line(line).into_owned(),
+            self.font_id.clone(),
+            self.text_color,
+        );
+        self.ctx.fonts_mut(|f| f.layout_job(job))
+    }
+
+    /// The horizontal position of the cursor, relative to the start of its line.
+    fn x_from_cursor(&self, text: &dyn TextBuffer, ccursor: CCursor) -> f32 {
+        let line = text.line_from_char_index(ccursor.index);
+        let column = ccursor.index - text.char_index_from_line(line);
+        self.layout(text, line)
+            .pos_from_cursor(CCursor::new(column))
+            .min
+            .x
+    }
+
+    /// The cursor closest to the given horizontal position on the line.
+    fn cursor_from_x(&self, text: &dyn TextBuffer, line: usize, x: f32) -> CCursor {
+        let galley = self.layout(text, line);
+        let column = galley.cursor_from_pos(vec2(x, 0.5 * galley.size().y)).index;
+        CCursor::new(text.char_index_from_line(line) + column)
+    }
+}
+
+/// Moves the cursors of a [`VirtualTextEdit`] line by line, for the shared [`events`].
+struct LineLayout<'a> {
+    layouter: &'a LineLayouter,
+
+    /// How many lines page up and page down move.
+    page_lines: usize,
+}
+
+impl EditLayout for LineLayout<'_> {
+    fn clamp_cursor(&self, text: &dyn TextBuffer, ccursor: CCursor) -> CCursor {
+        CCursor {
+            index: ccursor.index.at_most(text.char_count()),
+            ..ccursor
+        }
+    }
+
+    fn move_cursor(
+        &self,
+        text: &dyn TextBuffer,
+        os: OperatingSystem,
+        event: &Event,
+        cursor_range: &mut CCursorRange,
+    ) -> bool {
+        match event {
+            Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } => move_cursor(
+                os,
+                text,
+                self.layouter,
+                cursor_range,
+                *key,
+                modifiers,
+                self.page_lines,
+            ),
+            _ => false,
+        }
+    }
+
+    fn begin_of_paragraph(&self, text: &dyn TextBuffer, ccursor: CCursor) -> CCursor {
+        CCursor::new(text.char_index_from_line(text.line_from_char_index(ccursor.index)))
+    }
+
+    fn end_of_paragraph(&self, text: &dyn TextBuffer, ccursor: CCursor) -> CCursor {
+        line_end(text, text.line_from_char_index(ccursor.index))
+    }
+
+    fn text_changed(&mut self, _ui: &Ui, _text: &dyn TextBuffer) {
+        // The visible lines are laid out when they are painted.
+    }
+}
+
+/// The cursor at the end of the line, before its newline.
+fn line_end(text: &dyn TextBuffer, line: usize) -> CCursor {
+    CCursor::new(
+        text.char_index_from_line(line + 1)
+            .saturating_sub(usize::from(line + 1 < text.line_count())),
+    )
+}
+
+// ----------------------------------------------------------------------------
+
+impl Widget for VirtualTextEdit<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let Self {
+            text,
+            id,
+            id_salt,
+            font_selection,
+            text_color,
+            desired_width,
+            interactive,
+        } = self;
+
+        let font_id = font_selection.resolve(ui.style());
+        let row_height = ui.fonts_mut(|f| f.row_height(&font_id));
+        let text_color = text_color
+            .or(ui.visuals().override_text_color)
+            .unwrap_or_else(|| ui.visuals().widgets.inactive.text_color());
+        let layouter = LineLayouter {
+            ctx: ui.ctx().clone(),
+            font_id,
+            text_color,
+        };
+
+        let id = id.unwrap_or_else(|| {
+            if let Some(id_salt) = id_salt {
+                ui.make_persistent_id(id_salt)
+            } else {
+                ui.next_auto_id()
+            }
+        });
+        let mut state = VirtualTextEditState::load(ui.ctx(), id).unwrap_or_default();
+
+        let sense = if interactive {
+            Sense::click_and_drag()
+        } else {
+            Sense::hover()
+        };
+
+        // Handle the keyboard before allocating space, so that the size is that of the edited text:
+        let mut text_changed = false;
+        let mut cursor_moved = false;
+        if interactive && ui.memory(|mem| mem.has_focus(id)) {
+            let event_filter = EventFilter {
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                tab: true,
+                // Escape goes back to a single cursor:
+                escape: !state.text_edit.extra_char_ranges().is_empty(),
+            };
+            ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
+
+            let visible_lines = (ui.clip_rect().height() / row_height).floor() as usize;
+            let prev_cursor_ranges = state.text_edit.char_ranges();
+            let mut editor = Editor::new(ui.input(|i| i.time), None, None);
+            (text_changed, _) = events(
+                ui,
+                &mut state.text_edit,
+                &mut editor,
+                text,
+                &mut LineLayout {
+                    layouter: &layouter,
+                    page_lines: visible_lines.at_least(1),
+                },
+                id,
+                true,
+                false,
+                CCursorRange::default(),
+                usize::MAX,
+                event_filter,
+                Some(KeyboardShortcut::new(Modifiers::NONE, Key::Enter)),
+            );
+            cursor_moved = state.text_edit.char_ranges() != prev_cursor_ranges;
+            if text_changed || cursor_moved {
+                state.text_edit.last_interaction_time = ui.input(|i| i.time);
+            }
+        }
+
+        let line_count = text.line_count();
+        if line_count <= state.widest_line {
+            state.widest_line = 0;
+            state.max_line_width = 0.0;
+        }
+        let width = desired_width
+            .unwrap_or_else(|| ui.available_width())
+            .max(state.max_line_width + 2.0); // room for the cursor
+        let (_, rect) = ui.allocate_space(vec2(width, line_count as f32 * row_height));
+
+        let mut response = ui.interact(rect, id, sense);
+        if text_changed {
+            response.mark_changed();
+        }
+
+        let line_at_y = |y: f32| {
+            (((y - rect.top()) / row_height).floor().max(0.0) as usize)
+                .at_most(line_count.saturating_sub(1))
+        };
+
+        if interactive && let Some(pointer_pos) = response.interact_pointer_pos() {
+            let line = line_at_y(pointer_pos.y);
+            let cursor_at_pointer = layouter.cursor_from_x(text, line, pointer_pos.x - rect.left());
+
+            let did_interact = if response.hovered() && ui.input(|i| i.pointer.any_pressed()) {
+                let mut cursor_range = CCursorRange::one(cursor_at_pointer);
+                if ui.input(|i| i.modifiers.shift)
+                    && let Some(previous) = state.text_edit.cursor.char_range()
+                {
+                    cursor_range.secondary = previous.secondary;
+                }
+                state.text_edit.cursor.set_char_range(Some(cursor_range));
+                true
+            } else if ui.ctx().is_being_dragged(id)
+                && let Some(mut cursor_range) = state.text_edit.cursor.char_range()
+            {
+                cursor_range.primary = cursor_at_pointer;
+                state.text_edit.cursor.set_char_range(Some(cursor_range));
+                true
+            } else {
+                false
+            };
+
+            if did_interact || response.clicked() {
+                ui.memory_mut(|mem| mem.request_focus(id));
+                state.text_edit.last_interaction_time = ui.input(|i| i.time);
+            }
+        }
+
+        if interactive && response.hovered() {
+            ui.ctx().set_cursor_icon(CursorIcon::Text);
+        }
+
+        let has_focus = ui.memory(|mem| mem.has_focus(id));
+
+        // Only lay out the lines we can see:
+        let clip_rect = ui.clip_rect().intersect(rect);
+        let visible_lines = if clip_rect.is_positive() {
+            line_at_y(clip_rect.top())..(line_at_y(clip_rect.bottom()) + 1).at_most(line_count)
+        } else {
+            0..0
+        };
+
+        // The main cursor range comes first:
+        let cursor_ranges: Vec<CCursorRange> = if has_focus {
+            let char_count = text.char_count();
+            (state.text_edit.char_ranges().into_iter())
+                .map(|mut range| {
+                    range.primary.index = range.primary.index.at_most(char_count);
+                    range.secondary.index = range.secondary.index.at_most(char_count);
+                    range
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let cursor_rect_at = |line: usize, x: f32| {
+            Rect::from_min_size(
+                rect.left_top() + vec2(x, line as f32 * row_height),
+                vec2(0.0, row_height),
+            )
+            .expand(1.5)
+        };
+        let mut cursor_rects = Vec::new();
+        let mut main_cursor_rect = None;
+        let mut widest_visible: Option<(usize, f32)> = None;
+        let widest_line_visible = visible_lines.contains(&state.widest_line);
+
+        let painter = ui.painter_at(rect.expand(1.0)); // expand to avoid clipping cursor
+        for line in visible_lines.clone() {
+            let galley = layouter.layout(text, line);
+            let line_pos = rect.left_top() + vec2(0.0, line as f32 * row_height);
+            let line_width = galley.size().x;
+            if widest_visible.is_none_or(|(_, width)| width < line_width) {
+                widest_visible = Some((line, line_width));
+            }
+
+            let line_start = if cursor_ranges.is_empty() {
+                0
+            } else {
+                text.char_index_from_line(line)
+            };
+            let line_end = line_start + galley.chars().count();
+            let x_from_index = |index: usize| {
+                let column = index.clamp(line_start, line_end) - line_start;
+                galley.pos_from_cursor(CCursor::new(column)).min.x
+            };
+
+            for (i, cursor_range) in cursor_ranges.iter().enumerate() {
+                let selection = cursor_range.as_sorted_char_range();
+                if !selection.is_empty()
+                    && selection.start <= line_end
+                    && line_start < selection.end
+                {
+                    let mut right = x_from_index(selection.end);
+                    if line_end < selection.end {
+                        right += 0.5 * row_height; // visualize that we select the newline
+                    }
+                    let selection_rect = Rect::from_x_y_ranges(
+                        line_pos.x + x_from_index(selection.start)..=line_pos.x + right,
+                        line_pos.y..=line_pos.y + row_height,
+                    );
+                    painter.rect_filled(selection_rect, 0.0, ui.visuals().selection.bg_fill);
+                }
+
+                if (line_start..=line_end).contains(&cursor_range.primary.index) {
+                    let cursor_rect =
+                        cursor_rect_at(line, x_from_index(cursor_range.primary.index));
+                    if i == 0 {
+                        main_cursor_rect = Some(cursor_rect);
+                    }
+                    cursor_rects.push(cursor_rect);
+                }
+            }
+
+            painter.galley(line_pos, galley, text_color);
+        }
+
+        if let Some((line, width)) = widest_visible
+            && (widest_line_visible || state.max_line_width < width)
+        {
+            state.widest_line = line;
+            state.max_line_width = width;
+        }
+
+        if let Some(main_cursor_range) = cursor_ranges.first() {
+            // The main cursor may be outside the visible lines, e.g. after jumping to the end:
+            let main_cursor_rect = main_cursor_rect.unwrap_or_else(|| {
+                let ccursor = main_cursor_range.primary;
+                cursor_rect_at(
+                    text.line_from_char_index(ccursor.index),
+                    layouter.x_from_cursor(text, ccursor),
+                )
+            });
+
+            if cursor_moved {
+                ui.scroll_to_rect(main_cursor_rect, None);
+            }
+
+            if interactive && text.is_mutable() {
+                let now = ui.input(|i| i.time);
+                if ui.input(|i| i.focused) {
+                    paint_text_cursors(
+                        ui,
+                        &painter,
+                        &cursor_rects,
+                        now - state.text_edit.last_interaction_time,
+                    );
+                }
+
+                // Set IME output (in screen coords) when text is editable and visible
+                let to_global = ui
+                    .ctx()
+                    .layer_transform_to_global(ui.layer_id())
+                    .unwrap_or_default();
+
+                ui.ctx().output_mut(|o| {
+                    o.ime = Some(crate::output::IMEOutput {
+                        rect: to_global * rect,
+                        cursor_rect: to_global * main_cursor_rect,
+                    });
+                });
+            } else {
+                for &cursor_rect in &cursor_rects {
+                    paint_cursor_end(&painter, ui.visuals(), cursor_rect);
+                }
+            }
+        }
+
+        if response.lost_focus() {
+            // Leave multi-cursor editing:
+            let text_edit = &mut state.text_edit;
+            text_edit.set_char_ranges(text_edit.cursor.char_range().into_iter().collect());
+        }
+
+        // Ensures correct IME behavior when the text input area gains or loses focus.
+        if state.text_edit.ime_enabled && (response.gained_focus() || response.lost_focus()) {
+            state.text_edit.ime_enabled = false;
+            if let Some(mut ccursor_range) = state.text_edit.cursor.char_range() {
+                ccursor_range.secondary.index = ccursor_range.primary.index;
+                state.text_edit.cursor.set_char_range(Some(ccursor_range));
+            }
+            ui.input_mut(|i| i.events.retain(|e| !matches!(e, Event::Ime(_))));
+        }
+
+        state.store(ui.ctx(), id);
+
+        let visible_text = || {
+            let start = text.char_index_from_line(visible_lines.start);
+            let end = text.char_index_from_line(visible_lines.end);
+            text.char_range_text(start..end).into_owned()
+        };
+
+        response.widget_info(|| {
+            let visible_text = visible_text();
+            WidgetInfo::text_edit(ui.is_enabled(), &visible_text, &visible_text, "")
+        });
+
+        #[cfg(feature = "accesskit")]
+        ui.ctx().accesskit_node_builder(id, |builder| {
+            builder.set_role(accesskit::Role::MultilineTextInput);
+            builder.set_value(visible_text());
+        });
+
+        response
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Move the cursor with the keyboard, line by line.
+///
+/// Returns `true` if the key moves the cursor.
+fn move_cursor(
+    os: OperatingSystem,
+    text: &dyn TextBuffer,
+    layouter: &LineLayouter,
+    cursor_range: &mut CCursorRange,
+    key: Key,
+    modifiers: &Modifiers,
+    page_lines: usize,
+) -> bool {
+    let char_count = text.char_count();
+    let cursor = cursor_range.primary;
+    let line = text.line_from_char_index(cursor.index);
+    let line_start = CCursor::new(text.char_index_from_line(line));
+    let line_end = line_end(text, line);
+
+    let h_pos = cursor_range.h_pos;
+    let vertical_move = |lines_down: isize| {
+        let x = h_pos.unwrap_or_else(|| layouter.x_from_cursor(text, cursor));
+        let target_line = line as isize + lines_down;
+        let new_cursor = if target_line < 0 {
+            CCursor::new(0)
+        } else if text.line_count() as isize <= target_line {
+            CCursor::new(char_count)
+        } else {
+            layouter.cursor_from_x(text, target_line as usize, x)
+        };
+        (new_cursor, x)
+    };
+
+    let new_cursor = match key {
+        Key::A if modifiers.command => {
+            *cursor_range = CCursorRange::two(CCursor::new(0), CCursor::new(char_count));
+            return true;
+        }
+        Key::ArrowLeft | Key::ArrowRight if modifiers.is_none() && !cursor_range.is_empty() => {
+            let [min, max] = cursor_range.sorted_cursors();
+            *cursor_range = CCursorRange::one(if key == Key::ArrowLeft { min } else { max });
+            return true;
+        }
+        Key::ArrowLeft if modifiers.alt || modifiers.ctrl => {
+            // Jump by words within the line:
+            if cursor == line_start {
+                CCursor::new(cursor.index.saturating_sub(1))
+            } else {
+                let line_text = text.line(line);
+                let column = ccursor_previous_word(&line_text, cursor - line_start.index);
+                line_start + column.index
+            }
+        }
+        Key::ArrowRight if modifiers.alt || modifiers.ctrl => {
+            if cursor == line_end {
+                CCursor::new((cursor.index + 1).at_most(char_count))
+            } else {
+                let line_text = text.line(line);
+                let column = ccursor_next_word(&line_text, cursor - line_start.index);
+                line_start + column.index
+            }
+        }
+        Key::ArrowLeft if modifiers.mac_cmd => line_start,
+        Key::ArrowRight if modifiers.mac_cmd => line_end,
+        Key::ArrowLeft => CCursor::new(cursor.index.saturating_sub(1)),
+        Key::ArrowRight => CCursor::new((cursor.index + 1).at_most(char_count)),
+        Key::ArrowUp if modifiers.command => CCursor::new(0),
+        Key::ArrowDown if modifiers.command => CCursor::new(char_count),
+        Key::Home if modifiers.ctrl => CCursor::new(0),
+        Key::End if modifiers.ctrl => CCursor::new(char_count),
+        Key::ArrowUp | Key::ArrowDown | Key::PageUp | Key::PageDown => {
+            let lines_down = match key {
+                Key::ArrowUp => -1,
+                Key::ArrowDown => 1,
+                Key::PageUp => -(page_lines as isize),
+                _ => page_lines as isize,
+            };
+            let (new_cursor, x) = vertical_move(lines_down);
+            cursor_range.h_pos = Some(x);
+            new_cursor
+        }
+        Key::Home => line_start,
+        Key::End => line_end,
+        Key::A if os == OperatingSystem::Mac && modifiers.ctrl => line_start,
+        Key::E if os == OperatingSystem::Mac && modifiers.ctrl => line_end,
+        _ => return false,
+    };
+
+    if !matches!(
+        key,
+        Key::ArrowUp | Key::ArrowDown | Key::PageUp | Key::PageDown
+    ) {
+        cursor_range.h_pos = None;
+    }
+    cursor_range.primary = new_cursor;
+    if !modifiers.shift {
+        cursor_range.secondary = new_cursor;
+    }
+    true
+}
@@ -146,7 +146,12 @@ fn available_characters(ui: &egui::Ui, family: &egui::FontFamily) -> BTreeMap<ch
             .font(family)
             .characters()
             .iter()
-            .filter(|(chr, _fonts)| !chr.is_whitespace() && !chr.is_ascii_control())
+            .filter(|(chr, _fonts)| {
+                // A soft hyphen is invisible unless it hyphenates a word at the end of a row:
+                !chr.is_whitespace()
+                    && !chr.is_ascii_control()
+                    && **chr != egui::epaint::text::SOFT_HYPHEN
+            })
             .map(|(chr, fonts)| {
                 (
                     *chr,
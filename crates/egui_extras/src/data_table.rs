@@ -0,0 +1,933 @@
+//! A data table on top of [`TableBuilder`], that takes care of sorting, selection,
+//! column order, hiding columns and editing cells.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use egui::{
+    DragAndDrop, Event, EventFilter, Id, Key, Label, Modifiers, Response, RichText, Sense, Ui,
+};
+
+use crate::{Column, TableBuilder};
+
+/// Which way a [`DataTable`] is sorted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// The sorting, selection and columns of a [`DataTable`], stored between frames.
+///
+/// Rows are identified by their index in the slice given to [`DataTable::show`],
+/// and columns by their title.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct DataTableState {
+    sort: Option<(String, SortOrder)>,
+
+    selected: BTreeSet<usize>,
+
+    /// The row that keyboard navigation moves from.
+    cursor: Option<usize>,
+
+    /// Where a shift-click or shift-arrow range selection starts.
+    anchor: Option<usize>,
+
+    /// The titles of the columns, in the order the user put them.
+    column_order: Vec<String>,
+
+    hidden: BTreeSet<String>,
+
+    /// The width of each column when it was last shown, by title,
+    /// so a column keeps the width the user gave it when columns are moved or hidden.
+    column_widths: BTreeMap<String, f32>,
+
+    /// The titles of the columns that were shown last frame, in order.
+    shown_columns: Vec<String>,
+
+    /// The cell that is being edited, and whether its editor got the focus yet.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    editing: Option<(usize, String, bool)>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    sorted_order: Option<SortedOrder>,
+}
+
+/// The order the rows were sorted in, and what it was sorted for.
+#[derive(Clone, Debug)]
+struct SortedOrder {
+    sort: Option<(String, SortOrder)>,
+    num_rows: usize,
+
+    /// The [`DataTable::revision`] of the rows.
+    revision: u64,
+
+    order: Arc<[usize]>,
+}
+
+impl DataTableState {
+    pub fn load(ctx: &egui::Context, id: Id) -> Option<Self> {
+        #[cfg(feature = "serde")]
+        let state = ctx.data_mut(|d| d.get_persisted(id));
+        #[cfg(not(feature = "serde"))]
+        let state = ctx.data_mut(|d| d.get_temp(id));
+        state
+    }
+
+    pub fn store(self, ctx: &egui::Context, id: Id) {
+        #[cfg(feature = "serde")]
+        ctx.data_mut(|d| d.insert_persisted(id, self));
+        #[cfg(not(feature = "serde"))]
+        ctx.data_mut(|d| d.insert_temp(id, self));
+    }
+
+    /// The title of the column the table is sorted by, and in which order.
+    pub fn sort(&self) -> Option<(&str, SortOrder)> {
+        self.sort
+            .as_ref()
+            .map(|(column, order)| (column.as_str(), *order))
+    }
+
+    pub fn set_sort(&mut self, sort: Option<(String, SortOrder)>) {
+        self.sort = sort;
+    }
+
+    /// Sort the rows again the next time the table is shown.
+    ///
+    /// Load the state with [`Self::load`] and store it again for this to have an effect.
+    /// Usually it is easier to change [`DataTable::revision`] when the rows change.
+    pub fn invalidate_sort(&mut self) {
+        self.sorted_order = None;
+    }
+
+    /// The order in which to show the rows, sorted again only if needed.
+    fn order<Row>(
+        &mut self,
+        columns: &[DataColumn<'_, Row>],
+        rows: &[Row],
+        revision: u64,
+    ) -> Arc<[usize]> {
+        match &self.sorted_order {
+            Some(sorted)
+                if sorted.sort == self.sort
+                    && sorted.num_rows == rows.len()
+                    && sorted.revision == revision =>
+            {
+                sorted.order.clone()
+            }
+            _ => {
+                let order: Arc<[usize]> = sorted_order(columns, self.sort.as_ref(), rows).into();
+                self.sorted_order = Some(SortedOrder {
+                    sort: self.sort.clone(),
+                    num_rows: rows.len(),
+                    revision,
+                    order: order.clone(),
+                });
+                order
+            }
+        }
+    }
+
+    /// The indices of the selected rows.
+    pub fn selected(&self) -> &BTreeSet<usize> {
+        &self.selected
+    }
+
+    pub fn is_selected(&self, row: usize) -> bool {
+        self.selected.contains(&row)
+    }
+
+    /// Select only these rows.
+    pub fn set_selected(&mut self, selected: impl IntoIterator<Item = usize>) {
+        self.selected = selected.into_iter().collect();
+        self.cursor = self.selected.last().copied();
+        self.anchor = self.cursor;
+    }
+
+    pub fn is_hidden(&self, column: &str) -> bool {
+        self.hidden.contains(column)
+    }
+
+    pub fn set_hidden(&mut self, column: &str, hidden: bool) {
+        if hidden {
+            self.hidden.insert(column.to_owned());
+        } else {
+            self.hidden.remove(column);
+        }
+    }
+
+    /// How wide the column was when it was last shown.
+    pub fn column_width(&self, column: &str) -> Option<f32> {
+        self.column_widths.get(column).copied()
+    }
+
+    /// Select `row` in response to a click.
+    ///
+    /// `order` is the order in which the rows are shown.
+    fn click(&mut self, order: &[usize], row: usize, modifiers: Modifiers) {
+        if modifiers.shift
+            && let Some(anchor) = self.anchor
+        {
+            if !modifiers.command {
+                self.selected.clear();
+            }
+            self.select_range(order, anchor, row);
+        } else if modifiers.command {
+            if !self.selected.remove(&row) {
+                self.selected.insert(row);
+            }
+            self.anchor = Some(row);
+        } else {
+            self.selected = BTreeSet::from([row]);
+            self.anchor = Some(row);
+        }
+        self.cursor = Some(row);
+    }
+
+    /// Move the cursor by `delta` shown rows, and select the row it ends up on.
+    fn step(&mut self, order: &[usize], delta: isize, extend: bool) {
+        let Some(last) = order.len().checked_sub(1) else {
+            return;
+        };
+        let position = self
+            .cursor
+            .and_then(|cursor| order.iter().position(|row| *row == cursor));
+        let position = match position {
+            Some(position) => position.saturating_add_signed(delta).min(last),
+            None if delta < 0 => last,
+            None => 0,
+        };
+        let row = order[position];
+
+        match self.anchor {
+            Some(anchor) if extend => {
+                self.selected.clear();
+                self.select_range(order, anchor, row);
+            }
+            _ => {
+                self.selected = BTreeSet::from([row]);
+                self.anchor = Some(row);
+            }
+        }
+        self.cursor = Some(row);
+    }
+
+    /// Select the shown rows from `from` to `to`.
+    fn select_range(&mut self, order: &[usize], from: usize, to: usize) {
+        let position = |row| order.iter().position(|r| *r == row);
+        if let (Some(from), Some(to)) = (position(from), position(to)) {
+            let range = from.min(to)..=from.max(to);
+            self.selected.extend(&order[range]);
+        }
+    }
+
+    /// Put `column` just before or after `target`.
+    pub fn move_column(&mut self, column: &str, target: &str, after: bool) {
+        if column == target {
+            return;
+        }
+        self.column_order.retain(|c| c != column);
+        if let Some(index) = self.column_order.iter().position(|c| c == target) {
+            let index = if after { index + 1 } else { index };
+            self.column_order.insert(index, column.to_owned());
+        }
+    }
+}
+
+type CellText<'a, Row> = Box<dyn Fn(&Row) -> String + 'a>;
+type CellUi<'a, Row> = Box<dyn Fn(&mut Ui, &Row) + 'a>;
+type CompareRows<'a, Row> = Box<dyn Fn(&Row, &Row) -> Ordering + 'a>;
+type EditCell<'a, Row> = Box<dyn Fn(&mut Ui, &mut Row) -> Response + 'a>;
+
+/// A column of a [`DataTable`].
+///
+/// The text of each cell is used for showing, sorting and copying, unless overridden.
+pub struct DataColumn<'a, Row> {
+    title: String,
+    width: Column,
+    text: CellText<'a, Row>,
+    cell_ui: Option<CellUi<'a, Row>>,
+    compare: Option<CompareRows<'a, Row>>,
+    edit: Option<EditCell<'a, Row>>,
+    sortable: bool,
+    hideable: bool,
+}
+
+impl<'a, Row> DataColumn<'a, Row> {
+    /// The title must be unique in the table, since it identifies the column.
+    pub fn new(title: impl Into<String>, text: impl Fn(&Row) -> String + 'a) -> Self {
+        Self {
+            title: title.into(),
+            width: Column::auto().resizable(true),
+            text: Box::new(text),
+            cell_ui: None,
+            compare: None,
+            edit: None,
+            sortable: true,
+            hideable: true,
+        }
+    }
+
+    /// How wide the column is.
+    ///
+    /// Default: [`Column::auto`], resizable.
+    #[inline]
+    pub fn width(mut self, width: Column) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Show the cells with this, instead of as a label with the text.
+    #[inline]
+    pub fn cell_ui(mut self, cell_ui: impl Fn(&mut Ui, &Row) + 'a) -> Self {
+        self.cell_ui = Some(Box::new(cell_ui));
+        self
+    }
+
+    /// Sort by this, instead of by the text.
+    #[inline]
+    pub fn sort_by(mut self, compare: impl Fn(&Row, &Row) -> Ordering + 'a) -> Self {
+        self.compare = Some(Box::new(compare));
+        self
+    }
+
+    /// Can the user sort the table by clicking the header of this column?
+    ///
+    /// Default: `true`.
+    #[inline]
+    pub fn sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+
+    /// Can the user hide this column, from the context menu of the header?
+    ///
+    /// Default: `true`.
+    #[inline]
+    pub fn hideable(mut self, hideable: bool) -> Self {
+        self.hideable = hideable;
+        self
+    }
+
+    /// Let the user edit the cells of this column, by double-clicking them or pressing F2.
+    ///
+    /// The editing ends when the returned [`Response`] loses the focus,
+    /// so this should usually be a [`egui::TextEdit`] or a [`egui::DragValue`].
+    #[inline]
+    pub fn editable(mut self, edit: impl Fn(&mut Ui, &mut Row) -> Response + 'a) -> Self {
+        self.edit = Some(Box::new(edit));
+        self
+    }
+}
+
+/// What [`DataTable::show`] returns.
+pub struct DataTableOutput {
+    /// The response of the whole table.
+    ///
+    /// It has the keyboard focus when the table does, and is [`Response::changed`] when the selection changed.
+    ///
+    /// The [`DataTableState`] is stored under its id, see [`DataTableState::load`].
+    pub response: Response,
+
+    /// The rows that were edited this frame.
+    pub edited: Vec<usize>,
+}
+
+/// The column of a [`DataTable`] that is being dragged to another place.
+struct DraggedColumn {
+    table_id: Id,
+    title: String,
+}
+
+/// A table of rows of data, on top of [`TableBuilder`].
+///
+/// The user can:
+/// * sort by clicking a column header,
+/// * select rows by clicking them, with shift and ctrl/cmd for multiple rows, or with the arrow keys,
+/// * move columns by dragging their headers, and hide them from the context menu of a header,
+/// * edit the cells of [`DataColumn::editable`] columns,
+/// * copy the selected rows as tab-separated values.
+///
+/// The sorting, selection and columns are remembered in [`egui::Memory`],
+/// and persisted with the `serde` feature.
+/// The rows are only sorted again when the sorting, the number of rows or [`DataTable::revision`] changes,
+/// or a cell is edited, so change the revision when you change the rows some other way.
+///
+/// ```
+/// # egui::__run_test_ui(|ui| {
+/// use egui_extras::{DataColumn, DataTable};
+///
+/// struct Fruit {
+///     name: String,
+///     count: u32,
+/// }
+///
+/// let mut fruits = vec![
+///     Fruit { name: "Apple".to_owned(), count: 3 },
+///     Fruit { name: "Banana".to_owned(), count: 12 },
+/// ];
+///
+/// DataTable::new("fruits")
+///     .column(
+///         DataColumn::new("Name", |fruit: &Fruit| fruit.name.clone())
+///             .editable(|ui, fruit| ui.text_edit_singleline(&mut fruit.name)),
+///     )
+///     .column(
+///         DataColumn::new("Count", |fruit: &Fruit| fruit.count.to_string())
+///             .sort_by(|a, b| a.count.cmp(&b.count)),
+///     )
+///     .show(ui, &mut fruits);
+/// # });
+/// ```
+pub struct DataTable<'a, Row> {
+    id_salt: Id,
+    columns: Vec<DataColumn<'a, Row>>,
+    row_height: Option<f32>,
+    striped: Option<bool>,
+    revision: u64,
+}
+
+impl<'a, Row> DataTable<'a, Row> {
+    pub fn new(id_salt: impl std::hash::Hash) -> Self {
+        Self {
+            id_salt: Id::new(id_salt),
+            columns: Vec::new(),
+            row_height: None,
+            striped: None,
+            revision: 0,
+        }
+    }
+
+    /// Add a column.
+    #[inline]
+    pub fn column(mut self, column: DataColumn<'a, Row>) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// The height of each row, and of the header.
+    ///
+    /// Default: [`egui::style::Spacing::interact_size`]`.y`.
+    #[inline]
+    pub fn row_height(mut self, row_height: f32) -> Self {
+        self.row_height = Some(row_height);
+        self
+    }
+
+    /// Default: whatever is in [`egui::Visuals::striped`].
+    #[inline]
+    pub fn striped(mut self, striped: bool) -> Self {
+        self.striped = Some(striped);
+        self
+    }
+
+    /// The revision of the rows, e.g. a counter that you increase whenever you change them.
+    ///
+    /// The rows are sorted again when this changes.
+    ///
+    /// Default: `0`.
+    #[inline]
+    pub fn revision(mut self, revision: u64) -> Self {
+        self.revision = revision;
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui, rows: &mut [Row]) -> DataTableOutput {
+        let Self {
+            id_salt,
+            columns,
+            row_height,
+            striped,
+            revision,
+        } = self;
+        let id = ui.make_persistent_id(id_salt);
+        let row_height = row_height.unwrap_or(ui.spacing().interact_size.y);
+        let mut state = DataTableState::load(ui.ctx(), id).unwrap_or_default();
+        state.selected.retain(|row| *row < rows.len());
+        if state
+            .editing
+            .as_ref()
+            .is_some_and(|(row, _, _)| rows.len() <= *row)
+        {
+            state.editing = None;
+        }
+        let prev_selected = state.selected.clone();
+
+        // Keep the columns the user moved where they put them, and add new ones at the end:
+        state
+            .column_order
+            .retain(|title| columns.iter().any(|c| c.title == *title));
+        for column in &columns {
+            if !state.column_order.contains(&column.title) {
+                state.column_order.push(column.title.clone());
+            }
+        }
+        let mut visible: Vec<&DataColumn<'a, Row>> = (state.column_order.iter())
+            .filter(|title| !state.hidden.contains(*title))
+            .filter_map(|title| columns.iter().find(|c| c.title == *title))
+            .collect();
+        if visible.is_empty()
+            && let Some(first) = columns.first()
+        {
+            state.hidden.remove(&first.title);
+            visible.push(first);
+        }
+
+        let order = state.order(&columns, rows, revision);
+
+        let has_focus = ui.memory(|mem| mem.has_focus(id));
+        let mut scroll_to_row = None;
+        if has_focus && state.editing.is_none() {
+            scroll_to_row = keyboard_navigation(ui, &mut state, &order);
+            if ui.input(|i| i.events.iter().any(|e| matches!(e, Event::Copy))) {
+                ui.ctx()
+                    .copy_text(to_tsv(&visible, &state.selected, &order, rows));
+            }
+            let edit = ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::F2));
+            if edit
+                && let Some(cursor) = state.cursor
+                && let Some(column) = visible.iter().find(|c| c.edit.is_some())
+            {
+                state.editing = Some((cursor, column.title.clone(), false));
+            }
+        }
+
+        let table_top = ui.cursor().min;
+        let shown_columns: Vec<String> = visible.iter().map(|c| c.title.clone()).collect();
+        let mut builder = TableBuilder::new(ui).id_salt(id_salt).sense(Sense::click());
+        if state.shown_columns != shown_columns {
+            // The table stores its widths by position, so start over from the widths we stored by title:
+            builder.reset();
+        }
+        for column in &visible {
+            builder = builder.column(match state.column_widths.get(&column.title) {
+                Some(width) => column.width.with_initial_width(*width),
+                None => column.width,
+            });
+        }
+        if let Some(striped) = striped {
+            builder = builder.striped(striped);
+        }
+        if let Some(row) = scroll_to_row {
+            builder = builder.scroll_to_row(row, None);
+        }
+
+        let mut edited = Vec::new();
+        builder
+            .header(row_height, |mut header| {
+                for column in &visible {
+                    let (_, response) = header.col(|ui| {
+                        header_cell(ui, id, &mut state, &columns, column);
+                    });
+                    column_drop_target(&response.ctx, id, &mut state, column, &response);
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, order.len(), |mut table_row| {
+                    let row = order[table_row.index()];
+                    table_row.set_selected(state.selected.contains(&row));
+                    for column in &visible {
+                        let (_, response) = table_row.col(|ui| {
+                            cell(ui, &mut state, &mut edited, &mut rows[row], row, column);
+                        });
+                        if response.double_clicked() && column.edit.is_some() {
+                            state.editing = Some((row, column.title.clone(), false));
+                        }
+                    }
+                    let response = table_row.response();
+                    if response.clicked() {
+                        let modifiers = response.ctx.input(|i| i.modifiers);
+                        state.click(&order, row, modifiers);
+                    }
+                });
+            });
+
+        if !edited.is_empty() {
+            state.invalidate_sort();
+        }
+
+        if let Some(widths) = crate::table::stored_column_widths(ui, id_salt)
+            && widths.len() == shown_columns.len()
+        {
+            state
+                .column_widths
+                .extend(shown_columns.iter().cloned().zip(widths));
+        }
+        state.shown_columns = shown_columns;
+
+        let table_rect = egui::Rect::from_min_max(table_top, ui.min_rect().max);
+        let mut response = ui.interact(table_rect, id, Sense::focusable_noninteractive());
+        if state.selected != prev_selected {
+            if !response.has_focus() && state.editing.is_none() {
+                response.request_focus();
+            }
+            response.mark_changed();
+        }
+        if response.has_focus() {
+            // Keep the arrow keys from moving the focus to other widgets:
+            let event_filter = EventFilter {
+                vertical_arrows: true,
+                ..Default::default()
+            };
+            ui.memory_mut(|mem| mem.set_focus_lock_filter(id, event_filter));
+        }
+
+        state.store(ui.ctx(), id);
+        DataTableOutput { response, edited }
+    }
+}
+
+/// The title of a column, which sorts when clicked, can be dragged to another place,
+/// and has a context menu for hiding columns.
+fn header_cell<Row>(
+    ui: &mut Ui,
+    table_id: Id,
+    state: &mut DataTableState,
+    columns: &[DataColumn<'_, Row>],
+    column: &DataColumn<'_, Row>,
+) {
+    let arrow = match state.sort() {
+        Some((title, SortOrder::Ascending)) if title == column.title => " ⏶",
+        Some((title, SortOrder::Descending)) if title == column.title => " ⏷",
+        _ => "",
+    };
+    let response = ui.add(
+        Label::new(RichText::new(format!("{}{arrow}", column.title)).strong())
+            .selectable(false)
+            .sense(Sense::click_and_drag()),
+    );
+
+    if column.sortable && response.clicked() {
+        state.sort = match state.sort.take() {
+            Some((title, SortOrder::Ascending)) if title == column.title => {
+                Some((title, SortOrder::Descending))
+            }
+            Some((title, SortOrder::Descending)) if title == column.title => None,
+            _ => Some((column.title.clone(), SortOrder::Ascending)),
+        };
+    }
+    if response.drag_started() {
+        DragAndDrop::set_payload(
+            ui.ctx(),
+            DraggedColumn {
+                table_id,
+                title: column.title.clone(),
+            },
+        );
+    }
+    if response.dragged() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+    }
+
+    response.context_menu(|ui| {
+        let num_visible = (columns.iter())
+            .filter(|c| !state.is_hidden(&c.title))
+            .count();
+        for column in columns.iter().filter(|c| c.hideable) {
+            let mut visible = !state.is_hidden(&column.title);
+            // Always keep one column:
+            let enabled = !visible || 1 < num_visible;
+            if ui
+                .add_enabled(
+                    enabled,
+                    egui::Checkbox::new(&mut visible, column.title.as_str()),
+                )
+                .changed()
+            {
+                state.set_hidden(&column.title, !visible);
+            }
+        }
+        ui.separator();
+        if ui.button("Reset columns").clicked() {
+            state.column_order.clear();
+            state.hidden.clear();
+            ui.close();
+        }
+    });
+}
+
+/// Show where a dragged column would go, and move it there when it is dropped.
+fn column_drop_target<Row>(
+    ctx: &egui::Context,
+    table_id: Id,
+    state: &mut DataTableState,
+    column: &DataColumn<'_, Row>,
+    response: &Response,
+) {
+    let Some(dragged) = response.dnd_hover_payload::<DraggedColumn>() else {
+        return;
+    };
+    let Some(pointer) = ctx.pointer_interact_pos() else {
+        return;
+    };
+    if dragged.table_id != table_id || dragged.title == column.title {
+        return;
+    }
+
+    let rect = response.rect;
+    let after = rect.center().x < pointer.x;
+    let x = if after { rect.right() } else { rect.left() };
+    let stroke = ctx.style().visuals.widgets.active.bg_stroke;
+    ctx.layer_painter(response.layer_id)
+        .vline(x, rect.y_range(), stroke);
+
+    if let Some(dragged) = response.dnd_release_payload::<DraggedColumn>() {
+        state.move_column(&dragged.title, &column.title, after);
+    }
+}
+
+/// A single cell, which is either shown or being edited.
+fn cell<Row>(
+    ui: &mut Ui,
+    state: &mut DataTableState,
+    edited: &mut Vec<usize>,
+    row: &mut Row,
+    index: usize,
+    column: &DataColumn<'_, Row>,
+) {
+    if let Some((edit_row, edit_column, has_focused)) = &mut state.editing
+        && *edit_row == index
+        && *edit_column == column.title
+        && let Some(edit) = &column.edit
+    {
+        let response = edit(ui, row);
+        if !*has_focused {
+            response.request_focus();
+            *has_focused = true;
+        } else if !response.has_focus() {
+            state.editing = None;
+        }
+        if response.changed() {
+            edited.push(index);
+        }
+        return;
+    }
+
+    if let Some(cell_ui) = &column.cell_ui {
+        cell_ui(ui, row);
+    } else {
+        ui.add(Label::new((column.text)(row)).selectable(false));
+    }
+}
+
+/// Move the cursor with the arrow keys, and select all with ctrl/cmd+A.
+///
+/// Returns the shown row to scroll to.
+fn keyboard_navigation(ui: &Ui, state: &mut DataTableState, order: &[usize]) -> Option<usize> {
+    let (modifiers, select_all, [down, up, home, end, page_down, page_up]) = ui.input_mut(|i| {
+        let modifiers = i.modifiers;
+        let select_all = i.consume_key(Modifiers::COMMAND, Key::A);
+        let keys = [
+            Key::ArrowDown,
+            Key::ArrowUp,
+            Key::Home,
+            Key::End,
+            Key::PageDown,
+            Key::PageUp,
+        ];
+        (
+            modifiers,
+            select_all,
+            keys.map(|key| i.consume_key(modifiers, key)),
+        )
+    });
+
+    if select_all {
+        state.selected = order.iter().copied().collect();
+        return None;
+    }
+
+    let page = 10;
+    let delta = if down {
+        1
+    } else if up {
+        -1
+    } else if home {
+        isize::MIN
+    } else if end {
+        isize::MAX
+    } else if page_down {
+        page
+    } else if page_up {
+        -page
+    } else {
+        return None;
+    };
+    state.step(order, delta, modifiers.shift);
+    let cursor = state.cursor?;
+    order.iter().position(|row| *row == cursor)
+}
+
+/// The order in which to show the rows.
+fn sorted_order<Row>(
+    columns: &[DataColumn<'_, Row>],
+    sort: Option<&(String, SortOrder)>,
+    rows: &[Row],
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    let Some((title, sort_order)) = sort else {
+        return order;
+    };
+    let Some(column) = columns.iter().find(|c| c.title == *title) else {
+        return order;
+    };
+
+    match (&column.compare, sort_order) {
+        (Some(compare), SortOrder::Ascending) => {
+            order.sort_by(|a, b| compare(&rows[*a], &rows[*b]));
+        }
+        (Some(compare), SortOrder::Descending) => {
+            order.sort_by(|a, b| compare(&rows[*b], &rows[*a]));
+        }
+        // Get the text of each row once, rather than twice for every comparison:
+        (None, SortOrder::Ascending) => {
+            order.sort_by_cached_key(|row| (column.text)(&rows[*row]));
+        }
+        (None, SortOrder::Descending) => {
+            order.sort_by_cached_key(|row| std::cmp::Reverse((column.text)(&rows[*row])));
+        }
+    }
+    order
+}
+
+/// The selected rows as tab-separated values, in the order they are shown,
+/// with the visible columns.
+fn to_tsv<Row>(
+    columns: &[&DataColumn<'_, Row>],
+    selected: &BTreeSet<usize>,
+    order: &[usize],
+    rows: &[Row],
+) -> String {
+    let mut tsv = String::new();
+    for row in order.iter().filter(|row| selected.contains(row)) {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| (column.text)(&rows[*row]).replace(['\t', '\n'], " "))
+            .collect();
+        tsv += &cells.join("\t");
+        tsv.push('\n');
+    }
+    tsv
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use egui::Modifiers;
+
+    use super::{DataColumn, DataTableState, SortOrder, sorted_order, to_tsv};
+
+    #[test]
+    fn test_selection() {
+        let order = [3, 1, 0, 2];
+        let mut state = DataTableState::default();
+        let selected =
+            |state: &DataTableState| state.selected().iter().copied().collect::<Vec<_>>();
+
+        state.click(&order, 1, Modifiers::NONE);
+        assert_eq!(selected(&state), [1]);
+        state.click(&order, 2, Modifiers::SHIFT);
+        assert_eq!(selected(&state), [0, 1, 2], "A range of the shown rows");
+        state.click(&order, 3, Modifiers::COMMAND);
+        assert_eq!(selected(&state), [0, 1, 2, 3]);
+        state.click(&order, 0, Modifiers::COMMAND);
+        assert_eq!(selected(&state), [1, 2, 3]);
+
+        state.click(&order, 1, Modifiers::NONE);
+        state.step(&order, 1, true);
+        assert_eq!(selected(&state), [0, 1]);
+        state.step(&order, -2, true);
+        assert_eq!(selected(&state), [1, 3], "The range is from the anchor");
+        state.step(&order, isize::MAX, false);
+        assert_eq!(selected(&state), [2]);
+    }
+
+    #[test]
+    fn test_sort_and_copy() {
+        let mut rows = vec![("b", 2), ("a\tb", 10), ("c", 1)];
+        let columns = [
+            DataColumn::new("Name", |row: &(&str, i32)| row.0.to_owned()),
+            DataColumn::new("Count", |row: &(&str, i32)| row.1.to_string())
+                .sort_by(|a, b| a.1.cmp(&b.1)),
+        ];
+        let sort = |title: &str, order| Some((title.to_owned(), order));
+
+        assert_eq!(sorted_order(&columns, None, &rows), [0, 1, 2]);
+        assert_eq!(
+            sorted_order(&columns, sort("Name", SortOrder::Ascending).as_ref(), &rows),
+            [1, 0, 2]
+        );
+        assert_eq!(
+            sorted_order(
+                &columns,
+                sort("Name", SortOrder::Descending).as_ref(),
+                &rows
+            ),
+            [2, 0, 1]
+        );
+        let order = sorted_order(
+            &columns,
+            sort("Count", SortOrder::Descending).as_ref(),
+            &rows,
+        );
+        assert_eq!(order, [1, 0, 2]);
+        assert_eq!(
+            sorted_order(
+                &columns,
+                sort("Count", SortOrder::Ascending).as_ref(),
+                &rows
+            ),
+            [2, 0, 1],
+            "Sorted by number, not by text"
+        );
+
+        rows[0].0 = "b\nb";
+        let columns: Vec<_> = columns.iter().collect();
+        assert_eq!(
+            to_tsv(&columns, &BTreeSet::from([0, 1]), &order, &rows),
+            "a b\t10\nb b\t2\n"
+        );
+    }
+
+    #[test]
+    fn test_sorted_order_is_cached() {
+        let mut rows = vec!["b", "a", "c"];
+        let columns = [DataColumn::new("Name", |row: &&str| (*row).to_owned())];
+        let mut state = DataTableState::default();
+        state.set_sort(Some(("Name".to_owned(), SortOrder::Ascending)));
+        assert_eq!(*state.order(&columns, &rows, 0), [1, 0, 2]);
+
+        // Changing a row keeps the order until the sort is invalidated:
+        rows[2] = "0";
+        assert_eq!(*state.order(&columns, &rows, 0), [1, 0, 2]);
+        state.invalidate_sort();
+        assert_eq!(*state.order(&columns, &rows, 0), [2, 1, 0]);
+
+        // … or the revision changes:
+        rows[2] = "d";
+        assert_eq!(*state.order(&columns, &rows, 0), [2, 1, 0]);
+        assert_eq!(*state.order(&columns, &rows, 1), [1, 0, 2]);
+
+        // A different number of rows, or sort, is sorted again:
+        rows.push("1");
+        assert_eq!(*state.order(&columns, &rows, 1), [3, 1, 0, 2]);
+        state.set_sort(Some(("Name".to_owned(), SortOrder::Descending)));
+        assert_eq!(*state.order(&columns, &rows, 1), [2, 0, 1, 3]);
+    }
+
+    #[test]
+    fn test_move_column() {
+        let mut state = DataTableState {
+            column_order: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()],
+            ..Default::default()
+        };
+        state.move_column("a", "c", true);
+        assert_eq!(state.column_order, ["b", "c", "a"]);
+        state.move_column("a", "b", false);
+        assert_eq!(state.column_order, ["a", "b", "c"]);
+    }
+}
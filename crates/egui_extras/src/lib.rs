@@ -12,6 +12,8 @@
 #[cfg(feature = "chrono")]
 mod datepicker;
 
+mod data_table;
+
 pub mod syntax_highlighting;
 
 #[doc(hidden)]
@@ -25,6 +27,7 @@ mod table;
 #[cfg(feature = "chrono")]
 pub use crate::datepicker::DatePickerButton;
 
+pub use crate::data_table::{DataColumn, DataTable, DataTableOutput, DataTableState, SortOrder};
 pub(crate) use crate::layout::StripLayout;
 pub use crate::sizing::Size;
 pub use crate::strip::*;
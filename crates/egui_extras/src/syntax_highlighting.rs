@@ -577,6 +577,7 @@ impl Highlighter {
                         underline,
                         ..Default::default()
                     },
+                    inline_box: None,
                 });
             }
         }
@@ -94,6 +94,16 @@ impl Column {
         }
     }
 
+    /// Start a resizable column out `width` wide, e.g. to give back a width the user dragged it to.
+    ///
+    /// Other columns are sized by their content or the remaining space, and are left alone.
+    pub(crate) fn with_initial_width(mut self, width: f32) -> Self {
+        if self.resizable == Some(true) && self.initial_width != InitialColumnSize::Remainder {
+            self.initial_width = InitialColumnSize::Absolute(width);
+        }
+        self
+    }
+
     /// Can this column be resized by dragging the column separator?
     ///
     /// If you don't call this, the fallback value of
@@ -180,6 +190,8 @@ fn to_sizing(columns: &[Column]) -> crate::sizing::Sizing {
 
 struct TableScrollOptions {
     vscroll: bool,
+    hscroll: bool,
+    frozen_columns: usize,
     drag_to_scroll: bool,
     stick_to_bottom: bool,
     scroll_to_row: Option<(usize, Option<Align>)>,
@@ -195,6 +207,8 @@ impl Default for TableScrollOptions {
     fn default() -> Self {
         Self {
             vscroll: true,
+            hscroll: false,
+            frozen_columns: 0,
             drag_to_scroll: true,
             stick_to_bottom: false,
             scroll_to_row: None,
@@ -208,6 +222,56 @@ impl Default for TableScrollOptions {
     }
 }
 
+/// Which part of a row is in view, when scrolling horizontally.
+#[derive(Clone, Copy)]
+struct ColumnsView {
+    /// This many leading columns stay in view.
+    frozen_columns: usize,
+
+    /// How far the table is scrolled horizontally.
+    scroll_offset_x: f32,
+
+    /// How far the frozen columns are moved to the right, to stay in view.
+    frozen_offset_x: f32,
+
+    /// Where in screen-space the other columns are visible, i.e. right of the frozen columns.
+    scrolled_x_range: Rangef,
+}
+
+impl ColumnsView {
+    /// `table_left` is where the table is on screen, and `content_left` is where its first
+    /// column would be if it weren't frozen.
+    fn new(
+        ui: &Ui,
+        widths: &[f32],
+        frozen_columns: usize,
+        table_left: f32,
+        content_left: f32,
+    ) -> Self {
+        let spacing_x = ui.spacing().item_spacing.x;
+        let frozen_columns = frozen_columns.min(widths.len());
+        let frozen_width: f32 = widths[..frozen_columns].iter().map(|w| w + spacing_x).sum();
+        let total_width: f32 = widths.iter().map(|w| w + spacing_x).sum();
+
+        let clip_x_range = ui.clip_rect().x_range();
+        let scrolled_x_range = if frozen_columns == 0 {
+            clip_x_range
+        } else {
+            let frozen_right = table_left + frozen_width - 0.5 * spacing_x;
+            Rangef::new(frozen_right.max(clip_x_range.min), clip_x_range.max)
+        };
+
+        let scroll_offset_x = table_left - content_left;
+        Self {
+            frozen_columns,
+            scroll_offset_x,
+            // Never move the frozen columns past the end of the table, or it would keep growing:
+            frozen_offset_x: scroll_offset_x.clamp(0.0, (total_width - frozen_width).at_least(0.0)),
+            scrolled_x_range,
+        }
+    }
+}
+
 // -----------------------------------------------------------------=----------
 
 /// Builder for a [`Table`] with (optional) fixed header and scrolling body.
@@ -327,6 +391,27 @@ impl<'a> TableBuilder<'a> {
         self
     }
 
+    /// Enable horizontal scrolling in body (default: `false`).
+    ///
+    /// The header scrolls along with the body.
+    /// Use [`TableRow::cols`] to only add the cells that are in view.
+    ///
+    /// See also [`Self::frozen_columns`].
+    #[inline]
+    pub fn hscroll(mut self, hscroll: bool) -> Self {
+        self.scroll_options.hscroll = hscroll;
+        self
+    }
+
+    /// Keep this many leading columns in view when scrolling horizontally, e.g. for row labels.
+    ///
+    /// Only used together with [`Self::hscroll`]. Default: `0`.
+    #[inline]
+    pub fn frozen_columns(mut self, frozen_columns: usize) -> Self {
+        self.scroll_options.frozen_columns = frozen_columns;
+        self
+    }
+
     /// Enables scrolling the table's contents using mouse drag (default: `true`).
     ///
     /// See [`ScrollArea::drag_to_scroll`] for more.
@@ -488,12 +573,25 @@ impl<'a> TableBuilder<'a> {
 
         let mut max_used_widths = vec![0.0; columns.len()];
         let table_top = ui.cursor().top();
+        let table_rect = ui.available_rect_before_wrap();
 
         let mut ui_builder = egui::UiBuilder::new();
         if is_sizing_pass {
             ui_builder = ui_builder.sizing_pass();
         }
-        ui.scope_builder(ui_builder, |ui| {
+        let frozen_columns = if scroll_options.hscroll {
+            scroll_options.frozen_columns
+        } else {
+            0
+        };
+        let add_header = |ui: &mut Ui| {
+            let view = ColumnsView::new(
+                ui,
+                &state.column_widths,
+                frozen_columns,
+                table_rect.left(),
+                ui.max_rect().left(),
+            );
             let mut layout = StripLayout::new(ui, CellDirection::Horizontal, cell_layout, sense);
             let mut response: Option<Response> = None;
             add_header_row(TableRow {
@@ -501,6 +599,7 @@ impl<'a> TableBuilder<'a> {
                 columns: &columns,
                 widths: &state.column_widths,
                 max_used_widths: &mut max_used_widths,
+                view,
                 row_index: 0,
                 col_index: 0,
                 height,
@@ -511,7 +610,33 @@ impl<'a> TableBuilder<'a> {
                 response: &mut response,
             });
             layout.allocate_rect();
-        });
+        };
+
+        let mut scroll_offset_x = None;
+        if scroll_options.hscroll {
+            // The header scrolls horizontally together with the `ScrollArea` in `Table::body`,
+            // which is shown with this same offset:
+            let scroll_area_id = ui.make_persistent_id(Id::new(state_id.with("__scroll_area")));
+            let offset_x = egui::scroll_area::State::load(ui.ctx(), scroll_area_id)
+                .map_or(0.0, |state| state.offset.x);
+            scroll_offset_x = Some(offset_x);
+            let visible_rect = table_rect.with_max_x(table_rect.left() + available_width);
+
+            let mut header_ui =
+                ui.new_child(ui_builder.max_rect(table_rect.translate(egui::vec2(-offset_x, 0.0))));
+            header_ui.shrink_clip_rect(visible_rect);
+            add_header(&mut header_ui);
+
+            // Only take up the space that is in view:
+            let used_rect = header_ui.min_rect();
+            let header_right = used_rect.right().at_most(visible_rect.right());
+            ui.advance_cursor_after_rect(Rect::from_x_y_ranges(
+                table_rect.left()..=header_right,
+                used_rect.y_range(),
+            ));
+        } else {
+            ui.scope_builder(ui_builder, add_header);
+        }
 
         Table {
             ui,
@@ -526,6 +651,7 @@ impl<'a> TableBuilder<'a> {
             striped,
             cell_layout,
             scroll_options,
+            scroll_offset_x,
             sense,
         }
     }
@@ -571,6 +697,7 @@ impl<'a> TableBuilder<'a> {
             striped,
             cell_layout,
             scroll_options,
+            scroll_offset_x: None,
             sense,
         }
         .body(add_body_contents)
@@ -672,6 +799,16 @@ impl TableState {
     }
 }
 
+/// The column widths that the table with this `id_salt` in `ui` stored last.
+pub(crate) fn stored_column_widths(ui: &Ui, id_salt: impl std::hash::Hash) -> Option<Vec<f32>> {
+    let state_id = ui.id().with(Id::new(id_salt));
+    #[cfg(feature = "serde")]
+    let state = ui.data_mut(|d| d.get_persisted::<TableState>(state_id));
+    #[cfg(not(feature = "serde"))]
+    let state = ui.data_mut(|d| d.get_temp::<TableState>(state_id));
+    state.map(|state| state.column_widths)
+}
+
 // ----------------------------------------------------------------------------
 
 /// Table struct which can construct a [`TableBody`].
@@ -696,6 +833,9 @@ pub struct Table<'a> {
 
     scroll_options: TableScrollOptions,
 
+    /// The horizontal scroll offset the header was laid out with, if it scrolls horizontally.
+    scroll_offset_x: Option<f32>,
+
     sense: egui::Sense,
 }
 
@@ -725,11 +865,14 @@ impl Table<'_> {
             striped,
             cell_layout,
             scroll_options,
+            scroll_offset_x,
             sense,
         } = self;
 
         let TableScrollOptions {
             vscroll,
+            hscroll,
+            frozen_columns,
             drag_to_scroll,
             stick_to_bottom,
             scroll_to_row,
@@ -743,7 +886,9 @@ impl Table<'_> {
 
         let cursor_position = ui.cursor().min;
 
-        let mut scroll_area = ScrollArea::new([false, vscroll])
+        let frozen_columns = if hscroll { frozen_columns } else { 0 };
+
+        let mut scroll_area = ScrollArea::new([hscroll, vscroll])
             .id_salt(state_id.with("__scroll_area"))
             .scroll_source(ScrollSource {
                 drag: drag_to_scroll,
@@ -759,15 +904,29 @@ impl Table<'_> {
         if let Some(scroll_offset_y) = scroll_offset_y {
             scroll_area = scroll_area.vertical_scroll_offset(scroll_offset_y);
         }
+        if let Some(scroll_offset_x) = scroll_offset_x {
+            // Keep the body in line with the header:
+            scroll_area = scroll_area.horizontal_scroll_offset(scroll_offset_x);
+        }
 
         let columns_ref = &columns;
         let widths_ref = &state.column_widths;
         let max_used_widths_ref = &mut max_used_widths;
+        let mut body_view = None;
+        let body_view_ref = &mut body_view;
 
         let scroll_area_out = scroll_area.show(ui, move |ui| {
             let mut scroll_to_y_range = None;
 
             let clip_rect = ui.clip_rect();
+            let view = ColumnsView::new(
+                ui,
+                widths_ref,
+                frozen_columns,
+                cursor_position.x,
+                ui.max_rect().left(),
+            );
+            *body_view_ref = Some(view);
 
             let mut ui_builder = egui::UiBuilder::new();
             if is_sizing_pass {
@@ -785,6 +944,7 @@ impl Table<'_> {
                     columns: columns_ref,
                     widths: widths_ref,
                     max_used_widths: max_used_widths_ref,
+                    view,
                     striped,
                     row_index: 0,
                     y_range: clip_rect.y_range(),
@@ -801,8 +961,13 @@ impl Table<'_> {
             });
 
             if let Some(y_range) = scroll_to_y_range {
-                let x = 0.0; // ignored, we only have vertical scrolling
-                let rect = egui::Rect::from_x_y_ranges(x..=x, y_range);
+                let x_range = if hscroll {
+                    // Stay where we are horizontally:
+                    clip_rect.x_range()
+                } else {
+                    Rangef::point(0.0) // ignored, we only have vertical scrolling
+                };
+                let rect = egui::Rect::from_x_y_ranges(x_range, y_range);
                 let align = scroll_to_row.and_then(|(_, a)| a);
                 ui.scroll_to_rect(rect, align);
             }
@@ -810,6 +975,8 @@ impl Table<'_> {
 
         let bottom = ui.min_rect().bottom();
 
+        let view = body_view.expect("The body is always shown");
+
         let spacing_x = ui.spacing().item_spacing.x;
         let mut x = cursor_position.x - spacing_x * 0.5;
         for (i, column_width) in state.column_widths.iter_mut().enumerate() {
@@ -848,9 +1015,18 @@ impl Table<'_> {
 
             x += *column_width + spacing_x;
 
+            // The resize handle of a scrolled column moves with it, and may be out of view:
+            let is_frozen = i < view.frozen_columns;
+            let x = if is_frozen {
+                x
+            } else {
+                x - view.scroll_offset_x
+            };
+            let handle_in_view = is_frozen || view.scrolled_x_range.contains(x);
+
             if column.is_auto() && (is_sizing_pass || !column_is_resizable) {
                 *column_width = width_range.clamp(max_used_widths[i]);
-            } else if column_is_resizable {
+            } else if column_is_resizable && handle_in_view {
                 let column_resize_id = state_id.with("resize_column").with(i);
 
                 let mut p0 = egui::pos2(x, table_top);
@@ -931,6 +1107,8 @@ pub struct TableBody<'a> {
     /// Accumulated maximum used widths for each column.
     max_used_widths: &'a mut [f32],
 
+    view: ColumnsView,
+
     striped: bool,
     row_index: usize,
     y_range: Rangef,
@@ -988,6 +1166,7 @@ impl<'a> TableBody<'a> {
             columns: self.columns,
             widths: self.widths,
             max_used_widths: self.max_used_widths,
+            view: self.view,
             row_index: self.row_index,
             col_index: 0,
             height,
@@ -1070,6 +1249,7 @@ impl<'a> TableBody<'a> {
                 columns: self.columns,
                 widths: self.widths,
                 max_used_widths: self.max_used_widths,
+                view: self.view,
                 row_index,
                 col_index: 0,
                 height: row_height_sans_spacing,
@@ -1152,6 +1332,7 @@ impl<'a> TableBody<'a> {
                     columns: self.columns,
                     widths: self.widths,
                     max_used_widths: self.max_used_widths,
+                    view: self.view,
                     row_index,
                     col_index: 0,
                     height: row_height,
@@ -1175,6 +1356,7 @@ impl<'a> TableBody<'a> {
                 columns: self.columns,
                 widths: self.widths,
                 max_used_widths: self.max_used_widths,
+                view: self.view,
                 row_index,
                 col_index: 0,
                 height: row_height,
@@ -1261,6 +1443,8 @@ pub struct TableRow<'a, 'b> {
     /// grows during building with the maximum widths
     max_used_widths: &'b mut [f32],
 
+    view: ColumnsView,
+
     row_index: usize,
     col_index: usize,
     height: f32,
@@ -1310,6 +1494,18 @@ impl TableRow<'_, '_> {
             sizing_pass: auto_size_this_frame || self.layout.ui.is_sizing_pass(),
         };
 
+        // Frozen columns stay in view, and the other columns scroll underneath them:
+        let is_frozen = col_index < self.view.frozen_columns;
+        let clip_rect = self.layout.ui.clip_rect();
+        if is_frozen {
+            self.layout.cursor.x += self.view.frozen_offset_x;
+        } else if 0 < self.view.frozen_columns {
+            self.layout.ui.shrink_clip_rect(Rect::from_x_y_ranges(
+                self.view.scrolled_x_range,
+                clip_rect.y_range(),
+            ));
+        }
+
         let (used_rect, response) = self.layout.add(
             flags,
             width,
@@ -1318,6 +1514,11 @@ impl TableRow<'_, '_> {
             add_cell_contents,
         );
 
+        if is_frozen {
+            self.layout.cursor.x -= self.view.frozen_offset_x;
+        }
+        self.layout.ui.set_clip_rect(clip_rect);
+
         if let Some(max_w) = self.max_used_widths.get_mut(col_index) {
             *max_w = max_w.max(used_rect.width());
         }
@@ -1331,6 +1532,75 @@ impl TableRow<'_, '_> {
         (used_rect, response)
     }
 
+    /// Add the remaining columns of this row, but only lay out the cells that are in view.
+    ///
+    /// `add_cell_contents` is called with the column index of each cell in view,
+    /// and the other cells are skipped.
+    /// This is a lot faster than calling [`Self::col`] for every column of a wide table,
+    /// e.g. with [`TableBuilder::hscroll`].
+    ///
+    /// ### Example
+    /// ```
+    /// # egui::__run_test_ui(|ui| {
+    /// use egui_extras::{TableBuilder, Column};
+    /// TableBuilder::new(ui)
+    ///     .hscroll(true)
+    ///     .frozen_columns(1)
+    ///     .columns(Column::exact(60.0), 500)
+    ///     .body(|body| {
+    ///         body.rows(18.0, 10_000, |mut row| {
+    ///             let row_index = row.index();
+    ///             row.cols(|ui, col_index| {
+    ///                 ui.label(format!("{row_index}:{col_index}"));
+    ///             });
+    ///         });
+    ///     });
+    /// # });
+    /// ```
+    pub fn cols(&mut self, mut add_cell_contents: impl FnMut(&mut Ui, usize)) {
+        while self.col_index < self.widths.len() {
+            let col_index = self.col_index;
+            if self.is_col_in_view(col_index) {
+                self.col(|ui| add_cell_contents(ui, col_index));
+            } else {
+                self.skip_col(col_index);
+            }
+        }
+    }
+
+    fn is_col_in_view(&self, col_index: usize) -> bool {
+        let auto_size_this_frame = self
+            .columns
+            .get(col_index)
+            .is_some_and(|c| c.auto_size_this_frame);
+        if col_index < self.view.frozen_columns
+            || auto_size_this_frame
+            || self.layout.ui.is_sizing_pass()
+        {
+            return true;
+        }
+
+        let left = self.layout.cursor.x;
+        let x_range = Rangef::new(left, left + self.widths[col_index]);
+        self.view.scrolled_x_range.intersects(x_range)
+    }
+
+    /// Leave room for a cell, without adding it.
+    fn skip_col(&mut self, col_index: usize) {
+        let width = self.widths[col_index];
+        self.layout
+            .empty(CellSize::Absolute(width), CellSize::Absolute(self.height));
+
+        if self.columns.get(col_index).is_some_and(|c| c.is_auto())
+            && let Some(max_w) = self.max_used_widths.get_mut(col_index)
+        {
+            // Keep the width of an auto-sized column, even if all its cells are out of view:
+            *max_w = max_w.max(width);
+        }
+
+        self.col_index += 1;
+    }
+
     /// Set the selection highlight state for cells added after a call to this function.
     #[inline]
     pub fn set_selected(&mut self, selected: bool) {
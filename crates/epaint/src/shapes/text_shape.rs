@@ -143,6 +143,7 @@ impl TextShape {
             let text::Row {
                 section_index_at_start: _,
                 glyphs: _, // TODO(emilk): would it make sense to transform these?
+                hyphen: _,
                 size,
                 visuals,
                 ends_with_newline: _,
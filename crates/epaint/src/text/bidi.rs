@@ -0,0 +1,230 @@
+//! Support for bidirectional text (e.g. Arabic or Hebrew mixed with English),
+//! using the [Unicode Bidirectional Algorithm](https://unicode.org/reports/tr9/).
+//!
+//! Text is measured and line-broken in logical order.
+//! Once the rows are known, each row is reordered for display.
+//!
+//! The glyphs in a [`super::Row`] are always kept in logical order, so that
+//! a glyph index is the same as a character offset into the row.
+//! Only their positions are changed by the reordering.
+//!
+//! The levels are only resolved with the `text_shaping` feature.
+//! Without it, all text is laid out left-to-right.
+
+use std::ops::Range;
+
+#[cfg(feature = "text_shaping")]
+use unicode_bidi::{BidiClass, BidiInfo, bidi_class};
+
+use super::Glyph;
+
+/// The resolved embedding levels of the text of a [`super::LayoutJob`].
+///
+/// An odd level means right-to-left, and even level left-to-right.
+pub(super) struct BidiLevels {
+    /// One level per byte of the text.
+    levels: Vec<u8>,
+
+    /// Byte range and base level of each bidi paragraph.
+    paragraphs: Vec<(Range<usize>, u8)>,
+}
+
+impl BidiLevels {
+    /// Returns `None` if the text has nothing right-to-left in it,
+    /// which is by far the most common case.
+    #[cfg(feature = "text_shaping")]
+    pub fn new(text: &str) -> Option<Self> {
+        if text.is_ascii() || !text.chars().any(is_rtl_trigger) {
+            return None;
+        }
+
+        let info = BidiInfo::new(text, None);
+        Some(Self {
+            levels: info.levels.iter().map(|level| level.number()).collect(),
+            paragraphs: info
+                .paragraphs
+                .iter()
+                .map(|paragraph| (paragraph.range.clone(), paragraph.level.number()))
+                .collect(),
+        })
+    }
+
+    /// Without the `text_shaping` feature, the text is always laid out left-to-right.
+    #[cfg(not(feature = "text_shaping"))]
+    pub fn new(_text: &str) -> Option<Self> {
+        None
+    }
+
+    /// The resolved level of the character starting at the given byte.
+    pub fn level_at(&self, byte_index: usize) -> u8 {
+        self.levels.get(byte_index).copied().unwrap_or_default()
+    }
+
+    /// The base level of the paragraph containing the given byte.
+    pub fn paragraph_level_at(&self, byte_index: usize) -> u8 {
+        self.paragraphs
+            .iter()
+            .find(|(range, _)| range.contains(&byte_index))
+            .or_else(|| self.paragraphs.last())
+            .map_or(0, |(_, level)| *level)
+    }
+}
+
+/// Can this character introduce right-to-left text?
+#[cfg(feature = "text_shaping")]
+fn is_rtl_trigger(c: char) -> bool {
+    matches!(
+        bidi_class(c),
+        BidiClass::R | BidiClass::AL | BidiClass::RLE | BidiClass::RLO | BidiClass::RLI
+    )
+}
+
+/// The mirrored version of a character, for use in right-to-left runs.
+///
+/// Uses the `Bidi_Mirroring_Glyph` property of the Unicode Character Database.
+#[cfg(feature = "text_shaping")]
+pub(super) fn mirrored(c: char) -> Option<char> {
+    unicode_bidi_mirroring::get_mirrored(c).or_else(|| {
+        MIRRORED_SECOND
+            .binary_search_by_key(&c, |(second, _)| *second)
+            .ok()
+            .map(|index| MIRRORED_SECOND[index].1)
+    })
+}
+
+/// Right-to-left runs only exist with the `text_shaping` feature.
+#[cfg(not(feature = "text_shaping"))]
+pub(super) fn mirrored(_c: char) -> Option<char> {
+    None
+}
+
+/// The pairs that `get_mirrored` only finds from their first character, as (second, first), sorted.
+///
+/// It binary searches the second characters of its pairs as if they were sorted, which they are not.
+#[cfg(feature = "text_shaping")]
+#[rustfmt::skip]
+const MIRRORED_SECOND: &[(char, char)] = &[
+    ('\u{223D}', '\u{223C}'), // ∽ → ∼
+    ('\u{224C}', '\u{2245}'), // ≌ → ≅
+    ('\u{2253}', '\u{2252}'), // ≓ → ≒
+    ('\u{2255}', '\u{2254}'), // ≕ → ≔
+    ('\u{2265}', '\u{2264}'), // ≥ → ≤
+    ('\u{22A3}', '\u{22A2}'), // ⊣ → ⊢
+    ('\u{22CD}', '\u{2243}'), // ⋍ → ≃
+    ('\u{27DC}', '\u{22B8}'), // ⟜ → ⊸
+    ('\u{298F}', '\u{298E}'), // ⦏ → ⦎
+    ('\u{2990}', '\u{298D}'), // ⦐ → ⦍
+    ('\u{299B}', '\u{2221}'), // ⦛ → ∡
+    ('\u{29A0}', '\u{2222}'), // ⦠ → ∢
+    ('\u{29A3}', '\u{2220}'), // ⦣ → ∠
+    ('\u{29B8}', '\u{2298}'), // ⦸ → ⊘
+    ('\u{29F5}', '\u{2215}'), // ⧵ → ∕
+    ('\u{2ADE}', '\u{22A6}'), // ⫞ → ⊦
+    ('\u{2AE3}', '\u{22A9}'), // ⫣ → ⊩
+    ('\u{2AE4}', '\u{22A8}'), // ⫤ → ⊨
+    ('\u{2AE5}', '\u{22AB}'), // ⫥ → ⊫
+    ('\u{2AEE}', '\u{2224}'), // ⫮ → ∤
+    ('\u{2BFE}', '\u{221F}'), // ⯾ → ∟
+];
+
+/// Rule L1: whitespace at the end of a row, and before a tab,
+/// is reset to the level of the paragraph.
+///
+/// `glyphs` is one row, in logical order.
+pub(super) fn reset_whitespace_levels(glyphs: &mut [Glyph], paragraph_level: u8) {
+    if paragraph_level == 0 && glyphs.iter().all(|glyph| glyph.bidi_level == 0) {
+        return;
+    }
+
+    let mut trailing_whitespace = true;
+    for glyph in glyphs.iter_mut().rev() {
+        if glyph.chr == '\t' {
+            glyph.bidi_level = paragraph_level;
+            trailing_whitespace = true;
+        } else if glyph.chr.is_whitespace() {
+            if trailing_whitespace {
+                glyph.bidi_level = paragraph_level;
+            }
+        } else {
+            trailing_whitespace = false;
+        }
+    }
+}
+
+/// Rule L2: reverse the right-to-left runs of a row.
+///
+/// `glyphs` is one row, in logical order, with positions in logical order.
+/// The glyphs stay in logical order, but are moved to their visual positions.
+pub(super) fn reorder_row(glyphs: &mut [Glyph]) {
+    let Some(max_level) = glyphs.iter().map(|glyph| glyph.bidi_level).max() else {
+        return;
+    };
+    let min_level = glyphs
+        .iter()
+        .map(|glyph| glyph.bidi_level)
+        .min()
+        .unwrap_or_default();
+    let lowest_odd_level = min_level | 1;
+    if max_level < lowest_odd_level {
+        return; // Only left-to-right text
+    }
+
+    let mut visual_order: Vec<usize> = (0..glyphs.len()).collect();
+    for level in (lowest_odd_level..=max_level).rev() {
+        let mut i = 0;
+        while i < visual_order.len() {
+            if glyphs[visual_order[i]].bidi_level < level {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            while i < visual_order.len() && level <= glyphs[visual_order[i]].bidi_level {
+                i += 1;
+            }
+            visual_order[run_start..i].reverse();
+        }
+    }
+
+    // The space each glyph takes up, including kerning and letter spacing:
+    let spans: Vec<f32> = glyphs
+        .iter()
+        .zip(glyphs.iter().skip(1).map(Some).chain([None]))
+        .map(|(glyph, next)| next.map_or(glyph.advance_width, |next| next.pos.x - glyph.pos.x))
+        .collect();
+
+    let mut x = glyphs[0].pos.x;
+    for i in visual_order {
+        glyphs[i].pos.x = x;
+        x += spans[i];
+    }
+}
+
+#[cfg(all(test, feature = "text_shaping"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirrored() {
+        for (a, b) in [
+            ('(', ')'),
+            ('[', ']'),
+            ('«', '»'),
+            ('≤', '≥'),
+            ('⟨', '⟩'),
+            ('⁅', '⁆'),
+            ('≪', '≫'),
+            ('∈', '∋'),
+            ('⊂', '⊃'),
+        ] {
+            assert_eq!(mirrored(a), Some(b));
+            assert_eq!(mirrored(b), Some(a));
+        }
+        assert_eq!(mirrored('a'), None);
+        assert_eq!(mirrored('-'), None);
+
+        for (second, first) in MIRRORED_SECOND {
+            assert_eq!(mirrored(*first), Some(*second));
+        }
+        assert!(MIRRORED_SECOND.is_sorted());
+    }
+}
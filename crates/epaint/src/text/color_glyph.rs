@@ -0,0 +1,1476 @@
+//! Rasterization of color glyphs (e.g. emoji).
+//!
+//! Supports vector glyphs made up of colored layers (`COLR` v0 and v1, with colors from `CPAL`),
+//! and embedded bitmaps (`CBDT` and `sbix`).
+//!
+//! Color glyphs are stored as premultiplied color texels in the font atlas,
+//! and are painted as-is, without being tinted by the text color.
+//! The exception is anything painted with the "foreground color" (palette index `0xFFFF`),
+//! which is stored as a separate coverage mask, and painted with the text color on top.
+//!
+//! Only used with the `color_fonts` feature.
+
+use ab_glyph::{Font as _, OutlineCurve, OutlinedGlyph, PxScale};
+use ecolor::Color32;
+use emath::{Vec2, vec2};
+use ttf_parser::{
+    GlyphId, RasterImageFormat, RgbaColor, Transform,
+    colr::{ClipBox, CompositeMode, GradientExtend, Paint, Painter},
+};
+
+use super::FontData;
+
+pub(super) use face::ColorFace;
+
+mod face {
+    #![expect(clippy::mem_forget)] // Used inside of `self_cell!`
+
+    use std::sync::Arc;
+
+    use super::FontData;
+
+    type Face<'a> = ttf_parser::Face<'a>;
+
+    self_cell::self_cell!(
+        /// A font file with color glyphs, parsed once so that it is ready for rasterizing them.
+        pub(crate) struct ColorFace {
+            owner: Arc<FontData>,
+
+            #[covariant]
+            dependent: Face,
+        }
+    );
+}
+
+impl ColorFace {
+    /// Returns `None` if the font has no color glyphs, or can't be parsed.
+    pub(super) fn parse(font_data: std::sync::Arc<FontData>) -> Option<Self> {
+        let face = Self::try_new(font_data, |font_data| {
+            ttf_parser::Face::parse(&font_data.font, font_data.index)
+        })
+        .ok()?;
+        let tables = face.borrow_dependent().tables();
+        (tables.colr.is_some() || tables.cbdt.is_some() || tables.sbix.is_some()).then_some(face)
+    }
+}
+
+/// Premultiplied RGBA, in `0..=1`.
+type Pixel = [f32; 4];
+
+const TRANSPARENT: Pixel = [0.0; 4];
+
+/// A rasterized color glyph.
+pub(super) struct ColorGlyphImage {
+    /// Width and height, in physical pixels.
+    pub size: [usize; 2],
+
+    /// Offset from the pen position on the baseline to the top left corner of the image.
+    ///
+    /// Unit: physical pixels.
+    pub offset: Vec2,
+
+    /// The glyph as painted with an opaque black text color, row by row.
+    pixels: Vec<Pixel>,
+
+    /// How much of each pixel is painted with the text color.
+    foreground: Vec<f32>,
+}
+
+impl ColorGlyphImage {
+    /// A glyph which is painted with the text color only.
+    #[cfg(feature = "text_shaping")]
+    pub fn from_outline(outlined: &OutlinedGlyph) -> Option<Self> {
+        let bounds = outlined.px_bounds();
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let mut foreground = vec![0.0; width * height];
+        outlined.draw(|x, y, coverage| {
+            let (x, y) = (x as usize, y as usize);
+            if x < width && y < height {
+                foreground[y * width + x] = coverage;
+            }
+        });
+
+        Some(Self {
+            size: [width, height],
+            offset: vec2(bounds.min.x, bounds.min.y),
+            pixels: foreground.iter().map(|&c| [0.0, 0.0, 0.0, c]).collect(),
+            foreground,
+        })
+    }
+
+    /// Paint the images on top of each other, e.g. for a cluster of glyphs.
+    #[cfg(feature = "text_shaping")]
+    pub fn composite(images: &[Self]) -> Option<Self> {
+        let offset = |image: &Self| image.offset.round();
+        let min = images.iter().map(offset).reduce(Vec2::min)?;
+        let max = images
+            .iter()
+            .map(|image| offset(image) + vec2(image.size[0] as f32, image.size[1] as f32))
+            .reduce(Vec2::max)?;
+        let width = (max.x - min.x) as usize;
+        let height = (max.y - min.y) as usize;
+
+        let mut pixels = vec![TRANSPARENT; width * height];
+        let mut foreground = vec![0.0; width * height];
+        for image in images {
+            let dx = (offset(image).x - min.x) as usize;
+            let dy = (offset(image).y - min.y) as usize;
+            for y in 0..image.size[1] {
+                for x in 0..image.size[0] {
+                    let src = y * image.size[0] + x;
+                    let dst = (dy + y) * width + dx + x;
+                    let alpha = image.pixels[src][3];
+                    foreground[dst] = image.foreground[src] + foreground[dst] * (1.0 - alpha);
+                    pixels[dst] = source_over(image.pixels[src], pixels[dst]);
+                }
+            }
+        }
+
+        Some(Self {
+            size: [width, height],
+            offset: min,
+            pixels,
+            foreground,
+        })
+    }
+
+    /// Split the image into the colors to paint as-is,
+    /// and the coverage to paint on top of them with the text color.
+    pub fn split(&self) -> (Vec<Color32>, Vec<f32>) {
+        // Each pixel is `pixel + foreground * text_color`.
+        // Painting the `foreground` coverage on top of a color reveals `1 - foreground` of it,
+        // so we need to scale the color up to get the same result:
+        let colors = self
+            .pixels
+            .iter()
+            .zip(&self.foreground)
+            .map(|(&[r, g, b, a], &foreground)| {
+                let revealed = 1.0 - foreground;
+                if revealed < 1.0 / 255.0 {
+                    return Color32::TRANSPARENT;
+                }
+                let [r, g, b, a] = [r, g, b, a - foreground]
+                    .map(|c| (c / revealed * 255.0).round().clamp(0.0, 255.0) as u8);
+                Color32::from_rgba_premultiplied(r, g, b, a)
+            })
+            .collect();
+        (colors, self.foreground.clone())
+    }
+}
+
+/// Rasterize a glyph in color.
+///
+/// Returns `None` if the glyph has no color version,
+/// in which case the outline should be used as usual.
+///
+/// `px_scale_factor` translates font units to physical pixels,
+/// and `position` is where to put the origin of the glyph, in physical pixels.
+pub(super) fn rasterize(
+    face: &ColorFace,
+    ab_glyph_font: &ab_glyph::FontArc,
+    glyph_id: ab_glyph::GlyphId,
+    px_scale_factor: f32,
+    position: Vec2,
+) -> Option<ColorGlyphImage> {
+    profiling::function_scope!();
+
+    let face = face.borrow_dependent();
+    let ttf_glyph_id = GlyphId(glyph_id.0);
+
+    if face.is_color_glyph(ttf_glyph_id) {
+        let scaler = Scaler {
+            font: ab_glyph_font,
+            px_scale_factor,
+            position,
+        };
+        return rasterize_colr(face, ttf_glyph_id, &scaler);
+    }
+
+    let pixels_per_em = px_scale_factor * face.units_per_em() as f32;
+    let raster = face.glyph_raster_image(ttf_glyph_id, pixels_per_em.round() as u16)?;
+    let mut image = rasterize_bitmap(&raster, pixels_per_em)?;
+    image.offset += position;
+    Some(image)
+}
+
+// ----------------------------------------------------------------------------
+
+fn rasterize_colr(
+    face: &ttf_parser::Face<'_>,
+    glyph_id: GlyphId,
+    scaler: &Scaler<'_>,
+) -> Option<ColorGlyphImage> {
+    const PALETTE: u16 = 0;
+    const BLACK: RgbaColor = RgbaColor {
+        red: 0,
+        green: 0,
+        blue: 0,
+        alpha: 255,
+    };
+    const WHITE: RgbaColor = RgbaColor {
+        red: 255,
+        green: 255,
+        blue: 255,
+        alpha: 255,
+    };
+
+    // We paint the glyph with both a black and a white foreground color.
+    // Where they differ, the text color shines through.
+    let mut black = ColrPainter::default();
+    face.paint_color_glyph(glyph_id, PALETTE, BLACK, &mut black)?;
+    let mut white = ColrPainter::default();
+    face.paint_color_glyph(glyph_id, PALETTE, WHITE, &mut white)?;
+
+    let bounds = scaler.paint_bounds(&black.commands)?;
+    let width = bounds.width() as usize;
+    let height = bounds.height() as usize;
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let pixels = scaler.render(&black.commands, bounds);
+
+    let uses_foreground = black
+        .commands
+        .iter()
+        .zip(&white.commands)
+        .any(|(black, white)| black.colors() != white.colors());
+    let foreground = if uses_foreground {
+        let white = scaler.render(&white.commands, bounds);
+        pixels
+            .iter()
+            .zip(&white)
+            .map(|(black, white)| {
+                let difference = (0..3).map(|i| white[i] - black[i]).sum::<f32>() / 3.0;
+                difference.clamp(0.0, black[3])
+            })
+            .collect()
+    } else {
+        vec![0.0; pixels.len()]
+    };
+
+    Some(ColorGlyphImage {
+        size: [width, height],
+        offset: vec2(bounds.min.x, bounds.min.y),
+        pixels,
+        foreground,
+    })
+}
+
+/// A clip region of a `COLR` glyph.
+#[derive(Clone, Copy)]
+enum Clip {
+    /// Nothing is visible.
+    Nothing,
+
+    /// The outline of a glyph, with the transform at the time.
+    Glyph(GlyphId, Transform),
+
+    /// A rectangle in font units, with the transform at the time.
+    Box(ClipBox, Transform),
+}
+
+/// A recorded call to the [`Painter`] of a `COLR` glyph.
+enum Command<'a> {
+    PushClip(Clip),
+    PopClip,
+    PushLayer(CompositeMode),
+    PopLayer,
+
+    /// Fill the current clip region, with the paint in the current transform.
+    ///
+    /// `COLR` v0 glyphs fill an outline without first making it a clip,
+    /// in which case we also get that outline here.
+    Paint {
+        paint: Paint<'a>,
+        outline: Option<(GlyphId, Transform)>,
+        transform: Transform,
+    },
+}
+
+impl Command<'_> {
+    /// All colors used by this command, if any.
+    fn colors(&self) -> Vec<RgbaColor> {
+        match self {
+            Self::Paint { paint, .. } => match paint {
+                Paint::Solid(color) => vec![*color],
+                Paint::LinearGradient(gradient) => {
+                    gradient.stops(0, &[]).map(|stop| stop.color).collect()
+                }
+                Paint::RadialGradient(gradient) => {
+                    gradient.stops(0, &[]).map(|stop| stop.color).collect()
+                }
+                Paint::SweepGradient(gradient) => {
+                    gradient.stops(0, &[]).map(|stop| stop.color).collect()
+                }
+            },
+            _ => vec![],
+        }
+    }
+}
+
+/// Records the painting of a `COLR` glyph, so that we can first find its bounds, and then render it.
+#[derive(Default)]
+struct ColrPainter<'a> {
+    /// The current transform, in font units.
+    transforms: Vec<Transform>,
+
+    /// The last outlined glyph, and the transform at the time.
+    outline: Option<(GlyphId, Transform)>,
+
+    commands: Vec<Command<'a>>,
+}
+
+impl ColrPainter<'_> {
+    fn transform(&self) -> Transform {
+        self.transforms.last().copied().unwrap_or_default()
+    }
+}
+
+impl<'a> Painter<'a> for ColrPainter<'a> {
+    fn outline_glyph(&mut self, glyph_id: GlyphId) {
+        self.outline = Some((glyph_id, self.transform()));
+    }
+
+    fn paint(&mut self, paint: Paint<'a>) {
+        self.commands.push(Command::Paint {
+            paint,
+            outline: self.outline.take(),
+            transform: self.transform(),
+        });
+    }
+
+    fn push_clip(&mut self) {
+        let clip = self
+            .outline
+            .take()
+            .map_or(Clip::Nothing, |(glyph_id, transform)| {
+                Clip::Glyph(glyph_id, transform)
+            });
+        self.commands.push(Command::PushClip(clip));
+    }
+
+    fn push_clip_box(&mut self, clipbox: ClipBox) {
+        self.commands
+            .push(Command::PushClip(Clip::Box(clipbox, self.transform())));
+    }
+
+    fn pop_clip(&mut self) {
+        self.commands.push(Command::PopClip);
+    }
+
+    fn push_layer(&mut self, mode: CompositeMode) {
+        self.commands.push(Command::PushLayer(mode));
+    }
+
+    fn pop_layer(&mut self) {
+        self.commands.push(Command::PopLayer);
+    }
+
+    fn push_transform(&mut self, transform: Transform) {
+        self.transforms
+            .push(Transform::combine(self.transform(), transform));
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+/// Renders the recorded [`Command`]s of a `COLR` glyph.
+struct Scaler<'f> {
+    font: &'f ab_glyph::FontArc,
+
+    /// Translates font units to physical pixels.
+    px_scale_factor: f32,
+
+    /// Where to put the origin of the glyph, in physical pixels.
+    position: Vec2,
+}
+
+impl Scaler<'_> {
+    fn outline(&self, clip: Clip) -> Option<OutlinedGlyph> {
+        let (glyph_id, outline) = match clip {
+            Clip::Nothing => return None,
+            Clip::Glyph(glyph_id, transform) => {
+                let glyph_id = ab_glyph::GlyphId(glyph_id.0);
+                let outline = transform_outline(self.font.outline(glyph_id)?, transform);
+                (glyph_id, outline)
+            }
+            Clip::Box(clip_box, transform) => {
+                let ClipBox {
+                    x_min,
+                    y_min,
+                    x_max,
+                    y_max,
+                } = clip_box;
+                let corners = [
+                    ab_glyph::point(x_min, y_min),
+                    ab_glyph::point(x_max, y_min),
+                    ab_glyph::point(x_max, y_max),
+                    ab_glyph::point(x_min, y_max),
+                ];
+                let outline = ab_glyph::Outline {
+                    // See `transform_outline`:
+                    bounds: ab_glyph::Rect {
+                        min: ab_glyph::point(x_min, y_max),
+                        max: ab_glyph::point(x_max, y_min),
+                    },
+                    curves: (0..4)
+                        .map(|i| OutlineCurve::Line(corners[i], corners[(i + 1) % 4]))
+                        .collect(),
+                };
+                (ab_glyph::GlyphId(0), transform_outline(outline, transform))
+            }
+        };
+
+        let glyph = ab_glyph::Glyph {
+            id: glyph_id,
+            scale: PxScale::from(0.0), // Not used, see `FontImpl::allocate_glyph`
+            position: ab_glyph::point(self.position.x, self.position.y),
+        };
+        let scale = ab_glyph::PxScaleFactor {
+            horizontal: self.px_scale_factor,
+            vertical: self.px_scale_factor,
+        };
+        Some(OutlinedGlyph::new(glyph, outline, scale))
+    }
+
+    /// The coverage of a clip region, in an image with the given bounds.
+    fn coverage(&self, clip: Clip, bounds: ab_glyph::Rect) -> Vec<f32> {
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+        let mut coverage = vec![0.0; width * height];
+        if let Some(outlined) = self.outline(clip) {
+            let glyph_bb = outlined.px_bounds();
+            let dx = (glyph_bb.min.x - bounds.min.x) as i64;
+            let dy = (glyph_bb.min.y - bounds.min.y) as i64;
+            outlined.draw(|x, y, v| {
+                let (x, y) = (dx + x as i64, dy + y as i64);
+                if (0..width as i64).contains(&x) && (0..height as i64).contains(&y) {
+                    coverage[y as usize * width + x as usize] = v;
+                }
+            });
+        }
+        coverage
+    }
+
+    /// The bounds of everything painted, in physical pixels relative to the pen position.
+    fn paint_bounds(&self, commands: &[Command<'_>]) -> Option<ab_glyph::Rect> {
+        let bounds_of = |clip: Clip| self.outline(clip).map(|outlined| outlined.px_bounds());
+
+        // `None` for a clip region that is empty:
+        let mut clips: Vec<Option<ab_glyph::Rect>> = vec![];
+        let mut painted: Option<ab_glyph::Rect> = None;
+
+        for command in commands {
+            match command {
+                Command::PushClip(clip) => {
+                    let mut region = bounds_of(*clip);
+                    if let Some(parent) = clips.last() {
+                        region = intersect(*parent, region);
+                    }
+                    clips.push(region);
+                }
+                Command::PopClip => {
+                    clips.pop();
+                }
+                Command::PushLayer(_) | Command::PopLayer => {}
+                Command::Paint { outline, .. } => {
+                    let mut region = clips.last().copied();
+                    if let Some((glyph_id, transform)) = outline {
+                        let outline = bounds_of(Clip::Glyph(*glyph_id, *transform));
+                        region = Some(region.map_or(outline, |region| intersect(region, outline)));
+                    }
+
+                    // Paints without any clip are unbounded, and only matter where something else is painted.
+                    if let Some(Some(region)) = region {
+                        painted = Some(painted.map_or(region, |painted| union(painted, region)));
+                    }
+                }
+            }
+        }
+
+        painted
+    }
+
+    /// Render premultiplied colors, row by row, within the given bounds.
+    fn render(&self, commands: &[Command<'_>], bounds: ab_glyph::Rect) -> Vec<Pixel> {
+        let width = bounds.width() as usize;
+        let height = bounds.height() as usize;
+        let blank = || vec![TRANSPARENT; width * height];
+
+        // Each layer is composited onto the one below it when it is popped:
+        let mut layers = vec![(blank(), CompositeMode::SourceOver)];
+        let mut clips: Vec<Vec<f32>> = vec![];
+
+        for command in commands {
+            match command {
+                Command::PushClip(clip) => {
+                    let mut mask = self.coverage(*clip, bounds);
+                    if let Some(parent) = clips.last() {
+                        for (m, p) in mask.iter_mut().zip(parent) {
+                            *m *= p;
+                        }
+                    }
+                    clips.push(mask);
+                }
+                Command::PopClip => {
+                    clips.pop();
+                }
+                Command::PushLayer(mode) => {
+                    layers.push((blank(), *mode));
+                }
+                Command::PopLayer => {
+                    pop_layer(&mut layers);
+                }
+                Command::Paint {
+                    paint,
+                    outline,
+                    transform,
+                } => {
+                    let Some(shader) = Shader::new(paint, *transform) else {
+                        continue;
+                    };
+                    let outline_mask = outline.map(|(glyph_id, transform)| {
+                        self.coverage(Clip::Glyph(glyph_id, transform), bounds)
+                    });
+                    let Some((canvas, _)) = layers.last_mut() else {
+                        continue;
+                    };
+
+                    for y in 0..height {
+                        for x in 0..width {
+                            let i = y * width + x;
+                            let coverage = clips.last().map_or(1.0, |mask| mask[i])
+                                * outline_mask.as_ref().map_or(1.0, |mask| mask[i]);
+                            if coverage <= 0.0 {
+                                continue;
+                            }
+
+                            // The center of the pixel, in font units (with y up):
+                            let pos = vec2(
+                                bounds.min.x + x as f32 + 0.5 - self.position.x,
+                                -(bounds.min.y + y as f32 + 0.5 - self.position.y),
+                            ) / self.px_scale_factor;
+
+                            let src = shader.sample(pos).map(|c| c * coverage);
+                            canvas[i] = source_over(src, canvas[i]);
+                        }
+                    }
+                }
+            }
+        }
+
+        while 1 < layers.len() {
+            pop_layer(&mut layers);
+        }
+        layers.pop().map(|(canvas, _)| canvas).unwrap_or_else(blank)
+    }
+}
+
+fn intersect(a: Option<ab_glyph::Rect>, b: Option<ab_glyph::Rect>) -> Option<ab_glyph::Rect> {
+    let (a, b) = (a?, b?);
+    let rect = ab_glyph::Rect {
+        min: ab_glyph::point(a.min.x.max(b.min.x), a.min.y.max(b.min.y)),
+        max: ab_glyph::point(a.max.x.min(b.max.x), a.max.y.min(b.max.y)),
+    };
+    (rect.min.x < rect.max.x && rect.min.y < rect.max.y).then_some(rect)
+}
+
+fn union(a: ab_glyph::Rect, b: ab_glyph::Rect) -> ab_glyph::Rect {
+    ab_glyph::Rect {
+        min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+        max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+    }
+}
+
+fn pop_layer(layers: &mut Vec<(Vec<Pixel>, CompositeMode)>) {
+    if layers.len() < 2 {
+        return;
+    }
+    let Some((source, mode)) = layers.pop() else {
+        return;
+    };
+    let Some((backdrop, _)) = layers.last_mut() else {
+        return;
+    };
+    for (dst, src) in backdrop.iter_mut().zip(source) {
+        *dst = composite(mode, src, *dst);
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A [`Paint`], ready to be sampled.
+struct Shader {
+    kind: ShaderKind,
+
+    /// From font units to the coordinate system of the gradient.
+    inverse_transform: Transform,
+
+    /// Sorted by offset.
+    stops: Vec<(f32, Pixel)>,
+
+    extend: GradientExtend,
+}
+
+enum ShaderKind {
+    Solid(Pixel),
+
+    /// The color changes along `direction`, starting at `start`.
+    Linear {
+        start: Vec2,
+        direction: Vec2,
+    },
+
+    /// A gradient between two circles.
+    Radial {
+        center_0: Vec2,
+        radius_0: f32,
+        center_1: Vec2,
+        radius_1: f32,
+    },
+
+    /// The color changes with the angle around the center.
+    ///
+    /// Angles are in degrees, counter-clockwise.
+    Sweep {
+        center: Vec2,
+        start_angle: f32,
+        end_angle: f32,
+    },
+}
+
+impl Shader {
+    /// Returns `None` if nothing would be painted.
+    fn new(paint: &Paint<'_>, transform: Transform) -> Option<Self> {
+        let (kind, stops, extend) = match paint {
+            Paint::Solid(color) => (
+                ShaderKind::Solid(premultiplied(*color)),
+                vec![],
+                GradientExtend::Pad,
+            ),
+            Paint::LinearGradient(gradient) => {
+                let start = vec2(gradient.x0, gradient.y0);
+                let end = vec2(gradient.x1, gradient.y1);
+                let rotation = vec2(gradient.x2, gradient.y2);
+
+                // The gradient is along start→end,
+                // projected onto the normal of start→rotation:
+                let normal = (rotation - start).rot90();
+                let direction = if normal == Vec2::ZERO {
+                    end - start
+                } else {
+                    normal * (end - start).dot(normal) / normal.length_sq()
+                };
+                if direction == Vec2::ZERO {
+                    return None;
+                }
+
+                (
+                    ShaderKind::Linear { start, direction },
+                    sorted_stops(gradient.stops(0, &[])),
+                    gradient.extend,
+                )
+            }
+            Paint::RadialGradient(gradient) => (
+                ShaderKind::Radial {
+                    center_0: vec2(gradient.x0, gradient.y0),
+                    radius_0: gradient.r0,
+                    center_1: vec2(gradient.x1, gradient.y1),
+                    radius_1: gradient.r1,
+                },
+                sorted_stops(gradient.stops(0, &[])),
+                gradient.extend,
+            ),
+            Paint::SweepGradient(gradient) => {
+                // The angles are stored with a bias of 1.0, in units of 180°:
+                let start_angle = (gradient.start_angle + 1.0) * 180.0;
+                let end_angle = (gradient.end_angle + 1.0) * 180.0;
+                if start_angle == end_angle {
+                    return None;
+                }
+
+                (
+                    ShaderKind::Sweep {
+                        center: vec2(gradient.center_x, gradient.center_y),
+                        start_angle,
+                        end_angle,
+                    },
+                    sorted_stops(gradient.stops(0, &[])),
+                    gradient.extend,
+                )
+            }
+        };
+
+        if !matches!(kind, ShaderKind::Solid(_)) && stops.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            kind,
+            inverse_transform: invert(transform)?,
+            stops,
+            extend,
+        })
+    }
+
+    /// The color at a position, in font units.
+    fn sample(&self, pos: Vec2) -> Pixel {
+        let t = self.inverse_transform;
+        let pos = vec2(
+            t.a * pos.x + t.c * pos.y + t.e,
+            t.b * pos.x + t.d * pos.y + t.f,
+        );
+
+        let offset = match self.kind {
+            ShaderKind::Solid(color) => return color,
+            ShaderKind::Linear { start, direction } => {
+                (pos - start).dot(direction) / direction.length_sq()
+            }
+            ShaderKind::Radial {
+                center_0,
+                radius_0,
+                center_1,
+                radius_1,
+            } => {
+                let Some(offset) = radial_offset(pos, center_0, radius_0, center_1, radius_1)
+                else {
+                    return TRANSPARENT;
+                };
+                offset
+            }
+            ShaderKind::Sweep {
+                center,
+                start_angle,
+                end_angle,
+            } => {
+                let angle = (pos - center).angle().to_degrees().rem_euclid(360.0);
+                (angle - start_angle) / (end_angle - start_angle)
+            }
+        };
+
+        self.color_at(offset)
+    }
+
+    fn color_at(&self, offset: f32) -> Pixel {
+        let (Some(&(first, first_color)), Some(&(last, last_color))) =
+            (self.stops.first(), self.stops.last())
+        else {
+            return TRANSPARENT;
+        };
+
+        let length = last - first;
+        let offset = if 0.0 < length {
+            match self.extend {
+                GradientExtend::Pad => offset,
+                GradientExtend::Repeat => first + (offset - first).rem_euclid(length),
+                GradientExtend::Reflect => {
+                    let t = (offset - first).rem_euclid(2.0 * length);
+                    first + if length < t { 2.0 * length - t } else { t }
+                }
+            }
+        } else {
+            offset
+        };
+
+        if offset <= first {
+            return first_color;
+        }
+        if last <= offset {
+            return last_color;
+        }
+
+        let i = self.stops.partition_point(|(stop, _)| *stop <= offset);
+        let (offset_0, color_0) = self.stops[i - 1];
+        let (offset_1, color_1) = self.stops[i];
+        let t = (offset - offset_0) / (offset_1 - offset_0);
+        std::array::from_fn(|c| color_0[c] + (color_1[c] - color_0[c]) * t)
+    }
+}
+
+fn sorted_stops(stops: impl Iterator<Item = ttf_parser::colr::ColorStop>) -> Vec<(f32, Pixel)> {
+    let mut stops: Vec<(f32, Pixel)> = stops
+        .map(|stop| (stop.stop_offset, premultiplied(stop.color)))
+        .collect();
+    stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+    stops
+}
+
+/// The offset of the largest circle of a two-point conical gradient that goes through `pos`.
+fn radial_offset(
+    pos: Vec2,
+    center_0: Vec2,
+    radius_0: f32,
+    center_1: Vec2,
+    radius_1: f32,
+) -> Option<f32> {
+    // Solve |pos - center(t)| = radius(t) for t, where the circle moves linearly with t:
+    let center_delta = center_1 - center_0;
+    let radius_delta = radius_1 - radius_0;
+    let pos = pos - center_0;
+
+    let a = center_delta.length_sq() - radius_delta * radius_delta;
+    let b = pos.dot(center_delta) + radius_0 * radius_delta;
+    let c = pos.length_sq() - radius_0 * radius_0;
+
+    let has_radius = |t: f32| 0.0 <= radius_0 + t * radius_delta;
+
+    if a == 0.0 {
+        if b == 0.0 {
+            return None;
+        }
+        let t = c / (2.0 * b);
+        return has_radius(t).then_some(t);
+    }
+
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let t0 = (b + discriminant.sqrt()) / a;
+    let t1 = (b - discriminant.sqrt()) / a;
+    let (large, small) = (t0.max(t1), t0.min(t1));
+    if has_radius(large) {
+        Some(large)
+    } else {
+        has_radius(small).then_some(small)
+    }
+}
+
+fn invert(t: Transform) -> Option<Transform> {
+    let determinant = t.a * t.d - t.b * t.c;
+    if determinant == 0.0 || !determinant.is_finite() {
+        return None;
+    }
+    let a = t.d / determinant;
+    let b = -t.b / determinant;
+    let c = -t.c / determinant;
+    let d = t.a / determinant;
+    Some(Transform {
+        a,
+        b,
+        c,
+        d,
+        e: -(a * t.e + c * t.f),
+        f: -(b * t.e + d * t.f),
+    })
+}
+
+fn premultiplied(color: RgbaColor) -> Pixel {
+    let alpha = color.alpha as f32 / 255.0;
+    [
+        color.red as f32 / 255.0 * alpha,
+        color.green as f32 / 255.0 * alpha,
+        color.blue as f32 / 255.0 * alpha,
+        alpha,
+    ]
+}
+
+// ----------------------------------------------------------------------------
+// Compositing, see <https://www.w3.org/TR/compositing-1/>.
+
+fn source_over(src: Pixel, dst: Pixel) -> Pixel {
+    std::array::from_fn(|i| src[i] + dst[i] * (1.0 - src[3]))
+}
+
+fn composite(mode: CompositeMode, src: Pixel, dst: Pixel) -> Pixel {
+    let (sa, da) = (src[3], dst[3]);
+    let porter_duff =
+        |fa: f32, fb: f32| -> Pixel { std::array::from_fn(|i| src[i] * fa + dst[i] * fb) };
+
+    match mode {
+        CompositeMode::Clear => TRANSPARENT,
+        CompositeMode::Source => src,
+        CompositeMode::Destination => dst,
+        CompositeMode::SourceOver => porter_duff(1.0, 1.0 - sa),
+        CompositeMode::DestinationOver => porter_duff(1.0 - da, 1.0),
+        CompositeMode::SourceIn => porter_duff(da, 0.0),
+        CompositeMode::DestinationIn => porter_duff(0.0, sa),
+        CompositeMode::SourceOut => porter_duff(1.0 - da, 0.0),
+        CompositeMode::DestinationOut => porter_duff(0.0, 1.0 - sa),
+        CompositeMode::SourceAtop => porter_duff(da, 1.0 - sa),
+        CompositeMode::DestinationAtop => porter_duff(1.0 - da, sa),
+        CompositeMode::Xor => porter_duff(1.0 - da, 1.0 - sa),
+        CompositeMode::Plus => std::array::from_fn(|i| (src[i] + dst[i]).min(1.0)),
+
+        CompositeMode::Screen => blend(src, dst, separable(screen)),
+        CompositeMode::Overlay => blend(src, dst, separable(|s, d| hard_light(d, s))),
+        CompositeMode::Darken => blend(src, dst, separable(f32::min)),
+        CompositeMode::Lighten => blend(src, dst, separable(f32::max)),
+        CompositeMode::ColorDodge => blend(
+            src,
+            dst,
+            separable(|s, d| {
+                if d <= 0.0 {
+                    0.0
+                } else if 1.0 <= s {
+                    1.0
+                } else {
+                    (d / (1.0 - s)).min(1.0)
+                }
+            }),
+        ),
+        CompositeMode::ColorBurn => blend(
+            src,
+            dst,
+            separable(|s, d| {
+                if 1.0 <= d {
+                    1.0
+                } else if s <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - d) / s).min(1.0)
+                }
+            }),
+        ),
+        CompositeMode::HardLight => blend(src, dst, separable(hard_light)),
+        CompositeMode::SoftLight => blend(
+            src,
+            dst,
+            separable(|s, d| {
+                if s <= 0.5 {
+                    d - (1.0 - 2.0 * s) * d * (1.0 - d)
+                } else {
+                    let dd = if d <= 0.25 {
+                        ((16.0 * d - 12.0) * d + 4.0) * d
+                    } else {
+                        d.sqrt()
+                    };
+                    d + (2.0 * s - 1.0) * (dd - d)
+                }
+            }),
+        ),
+        CompositeMode::Difference => blend(src, dst, separable(|s, d| (s - d).abs())),
+        CompositeMode::Exclusion => blend(src, dst, separable(|s, d| s + d - 2.0 * s * d)),
+        CompositeMode::Multiply => blend(src, dst, separable(|s, d| s * d)),
+
+        CompositeMode::Hue => blend(src, dst, |s, d| set_lum(set_sat(s, sat(d)), lum(d))),
+        CompositeMode::Saturation => blend(src, dst, |s, d| set_lum(set_sat(d, sat(s)), lum(d))),
+        CompositeMode::Color => blend(src, dst, |s, d| set_lum(s, lum(d))),
+        CompositeMode::Luminosity => blend(src, dst, |s, d| set_lum(d, lum(s))),
+    }
+}
+
+type Rgb = [f32; 3];
+
+/// Blend the colors with a blend mode, and composite the result with "source over".
+fn blend(src: Pixel, dst: Pixel, blend_mode: impl Fn(Rgb, Rgb) -> Rgb) -> Pixel {
+    let (sa, da) = (src[3], dst[3]);
+    let unmultiply = |c: Pixel| -> Rgb {
+        let a = c[3];
+        std::array::from_fn(|i| if 0.0 < a { c[i] / a } else { 0.0 })
+    };
+    let [r, g, b] = blend_mode(unmultiply(src), unmultiply(dst));
+    let mix =
+        |i: usize, blended: f32| src[i] * (1.0 - da) + dst[i] * (1.0 - sa) + sa * da * blended;
+    [mix(0, r), mix(1, g), mix(2, b), sa + da - sa * da]
+}
+
+fn separable(f: impl Fn(f32, f32) -> f32) -> impl Fn(Rgb, Rgb) -> Rgb {
+    move |s, d| std::array::from_fn(|i| f(s[i], d[i]))
+}
+
+fn screen(s: f32, d: f32) -> f32 {
+    s + d - s * d
+}
+
+fn hard_light(s: f32, d: f32) -> f32 {
+    if s <= 0.5 {
+        d * 2.0 * s
+    } else {
+        screen(d, 2.0 * s - 1.0)
+    }
+}
+
+fn lum([r, g, b]: Rgb) -> f32 {
+    0.3 * r + 0.59 * g + 0.11 * b
+}
+
+fn set_lum(c: Rgb, l: f32) -> Rgb {
+    let d = l - lum(c);
+    let c = c.map(|c| c + d);
+
+    // Clip the color to `0..=1`, preserving the luminosity:
+    let l = lum(c);
+    let min = c[0].min(c[1]).min(c[2]);
+    let max = c[0].max(c[1]).max(c[2]);
+    c.map(|mut c| {
+        if min < 0.0 {
+            c = l + (c - l) * l / (l - min);
+        }
+        if 1.0 < max {
+            c = l + (c - l) * (1.0 - l) / (max - l);
+        }
+        c
+    })
+}
+
+fn sat(c: Rgb) -> f32 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn set_sat(c: Rgb, s: f32) -> Rgb {
+    let min = c[0].min(c[1]).min(c[2]);
+    let max = c[0].max(c[1]).max(c[2]);
+    if min < max {
+        c.map(|c| (c - min) * s / (max - min))
+    } else {
+        [0.0; 3]
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Apply a transform to an outline (in font units, with y up).
+fn transform_outline(outline: ab_glyph::Outline, t: Transform) -> ab_glyph::Outline {
+    if t.is_default() {
+        return outline;
+    }
+
+    let apply = |p: ab_glyph::Point| {
+        ab_glyph::point(t.a * p.x + t.c * p.y + t.e, t.b * p.x + t.d * p.y + t.f)
+    };
+
+    let curves: Vec<OutlineCurve> = outline
+        .curves
+        .into_iter()
+        .map(|curve| match curve {
+            OutlineCurve::Line(p0, p1) => OutlineCurve::Line(apply(p0), apply(p1)),
+            OutlineCurve::Quad(p0, p1, p2) => OutlineCurve::Quad(apply(p0), apply(p1), apply(p2)),
+            OutlineCurve::Cubic(p0, p1, p2, p3) => {
+                OutlineCurve::Cubic(apply(p0), apply(p1), apply(p2), apply(p3))
+            }
+        })
+        .collect();
+
+    // The curves are contained in the hull of their control points.
+    // Like `ab_glyph`, we put the top (max y) in `min`, since that is where the image starts:
+    let mut bounds = ab_glyph::Rect {
+        min: ab_glyph::point(f32::INFINITY, f32::NEG_INFINITY),
+        max: ab_glyph::point(f32::NEG_INFINITY, f32::INFINITY),
+    };
+    for curve in &curves {
+        let points: &[ab_glyph::Point] = match curve {
+            OutlineCurve::Line(p0, p1) => &[*p0, *p1],
+            OutlineCurve::Quad(p0, p1, p2) => &[*p0, *p1, *p2],
+            OutlineCurve::Cubic(p0, p1, p2, p3) => &[*p0, *p1, *p2, *p3],
+        };
+        for p in points {
+            bounds.min.x = bounds.min.x.min(p.x);
+            bounds.min.y = bounds.min.y.max(p.y);
+            bounds.max.x = bounds.max.x.max(p.x);
+            bounds.max.y = bounds.max.y.min(p.y);
+        }
+    }
+
+    ab_glyph::Outline { bounds, curves }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Decode and scale an embedded bitmap glyph.
+fn rasterize_bitmap(
+    raster: &ttf_parser::RasterGlyphImage<'_>,
+    pixels_per_em: f32,
+) -> Option<ColorGlyphImage> {
+    let (size, pixels) = match raster.format {
+        RasterImageFormat::PNG => decode_png(raster.data)?,
+        RasterImageFormat::BitmapPremulBgra32 => {
+            let size = [raster.width as usize, raster.height as usize];
+            let pixels = raster
+                .data
+                .chunks_exact(4)
+                .map(|bgra| Color32::from_rgba_premultiplied(bgra[2], bgra[1], bgra[0], bgra[3]))
+                .collect();
+            (size, pixels)
+        }
+        _ => return None, // Monochrome and grayscale bitmaps are better served by the outline.
+    };
+    if size[0] == 0 || size[1] == 0 || pixels.len() != size[0] * size[1] {
+        return None;
+    }
+
+    // Bitmaps come in a few fixed sizes ("strikes"), so we need to scale it:
+    let scale = pixels_per_em / raster.pixels_per_em.max(1) as f32;
+    let scaled_size = size.map(|s| ((s as f32 * scale).round() as usize).max(1));
+    let pixels = resample(&pixels, size, scaled_size);
+
+    // The offset is to the bottom left corner, with y up:
+    let offset = vec2(
+        raster.x as f32 * scale,
+        -(raster.y as f32 * scale) - scaled_size[1] as f32,
+    );
+
+    Some(ColorGlyphImage {
+        size: scaled_size,
+        offset,
+        foreground: vec![0.0; pixels.len()],
+        pixels: pixels
+            .iter()
+            .map(|color| color.to_array().map(|c| c as f32 / 255.0))
+            .collect(),
+    })
+}
+
+/// Returns premultiplied pixels.
+fn decode_png(data: &[u8]) -> Option<([usize; 2], Vec<Color32>)> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().ok()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).ok()?;
+    let buf = &buf[..info.buffer_size()];
+
+    let pixels: Vec<Color32> = match info.color_type {
+        png::ColorType::Rgba => buf
+            .chunks_exact(4)
+            .map(|p| Color32::from_rgba_unmultiplied(p[0], p[1], p[2], p[3]))
+            .collect(),
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .map(|p| Color32::from_rgb(p[0], p[1], p[2]))
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .map(|p| Color32::from_rgba_unmultiplied(p[0], p[0], p[0], p[1]))
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().map(|&l| Color32::from_gray(l)).collect(),
+        png::ColorType::Indexed => return None, // Expanded by `normalize_to_color8`
+    };
+
+    Some(([info.width as usize, info.height as usize], pixels))
+}
+
+/// Bilinear resampling of premultiplied pixels.
+fn resample(pixels: &[Color32], from: [usize; 2], to: [usize; 2]) -> Vec<Color32> {
+    if from == to {
+        return pixels.to_vec();
+    }
+
+    let sample = |x: usize, y: usize| -> [f32; 4] {
+        pixels[y.min(from[1] - 1) * from[0] + x.min(from[0] - 1)]
+            .to_array()
+            .map(|c| c as f32)
+    };
+
+    let mut out = Vec::with_capacity(to[0] * to[1]);
+    for y in 0..to[1] {
+        let sy = ((y as f32 + 0.5) * from[1] as f32 / to[1] as f32 - 0.5).max(0.0);
+        let (y0, ty) = (sy as usize, sy.fract());
+        for x in 0..to[0] {
+            let sx = ((x as f32 + 0.5) * from[0] as f32 / to[0] as f32 - 0.5).max(0.0);
+            let (x0, tx) = (sx as usize, sx.fract());
+
+            let [p00, p10, p01, p11] = [
+                sample(x0, y0),
+                sample(x0 + 1, y0),
+                sample(x0, y0 + 1),
+                sample(x0 + 1, y0 + 1),
+            ];
+            let c: [u8; 4] = std::array::from_fn(|i| {
+                let top = p00[i] + (p10[i] - p00[i]) * tx;
+                let bottom = p01[i] + (p11[i] - p01[i]) * tx;
+                (top + (bottom - top) * ty).round() as u8
+            });
+            out.push(Color32::from_rgba_premultiplied(c[0], c[1], c[2], c[3]));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{
+        AlphaFromCoverage,
+        text::{FontDefinitions, FontFamily, FontId, FontsImpl, LayoutJob, TextFormat, layout},
+    };
+
+    /// Big-endian font data.
+    #[derive(Default)]
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn u8(&mut self, value: u8) -> &mut Self {
+            self.0.push(value);
+            self
+        }
+
+        fn u16(&mut self, value: u16) -> &mut Self {
+            self.0.extend(value.to_be_bytes());
+            self
+        }
+
+        fn i16(&mut self, value: i16) -> &mut Self {
+            self.0.extend(value.to_be_bytes());
+            self
+        }
+
+        fn u24(&mut self, value: u32) -> &mut Self {
+            self.0.extend(&value.to_be_bytes()[1..]);
+            self
+        }
+
+        fn u32(&mut self, value: u32) -> &mut Self {
+            self.0.extend(value.to_be_bytes());
+            self
+        }
+    }
+
+    /// A rectangle in the `glyf` table.
+    fn rect_glyph(w: &mut Writer, [x0, y0, x1, y1]: [i16; 4]) {
+        w.i16(1).i16(x0).i16(y0).i16(x1).i16(y1); // One contour, and the bounds
+        w.u16(3).u16(0); // End point of the contour, and no instructions
+        w.u8(1).u8(1).u8(1).u8(1); // The points are on the curve
+        w.i16(x0).i16(x1 - x0).i16(0).i16(x0 - x1); // Relative x
+        w.i16(y0).i16(0).i16(y1 - y0).i16(0); // Relative y
+    }
+
+    /// A minimal font with two color glyphs, 1000 units per em:
+    ///
+    /// * `A` (glyph 1) is a `COLR` v0 glyph: a red left half, and a right half in the text color.
+    /// * `B` (glyph 4) is a `COLR` v1 glyph: a red-to-blue gradient, with a clip box cutting off the right half.
+    fn test_font() -> Vec<u8> {
+        const NUM_GLYPHS: u16 = 5;
+
+        let mut glyf = Writer::default();
+        let mut loca = Writer::default();
+        loca.u16(0).u16(0); // Glyph 0 is empty
+        for rect in [
+            [0, 0, 1000, 800],
+            [0, 0, 500, 800],
+            [500, 0, 1000, 800],
+            [0, 0, 1000, 800],
+        ] {
+            rect_glyph(&mut glyf, rect);
+            loca.u16((glyf.0.len() / 2) as u16);
+        }
+
+        let mut colr = Writer::default();
+        colr.u16(1).u16(1).u32(34).u32(40).u16(2); // Version 1, with a v0 base glyph with 2 layers
+        colr.u32(48).u32(0).u32(95).u32(0).u32(0); // Base glyph list and clip list
+        colr.u16(1).u16(0).u16(2); // @34: v0 base glyph
+        colr.u16(2).u16(0).u16(3).u16(0xFFFF); // @40: Red and foreground layers
+        colr.u32(1).u16(4).u32(10); // @48: v1 base glyph list
+        colr.u8(10).u24(6).u16(4); // @58: PaintGlyph
+        colr.u8(4).u24(16); // @64: PaintLinearGradient
+        colr.i16(0).i16(0).i16(1000).i16(0).i16(0).i16(1000);
+        colr.u8(0).u16(2); // @80: Color line, padded
+        colr.u16(0).u16(0).u16(0x4000); // Red at 0.0
+        colr.u16(0x4000).u16(1).u16(0x4000); // Blue at 1.0
+        colr.u8(1).u32(1).u16(4).u16(4).u24(12); // @95: Clip list
+        colr.u8(1).i16(0).i16(0).i16(500).i16(800); // @107: Clip box
+        assert_eq!(colr.0.len(), 116);
+
+        let mut cpal = Writer::default();
+        cpal.u16(0).u16(2).u16(1).u16(2).u32(14).u16(0);
+        cpal.u8(0).u8(0).u8(255).u8(255); // Red, as BGRA
+        cpal.u8(255).u8(0).u8(0).u8(255); // Blue, as BGRA
+
+        let mut cmap = Writer::default();
+        cmap.u16(0).u16(1).u16(3).u16(1).u32(12); // Windows Unicode BMP
+        cmap.u16(4).u16(40).u16(0).u16(6).u16(4).u16(1).u16(2); // Format 4, with 3 segments
+        cmap.u16('A' as u16).u16('B' as u16).u16(0xFFFF).u16(0); // End codes
+        cmap.u16('A' as u16).u16('B' as u16).u16(0xFFFF); // Start codes
+        cmap.u16(1_u16.wrapping_sub('A' as u16));
+        cmap.u16(4_u16.wrapping_sub('B' as u16));
+        cmap.u16(1);
+        cmap.u16(0).u16(0).u16(0); // Range offsets
+
+        let mut head = Writer::default();
+        head.u32(0x0001_0000)
+            .u32(0)
+            .u32(0)
+            .u32(0x5F0F_3CF5)
+            .u16(0)
+            .u16(1000);
+        head.u32(0).u32(0).u32(0).u32(0); // Created and modified
+        head.i16(0).i16(0).i16(1000).i16(800); // Bounds
+        head.u16(0).u16(8).i16(2).i16(0).i16(0); // Short `loca` offsets
+
+        let mut hhea = Writer::default();
+        hhea.u32(0x0001_0000).i16(800).i16(-200).i16(0).u16(1000);
+        hhea.i16(0).i16(0).i16(1000).i16(1).i16(0).i16(0);
+        hhea.i16(0).i16(0).i16(0).i16(0).i16(0).u16(NUM_GLYPHS);
+
+        let mut hmtx = Writer::default();
+        for _ in 0..NUM_GLYPHS {
+            hmtx.u16(1000).i16(0);
+        }
+
+        let mut maxp = Writer::default();
+        maxp.u32(0x0000_5000).u16(NUM_GLYPHS);
+
+        // Sorted by tag:
+        let tables = [
+            (b"COLR", colr),
+            (b"CPAL", cpal),
+            (b"cmap", cmap),
+            (b"glyf", glyf),
+            (b"head", head),
+            (b"hhea", hhea),
+            (b"hmtx", hmtx),
+            (b"loca", loca),
+            (b"maxp", maxp),
+        ];
+
+        let mut font = Writer::default();
+        font.u32(0x0001_0000)
+            .u16(tables.len() as u16)
+            .u16(0)
+            .u16(0)
+            .u16(0);
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, table) in &tables {
+            font.0.extend(*tag);
+            font.u32(0).u32(offset as u32).u32(table.0.len() as u32);
+            offset += table.0.len().next_multiple_of(4);
+        }
+        for (_, table) in &tables {
+            font.0.extend(&table.0);
+            font.0.resize(font.0.len().next_multiple_of(4), 0);
+        }
+        font.0
+    }
+
+    fn rasterize_test_glyph(glyph_id: u16) -> Option<ColorGlyphImage> {
+        let face = ColorFace::parse(FontData::from_owned(test_font()).into()).unwrap();
+        let font = ab_glyph::FontArc::try_from_vec(test_font()).unwrap();
+        let px_scale_factor = 0.02; // 20 pixels per em
+        rasterize(
+            &face,
+            &font,
+            ab_glyph::GlyphId(glyph_id),
+            px_scale_factor,
+            Vec2::ZERO,
+        )
+    }
+
+    #[test]
+    fn test_colr_foreground() {
+        assert!(
+            rasterize_test_glyph(2).is_none(),
+            "Only base glyphs are color glyphs"
+        );
+
+        let image = rasterize_test_glyph(1).unwrap();
+        assert_eq!(image.size, [20, 16]);
+        assert_eq!(image.offset, vec2(0.0, -16.0));
+
+        let (colors, foreground) = image.split();
+        let left = 8 * 20 + 5;
+        let right = 8 * 20 + 15;
+        assert_eq!(colors[left], Color32::RED);
+        assert_eq!(foreground[left], 0.0);
+        assert_eq!(colors[right], Color32::TRANSPARENT);
+        assert!(0.99 < foreground[right], "Painted with the text color");
+    }
+
+    #[test]
+    fn test_colr_gradient_and_clip_box() {
+        let image = rasterize_test_glyph(4).unwrap();
+        assert_eq!(image.size, [10, 16], "The clip box cuts off the right half");
+
+        let (colors, foreground) = image.split();
+        assert!(foreground.iter().all(|&coverage| coverage == 0.0));
+
+        let left = colors[8 * 10];
+        let right = colors[8 * 10 + 9];
+        assert!(240 < left.r() && left.b() < 15, "{left:?}");
+        assert!(
+            (120..150).contains(&right.r()) && (110..140).contains(&right.b()),
+            "Halfway between red and blue: {right:?}"
+        );
+        assert_eq!(left.a(), 255);
+        assert_eq!(right.a(), 255);
+    }
+
+    #[test]
+    fn test_composite() {
+        let red = premultiplied(RgbaColor::new(255, 0, 0, 255));
+        let white = premultiplied(RgbaColor::new(255, 255, 255, 255));
+        let half_blue = premultiplied(RgbaColor::new(0, 0, 255, 128));
+
+        assert_eq!(composite(CompositeMode::Multiply, red, white), red);
+        assert_eq!(composite(CompositeMode::Screen, red, white), white);
+        assert_eq!(
+            composite(CompositeMode::DestinationIn, half_blue, red)[3],
+            half_blue[3]
+        );
+        assert_eq!(composite(CompositeMode::Clear, red, white), TRANSPARENT);
+        assert_eq!(
+            composite(CompositeMode::SourceOver, half_blue, red),
+            source_over(half_blue, red)
+        );
+    }
+
+    #[test]
+    fn test_layout_color_glyphs() {
+        let mut definitions = FontDefinitions::empty();
+        definitions.font_data.insert(
+            "test".to_owned(),
+            Arc::new(FontData::from_owned(test_font())),
+        );
+        definitions
+            .families
+            .insert(FontFamily::Proportional, vec!["test".to_owned()]);
+        let mut fonts = FontsImpl::new(1024, AlphaFromCoverage::default(), definitions);
+
+        let text_color = Color32::GREEN;
+        let format = TextFormat::simple(FontId::proportional(20.0), text_color);
+        let job = LayoutJob::single_section("AB".into(), format);
+        let galley = layout(&mut fonts, 1.0, job.into());
+        let row = &galley.rows[0];
+
+        let [a, b] = [&row.glyphs[0], &row.glyphs[1]];
+        assert!(a.is_color());
+        assert!(!a.uv_rect.is_nothing(), "`A` has a part in the text color");
+        assert!(b.is_color());
+        assert!(b.uv_rect.is_nothing(), "`B` doesn't use the text color");
+
+        let visuals = &row.visuals;
+        assert_eq!(visuals.mesh.vertices.len(), 3 * 4);
+        for (i, vertex) in visuals.mesh.vertices.iter().enumerate() {
+            if visuals.glyph_vertex_range.contains(&i) {
+                assert_eq!(vertex.color, text_color);
+            } else {
+                assert_eq!(
+                    vertex.color,
+                    Color32::WHITE,
+                    "Color glyphs are painted as-is"
+                );
+            }
+        }
+        assert_eq!(visuals.glyph_vertex_range.len(), 4);
+    }
+
+    #[test]
+    fn test_resample() {
+        let red = Color32::RED;
+        let pixels = vec![red; 4 * 4];
+        let resampled = resample(&pixels, [4, 4], [2, 2]);
+        assert_eq!(resampled, vec![red; 2 * 2]);
+
+        let pixels = vec![Color32::BLACK, Color32::WHITE];
+        let resampled = resample(&pixels, [2, 1], [4, 1]);
+        assert_eq!(resampled[0], Color32::BLACK);
+        assert_eq!(resampled[3], Color32::WHITE);
+        assert!(resampled[1].r() < resampled[2].r());
+    }
+
+    #[cfg(feature = "default_fonts")]
+    #[test]
+    fn test_monochrome_font_has_no_color_glyphs() {
+        let font_data = FontData::from_static(epaint_default_fonts::HACK_REGULAR);
+        assert!(ColorFace::parse(font_data.into()).is_none());
+    }
+}
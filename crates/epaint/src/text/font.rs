@@ -3,11 +3,15 @@ use std::collections::BTreeMap;
 use ab_glyph::{Font as _, OutlinedGlyph, PxScale};
 use emath::{GuiRounding as _, OrderedFloat, Vec2, vec2};
 
+#[cfg(feature = "color_fonts")]
+use super::color_glyph;
+#[cfg(feature = "text_shaping")]
+use super::shaping;
 use crate::{
     TextureAtlas,
     text::{
         FontTweak,
-        fonts::{CachedFamily, FontFaceKey},
+        fonts::{CachedFamily, FallbackFonts, FontFaceKey},
     },
 };
 
@@ -129,8 +133,14 @@ pub struct GlyphAllocation {
     /// Unit: screen pixels.
     pub advance_width_px: f32,
 
-    /// UV rectangle for drawing.
+    /// UV rectangle for drawing, tinted by the text color.
     pub uv_rect: UvRect,
+
+    /// UV rectangle of the parts of a color glyph (e.g. an emoji) that are painted as-is.
+    ///
+    /// These are painted underneath [`Self::uv_rect`], which then contains any parts of the glyph
+    /// that are painted with the text color.
+    pub color_uv_rect: UvRect,
 }
 
 #[derive(Hash, PartialEq, Eq)]
@@ -160,6 +170,25 @@ impl GlyphCacheKey {
             bin,
         )))
     }
+
+    /// For an image containing several glyphs, see [`FontImpl::allocate_cluster`].
+    #[cfg(feature = "text_shaping")]
+    fn new_cluster(
+        glyphs: &[(ab_glyph::GlyphId, Vec2)],
+        metrics: &ScaledMetrics,
+        bin: SubpixelBin,
+    ) -> Self {
+        let glyphs: Vec<_> = glyphs
+            .iter()
+            .map(|(id, offset)| (*id, offset.x.to_bits(), offset.y.to_bits()))
+            .collect();
+        Self(crate::util::hash((
+            glyphs,
+            metrics.pixels_per_point.to_bits(),
+            metrics.px_scale_factor.to_bits(),
+            bin,
+        )))
+    }
 }
 
 // ----------------------------------------------------------------------------
@@ -172,6 +201,18 @@ pub struct FontImpl {
     tweak: FontTweak,
     glyph_info_cache: ahash::HashMap<char, GlyphInfo>,
     glyph_alloc_cache: ahash::HashMap<GlyphCacheKey, GlyphAllocation>,
+
+    /// The parsed font file, for shaping with [`rustybuzz`].
+    ///
+    /// `None` if `rustybuzz` can't parse it, in which case we lay out one character at a time.
+    #[cfg(feature = "text_shaping")]
+    shaping_face: Option<shaping::ShapingFace>,
+
+    /// The parsed font file, for rasterizing color glyphs.
+    ///
+    /// `None` if the font has no color glyphs.
+    #[cfg(feature = "color_fonts")]
+    color_face: Option<color_glyph::ColorFace>,
 }
 
 trait FontExt {
@@ -198,9 +239,60 @@ impl FontImpl {
             tweak,
             glyph_info_cache: Default::default(),
             glyph_alloc_cache: Default::default(),
+            #[cfg(feature = "text_shaping")]
+            shaping_face: None,
+            #[cfg(feature = "color_fonts")]
+            color_face: None,
         }
     }
 
+    /// Enable color glyphs in this font, if it has any.
+    #[cfg(feature = "color_fonts")]
+    pub fn with_color_glyphs(mut self, font_data: std::sync::Arc<crate::text::FontData>) -> Self {
+        self.color_face = color_glyph::ColorFace::parse(font_data);
+        self
+    }
+
+    /// Enable shaping of text in this font.
+    #[cfg(feature = "text_shaping")]
+    pub fn with_shaping(mut self, font_data: std::sync::Arc<crate::text::FontData>) -> Self {
+        self.shaping_face = shaping::ShapingFace::parse(font_data);
+        if self.shaping_face.is_none() {
+            log::warn!("Text shaping is not supported for font {:?}", self.name);
+        }
+        self
+    }
+
+    /// Can this character be part of a run of shaped text in this font?
+    ///
+    /// Characters that we treat specially (like `\t`) are laid out one at a time.
+    #[cfg(feature = "text_shaping")]
+    pub(super) fn can_shape(&mut self, c: char) -> bool {
+        if self.shaping_face.is_none() || c == '\t' || c == '\u{2009}' {
+            return false;
+        }
+        if is_joiner(c) {
+            return true; // These affect the shaping of their neighbors.
+        }
+        !invisible_char(c) && self.glyph_info(c).is_some_and(|info| info.id.is_some())
+    }
+
+    /// Shape a run of text, see [`shaping::shape`].
+    ///
+    /// All characters in `text` should pass [`Self::can_shape`].
+    #[cfg(feature = "text_shaping")]
+    pub(super) fn shape(
+        &self,
+        text: &str,
+        rtl: bool,
+        features: &[crate::text::FontFeature],
+    ) -> Vec<shaping::ShapedCluster> {
+        self.shaping_face
+            .as_ref()
+            .map(|face| shaping::shape(face, text, rtl, features))
+            .unwrap_or_default()
+    }
+
     /// Code points that will always be replaced by the replacement character.
     ///
     /// See also [`invisible_char`].
@@ -372,6 +464,27 @@ impl FontImpl {
 
         debug_assert!(glyph_id.0 != 0, "Can't allocate glyph for id 0");
 
+        #[cfg(feature = "color_fonts")]
+        if let Some(image) = self.color_face.as_ref().and_then(|color_face| {
+            color_glyph::rasterize(
+                color_face,
+                &self.ab_glyph_font,
+                glyph_id,
+                metrics.px_scale_factor,
+                vec2(bin.as_float(), 0.0),
+            )
+        }) {
+            let (uv_rect, color_uv_rect) = allocate_color_image(atlas, metrics, &image);
+            let allocation = GlyphAllocation {
+                id: glyph_id,
+                advance_width_px,
+                uv_rect,
+                color_uv_rect,
+            };
+            entry.insert(allocation);
+            return (allocation, h_pos_round);
+        }
+
         let uv_rect = self.ab_glyph_font.outline(glyph_id).map(|outline| {
             let glyph = ab_glyph::Glyph {
                 id: glyph_id,
@@ -431,10 +544,278 @@ impl FontImpl {
             id: glyph_id,
             advance_width_px,
             uv_rect,
+            color_uv_rect: UvRect::default(),
         };
         entry.insert(allocation);
         (allocation, h_pos_round)
     }
+
+    /// Allocate a shaped cluster of glyphs as a single image.
+    ///
+    /// `glyphs` are the glyphs of the cluster with their offsets from the start of the cluster,
+    /// in font units (see [`shaping::ShapedCluster`]).
+    #[cfg(feature = "text_shaping")]
+    pub(super) fn allocate_cluster(
+        &mut self,
+        atlas: &mut TextureAtlas,
+        metrics: &ScaledMetrics,
+        glyphs: &[(ab_glyph::GlyphId, Vec2)],
+        advance_width_unscaled: f32,
+        chr: char,
+        h_pos: f32,
+    ) -> (GlyphAllocation, i32) {
+        let advance_width_px = advance_width_unscaled * metrics.px_scale_factor;
+
+        if let [(glyph_id, offset)] = glyphs
+            && *offset == Vec2::ZERO
+        {
+            // The common case: a single glyph, which can share the cache with unshaped text.
+            let glyph_info = GlyphInfo {
+                id: Some(*glyph_id),
+                advance_width_unscaled: advance_width_unscaled.into(),
+            };
+            return self.allocate_glyph(atlas, metrics, glyph_info, chr, h_pos);
+        }
+
+        let (h_pos_round, bin) = SubpixelBin::new(h_pos);
+
+        let glyphs: Vec<(ab_glyph::GlyphId, Vec2)> = glyphs
+            .iter()
+            .map(|(glyph_id, offset)| (*glyph_id, *offset * metrics.px_scale_factor))
+            .collect();
+
+        let entry = match self
+            .glyph_alloc_cache
+            .entry(GlyphCacheKey::new_cluster(&glyphs, metrics, bin))
+        {
+            std::collections::hash_map::Entry::Occupied(glyph_alloc) => {
+                let mut glyph_alloc = *glyph_alloc.get();
+                glyph_alloc.advance_width_px = advance_width_px;
+                return (glyph_alloc, h_pos_round);
+            }
+            std::collections::hash_map::Entry::Vacant(entry) => entry,
+        };
+
+        #[cfg(feature = "color_fonts")]
+        if let Some((uv_rect, color_uv_rect)) = self.color_face.as_ref().and_then(|color_face| {
+            allocate_color_cluster(
+                color_face,
+                &self.ab_glyph_font,
+                atlas,
+                metrics,
+                &glyphs,
+                bin.as_float(),
+            )
+        }) {
+            let allocation = GlyphAllocation {
+                id: glyphs.last().map_or(ab_glyph::GlyphId(0), |(id, _)| *id),
+                advance_width_px,
+                uv_rect,
+                color_uv_rect,
+            };
+            entry.insert(allocation);
+            return (allocation, h_pos_round);
+        }
+
+        let outlined: Vec<OutlinedGlyph> = glyphs
+            .iter()
+            .filter(|(glyph_id, _)| glyph_id.0 != 0)
+            .filter_map(|(glyph_id, offset)| {
+                outline_glyph(
+                    &self.ab_glyph_font,
+                    *glyph_id,
+                    vec2(bin.as_float(), 0.0) + *offset,
+                    metrics.px_scale_factor,
+                )
+            })
+            .collect();
+
+        let bounds = outlined
+            .iter()
+            .map(|outlined| outlined.px_bounds())
+            .reduce(|a, b| ab_glyph::Rect {
+                min: ab_glyph::point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+                max: ab_glyph::point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+            });
+
+        let uv_rect = bounds.map_or_else(UvRect::default, |bb| {
+            let width = bb.width() as usize;
+            let height = bb.height() as usize;
+            if width == 0 || height == 0 {
+                return UvRect::default();
+            }
+
+            // Marks may overlap their base glyph, so we accumulate the coverage before writing it:
+            let mut coverage = vec![0.0_f32; width * height];
+            for outlined in &outlined {
+                let glyph_bb = outlined.px_bounds();
+                let dx = (glyph_bb.min.x - bb.min.x) as usize;
+                let dy = (glyph_bb.min.y - bb.min.y) as usize;
+                outlined.draw(|x, y, v| {
+                    let (x, y) = (dx + x as usize, dy + y as usize);
+                    if x < width && y < height {
+                        let c = &mut coverage[y * width + x];
+                        *c = (*c + v).min(1.0);
+                    }
+                });
+            }
+
+            let text_alpha_from_coverage = atlas.text_alpha_from_coverage;
+            let (glyph_pos, image) = atlas.allocate((width, height));
+            for y in 0..height {
+                for x in 0..width {
+                    let v = coverage[y * width + x];
+                    if 0.0 < v {
+                        image[(glyph_pos.0 + x, glyph_pos.1 + y)] =
+                            text_alpha_from_coverage.color_from_coverage(v);
+                    }
+                }
+            }
+
+            let offset_in_pixels = vec2(bb.min.x, bb.min.y);
+            UvRect {
+                offset: offset_in_pixels / metrics.pixels_per_point
+                    + metrics.y_offset_in_points * Vec2::Y,
+                size: vec2(width as f32, height as f32) / metrics.pixels_per_point,
+                min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
+                max: [(glyph_pos.0 + width) as u16, (glyph_pos.1 + height) as u16],
+            }
+        });
+
+        let allocation = GlyphAllocation {
+            id: glyphs.last().map_or(ab_glyph::GlyphId(0), |(id, _)| *id),
+            advance_width_px,
+            uv_rect,
+            color_uv_rect: UvRect::default(),
+        };
+        entry.insert(allocation);
+        (allocation, h_pos_round)
+    }
+}
+
+/// Outline a glyph at the given position, in physical pixels.
+#[cfg(feature = "text_shaping")]
+fn outline_glyph(
+    font: &ab_glyph::FontArc,
+    glyph_id: ab_glyph::GlyphId,
+    position: Vec2,
+    px_scale_factor: f32,
+) -> Option<OutlinedGlyph> {
+    let outline = font.outline(glyph_id)?;
+    let glyph = ab_glyph::Glyph {
+        id: glyph_id,
+        // See `FontImpl::allocate_glyph`.
+        scale: PxScale::from(0.0),
+        position: ab_glyph::point(position.x, position.y),
+    };
+    Some(OutlinedGlyph::new(
+        glyph,
+        outline,
+        ab_glyph::PxScaleFactor {
+            horizontal: px_scale_factor,
+            vertical: px_scale_factor,
+        },
+    ))
+}
+
+/// Allocate a shaped cluster as a single color image.
+///
+/// Returns `None` if none of the glyphs of the cluster are color glyphs.
+/// Otherwise returns the [`GlyphAllocation::uv_rect`] and [`GlyphAllocation::color_uv_rect`].
+#[cfg(all(feature = "text_shaping", feature = "color_fonts"))]
+fn allocate_color_cluster(
+    color_face: &color_glyph::ColorFace,
+    font: &ab_glyph::FontArc,
+    atlas: &mut TextureAtlas,
+    metrics: &ScaledMetrics,
+    glyphs: &[(ab_glyph::GlyphId, Vec2)],
+    x_offset: f32,
+) -> Option<(UvRect, UvRect)> {
+    let glyphs: Vec<(ab_glyph::GlyphId, Vec2)> = glyphs
+        .iter()
+        .filter(|(glyph_id, _)| glyph_id.0 != 0)
+        .map(|(glyph_id, offset)| (*glyph_id, vec2(x_offset, 0.0) + *offset))
+        .collect();
+
+    let color_images: Vec<Option<color_glyph::ColorGlyphImage>> = glyphs
+        .iter()
+        .map(|(glyph_id, position)| {
+            color_glyph::rasterize(
+                color_face,
+                font,
+                *glyph_id,
+                metrics.px_scale_factor,
+                *position,
+            )
+        })
+        .collect();
+    if color_images.iter().all(Option::is_none) {
+        return None;
+    }
+
+    // The other glyphs are painted with the text color:
+    let images: Vec<color_glyph::ColorGlyphImage> = glyphs
+        .iter()
+        .zip(color_images)
+        .filter_map(|((glyph_id, position), image)| {
+            image.or_else(|| {
+                let outlined = outline_glyph(font, *glyph_id, *position, metrics.px_scale_factor)?;
+                color_glyph::ColorGlyphImage::from_outline(&outlined)
+            })
+        })
+        .collect();
+
+    Some(
+        color_glyph::ColorGlyphImage::composite(&images).map_or_else(Default::default, |image| {
+            allocate_color_image(atlas, metrics, &image)
+        }),
+    )
+}
+
+/// Allocate a color glyph in the atlas.
+///
+/// Returns the [`GlyphAllocation::uv_rect`] and [`GlyphAllocation::color_uv_rect`].
+#[cfg(feature = "color_fonts")]
+fn allocate_color_image(
+    atlas: &mut TextureAtlas,
+    metrics: &ScaledMetrics,
+    image: &color_glyph::ColorGlyphImage,
+) -> (UvRect, UvRect) {
+    let [width, height] = image.size;
+    let (colors, foreground) = image.split();
+    let text_alpha_from_coverage = atlas.text_alpha_from_coverage;
+
+    let mut allocate = |color: &dyn Fn(usize) -> ecolor::Color32| {
+        let (glyph_pos, atlas_image) = atlas.allocate((width, height));
+        for y in 0..height {
+            for x in 0..width {
+                atlas_image[(glyph_pos.0 + x, glyph_pos.1 + y)] = color(y * width + x);
+            }
+        }
+        UvRect {
+            offset: image.offset / metrics.pixels_per_point + metrics.y_offset_in_points * Vec2::Y,
+            size: vec2(width as f32, height as f32) / metrics.pixels_per_point,
+            min: [glyph_pos.0 as u16, glyph_pos.1 as u16],
+            max: [(glyph_pos.0 + width) as u16, (glyph_pos.1 + height) as u16],
+        }
+    };
+
+    let color_uv_rect = if colors
+        .iter()
+        .any(|color| *color != ecolor::Color32::TRANSPARENT)
+    {
+        allocate(&|i| colors[i])
+    } else {
+        UvRect::default()
+    };
+
+    let uv_rect = if foreground.iter().any(|&coverage| 0.0 < coverage) {
+        allocate(&|i| text_alpha_from_coverage.color_from_coverage(foreground[i]))
+    } else {
+        UvRect::default()
+    };
+
+    (uv_rect, color_uv_rect)
 }
 
 // TODO(emilk): rename?
@@ -442,6 +823,8 @@ impl FontImpl {
 pub struct Font<'a> {
     pub(super) fonts_by_id: &'a mut nohash_hasher::IntMap<FontFaceKey, FontImpl>,
     pub(super) cached_family: &'a mut CachedFamily,
+    pub(super) fonts_by_name: &'a mut ahash::HashMap<String, FontFaceKey>,
+    pub(super) fallback_fonts: &'a mut FallbackFonts,
     pub(super) atlas: &'a mut TextureAtlas,
 }
 
@@ -503,7 +886,11 @@ impl Font<'_> {
 
         let font_index_glyph_info = self
             .cached_family
-            .glyph_info_no_cache_or_fallback(c, self.fonts_by_id);
+            .glyph_info_no_cache_or_fallback(c, self.fonts_by_id)
+            .or_else(|| {
+                self.fallback_fonts
+                    .glyph_info(c, self.fonts_by_id, self.fonts_by_name)
+            });
         let font_index_glyph_info =
             font_index_glyph_info.unwrap_or(self.cached_family.replacement_glyph);
         self.cached_family
@@ -552,11 +939,12 @@ fn invisible_char(c: char) -> bool {
 
     // From https://www.fileformat.info/info/unicode/category/Cf/list.htm
 
-    // TODO(emilk): heed bidi characters
+    // Bidi control characters are handled during layout, see `text::bidi`.
 
     matches!(
         c,
-        '\u{200B}' // ZERO WIDTH SPACE
+        '\u{AD}' // SOFT HYPHEN (shown as a hyphen at the end of a row, see `TextWrapping::hyphenation`)
+            | '\u{200B}' // ZERO WIDTH SPACE
             | '\u{200C}' // ZERO WIDTH NON-JOINER
             | '\u{200D}' // ZERO WIDTH JOINER
             | '\u{200E}' // LEFT-TO-RIGHT MARK
@@ -585,6 +973,13 @@ fn invisible_char(c: char) -> bool {
     )
 }
 
+/// Zero-width joiners and non-joiners, which are invisible but affect shaping.
+#[cfg(feature = "text_shaping")]
+#[inline]
+pub(super) fn is_joiner(c: char) -> bool {
+    matches!(c, '\u{200C}' | '\u{200D}')
+}
+
 #[inline]
 pub(super) fn is_cjk_ideograph(c: char) -> bool {
     ('\u{4E00}' <= c && c <= '\u{9FFF}')
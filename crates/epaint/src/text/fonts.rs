@@ -9,7 +9,7 @@ use std::{
 use crate::{
     AlphaFromCoverage, TextureAtlas,
     text::{
-        Galley, LayoutJob, LayoutSection,
+        Galley, LayoutJob, LayoutSection, Script,
         font::{Font, FontImpl, GlyphInfo},
     },
 };
@@ -199,6 +199,13 @@ impl Default for FontTweak {
 // ----------------------------------------------------------------------------
 
 fn ab_glyph_font_from_font_data(name: &str, data: &FontData) -> ab_glyph::FontArc {
+    try_ab_glyph_font_from_font_data(data)
+        .unwrap_or_else(|err| panic!("Error parsing {name:?} TTF/OTF font file: {err}"))
+}
+
+fn try_ab_glyph_font_from_font_data(
+    data: &FontData,
+) -> Result<ab_glyph::FontArc, ab_glyph::InvalidFont> {
     match &data.font {
         std::borrow::Cow::Borrowed(bytes) => {
             ab_glyph::FontRef::try_from_slice_and_index(bytes, data.index)
@@ -209,7 +216,19 @@ fn ab_glyph_font_from_font_data(name: &str, data: &FontData) -> ab_glyph::FontAr
                 .map(ab_glyph::FontArc::from)
         }
     }
-    .unwrap_or_else(|err| panic!("Error parsing {name:?} TTF/OTF font file: {err}"))
+}
+
+fn font_impl_from_font_data(
+    name: &str,
+    font_data: &Arc<FontData>,
+    ab_glyph: ab_glyph::FontArc,
+) -> FontImpl {
+    let font_impl = FontImpl::new(name.to_owned(), ab_glyph, font_data.tweak);
+    #[cfg(feature = "text_shaping")]
+    let font_impl = font_impl.with_shaping(font_data.clone());
+    #[cfg(feature = "color_fonts")]
+    let font_impl = font_impl.with_color_glyphs(font_data.clone());
+    font_impl
 }
 
 /// Describes the font data and the sizes to use.
@@ -242,6 +261,9 @@ fn ab_glyph_font_from_font_data(name: &str, data: &FontData) -> ab_glyph::FontAr
 ///
 /// egui_ctx.set_fonts(fonts);
 /// ```
+///
+/// Characters that are missing from all the fonts of a family are looked up
+/// in [`Self::script_fallbacks`], and then in the fonts provided by [`Self::font_loader`].
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
@@ -258,6 +280,72 @@ pub struct FontDefinitions {
     /// the first font and then move to the second, and so on.
     /// So the first font is the primary, and then comes a list of fallbacks in order of priority.
     pub families: BTreeMap<FontFamily, Vec<String>>,
+
+    /// Which fonts (names) to use for each [`Script`],
+    /// for characters that are missing from all the fonts of a [`FontFamily`].
+    ///
+    /// The list should be a list of keys into [`Self::font_data`].
+    /// Names that are not in [`Self::font_data`] are skipped, with a warning.
+    ///
+    /// This lets you use one font for e.g. Chinese characters and another for Cyrillic,
+    /// without each font being tried for every missing character.
+    pub script_fallbacks: BTreeMap<Script, Vec<String>>,
+
+    /// Lazily provides fonts for characters that none of the other fonts have.
+    ///
+    /// This lets you load big fonts (e.g. for CJK) only once they are needed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub font_loader: Option<FontLoader>,
+}
+
+/// Lazily provides a font for a character that none of the loaded fonts have.
+///
+/// The function is given the missing character, and should return the name and data
+/// of a font that has it, or `None` if there is no such font.
+/// It is called at most once for each character,
+/// but again after the fonts have been recreated (e.g. when the font atlas is full),
+/// so you should keep the returned [`FontData`] around instead of loading it again.
+///
+/// Returning the name of a font that is already loaded (including those in
+/// [`FontDefinitions::font_data`]) reuses that font.
+///
+/// The function is called synchronously during text layout, while the lock on [`Fonts`] is held.
+/// So it must not use [`Fonts`] itself (that would deadlock), and anything slow
+/// (like reading a big font file from disk) will stall the frame.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use epaint::text::{FontData, FontDefinitions, FontLoader, Script};
+/// # fn load_cjk_font() -> FontData { unimplemented!() }
+/// let cjk_font: Arc<std::sync::OnceLock<Arc<FontData>>> = Default::default();
+///
+/// let mut fonts = FontDefinitions::default();
+/// fonts.font_loader = Some(FontLoader::new(move |c| {
+///     matches!(Script::of(c), Some(Script::Han | Script::Hiragana | Script::Katakana))
+///         .then(|| ("cjk".to_owned(), cjk_font.get_or_init(|| Arc::new(load_cjk_font())).clone()))
+/// }));
+/// ```
+#[derive(Clone)]
+pub struct FontLoader(Arc<dyn Fn(char) -> Option<(String, Arc<FontData>)> + Send + Sync>);
+
+impl FontLoader {
+    pub fn new(
+        load: impl Fn(char) -> Option<(String, Arc<FontData>)> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(load))
+    }
+}
+
+impl std::fmt::Debug for FontLoader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FontLoader").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for FontLoader {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -369,6 +457,8 @@ impl Default for FontDefinitions {
         Self {
             font_data,
             families,
+            script_fallbacks: Default::default(),
+            font_loader: None,
         }
     }
 }
@@ -383,6 +473,8 @@ impl FontDefinitions {
         Self {
             font_data: Default::default(),
             families,
+            script_fallbacks: Default::default(),
+            font_loader: None,
         }
     }
 
@@ -489,6 +581,81 @@ impl CachedFamily {
     }
 }
 
+/// Fonts for characters that are missing from all the fonts of a family.
+///
+/// See [`FontDefinitions::script_fallbacks`] and [`FontDefinitions::font_loader`].
+#[derive(Debug, Default)]
+pub(super) struct FallbackFonts {
+    by_script: BTreeMap<Script, Vec<FontFaceKey>>,
+
+    font_loader: Option<FontLoader>,
+
+    /// Fonts provided by the [`FontLoader`], in the order they were loaded.
+    loaded: Vec<FontFaceKey>,
+
+    /// Characters we have already asked the [`FontLoader`] for.
+    requested: ahash::HashSet<char>,
+}
+
+impl FallbackFonts {
+    pub(crate) fn glyph_info(
+        &mut self,
+        c: char,
+        fonts_by_id: &mut nohash_hasher::IntMap<FontFaceKey, FontImpl>,
+        fonts_by_name: &mut ahash::HashMap<String, FontFaceKey>,
+    ) -> Option<(FontFaceKey, GlyphInfo)> {
+        let script_fonts = Script::of(c)
+            .and_then(|script| self.by_script.get(&script))
+            .into_iter()
+            .flatten();
+        for font_key in script_fonts.chain(&self.loaded) {
+            let font_impl = fonts_by_id.get_mut(font_key).expect("Nonexistent font ID");
+            if let Some(glyph_info) = font_impl.glyph_info(c) {
+                return Some((*font_key, glyph_info));
+            }
+        }
+
+        let font_key = self.load_font_for(c, fonts_by_id, fonts_by_name)?;
+        let font_impl = fonts_by_id.get_mut(&font_key).expect("Nonexistent font ID");
+        Some((font_key, font_impl.glyph_info(c)?))
+    }
+
+    /// Ask the [`FontLoader`] (once) for a font that has this character.
+    fn load_font_for(
+        &mut self,
+        c: char,
+        fonts_by_id: &mut nohash_hasher::IntMap<FontFaceKey, FontImpl>,
+        fonts_by_name: &mut ahash::HashMap<String, FontFaceKey>,
+    ) -> Option<FontFaceKey> {
+        let font_loader = self.font_loader.as_ref()?;
+        if !self.requested.insert(c) {
+            return None;
+        }
+
+        let (name, font_data) = (font_loader.0)(c)?;
+        if let Some(&key) = fonts_by_name.get(&name) {
+            // Either loaded before, or one of `FontDefinitions::font_data`:
+            if !self.loaded.contains(&key) {
+                self.loaded.push(key);
+            }
+            return Some(key);
+        }
+
+        let ab_glyph = match try_ab_glyph_font_from_font_data(&font_data) {
+            Ok(ab_glyph) => ab_glyph,
+            Err(err) => {
+                log::warn!("Error parsing {name:?} TTF/OTF font file: {err}");
+                return None;
+            }
+        };
+        let key = FontFaceKey::new();
+        fonts_by_id.insert(key, font_impl_from_font_data(&name, &font_data, ab_glyph));
+        fonts_by_name.insert(name, key);
+        self.loaded.push(key);
+        Some(key)
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 /// The collection of fonts used by `epaint`.
@@ -701,6 +868,26 @@ impl FontsView<'_> {
         )
     }
 
+    /// Like [`Self::layout_job`], but looked up in the cache by `key` instead of by the job,
+    /// so that `job` is only called if nothing was laid out for `key` last frame.
+    ///
+    /// This saves creating and hashing a large job that has not changed.
+    /// `key` must change whenever the job would, e.g. a hash of a revision of the text and of its style.
+    pub fn layout_job_with_key(
+        &mut self,
+        key: u64,
+        job: impl FnOnce() -> LayoutJob,
+    ) -> Arc<Galley> {
+        let allow_split_paragraphs = true; // Optimization for editing text with many paragraphs.
+        self.galley_cache.layout_with_key(
+            self.fonts,
+            self.pixels_per_point,
+            key,
+            job,
+            allow_split_paragraphs,
+        )
+    }
+
     pub fn num_galleys_in_cache(&self) -> usize {
         self.galley_cache.num_galleys_in_cache()
     }
@@ -765,6 +952,7 @@ pub struct FontsImpl {
     fonts_by_id: nohash_hasher::IntMap<FontFaceKey, FontImpl>,
     fonts_by_name: ahash::HashMap<String, FontFaceKey>,
     family_cache: ahash::HashMap<FontFamily, CachedFamily>,
+    fallback_fonts: FallbackFonts,
 }
 
 impl FontsImpl {
@@ -782,14 +970,37 @@ impl FontsImpl {
         let mut fonts_by_id: nohash_hasher::IntMap<FontFaceKey, FontImpl> = Default::default();
         let mut font_impls: ahash::HashMap<String, FontFaceKey> = Default::default();
         for (name, font_data) in &definitions.font_data {
-            let tweak = font_data.tweak;
             let ab_glyph = ab_glyph_font_from_font_data(name, font_data);
-            let font_impl = FontImpl::new(name.clone(), ab_glyph, tweak);
+            let font_impl = font_impl_from_font_data(name, font_data, ab_glyph);
             let key = FontFaceKey::new();
             fonts_by_id.insert(key, font_impl);
             font_impls.insert(name.clone(), key);
         }
 
+        let fallback_fonts = FallbackFonts {
+            by_script: definitions
+                .script_fallbacks
+                .iter()
+                .map(|(script, font_names)| {
+                    let fonts = font_names
+                        .iter()
+                        .filter_map(|font_name| {
+                            let key = font_impls.get(font_name).copied();
+                            if key.is_none() {
+                                log::warn!(
+                                    "No font data found for the {script:?} fallback font {font_name:?}"
+                                );
+                            }
+                            key
+                        })
+                        .collect();
+                    (*script, fonts)
+                })
+                .collect(),
+            font_loader: definitions.font_loader.clone(),
+            ..Default::default()
+        };
+
         Self {
             max_texture_side,
             definitions,
@@ -797,6 +1008,7 @@ impl FontsImpl {
             fonts_by_id,
             fonts_by_name: font_impls,
             family_cache: Default::default(),
+            fallback_fonts,
         }
     }
 
@@ -822,6 +1034,8 @@ impl FontsImpl {
         Font {
             fonts_by_id: &mut self.fonts_by_id,
             cached_family,
+            fonts_by_name: &mut self.fonts_by_name,
+            fallback_fonts: &mut self.fallback_fonts,
             atlas: &mut self.atlas,
         }
     }
@@ -953,6 +1167,47 @@ impl GalleyCache {
             .1
     }
 
+    fn layout_with_key(
+        &mut self,
+        fonts: &mut FontsImpl,
+        pixels_per_point: f32,
+        key: u64,
+        job: impl FnOnce() -> LayoutJob,
+        allow_split_paragraphs: bool,
+    ) -> Arc<Galley> {
+        // Kept apart from the hashes of jobs:
+        let key = crate::util::hash(("layout_with_key", key, OrderedFloat(pixels_per_point)));
+        if let Some(cached) = self.cache.get(&key) {
+            let galley = cached.galley.clone();
+            self.keep(key);
+            return galley;
+        }
+
+        let (hash, galley) =
+            self.layout_internal(fonts, job(), pixels_per_point, allow_split_paragraphs);
+        self.cache.insert(
+            key,
+            CachedGalley {
+                last_used: self.generation,
+                children: Some([hash].into()),
+                galley: galley.clone(),
+            },
+        );
+        galley
+    }
+
+    /// Keep the galley with this hash in the cache, along with the galleys it was made from.
+    fn keep(&mut self, hash: u64) {
+        if let Some(cached) = self.cache.get_mut(&hash) {
+            cached.last_used = self.generation;
+            if let Some(children) = cached.children.clone() {
+                for child_hash in children.iter() {
+                    self.keep(*child_hash);
+                }
+            }
+        }
+    }
+
     /// Split on `\n` and lay out (and cache) each paragraph individually.
     fn layout_each_paragraph_individually(
         &mut self,
@@ -983,12 +1238,14 @@ impl GalleyCache {
                 text: job.text[start..end].to_owned(),
                 wrap: crate::text::TextWrapping {
                     max_rows: max_rows_remaining,
-                    ..job.wrap
+                    ..job.wrap.clone()
                 },
                 sections: Vec::new(),
                 break_on_newline: job.break_on_newline,
                 halign: job.halign,
                 justify: job.justify,
+                justify_word_spacing: job.justify_word_spacing,
+                tab_stops: job.tab_stops.clone(),
                 first_row_min_height: if is_first_paragraph {
                     job.first_row_min_height
                 } else {
@@ -1003,6 +1260,7 @@ impl GalleyCache {
                     leading_space,
                     byte_range: section_range,
                     format,
+                    inline_box,
                 } = section;
 
                 // `start` and `end` are the byte range of the current paragraph.
@@ -1033,6 +1291,7 @@ impl GalleyCache {
                         },
                         byte_range: new_range,
                         format: format.clone(),
+                        inline_box: *inline_box,
                     });
                 }
             }
@@ -1089,7 +1348,7 @@ mod tests {
     use core::f32;
 
     use super::*;
-    use crate::text::{TextWrapping, layout};
+    use crate::text::{InlineBox, TextWrapping, layout};
     use crate::{Stroke, text::TextFormat};
     use ecolor::Color32;
     use emath::Align;
@@ -1185,9 +1444,62 @@ mod tests {
                 job.append("", 0.0, TextFormat::default());
                 job
             },
+            {
+                let mut job = LayoutJob::default();
+                job.append("An icon ", 0.0, TextFormat::default());
+                job.append_inline_box(
+                    InlineBox::new(emath::vec2(16.0, 16.0)),
+                    TextFormat::default(),
+                );
+                job.append(" and\nsomething ", 0.0, TextFormat::default());
+                job.append_inline_box(
+                    InlineBox {
+                        size: emath::vec2(30.0, 24.0),
+                        baseline: 20.0,
+                    },
+                    TextFormat::default(),
+                );
+                job.append(" below the baseline", 0.0, TextFormat::default());
+                job.wrap.max_width = 90.0;
+                job
+            },
         ]
     }
 
+    #[test]
+    fn test_layout_with_key() {
+        let mut fonts = FontsImpl::new(
+            4096,
+            AlphaFromCoverage::default(),
+            FontDefinitions::default(),
+        );
+        let mut cache = GalleyCache::default();
+        let job = || LayoutJob::simple_format("1st\n2nd".to_owned(), TextFormat::default());
+
+        let galley = cache.layout_with_key(&mut fonts, 1.0, 42, job, true);
+        for _ in 0..3 {
+            cache.flush_cache();
+            let cached = cache.layout_with_key(
+                &mut fonts,
+                1.0,
+                42,
+                || panic!("Laid out again, though the key is the same"),
+                true,
+            );
+            assert!(Arc::ptr_eq(&cached, &galley));
+        }
+
+        // The paragraphs are kept too, for laying out an edit of the text quickly:
+        cache.flush_cache();
+        assert_eq!(
+            cache.num_galleys_in_cache(),
+            4,
+            "The key, the whole job and its two paragraphs"
+        );
+        cache.flush_cache();
+        assert_eq!(cache.num_galleys_in_cache(), 0, "Evicted when not used");
+    }
+
     #[expect(clippy::print_stdout)]
     #[test]
     fn test_split_paragraphs() {
@@ -1242,6 +1554,7 @@ mod tests {
                             "pixels_per_point: {pixels_per_point:.2}, input text: '{}'",
                             job.text
                         );
+                        assert_eq!(split.inline_box_rects(), whole.inline_box_rects());
                     }
                 }
             }
@@ -1312,4 +1625,97 @@ mod tests {
         let width = view.glyph_width(&FontId::new(12.0, FontFamily::Proportional), ' ');
         assert_eq!(width, 0.0);
     }
+
+    #[test]
+    fn test_script_fallbacks() {
+        let mut definitions = FontDefinitions::empty();
+        definitions.font_data.insert(
+            "Hack".to_owned(),
+            Arc::new(FontData::from_static(HACK_REGULAR)),
+        );
+        definitions
+            .script_fallbacks
+            .insert(Script::Cyrillic, vec!["Hack".to_owned()]);
+
+        let mut fonts = Fonts::new(1024, AlphaFromCoverage::default(), definitions);
+        let mut view = fonts.with_pixels_per_point(1.0);
+        let font_id = FontId::new(12.0, FontFamily::Proportional);
+
+        assert!(
+            view.has_glyph(&font_id, 'Ж'),
+            "Cyrillic should use the fallback"
+        );
+        assert!(!view.has_glyph(&font_id, 'a'), "Latin has no fallback");
+    }
+
+    #[test]
+    fn test_unknown_script_fallback_is_skipped() {
+        let mut definitions = FontDefinitions::empty();
+        definitions.font_data.insert(
+            "Hack".to_owned(),
+            Arc::new(FontData::from_static(HACK_REGULAR)),
+        );
+        definitions.script_fallbacks.insert(
+            Script::Cyrillic,
+            vec!["Missing".to_owned(), "Hack".to_owned()],
+        );
+
+        let mut fonts = Fonts::new(1024, AlphaFromCoverage::default(), definitions);
+        let mut view = fonts.with_pixels_per_point(1.0);
+        let font_id = FontId::new(12.0, FontFamily::Proportional);
+        assert!(view.has_glyph(&font_id, 'Ж'));
+    }
+
+    #[test]
+    fn test_font_loader() {
+        use std::sync::atomic::AtomicUsize;
+
+        let num_calls = Arc::new(AtomicUsize::new(0));
+        let mut definitions = FontDefinitions::empty();
+        definitions.font_loader = Some(FontLoader::new({
+            let num_calls = num_calls.clone();
+            move |c| {
+                num_calls.fetch_add(1, Ordering::Relaxed);
+                c.is_ascii().then(|| {
+                    let font_data = Arc::new(FontData::from_static(HACK_REGULAR));
+                    ("Hack".to_owned(), font_data)
+                })
+            }
+        }));
+
+        let mut fonts = Fonts::new(1024, AlphaFromCoverage::default(), definitions);
+        let mut view = fonts.with_pixels_per_point(1.0);
+        let proportional = FontId::new(12.0, FontFamily::Proportional);
+        let monospace = FontId::new(12.0, FontFamily::Monospace);
+
+        assert!(view.has_glyph(&proportional, 'a'));
+        assert_eq!(num_calls.load(Ordering::Relaxed), 1);
+
+        // The loaded font is reused for other characters and families:
+        assert!(view.has_glyphs(&proportional, "bc"));
+        assert!(view.has_glyph(&monospace, 'a'));
+        assert_eq!(num_calls.load(Ordering::Relaxed), 1);
+
+        // The loader is only asked once per character:
+        assert!(!view.has_glyph(&proportional, '😀'));
+        assert!(!view.has_glyph(&monospace, '😀'));
+        assert_eq!(num_calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_font_loader_reuses_font_data() {
+        let mut definitions = FontDefinitions::empty();
+        definitions.font_data.insert(
+            "Hack".to_owned(),
+            Arc::new(FontData::from_static(HACK_REGULAR)),
+        );
+        // Returning a known name should reuse that font, not parse the (empty) data:
+        definitions.font_loader = Some(FontLoader::new(|_| {
+            Some(("Hack".to_owned(), Arc::new(FontData::from_static(&[]))))
+        }));
+
+        let mut fonts = Fonts::new(1024, AlphaFromCoverage::default(), definitions);
+        let mut view = fonts.with_pixels_per_point(1.0);
+        assert!(view.has_glyph(&FontId::proportional(12.0), 'a'));
+    }
 }
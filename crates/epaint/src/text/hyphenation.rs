@@ -0,0 +1,249 @@
+//! Hyphenation of words that don't fit on a row.
+//!
+//! See [`crate::text::TextWrapping::hyphenation`].
+
+use std::sync::Arc;
+
+/// The soft hyphen (`U+00AD`) marks where a word may be hyphenated.
+///
+/// It is invisible, unless the word is broken there.
+pub const SOFT_HYPHEN: char = '\u{AD}';
+
+/// How to hyphenate words when wrapping text.
+///
+/// When a word is broken at a hyphenation point, a hyphen is shown at the end of the row.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Hyphenation {
+    /// Never hyphenate words.
+    #[default]
+    None,
+
+    /// Hyphenate words at the soft hyphens ([`SOFT_HYPHEN`]) in the text.
+    SoftHyphens,
+
+    /// Hyphenate words at the soft hyphens ([`SOFT_HYPHEN`]) in the text,
+    /// and hyphenate the other words at the hyphenation points found by the [`Hyphenator`].
+    Hyphenator(Arc<Hyphenator>),
+}
+
+impl Hyphenation {
+    #[inline]
+    pub fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+}
+
+/// Finds hyphenation points in words, using Liang's algorithm (the one used by TeX).
+///
+/// `epaint` doesn't come with any hyphenation patterns, since they are specific for each language.
+/// You can find patterns for most languages at <https://github.com/hyphenation/tex-hyphen>
+/// (look for the `hyph-*.pat.txt` and `hyph-*.hyp.txt` files).
+///
+/// ```
+/// # use epaint::text::Hyphenator;
+/// let hyphenator = Hyphenator::from_patterns("hy3ph he2n hena4 hen5at 1na n2at 1tio 2io o2n")
+///     .with_exceptions("ta-ble");
+/// assert_eq!(hyphenator.hyphenation_points("hyphenation"), vec![2, 6]); // hy-phen-ation
+/// assert_eq!(hyphenator.hyphenation_points("table"), vec![2]); // ta-ble
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Hyphenator {
+    /// The letters of each pattern, and the value before, between and after them.
+    patterns: ahash::HashMap<String, Vec<u8>>,
+
+    /// Length (in chars) of the longest pattern.
+    max_pattern_len: usize,
+
+    /// Words with explicit hyphenation points (char indices).
+    exceptions: ahash::HashMap<String, Vec<usize>>,
+
+    /// Don't hyphenate closer than this many chars to the start of a word.
+    min_prefix: usize,
+
+    /// Don't hyphenate closer than this many chars to the end of a word.
+    min_suffix: usize,
+
+    /// Hash of everything above, so that hashing a [`crate::text::LayoutJob`] is fast.
+    hash: u64,
+}
+
+impl Hyphenator {
+    /// Create a hyphenator from whitespace-separated TeX hyphenation patterns, e.g. `"hy3ph he2n 1na"`.
+    pub fn from_patterns(patterns: &str) -> Self {
+        let mut slf = Self {
+            patterns: Default::default(),
+            max_pattern_len: 0,
+            exceptions: Default::default(),
+            min_prefix: 2,
+            min_suffix: 3,
+            hash: 0,
+        };
+
+        for pattern in patterns.split_whitespace() {
+            let mut letters = String::new();
+            let mut values = vec![0];
+            for c in pattern.chars() {
+                if let Some(value) = c.to_digit(10) {
+                    *values.last_mut().unwrap() = value as u8;
+                } else {
+                    letters.push(c);
+                    values.push(0);
+                }
+            }
+            slf.max_pattern_len = slf.max_pattern_len.max(values.len() - 1);
+            slf.patterns.insert(letters, values);
+        }
+
+        slf.update_hash();
+        slf
+    }
+
+    /// Add words with explicit hyphenation points, e.g. `"ta-ble pro-ject"`.
+    ///
+    /// These words are not hyphenated using the patterns.
+    pub fn with_exceptions(mut self, exceptions: &str) -> Self {
+        for exception in exceptions.split_whitespace() {
+            let mut word = String::new();
+            let mut points = vec![];
+            for c in exception.chars() {
+                if c == '-' {
+                    points.push(word.chars().count());
+                } else {
+                    word.push(lowercase(c));
+                }
+            }
+            self.exceptions.insert(word, points);
+        }
+        self.update_hash();
+        self
+    }
+
+    /// Don't hyphenate closer than this many chars to the start and the end of a word.
+    ///
+    /// Default: `2` and `3`.
+    pub fn with_min_lengths(mut self, min_prefix: usize, min_suffix: usize) -> Self {
+        self.min_prefix = min_prefix;
+        self.min_suffix = min_suffix;
+        self.update_hash();
+        self
+    }
+
+    /// Hash the patterns, exceptions and min lengths, in an order that doesn't depend on the hash maps.
+    fn update_hash(&mut self) {
+        let mut patterns: Vec<_> = self.patterns.iter().collect();
+        patterns.sort();
+        let mut exceptions: Vec<_> = self.exceptions.iter().collect();
+        exceptions.sort();
+        self.hash = crate::util::hash((patterns, exceptions, self.min_prefix, self.min_suffix));
+    }
+
+    /// Where can this word be hyphenated?
+    ///
+    /// Returns the byte offsets into `word` where a hyphen can be inserted.
+    pub fn hyphenation_points(&self, word: &str) -> Vec<usize> {
+        let chars: Vec<char> = word.chars().map(lowercase).collect();
+        let num_chars = chars.len();
+        if num_chars < self.min_prefix + self.min_suffix {
+            return vec![];
+        }
+
+        let char_indices: Vec<usize> =
+            if let Some(points) = self.exceptions.get(&chars.iter().collect::<String>()) {
+                points.clone()
+            } else {
+                // `values[i]` is the value before `dotted[i]`:
+                let dotted: Vec<char> = std::iter::once('.')
+                    .chain(chars)
+                    .chain(std::iter::once('.'))
+                    .collect();
+                let mut values = vec![0; dotted.len() + 1];
+                let mut key = String::new();
+                for start in 0..dotted.len() {
+                    key.clear();
+                    for &c in dotted.iter().skip(start).take(self.max_pattern_len) {
+                        key.push(c);
+                        if let Some(pattern) = self.patterns.get(&key) {
+                            for (value, &pattern_value) in values[start..].iter_mut().zip(pattern) {
+                                *value = pattern_value.max(*value);
+                            }
+                        }
+                    }
+                }
+
+                // Odd values are hyphenation points:
+                (self.min_prefix..=num_chars - self.min_suffix)
+                    .filter(|&i| values[i + 1] % 2 == 1)
+                    .collect()
+            };
+
+        word.char_indices()
+            .enumerate()
+            .filter(|(char_index, _)| char_indices.contains(char_index))
+            .map(|(_, (byte_index, _))| byte_index)
+            .collect()
+    }
+}
+
+impl PartialEq for Hyphenator {
+    fn eq(&self, other: &Self) -> bool {
+        // Comparing the hashes first makes this fast when they differ:
+        self.hash == other.hash
+            && self.min_prefix == other.min_prefix
+            && self.min_suffix == other.min_suffix
+            && self.patterns == other.patterns
+            && self.exceptions == other.exceptions
+    }
+}
+
+impl Eq for Hyphenator {}
+
+impl std::hash::Hash for Hyphenator {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+/// Lowercase a char as a single char, so that the char indices of a word stay the same.
+///
+/// Used for both the exceptions and the words, so that they match.
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hyphenator;
+
+    #[test]
+    fn test_exceptions_match_words_with_multi_char_lowercase() {
+        // 'İ' lowercases to two chars, "i̇":
+        let hyphenator = Hyphenator::from_patterns("").with_exceptions("İs-tan-bul");
+        assert_eq!(hyphenator.hyphenation_points("İstanbul"), vec![3, 6]);
+        assert_eq!(hyphenator.hyphenation_points("istanbul"), vec![2, 5]);
+    }
+
+    #[test]
+    fn test_equality_compares_contents() {
+        let patterns = "hy3ph he2n 1na";
+        let hyphenator = Hyphenator::from_patterns(patterns).with_exceptions("ta-ble");
+        assert_eq!(
+            hyphenator,
+            Hyphenator::from_patterns(patterns)
+                .with_exceptions("")
+                .with_exceptions("ta-ble")
+        );
+        assert_ne!(
+            hyphenator,
+            Hyphenator::from_patterns(patterns).with_exceptions("tab-le")
+        );
+        assert_ne!(
+            hyphenator,
+            Hyphenator::from_patterns(patterns)
+                .with_exceptions("ta-ble")
+                .with_min_lengths(1, 1)
+        );
+    }
+}
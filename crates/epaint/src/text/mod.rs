@@ -1,8 +1,15 @@
 //! Everything related to text, fonts, text layout, cursors etc.
 
+mod bidi;
+#[cfg(feature = "color_fonts")]
+mod color_glyph;
 pub mod cursor;
 mod font;
 mod fonts;
+mod hyphenation;
+mod script;
+#[cfg(feature = "text_shaping")]
+mod shaping;
 mod text_layout;
 mod text_layout_types;
 
@@ -11,9 +18,11 @@ pub const TAB_SIZE: usize = 4;
 
 pub use {
     fonts::{
-        FontData, FontDefinitions, FontFamily, FontId, FontInsert, FontPriority, FontTweak, Fonts,
-        FontsImpl, FontsView, InsertFontFamily,
+        FontData, FontDefinitions, FontFamily, FontId, FontInsert, FontLoader, FontPriority,
+        FontTweak, Fonts, FontsImpl, FontsView, InsertFontFamily,
     },
+    hyphenation::{Hyphenation, Hyphenator, SOFT_HYPHEN},
+    script::Script,
     text_layout::*,
     text_layout_types::*,
 };
@@ -0,0 +1,117 @@
+//! Which Unicode script a character belongs to, for picking fallback fonts per script.
+//!
+//! See [`crate::text::FontDefinitions::script_fallbacks`].
+
+use unicode_script::UnicodeScript as _;
+
+/// A Unicode script (writing system), used to pick fallback fonts.
+///
+/// Only the scripts that usually need a dedicated font are listed here.
+/// Characters that are shared between scripts (digits, punctuation, emojis, …)
+/// don't belong to any script.
+///
+/// See [`crate::text::FontDefinitions::script_fallbacks`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Georgian,
+    Hebrew,
+    Arabic,
+    Syriac,
+    Thaana,
+    Devanagari,
+    Bengali,
+    Gurmukhi,
+    Gujarati,
+    Oriya,
+    Tamil,
+    Telugu,
+    Kannada,
+    Malayalam,
+    Sinhala,
+    Thai,
+    Lao,
+    Tibetan,
+    Myanmar,
+    Khmer,
+    Mongolian,
+    Ethiopic,
+    Cherokee,
+
+    /// Chinese characters, also used in Japanese (kanji) and Korean (hanja).
+    Han,
+    Hiragana,
+    Katakana,
+    Bopomofo,
+
+    /// Korean.
+    Hangul,
+}
+
+impl Script {
+    /// Which script does this character belong to?
+    ///
+    /// Returns `None` for characters that are shared between scripts,
+    /// and for scripts that are not listed in [`Script`].
+    pub fn of(c: char) -> Option<Self> {
+        use unicode_script::Script as S;
+
+        Some(match c.script() {
+            S::Latin => Self::Latin,
+            S::Greek => Self::Greek,
+            S::Cyrillic => Self::Cyrillic,
+            S::Armenian => Self::Armenian,
+            S::Georgian => Self::Georgian,
+            S::Hebrew => Self::Hebrew,
+            S::Arabic => Self::Arabic,
+            S::Syriac => Self::Syriac,
+            S::Thaana => Self::Thaana,
+            S::Devanagari => Self::Devanagari,
+            S::Bengali => Self::Bengali,
+            S::Gurmukhi => Self::Gurmukhi,
+            S::Gujarati => Self::Gujarati,
+            S::Oriya => Self::Oriya,
+            S::Tamil => Self::Tamil,
+            S::Telugu => Self::Telugu,
+            S::Kannada => Self::Kannada,
+            S::Malayalam => Self::Malayalam,
+            S::Sinhala => Self::Sinhala,
+            S::Thai => Self::Thai,
+            S::Lao => Self::Lao,
+            S::Tibetan => Self::Tibetan,
+            S::Myanmar => Self::Myanmar,
+            S::Khmer => Self::Khmer,
+            S::Mongolian => Self::Mongolian,
+            S::Ethiopic => Self::Ethiopic,
+            S::Cherokee => Self::Cherokee,
+            S::Han => Self::Han,
+            S::Hiragana => Self::Hiragana,
+            S::Katakana => Self::Katakana,
+            S::Bopomofo => Self::Bopomofo,
+            S::Hangul => Self::Hangul,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_of() {
+        assert_eq!(Script::of('a'), Some(Script::Latin));
+        assert_eq!(Script::of('Ж'), Some(Script::Cyrillic));
+        assert_eq!(Script::of('ש'), Some(Script::Hebrew));
+        assert_eq!(Script::of('漢'), Some(Script::Han));
+        assert_eq!(Script::of('か'), Some(Script::Hiragana));
+        assert_eq!(Script::of('한'), Some(Script::Hangul));
+        assert_eq!(Script::of('1'), None);
+        assert_eq!(Script::of(' '), None);
+        assert_eq!(Script::of('😀'), None);
+    }
+}
@@ -0,0 +1,140 @@
+//! OpenType text shaping (GSUB/GPOS), using [`rustybuzz`].
+//!
+//! Shaping turns a run of characters into positioned glyphs, applying ligatures,
+//! contextual forms (e.g. Arabic joining), mark positioning and kerning.
+//!
+//! The result is grouped into clusters: a cluster maps one or more characters
+//! to one or more glyphs, and can't be split up during layout.
+//!
+//! Only used with the `text_shaping` feature.
+
+use std::{ops::Range, sync::Arc};
+
+use emath::{Vec2, vec2};
+
+use super::{FontData, FontFeature};
+
+pub(super) use face::ShapingFace;
+
+mod face {
+    #![expect(clippy::mem_forget)] // Used inside of `self_cell!`
+
+    use std::sync::Arc;
+
+    use super::FontData;
+
+    type Face<'a> = rustybuzz::Face<'a>;
+
+    self_cell::self_cell!(
+        /// A font file, parsed once so that it is ready for shaping.
+        pub(crate) struct ShapingFace {
+            owner: Arc<FontData>,
+
+            #[covariant]
+            dependent: Face,
+        }
+    );
+}
+
+impl ShapingFace {
+    /// Returns `None` if [`rustybuzz`] can't parse the font.
+    pub(super) fn parse(font_data: Arc<FontData>) -> Option<Self> {
+        Self::try_new(font_data, |font_data| {
+            rustybuzz::Face::from_slice(&font_data.font, font_data.index).ok_or(())
+        })
+        .ok()
+    }
+}
+
+/// A group of characters that are shaped into one or more glyphs.
+#[derive(Clone, Debug)]
+pub(super) struct ShapedCluster {
+    /// The characters of this cluster, as a byte range into the shaped text.
+    pub byte_range: Range<usize>,
+
+    /// The glyphs of this cluster, in visual order,
+    /// with their offsets from the start of the cluster.
+    ///
+    /// Unit: font units ("unscaled"), with y pointing down.
+    pub glyphs: Vec<(ab_glyph::GlyphId, Vec2)>,
+
+    /// How far to advance after this cluster.
+    ///
+    /// Unit: font units ("unscaled").
+    pub advance_width_unscaled: f32,
+}
+
+/// Shape a run of text that uses a single font and has a single direction.
+///
+/// The returned clusters are in logical order, i.e. the order of the characters in `text`.
+pub(super) fn shape(
+    face: &ShapingFace,
+    text: &str,
+    rtl: bool,
+    features: &[FontFeature],
+) -> Vec<ShapedCluster> {
+    profiling::function_scope!();
+
+    let mut buffer = rustybuzz::UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    buffer.set_direction(if rtl {
+        rustybuzz::Direction::RightToLeft
+    } else {
+        rustybuzz::Direction::LeftToRight
+    });
+
+    let features: Vec<rustybuzz::Feature> = features
+        .iter()
+        .map(|feature| {
+            let tag = rustybuzz::ttf_parser::Tag::from_bytes(&feature.tag);
+            rustybuzz::Feature::new(tag, feature.value, ..)
+        })
+        .collect();
+
+    let output = rustybuzz::shape(face.borrow_dependent(), &features, buffer);
+
+    // Group the glyphs (which are in visual order) by cluster:
+    let mut clusters: Vec<ShapedCluster> = vec![];
+    let mut pen_x = 0;
+    for (info, pos) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+        let cluster_start = info.cluster as usize;
+        let cluster = match clusters.last_mut() {
+            Some(cluster) if cluster.byte_range.start == cluster_start => cluster,
+            _ => {
+                pen_x = 0;
+                clusters.push(ShapedCluster {
+                    byte_range: cluster_start..cluster_start,
+                    glyphs: vec![],
+                    advance_width_unscaled: 0.0,
+                });
+                clusters.last_mut().unwrap()
+            }
+        };
+
+        let offset = vec2((pen_x + pos.x_offset) as f32, -pos.y_offset as f32);
+        cluster
+            .glyphs
+            .push((ab_glyph::GlyphId(info.glyph_id as u16), offset));
+        cluster.advance_width_unscaled += pos.x_advance as f32;
+        pen_x += pos.x_advance;
+    }
+
+    if rtl {
+        clusters.reverse();
+    }
+
+    // Characters that were removed by the shaper belong to the cluster before them:
+    if let Some(first) = clusters.first_mut() {
+        first.byte_range.start = 0;
+    }
+
+    // Each cluster ends where the next one starts:
+    let mut end = text.len();
+    for cluster in clusters.iter_mut().rev() {
+        cluster.byte_range.end = end;
+        end = cluster.byte_range.start;
+    }
+
+    clusters
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{borrow::Cow, sync::Arc};
 
 use emath::{Align, GuiRounding as _, NumExt as _, Pos2, Rect, Vec2, pos2, vec2};
 
@@ -6,12 +6,20 @@ use crate::{
     Color32, Mesh, Stroke, Vertex,
     stroke::PathStroke,
     text::{
+        bidi::{self, BidiLevels},
         font::{ScaledMetrics, is_cjk, is_cjk_break_allowed},
         fonts::FontFaceKey,
+        hyphenation::{Hyphenation, SOFT_HYPHEN},
     },
 };
 
-use super::{FontsImpl, Galley, Glyph, LayoutJob, LayoutSection, PlacedRow, Row, RowVisuals};
+#[cfg(feature = "text_shaping")]
+use crate::text::font::{Font, is_joiner};
+
+use super::{
+    FontsImpl, Galley, Glyph, LayoutJob, LayoutSection, PlacedRow, Row, RowVisuals, TabAlign,
+    TabStop, TextWrapping,
+};
 
 // ----------------------------------------------------------------------------
 
@@ -58,15 +66,19 @@ struct Paragraph {
 
     /// In case of an empty paragraph ("\n"), use this as height.
     pub empty_paragraph_height: f32,
+
+    /// The base bidirectional level of the paragraph: `1` for right-to-left paragraphs.
+    pub bidi_level: u8,
 }
 
 impl Paragraph {
-    pub fn from_section_index(section_index_at_start: u32) -> Self {
+    pub fn from_section_index(section_index_at_start: u32, bidi_level: u8) -> Self {
         Self {
             cursor_x_px: 0.0,
             section_index_at_start,
             glyphs: vec![],
             empty_paragraph_height: 0.0,
+            bidi_level,
         }
     }
 }
@@ -95,12 +107,18 @@ pub fn layout(fonts: &mut FontsImpl, pixels_per_point: f32, job: Arc<LayoutJob>)
 
     // For most of this we ignore the y coordinate:
 
-    let mut paragraphs = vec![Paragraph::from_section_index(0)];
+    let bidi_levels = BidiLevels::new(&job.text);
+    let first_paragraph_level = bidi_levels
+        .as_ref()
+        .map_or(0, |levels| levels.paragraph_level_at(0));
+
+    let mut paragraphs = vec![Paragraph::from_section_index(0, first_paragraph_level)];
     for (section_index, section) in job.sections.iter().enumerate() {
         layout_section(
             fonts,
             pixels_per_point,
             &job,
+            bidi_levels.as_ref(),
             section_index as u32,
             section,
             &mut paragraphs,
@@ -109,12 +127,19 @@ pub fn layout(fonts: &mut FontsImpl, pixels_per_point: f32, job: Arc<LayoutJob>)
 
     let point_scale = PointScale::new(pixels_per_point);
 
+    if !job.tab_stops.is_empty() {
+        for paragraph in &mut paragraphs {
+            apply_tab_stops(point_scale, &job.tab_stops, &mut paragraph.glyphs, 0.0);
+        }
+    }
+
     let intrinsic_size = calculate_intrinsic_size(point_scale, &job, &paragraphs);
 
     let mut elided = false;
-    let mut rows = rows_from_paragraphs(paragraphs, &job, &mut elided);
+    let mut rows = rows_from_paragraphs(fonts, point_scale, paragraphs, &job, &mut elided);
     if elided && let Some(last_placed) = rows.last_mut() {
         let last_row = Arc::make_mut(&mut last_placed.row);
+        last_row.hyphen = None;
         replace_last_glyph_with_overflow_character(fonts, pixels_per_point, &job, last_row);
         if let Some(last) = last_row.glyphs.last() {
             last_row.size.x = last.max_x();
@@ -134,10 +159,20 @@ pub fn layout(fonts: &mut FontsImpl, pixels_per_point: f32, job: Arc<LayoutJob>)
                 job.halign,
                 job.wrap.max_width,
                 justify_row,
+                job.justify_word_spacing,
             );
         }
     }
 
+    if bidi_levels.is_some() {
+        // Now that we know where the rows start and end, we can put right-to-left runs in visual order:
+        for placed_row in &mut rows {
+            if placed_row.glyphs.iter().any(|glyph| glyph.bidi_level != 0) {
+                bidi::reorder_row(&mut Arc::make_mut(&mut placed_row.row).glyphs);
+            }
+        }
+    }
+
     // Calculate the Y positions and tessellate the text:
     galley_from_rows(point_scale, job, rows, elided, intrinsic_size)
 }
@@ -147,6 +182,7 @@ fn layout_section(
     fonts: &mut FontsImpl,
     pixels_per_point: f32,
     job: &LayoutJob,
+    bidi_levels: Option<&BidiLevels>,
     section_index: u32,
     section: &LayoutSection,
     out_paragraphs: &mut Vec<Paragraph>,
@@ -155,6 +191,7 @@ fn layout_section(
         leading_space,
         byte_range,
         format,
+        inline_box,
     } = section;
     let mut font = fonts.font(&format.font_id.family);
     let font_size = format.font_id.size;
@@ -178,13 +215,72 @@ fn layout_section(
     let mut current_font = FontFaceKey::INVALID;
     let mut current_font_impl_metrics = ScaledMetrics::default();
 
-    for chr in job.text[byte_range.clone()].chars() {
+    // Characters before this byte index have already been laid out as part of a shaped run.
+    #[cfg(feature = "text_shaping")]
+    let mut shaped_until = 0;
+
+    for (byte_offset, chr) in job.text[byte_range.clone()].char_indices() {
+        let byte_index = byte_range.start + byte_offset;
+
+        #[cfg(feature = "text_shaping")]
+        if byte_index < shaped_until {
+            continue;
+        }
+
         if job.break_on_newline && chr == '\n' {
-            out_paragraphs.push(Paragraph::from_section_index(section_index));
+            let bidi_level =
+                bidi_levels.map_or(0, |levels| levels.paragraph_level_at(byte_index + 1));
+            out_paragraphs.push(Paragraph::from_section_index(section_index, bidi_level));
             paragraph = out_paragraphs.last_mut().unwrap();
             paragraph.empty_paragraph_height = line_height; // TODO(emilk): replace this hack with actually including `\n` in the glyphs?
+        } else if let Some(inline_box) = inline_box {
+            let bidi_level = bidi_levels.map_or(0, |levels| levels.level_at(byte_index));
+
+            // Make room below the baseline for the descent of the font, like the text around it has:
+            let descent =
+                (line_height - font_metrics.ascent).max(inline_box.size.y - inline_box.baseline);
+            let height = inline_box.baseline + descent;
+
+            paragraph.glyphs.push(Glyph {
+                chr,
+                pos: pos2(paragraph.cursor_x_px / pixels_per_point, f32::NAN),
+                advance_width: inline_box.size.x,
+                line_height: height,
+                font_impl_height: height,
+                font_impl_ascent: inline_box.baseline,
+                font_height: height,
+                font_ascent: inline_box.baseline,
+                uv_rect: Default::default(),
+                color_uv_rect: Default::default(),
+                section_index,
+                bidi_level,
+                cluster_continuation: false,
+            });
+
+            paragraph.cursor_x_px += inline_box.size.x * pixels_per_point;
+            last_glyph_id = None;
         } else {
-            let (font_id, glyph_info) = font.glyph_info(chr);
+            let bidi_level = bidi_levels.map_or(0, |levels| levels.level_at(byte_index));
+
+            // Brackets etc are mirrored in right-to-left text:
+            let glyph_chr = if bidi_level % 2 == 1 {
+                bidi::mirrored(chr).unwrap_or(chr)
+            } else {
+                chr
+            };
+
+            let (font_id, glyph_info) = font.glyph_info(glyph_chr);
+
+            #[cfg(feature = "text_shaping")]
+            let shaping_run_end = shaping_run_end(
+                &mut font,
+                job,
+                bidi_levels,
+                font_id,
+                byte_index,
+                byte_range.end,
+            );
+
             let mut font_impl = font.fonts_by_id.get_mut(&font_id);
             if current_font != font_id {
                 current_font = font_id;
@@ -194,6 +290,67 @@ fn layout_section(
                     .unwrap_or_default();
             }
 
+            #[cfg(feature = "text_shaping")]
+            if let (Some(font_impl), Some(run_end)) = (font_impl.as_mut(), shaping_run_end) {
+                let run = &job.text[byte_index..run_end];
+                for cluster in font_impl.shape(run, bidi_level % 2 == 1, &format.font_features) {
+                    if last_glyph_id.is_some() {
+                        // Only apply extra_letter_spacing to glyphs after the first one:
+                        paragraph.cursor_x_px += extra_letter_spacing * pixels_per_point;
+                    }
+
+                    let cluster_text = &run[cluster.byte_range.clone()];
+                    let mut cluster_chars = cluster_text.chars();
+                    let Some(first_chr) = cluster_chars.next() else {
+                        continue;
+                    };
+
+                    let (glyph_alloc, physical_x) = font_impl.allocate_cluster(
+                        font.atlas,
+                        &current_font_impl_metrics,
+                        &cluster.glyphs,
+                        cluster.advance_width_unscaled,
+                        first_chr,
+                        paragraph.cursor_x_px,
+                    );
+
+                    let glyph = Glyph {
+                        chr: first_chr,
+                        pos: pos2(physical_x as f32 / pixels_per_point, f32::NAN),
+                        advance_width: glyph_alloc.advance_width_px / pixels_per_point,
+                        line_height,
+                        font_impl_height: current_font_impl_metrics.row_height,
+                        font_impl_ascent: current_font_impl_metrics.ascent,
+                        font_height: font_metrics.row_height,
+                        font_ascent: font_metrics.ascent,
+                        uv_rect: glyph_alloc.uv_rect,
+                        color_uv_rect: glyph_alloc.color_uv_rect,
+                        section_index,
+                        bidi_level,
+                        cluster_continuation: false,
+                    };
+                    paragraph.glyphs.push(glyph);
+
+                    // The rest of the characters in the cluster get zero-width glyphs after it:
+                    for chr in cluster_chars {
+                        paragraph.glyphs.push(Glyph {
+                            chr,
+                            pos: pos2(glyph.max_x(), f32::NAN),
+                            advance_width: 0.0,
+                            uv_rect: Default::default(),
+                            color_uv_rect: Default::default(),
+                            cluster_continuation: true,
+                            ..glyph
+                        });
+                    }
+
+                    paragraph.cursor_x_px += glyph_alloc.advance_width_px;
+                    last_glyph_id = Some(glyph_alloc.id);
+                }
+                shaped_until = run_end;
+                continue;
+            }
+
             if let (Some(font_impl), Some(last_glyph_id), Some(glyph_id)) =
                 (&font_impl, last_glyph_id, glyph_info.id)
             {
@@ -212,7 +369,7 @@ fn layout_section(
                     font.atlas,
                     &current_font_impl_metrics,
                     glyph_info,
-                    chr,
+                    glyph_chr,
                     paragraph.cursor_x_px,
                 )
             } else {
@@ -229,7 +386,10 @@ fn layout_section(
                 font_height: font_metrics.row_height,
                 font_ascent: font_metrics.ascent,
                 uv_rect: glyph_alloc.uv_rect,
+                color_uv_rect: glyph_alloc.color_uv_rect,
                 section_index,
+                bidi_level,
+                cluster_continuation: false,
             });
 
             paragraph.cursor_x_px += glyph_alloc.advance_width_px;
@@ -238,6 +398,45 @@ fn layout_section(
     }
 }
 
+/// Find the end of the run of text starting at `start` that can be shaped together.
+///
+/// A run uses a single font, has a single bidi level and doesn't cross a newline.
+/// Returns `None` if the character at `start` should not be shaped.
+#[cfg(feature = "text_shaping")]
+fn shaping_run_end(
+    font: &mut Font<'_>,
+    job: &LayoutJob,
+    bidi_levels: Option<&BidiLevels>,
+    font_id: FontFaceKey,
+    start: usize,
+    end: usize,
+) -> Option<usize> {
+    let bidi_level = bidi_levels.map_or(0, |levels| levels.level_at(start));
+    let font_impl = font.fonts_by_id.get_mut(&font_id)?;
+
+    let mut run_end = start;
+    for (byte_offset, chr) in job.text[start..end].char_indices() {
+        let byte_index = start + byte_offset;
+        let same_run = !(job.break_on_newline && chr == '\n')
+            && bidi_levels.map_or(0, |levels| levels.level_at(byte_index)) == bidi_level
+            && font_impl.can_shape(chr);
+        if !same_run {
+            break;
+        }
+        run_end = byte_index + chr.len_utf8();
+    }
+
+    // Check that the run actually uses this font, and not a fallback:
+    let run = &job.text[start..run_end];
+    let run_end = start
+        + run
+            .char_indices()
+            .find(|&(_, chr)| !is_joiner(chr) && font.glyph_info(chr).0 != font_id)
+            .map_or(run.len(), |(byte_offset, _)| byte_offset);
+
+    (start < run_end).then_some(run_end)
+}
+
 /// Calculate the intrinsic size of the text.
 ///
 /// The result is eventually passed to `Response::intrinsic_size`.
@@ -272,6 +471,8 @@ fn calculate_intrinsic_size(
 
 // Ignores the Y coordinate.
 fn rows_from_paragraphs(
+    fonts: &mut FontsImpl,
+    point_scale: PointScale,
     paragraphs: Vec<Paragraph>,
     job: &LayoutJob,
     elided: &mut bool,
@@ -280,7 +481,7 @@ fn rows_from_paragraphs(
 
     let mut rows = vec![];
 
-    for (i, paragraph) in paragraphs.into_iter().enumerate() {
+    for (i, mut paragraph) in paragraphs.into_iter().enumerate() {
         if job.wrap.max_rows <= rows.len() {
             *elided = true;
             break;
@@ -294,6 +495,7 @@ fn rows_from_paragraphs(
                 row: Arc::new(Row {
                     section_index_at_start: paragraph.section_index_at_start,
                     glyphs: vec![],
+                    hyphen: None,
                     visuals: Default::default(),
                     size: vec2(0.0, paragraph.empty_paragraph_height),
                     ends_with_newline: !is_last_paragraph,
@@ -303,18 +505,21 @@ fn rows_from_paragraphs(
             let paragraph_max_x = paragraph.glyphs.last().unwrap().max_x();
             if paragraph_max_x <= job.effective_wrap_width() {
                 // Early-out optimization: the whole paragraph fits on one row.
+                let mut glyphs = paragraph.glyphs;
+                bidi::reset_whitespace_levels(&mut glyphs, paragraph.bidi_level);
                 rows.push(PlacedRow {
                     pos: pos2(0.0, f32::NAN),
                     row: Arc::new(Row {
                         section_index_at_start: paragraph.section_index_at_start,
-                        glyphs: paragraph.glyphs,
+                        glyphs,
+                        hyphen: None,
                         visuals: Default::default(),
                         size: vec2(paragraph_max_x, 0.0),
                         ends_with_newline: !is_last_paragraph,
                     }),
                 });
             } else {
-                line_break(&paragraph, job, &mut rows, elided);
+                line_break(fonts, point_scale, &mut paragraph, job, &mut rows, elided);
                 let placed_row = rows.last_mut().unwrap();
                 let row = Arc::make_mut(&mut placed_row.row);
                 row.ends_with_newline = !is_last_paragraph;
@@ -326,15 +531,19 @@ fn rows_from_paragraphs(
 }
 
 fn line_break(
-    paragraph: &Paragraph,
+    fonts: &mut FontsImpl,
+    point_scale: PointScale,
+    paragraph: &mut Paragraph,
     job: &LayoutJob,
     out_rows: &mut Vec<PlacedRow>,
     elided: &mut bool,
 ) {
+    let pixels_per_point = point_scale.pixels_per_point();
     let wrap_width = job.effective_wrap_width();
 
     // Keeps track of good places to insert row break if we exceed `wrap_width`.
     let mut row_break_candidates = RowBreakCandidates::default();
+    let break_opportunities = BreakOpportunities::new(&paragraph.glyphs, &job.wrap);
 
     let mut first_row_indentation = paragraph.glyphs[0].pos.x;
     let mut row_start_x = 0.0;
@@ -361,6 +570,7 @@ fn line_break(
                     row: Arc::new(Row {
                         section_index_at_start: paragraph.section_index_at_start,
                         glyphs: vec![],
+                        hyphen: None,
                         visuals: Default::default(),
                         size: Vec2::ZERO,
                         ends_with_newline: false,
@@ -370,7 +580,7 @@ fn line_break(
                 first_row_indentation = 0.0;
             } else if let Some(last_kept_index) = row_break_candidates.get(job.wrap.break_anywhere)
             {
-                let glyphs: Vec<Glyph> = paragraph.glyphs[row_start_idx..=last_kept_index]
+                let mut glyphs: Vec<Glyph> = paragraph.glyphs[row_start_idx..=last_kept_index]
                     .iter()
                     .copied()
                     .map(|mut glyph| {
@@ -378,15 +588,21 @@ fn line_break(
                         glyph
                     })
                     .collect();
+                bidi::reset_whitespace_levels(&mut glyphs, paragraph.bidi_level);
 
                 let section_index_at_start = glyphs[0].section_index;
-                let paragraph_max_x = glyphs.last().unwrap().max_x();
+                let last_glyph = glyphs.last().unwrap();
+                let hyphen = row_break_candidates
+                    .is_hyphen(last_kept_index)
+                    .then(|| hyphen_glyph(fonts, pixels_per_point, job, last_glyph));
+                let paragraph_max_x = hyphen.as_ref().unwrap_or(last_glyph).max_x();
 
                 out_rows.push(PlacedRow {
                     pos: pos2(0.0, f32::NAN),
                     row: Arc::new(Row {
                         section_index_at_start,
                         glyphs,
+                        hyphen,
                         visuals: Default::default(),
                         size: vec2(paragraph_max_x, 0.0),
                         ends_with_newline: false,
@@ -397,12 +613,48 @@ fn line_break(
                 row_start_idx = last_kept_index + 1;
                 row_start_x = paragraph.glyphs[row_start_idx].pos.x;
                 row_break_candidates.forget_before_idx(row_start_idx);
+
+                if !job.tab_stops.is_empty() {
+                    // Tab stops are relative to the start of the row:
+                    apply_tab_stops(
+                        point_scale,
+                        &job.tab_stops,
+                        &mut paragraph.glyphs[row_start_idx..],
+                        row_start_x,
+                    );
+                }
+
+                // A hyphen that didn't fit on the previous row may fit on this one:
+                row_break_candidates.hyphen = (row_start_idx..i).rev().find(|&j| {
+                    hyphen_fits(
+                        fonts,
+                        pixels_per_point,
+                        job,
+                        paragraph,
+                        &break_opportunities,
+                        j,
+                        row_start_x,
+                        wrap_width,
+                    )
+                });
             } else {
                 // Found no place to break, so we have to overrun wrap_width.
             }
         }
 
-        row_break_candidates.add(i, &paragraph.glyphs[i..]);
+        row_break_candidates.add(i, &paragraph.glyphs[i..], &break_opportunities);
+        if hyphen_fits(
+            fonts,
+            pixels_per_point,
+            job,
+            paragraph,
+            &break_opportunities,
+            i,
+            row_start_x,
+            wrap_width,
+        ) {
+            row_break_candidates.hyphen = Some(i);
+        }
     }
 
     if row_start_idx < paragraph.glyphs.len() {
@@ -411,7 +663,7 @@ fn line_break(
         if job.wrap.max_rows <= out_rows.len() {
             *elided = true; // can't fit another row
         } else {
-            let glyphs: Vec<Glyph> = paragraph.glyphs[row_start_idx..]
+            let mut glyphs: Vec<Glyph> = paragraph.glyphs[row_start_idx..]
                 .iter()
                 .copied()
                 .map(|mut glyph| {
@@ -419,6 +671,7 @@ fn line_break(
                     glyph
                 })
                 .collect();
+            bidi::reset_whitespace_levels(&mut glyphs, paragraph.bidi_level);
 
             let section_index_at_start = glyphs[0].section_index;
             let paragraph_min_x = glyphs[0].pos.x;
@@ -429,6 +682,7 @@ fn line_break(
                 row: Arc::new(Row {
                     section_index_at_start,
                     glyphs,
+                    hyphen: None,
                     visuals: Default::default(),
                     size: vec2(paragraph_max_x - paragraph_min_x, 0.0),
                     ends_with_newline: false,
@@ -438,6 +692,134 @@ fn line_break(
     }
 }
 
+/// Move the text after each `\t` in `glyphs` to its [`TabStop`] by changing the width of the tab.
+///
+/// The tab stops are relative to `row_start_x`.
+fn apply_tab_stops(
+    point_scale: PointScale,
+    tab_stops: &[TabStop],
+    glyphs: &mut [Glyph],
+    row_start_x: f32,
+) {
+    for tab_index in 0..glyphs.len() {
+        if glyphs[tab_index].chr != '\t' {
+            continue;
+        }
+
+        let tab = &glyphs[tab_index];
+        let Some(tab_stop) = tab_stops
+            .iter()
+            .find(|tab_stop| tab.pos.x < row_start_x + tab_stop.x)
+        else {
+            return; // Past the last tab stop, so the rest of the tabs keep their width.
+        };
+        let tab_stop_x = row_start_x + tab_stop.x;
+
+        let text_start = tab_index + 1;
+        let text_end = glyphs[text_start..]
+            .iter()
+            .position(|glyph| glyph.chr == '\t')
+            .map_or(glyphs.len(), |len| text_start + len);
+        let text = &glyphs[text_start..text_end];
+        let Some(first) = text.first() else {
+            continue; // Nothing to align
+        };
+
+        let right_aligned =
+            |text: &[Glyph]| tab_stop_x - text.last().map_or(0.0, |last| last.max_x());
+        let offset = match tab_stop.align {
+            TabAlign::Left => tab_stop_x - first.pos.x,
+            TabAlign::Right => right_aligned(text),
+            TabAlign::Decimal(separator) => text
+                .iter()
+                .find(|glyph| glyph.chr == separator)
+                .map_or_else(|| right_aligned(text), |glyph| tab_stop_x - glyph.pos.x),
+        };
+
+        // The tab can't get narrower than nothing:
+        let offset = point_scale
+            .round_to_pixel(offset)
+            .at_least(-glyphs[tab_index].advance_width);
+
+        glyphs[tab_index].advance_width += offset;
+        for glyph in &mut glyphs[text_start..] {
+            glyph.pos.x += offset;
+        }
+    }
+}
+
+/// Can we hyphenate after glyph `index`, with the hyphen fitting on the row starting at `row_start_x`?
+#[expect(clippy::too_many_arguments)]
+fn hyphen_fits(
+    fonts: &mut FontsImpl,
+    pixels_per_point: f32,
+    job: &LayoutJob,
+    paragraph: &Paragraph,
+    break_opportunities: &BreakOpportunities,
+    index: usize,
+    row_start_x: f32,
+    wrap_width: f32,
+) -> bool {
+    break_opportunities.is_hyphenation_point(index) && {
+        let hyphen = hyphen_glyph(fonts, pixels_per_point, job, &paragraph.glyphs[index]);
+        hyphen.max_x() - row_start_x <= wrap_width
+    }
+}
+
+/// The hyphen to show after `last_glyph` when a word is hyphenated there.
+///
+/// Called before we have any Y coordinates.
+fn hyphen_glyph(
+    fonts: &mut FontsImpl,
+    pixels_per_point: f32,
+    job: &LayoutJob,
+    last_glyph: &Glyph,
+) -> Glyph {
+    const HYPHEN: char = '-';
+
+    let section = &job.sections[last_glyph.section_index as usize];
+    let font_size = section.format.font_id.size;
+    let mut font = fonts.font(&section.format.font_id.family);
+    let font_metrics = font.scaled_metrics(pixels_per_point, font_size);
+
+    let (font_id, glyph_info) = font.glyph_info(HYPHEN);
+    let x = last_glyph.max_x() + section.format.extra_letter_spacing;
+    let (font_impl_metrics, (glyph_alloc, physical_x)) = font
+        .fonts_by_id
+        .get_mut(&font_id)
+        .map(|font_impl| {
+            let metrics = font_impl.scaled_metrics(pixels_per_point, font_size);
+            let allocation = font_impl.allocate_glyph(
+                font.atlas,
+                &metrics,
+                glyph_info,
+                HYPHEN,
+                x * pixels_per_point,
+            );
+            (metrics, allocation)
+        })
+        .unwrap_or_default();
+
+    Glyph {
+        chr: HYPHEN,
+        pos: pos2(physical_x as f32 / pixels_per_point, f32::NAN),
+        advance_width: glyph_alloc.advance_width_px / pixels_per_point,
+        line_height: section
+            .format
+            .line_height
+            .unwrap_or(font_metrics.row_height),
+        font_impl_height: font_impl_metrics.row_height,
+        font_impl_ascent: font_impl_metrics.ascent,
+        font_height: font_metrics.row_height,
+        font_ascent: font_metrics.ascent,
+        uv_rect: glyph_alloc.uv_rect,
+        color_uv_rect: glyph_alloc.color_uv_rect,
+        section_index: last_glyph.section_index,
+        bidi_level: last_glyph.bidi_level,
+        cluster_continuation: false,
+    }
+}
+
 /// Trims the last glyphs in the row and replaces it with an overflow character (e.g. `…`).
 ///
 /// Called before we have any Y coordinates.
@@ -456,6 +838,7 @@ fn replace_last_glyph_with_overflow_character(
         .last()
         .map(|g| g.section_index)
         .unwrap_or(row.section_index_at_start);
+    let bidi_level = row.glyphs.last().map_or(0, |g| g.bidi_level);
     loop {
         let section = &job.sections[section_index as usize];
         let extra_letter_spacing = section.format.extra_letter_spacing;
@@ -531,12 +914,22 @@ fn replace_last_glyph_with_overflow_character(
                 font_height: font_metrics.row_height,
                 font_ascent: font_metrics.ascent,
                 uv_rect: replacement_glyph_alloc.uv_rect,
+                color_uv_rect: replacement_glyph_alloc.color_uv_rect,
                 section_index,
+                bidi_level,
+                cluster_continuation: false,
             });
             return;
         }
 
-        // We didn't fit - pop the last glyph and try again.
+        // We didn't fit - pop the last glyph (or cluster) and try again.
+        while row
+            .glyphs
+            .last()
+            .is_some_and(|glyph| glyph.cluster_continuation)
+        {
+            row.glyphs.pop();
+        }
         if let Some(last_glyph) = row.glyphs.pop() {
             section_index = last_glyph.section_index;
         } else {
@@ -554,9 +947,8 @@ fn halign_and_justify_row(
     halign: Align,
     wrap_width: f32,
     justify: bool,
+    justify_word_spacing: bool,
 ) {
-    #![expect(clippy::useless_let_if_seq)] // False positive
-
     let row = Arc::make_mut(&mut placed_row.row);
 
     if row.glyphs.is_empty() {
@@ -586,10 +978,18 @@ fn halign_and_justify_row(
     assert!(num_glyphs_in_range > 0, "Should have at least one glyph");
 
     let original_min_x = row.glyphs[glyph_range.0].logical_rect().min.x;
-    let original_max_x = row.glyphs[glyph_range.1 - 1].logical_rect().max.x;
+    let original_max_x = row.glyphs[glyph_range.1 - 1].logical_rect().max.x
+        + row.hyphen.map_or(0.0, |hyphen| hyphen.advance_width);
     let original_width = original_max_x - original_min_x;
 
-    let target_width = if justify && num_glyphs_in_range > 1 {
+    // Only stretch the text after the last tab, so that the tab stops stay put:
+    let stretch_start = row.glyphs[glyph_range.0..glyph_range.1]
+        .iter()
+        .rposition(|glyph| glyph.chr == '\t')
+        .map_or(glyph_range.0, |i| glyph_range.0 + i + 1);
+    let num_glyphs_to_stretch = glyph_range.1 - stretch_start;
+
+    let target_width = if justify && num_glyphs_to_stretch > 1 {
         wrap_width
     } else {
         original_width
@@ -601,42 +1001,56 @@ fn halign_and_justify_row(
         Align::RIGHT => (-target_width, 0.0),
     };
 
-    let num_spaces_in_range = row.glyphs[glyph_range.0..glyph_range.1]
+    let num_spaces_to_stretch = row.glyphs[stretch_start..glyph_range.1]
         .iter()
         .filter(|glyph| glyph.chr.is_whitespace())
         .count();
 
-    let mut extra_x_per_glyph = if num_glyphs_in_range == 1 {
-        0.0
+    // Don't contract:
+    let extra_width = (target_width - original_width).at_least(0.0);
+
+    let (extra_x_per_glyph, extra_x_per_space) = if justify_word_spacing {
+        // Stretch the space between words if we can, else the space between all glyphs:
+        if num_spaces_to_stretch > 0 {
+            (0.0, extra_width / num_spaces_to_stretch as f32)
+        } else if num_glyphs_to_stretch > 1 {
+            (extra_width / (num_glyphs_to_stretch as f32 - 1.0), 0.0)
+        } else {
+            (0.0, 0.0)
+        }
+    } else if num_glyphs_to_stretch > 1 {
+        let extra_x_per_glyph = extra_width / (num_glyphs_to_stretch as f32 - 1.0);
+        if 0 < num_spaces_to_stretch && num_spaces_to_stretch < num_glyphs_to_stretch {
+            // Add an integral number of pixels between each glyph,
+            // and add the balance to the spaces:
+            let extra_x_per_glyph = point_scale.floor_to_pixel(extra_x_per_glyph);
+            let extra_x_per_space = (extra_width
+                - extra_x_per_glyph * (num_glyphs_to_stretch as f32 - 1.0))
+                / (num_spaces_to_stretch as f32);
+            (extra_x_per_glyph, extra_x_per_space)
+        } else {
+            (extra_x_per_glyph, 0.0)
+        }
     } else {
-        (target_width - original_width) / (num_glyphs_in_range as f32 - 1.0)
+        (0.0, 0.0)
     };
-    extra_x_per_glyph = extra_x_per_glyph.at_least(0.0); // Don't contract
-
-    let mut extra_x_per_space = 0.0;
-    if 0 < num_spaces_in_range && num_spaces_in_range < num_glyphs_in_range {
-        // Add an integral number of pixels between each glyph,
-        // and add the balance to the spaces:
-
-        extra_x_per_glyph = point_scale.floor_to_pixel(extra_x_per_glyph);
-
-        extra_x_per_space = (target_width
-            - original_width
-            - extra_x_per_glyph * (num_glyphs_in_range as f32 - 1.0))
-            / (num_spaces_in_range as f32);
-    }
 
     placed_row.pos.x = point_scale.round_to_pixel(target_min_x);
-    let mut translate_x = -original_min_x - extra_x_per_glyph * glyph_range.0 as f32;
+    let mut translate_x = -original_min_x;
 
-    for glyph in &mut row.glyphs {
+    for (i, glyph) in row.glyphs.iter_mut().enumerate() {
         glyph.pos.x += translate_x;
         glyph.pos.x = point_scale.round_to_pixel(glyph.pos.x);
-        translate_x += extra_x_per_glyph;
-        if glyph.chr.is_whitespace() {
-            translate_x += extra_x_per_space;
+        if stretch_start <= i {
+            translate_x += extra_x_per_glyph;
+            if glyph.chr.is_whitespace() {
+                translate_x += extra_x_per_space;
+            }
         }
     }
+    if let Some(hyphen) = &mut row.hyphen {
+        hyphen.pos.x = row.glyphs.last().unwrap().max_x();
+    }
 
     // Note we ignore the leading/trailing whitespace here!
     row.size.x = target_max_x - target_min_x;
@@ -664,7 +1078,7 @@ fn galley_from_rows(
         max_row_height = point_scale.round_to_pixel(max_row_height);
 
         // Now position each glyph vertically:
-        for glyph in &mut row.glyphs {
+        for glyph in row.glyphs.iter_mut().chain(&mut row.hyphen) {
             let format = &job.sections[glyph.section_index as usize].format;
 
             glyph.pos.y = glyph.font_impl_ascent
@@ -674,7 +1088,8 @@ fn galley_from_rows(
 
                 // When mixing different `FontImpl` (e.g. latin and emojis),
                 // we always center the difference:
-                + 0.5 * (glyph.font_height - glyph.font_impl_height);
+                + 0.5 * (glyph.font_height - glyph.font_impl_height)
+                - format.baseline_shift;
 
             glyph.pos.y = point_scale.round_to_pixel(glyph.pos.y);
         }
@@ -704,7 +1119,7 @@ fn galley_from_rows(
         num_indices += row.visuals.mesh.indices.len();
 
         row.section_index_at_start = u32::MAX; // No longer in use.
-        for glyph in &mut row.glyphs {
+        for glyph in row.glyphs.iter_mut().chain(&mut row.hyphen) {
             glyph.section_index = u32::MAX; // No longer in use.
         }
     }
@@ -732,6 +1147,7 @@ fn galley_from_rows(
 struct FormatSummary {
     any_background: bool,
     any_underline: bool,
+    any_wavy_underline: bool,
     any_strikethrough: bool,
 }
 
@@ -740,6 +1156,7 @@ fn format_summary(job: &LayoutJob) -> FormatSummary {
     for section in &job.sections {
         format_summary.any_background |= section.format.background != Color32::TRANSPARENT;
         format_summary.any_underline |= section.format.underline != Stroke::NONE;
+        format_summary.any_wavy_underline |= section.format.wavy_underline != Stroke::NONE;
         format_summary.any_strikethrough |= section.format.strikethrough != Stroke::NONE;
     }
     format_summary
@@ -760,17 +1177,32 @@ fn tessellate_row(
     mesh.reserve_triangles(row.glyphs.len() * 2);
     mesh.reserve_vertices(row.glyphs.len() * 4);
 
+    // Backgrounds and lines are merged into runs going from left to right,
+    // so for bidirectional text we need the glyphs in visual order:
+    let visual_glyphs: Cow<'_, [Glyph]> = if row.has_rtl() {
+        let mut glyphs = row.glyphs.clone();
+        glyphs.sort_by(|a, b| a.pos.x.total_cmp(&b.pos.x));
+        Cow::Owned(glyphs)
+    } else {
+        Cow::Borrowed(&row.glyphs)
+    };
+
     if format_summary.any_background {
-        add_row_backgrounds(point_scale, job, row, &mut mesh);
+        add_row_backgrounds(point_scale, job, &visual_glyphs, &mut mesh);
     }
 
     let glyph_index_start = mesh.indices.len();
+
+    // The colored parts of color glyphs are outside of `glyph_vertex_range`,
+    // so that they are not affected by `override_text_color`:
+    tessellate_glyphs(point_scale, job, row, true, &mut mesh);
+
     let glyph_vertex_start = mesh.vertices.len();
-    tessellate_glyphs(point_scale, job, row, &mut mesh);
+    tessellate_glyphs(point_scale, job, row, false, &mut mesh);
     let glyph_vertex_end = mesh.vertices.len();
 
     if format_summary.any_underline {
-        add_row_hline(point_scale, row, &mut mesh, |glyph| {
+        add_row_hline(point_scale, &visual_glyphs, &mut mesh, false, |glyph| {
             let format = &job.sections[glyph.section_index as usize].format;
             let stroke = format.underline;
             let y = glyph.logical_rect().bottom();
@@ -778,8 +1210,19 @@ fn tessellate_row(
         });
     }
 
+    if format_summary.any_wavy_underline {
+        add_row_hline(point_scale, &visual_glyphs, &mut mesh, true, |glyph| {
+            let format = &job.sections[glyph.section_index as usize].format;
+            let stroke = format.wavy_underline;
+            // Keep the waves inside the row:
+            let y =
+                glyph.logical_rect().bottom() - wavy_line_amplitude(stroke) - 0.5 * stroke.width;
+            (stroke, y)
+        });
+    }
+
     if format_summary.any_strikethrough {
-        add_row_hline(point_scale, row, &mut mesh, |glyph| {
+        add_row_hline(point_scale, &visual_glyphs, &mut mesh, false, |glyph| {
             let format = &job.sections[glyph.section_index as usize].format;
             let stroke = format.strikethrough;
             let y = glyph.logical_rect().center().y;
@@ -799,8 +1242,15 @@ fn tessellate_row(
 
 /// Create background for glyphs that have them.
 /// Creates as few rectangular regions as possible.
-fn add_row_backgrounds(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
-    if row.glyphs.is_empty() {
+///
+/// The glyphs must be in visual (left-to-right) order.
+fn add_row_backgrounds(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    glyphs: &[Glyph],
+    mesh: &mut Mesh,
+) {
+    if glyphs.is_empty() {
         return;
     }
 
@@ -816,7 +1266,7 @@ fn add_row_backgrounds(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh
     let mut run_start = None;
     let mut last_rect = Rect::NAN;
 
-    for glyph in &row.glyphs {
+    for glyph in glyphs {
         let format = &job.sections[glyph.section_index as usize].format;
         let color = format.background;
         let rect = glyph.logical_rect();
@@ -844,9 +1294,21 @@ fn add_row_backgrounds(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh
     end_run(run_start.take(), last_rect.right());
 }
 
-fn tessellate_glyphs(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh: &mut Mesh) {
-    for glyph in &row.glyphs {
-        let uv_rect = glyph.uv_rect;
+/// Tessellate either the glyphs painted with the text color,
+/// or the colored parts of color glyphs (e.g. emoji).
+fn tessellate_glyphs(
+    point_scale: PointScale,
+    job: &LayoutJob,
+    row: &Row,
+    color_parts: bool,
+    mesh: &mut Mesh,
+) {
+    for glyph in row.glyphs.iter().chain(&row.hyphen) {
+        let uv_rect = if color_parts {
+            glyph.color_uv_rect
+        } else {
+            glyph.uv_rect
+        };
         if !uv_rect.is_nothing() {
             let mut left_top = glyph.pos + uv_rect.offset;
             left_top.x = point_scale.round_to_pixel(left_top.x);
@@ -860,7 +1322,12 @@ fn tessellate_glyphs(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh:
 
             let format = &job.sections[glyph.section_index as usize].format;
 
-            let color = format.color;
+            // The colored parts already have their colors in the texture:
+            let color = if color_parts {
+                Color32::WHITE
+            } else {
+                format.color
+            };
 
             if format.italics {
                 let idx = mesh.vertices.len() as u32;
@@ -896,11 +1363,21 @@ fn tessellate_glyphs(point_scale: PointScale, job: &LayoutJob, row: &Row, mesh:
     }
 }
 
+/// How far a wavy underline goes above and below its center line.
+fn wavy_line_amplitude(stroke: Stroke) -> f32 {
+    1.0 + stroke.width
+}
+
 /// Add a horizontal line over a row of glyphs with a stroke and y decided by a callback.
+///
+/// If `wavy` is set, the line is a zigzag around `y`, like a spell-checker underline.
+///
+/// The glyphs must be in visual (left-to-right) order.
 fn add_row_hline(
     point_scale: PointScale,
-    row: &Row,
+    glyphs: &[Glyph],
     mesh: &mut Mesh,
+    wavy: bool,
     stroke_and_y: impl Fn(&Glyph) -> (Stroke, f32),
 ) {
     let mut path = crate::tessellator::Path::default(); // reusing path to avoid re-allocations.
@@ -909,7 +1386,11 @@ fn add_row_hline(
         if let Some((stroke, start)) = start {
             let stop = pos2(stop_x, start.y);
             path.clear();
-            path.add_line_segment([start, stop]);
+            if wavy {
+                add_zigzag(&mut path, start, stop_x, wavy_line_amplitude(stroke));
+            } else {
+                path.add_line_segment([start, stop]);
+            }
             let feathering = 1.0 / point_scale.pixels_per_point();
             path.stroke_open(feathering, &PathStroke::from(stroke), mesh);
         }
@@ -918,7 +1399,7 @@ fn add_row_hline(
     let mut line_start = None;
     let mut last_right_x = f32::NAN;
 
-    for glyph in &row.glyphs {
+    for glyph in glyphs {
         let (stroke, mut y) = stroke_and_y(glyph);
         stroke.round_center_to_pixel(point_scale.pixels_per_point, &mut y);
 
@@ -941,6 +1422,25 @@ fn add_row_hline(
     end_line(line_start.take(), last_right_x);
 }
 
+/// Add a zigzag going from `start` to `stop_x`, `amplitude` above and below `start.y`.
+fn add_zigzag(path: &mut crate::tessellator::Path, start: Pos2, stop_x: f32, amplitude: f32) {
+    let half_period = 2.0 * amplitude;
+    let y = |x: f32| {
+        // A triangle wave, starting at the top:
+        let t = ((x - start.x) / half_period).rem_euclid(2.0);
+        start.y - amplitude + 2.0 * amplitude * (1.0 - (t - 1.0).abs())
+    };
+
+    let mut points = vec![pos2(start.x, y(start.x))];
+    let mut x = start.x + half_period;
+    while x < stop_x {
+        points.push(pos2(x, y(x)));
+        x += half_period;
+    }
+    points.push(pos2(stop_x, y(stop_x)));
+    path.add_open_points(&points);
+}
+
 // ----------------------------------------------------------------------------
 
 /// Keeps track of good places to break a long row of text.
@@ -968,13 +1468,38 @@ struct RowBreakCandidates {
     /// Breaking after just random character is some
     /// times necessary.
     any: Option<usize>,
+
+    /// A line break opportunity according to the Unicode line breaking algorithm.
+    ///
+    /// Replaces `space`, `cjk`, `pre_cjk` and `dash` when [`TextWrapping::unicode_line_breaks`] is on.
+    unicode: Option<usize>,
+
+    /// A hyphenation point, where the hyphen fits on the row.
+    ///
+    /// See [`TextWrapping::hyphenation`].
+    hyphen: Option<usize>,
 }
 
 impl RowBreakCandidates {
-    fn add(&mut self, index: usize, glyphs: &[Glyph]) {
+    fn add(&mut self, index: usize, glyphs: &[Glyph], break_opportunities: &BreakOpportunities) {
+        if glyphs
+            .get(1)
+            .is_some_and(|glyph| glyph.cluster_continuation)
+        {
+            return; // Never break up a cluster
+        }
+
         let chr = glyphs[0].chr;
         const NON_BREAKING_SPACE: char = '\u{A0}';
-        if chr.is_whitespace() && chr != NON_BREAKING_SPACE {
+        if let Some(unicode) = &break_opportunities.unicode {
+            if chr == SOFT_HYPHEN {
+                // Only a break if we hyphenate there, which sets `self.hyphen` instead.
+            } else if unicode[index] {
+                self.unicode = Some(index);
+            } else if chr.is_ascii_punctuation() {
+                self.punctuation = Some(index);
+            }
+        } else if chr.is_whitespace() && chr != NON_BREAKING_SPACE {
             self.space = Some(index);
         } else if is_cjk(chr) && (glyphs.len() == 1 || is_cjk_break_allowed(glyphs[1].chr)) {
             self.cjk = Some(index);
@@ -989,10 +1514,16 @@ impl RowBreakCandidates {
     }
 
     fn word_boundary(&self) -> Option<usize> {
-        [self.space, self.cjk, self.pre_cjk]
-            .into_iter()
-            .max()
-            .flatten()
+        [
+            self.space,
+            self.cjk,
+            self.pre_cjk,
+            self.unicode,
+            self.hyphen,
+        ]
+        .into_iter()
+        .max()
+        .flatten()
     }
 
     fn has_good_candidate(&self, break_anywhere: bool) -> bool {
@@ -1014,6 +1545,11 @@ impl RowBreakCandidates {
         }
     }
 
+    /// Would breaking after this index hyphenate a word?
+    fn is_hyphen(&self, index: usize) -> bool {
+        self.hyphen == Some(index)
+    }
+
     fn forget_before_idx(&mut self, index: usize) {
         let Self {
             space,
@@ -1022,6 +1558,8 @@ impl RowBreakCandidates {
             dash,
             punctuation,
             any,
+            unicode,
+            hyphen,
         } = self;
         if space.is_some_and(|s| s < index) {
             *space = None;
@@ -1041,6 +1579,112 @@ impl RowBreakCandidates {
         if any.is_some_and(|s| s < index) {
             *any = None;
         }
+        if unicode.is_some_and(|s| s < index) {
+            *unicode = None;
+        }
+        if hyphen.is_some_and(|s| s < index) {
+            *hyphen = None;
+        }
+    }
+}
+
+/// Where a paragraph can be broken, according to [`TextWrapping::unicode_line_breaks`]
+/// and [`TextWrapping::hyphenation`].
+///
+/// Each vector has one element per glyph, which is `true` if we can break after that glyph.
+struct BreakOpportunities {
+    /// Line break opportunities of the Unicode line breaking algorithm (UAX #14).
+    unicode: Option<Vec<bool>>,
+
+    /// Hyphenation points. Empty if there is no hyphenation.
+    hyphens: Vec<bool>,
+}
+
+impl BreakOpportunities {
+    fn new(glyphs: &[Glyph], wrap: &TextWrapping) -> Self {
+        let needs_text =
+            cfg!(feature = "text_shaping") && wrap.unicode_line_breaks && !wrap.break_anywhere
+                || matches!(wrap.hyphenation, Hyphenation::Hyphenator(_));
+        let text: String = if needs_text {
+            glyphs.iter().map(|glyph| glyph.chr).collect()
+        } else {
+            String::new()
+        };
+
+        // Byte offset into `text` of the end of each glyph:
+        let glyph_ends: Vec<usize> = text
+            .char_indices()
+            .map(|(byte_index, chr)| byte_index + chr.len_utf8())
+            .collect();
+
+        #[cfg(feature = "text_shaping")]
+        let unicode = (wrap.unicode_line_breaks && !wrap.break_anywhere).then(|| {
+            let mut unicode = vec![false; glyphs.len()];
+            for (byte_index, _) in unicode_linebreak::linebreaks(&text) {
+                if let Ok(glyph_index) = glyph_ends.binary_search(&byte_index) {
+                    unicode[glyph_index] = true;
+                }
+            }
+            unicode
+        });
+        #[cfg(not(feature = "text_shaping"))]
+        let unicode = None;
+
+        let mut hyphens = vec![];
+        if !wrap.hyphenation.is_none() {
+            hyphens = vec![false; glyphs.len()];
+
+            // Only hyphenate left-to-right words:
+            let is_word_glyph = |glyph: &Glyph| {
+                (glyph.chr.is_alphabetic() || glyph.chr == SOFT_HYPHEN) && !glyph.is_rtl()
+            };
+
+            let mut word_start = 0;
+            while word_start < glyphs.len() {
+                if !is_word_glyph(&glyphs[word_start]) {
+                    word_start += 1;
+                    continue;
+                }
+                let word_end = glyphs[word_start..]
+                    .iter()
+                    .position(|glyph| !is_word_glyph(glyph))
+                    .map_or(glyphs.len(), |len| word_start + len);
+                let word = &glyphs[word_start..word_end];
+
+                if word.iter().any(|glyph| glyph.chr == SOFT_HYPHEN) {
+                    // Soft hyphens override the hyphenator:
+                    for (i, glyph) in word.iter().enumerate() {
+                        hyphens[word_start + i] = glyph.chr == SOFT_HYPHEN;
+                    }
+                } else if let Hyphenation::Hyphenator(hyphenator) = &wrap.hyphenation {
+                    let word_byte_start = glyph_ends[word_start] - word[0].chr.len_utf8();
+                    let word_byte_end = glyph_ends[word_end - 1];
+                    let word_text = &text[word_byte_start..word_byte_end];
+                    for point in hyphenator.hyphenation_points(word_text) {
+                        let byte_index = word_byte_start + point;
+                        if let Ok(glyph_index) = glyph_ends.binary_search(&byte_index) {
+                            hyphens[glyph_index] = true;
+                        }
+                    }
+                }
+
+                word_start = word_end;
+            }
+
+            // Never break up a cluster:
+            for (i, glyph) in glyphs.iter().enumerate().skip(1) {
+                if glyph.cluster_continuation {
+                    hyphens[i - 1] = false;
+                }
+            }
+        }
+
+        Self { unicode, hyphens }
+    }
+
+    /// Can we hyphenate after this glyph?
+    fn is_hyphenation_point(&self, index: usize) -> bool {
+        self.hyphens.get(index).copied().unwrap_or(false)
     }
 }
 
@@ -1052,14 +1696,18 @@ mod tests {
 
     use super::{super::*, *};
 
-    #[test]
-    fn test_zero_max_width() {
-        let pixels_per_point = 1.0;
-        let mut fonts = FontsImpl::new(
+    fn test_fonts() -> FontsImpl {
+        FontsImpl::new(
             1024,
             AlphaFromCoverage::default(),
             FontDefinitions::default(),
-        );
+        )
+    }
+
+    #[test]
+    fn test_zero_max_width() {
+        let pixels_per_point = 1.0;
+        let mut fonts = test_fonts();
         let mut layout_job = LayoutJob::single_section("W".into(), TextFormat::default());
         layout_job.wrap.max_width = 0.0;
         let galley = layout(&mut fonts, pixels_per_point, layout_job.into());
@@ -1072,11 +1720,7 @@ mod tests {
 
         let pixels_per_point = 1.0;
 
-        let mut fonts = FontsImpl::new(
-            1024,
-            AlphaFromCoverage::default(),
-            FontDefinitions::default(),
-        );
+        let mut fonts = test_fonts();
         let text_format = TextFormat {
             font_id: FontId::monospace(12.0),
             ..Default::default()
@@ -1122,11 +1766,7 @@ mod tests {
     #[test]
     fn test_cjk() {
         let pixels_per_point = 1.0;
-        let mut fonts = FontsImpl::new(
-            1024,
-            AlphaFromCoverage::default(),
-            FontDefinitions::default(),
-        );
+        let mut fonts = test_fonts();
         let mut layout_job = LayoutJob::single_section(
             "日本語とEnglishの混在した文章".into(),
             TextFormat::default(),
@@ -1142,11 +1782,7 @@ mod tests {
     #[test]
     fn test_pre_cjk() {
         let pixels_per_point = 1.0;
-        let mut fonts = FontsImpl::new(
-            1024,
-            AlphaFromCoverage::default(),
-            FontDefinitions::default(),
-        );
+        let mut fonts = test_fonts();
         let mut layout_job = LayoutJob::single_section(
             "日本語とEnglishの混在した文章".into(),
             TextFormat::default(),
@@ -1162,11 +1798,7 @@ mod tests {
     #[test]
     fn test_truncate_width() {
         let pixels_per_point = 1.0;
-        let mut fonts = FontsImpl::new(
-            1024,
-            AlphaFromCoverage::default(),
-            FontDefinitions::default(),
-        );
+        let mut fonts = test_fonts();
         let mut layout_job =
             LayoutJob::single_section("# DNA\nMore text".into(), TextFormat::default());
         layout_job.wrap.max_width = f32::INFINITY;
@@ -1186,11 +1818,7 @@ mod tests {
     #[test]
     fn test_empty_row() {
         let pixels_per_point = 1.0;
-        let mut fonts = FontsImpl::new(
-            1024,
-            AlphaFromCoverage::default(),
-            FontDefinitions::default(),
-        );
+        let mut fonts = test_fonts();
 
         let font_id = FontId::default();
         let font_height = fonts
@@ -1220,14 +1848,455 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "text_shaping")]
     #[test]
-    fn test_end_with_newline() {
+    fn test_bidi() {
         let pixels_per_point = 1.0;
-        let mut fonts = FontsImpl::new(
-            1024,
-            AlphaFromCoverage::default(),
-            FontDefinitions::default(),
+        let mut fonts = test_fonts();
+        let job = LayoutJob::single_section("abc אבג (de)".into(), TextFormat::default());
+        let galley = layout(&mut fonts, pixels_per_point, job.into());
+        assert_eq!(galley.rows.len(), 1);
+
+        let row = &galley.rows[0];
+        assert_eq!(
+            row.text(),
+            "abc אבג (de)",
+            "Glyphs should stay in logical order"
+        );
+        assert!(row.has_rtl());
+
+        let x = |i: usize| row.glyphs[i].pos.x;
+        assert!(x(2) < x(6), "The hebrew word should come after `abc`");
+        assert!(
+            x(6) < x(5) && x(5) < x(4),
+            "The hebrew word should be right-to-left"
+        );
+        assert!(x(4) < x(8), "`(de)` should come after the hebrew word");
+
+        // The logical start of a right-to-left glyph is its right side:
+        let alef = &row.glyphs[4];
+        assert_eq!(row.x_offset(4), alef.max_x());
+        assert_eq!(row.char_at(alef.max_x() - 1.0), 4);
+        assert_eq!(row.char_at(alef.pos.x + 1.0), 5);
+
+        // Selecting the last two hebrew letters gives one contiguous range:
+        let x_ranges = row.x_ranges(5..7);
+        assert_eq!(x_ranges.len(), 1);
+        assert_eq!(x_ranges[0].min, x(6));
+        assert_eq!(x_ranges[0].max, row.glyphs[5].max_x());
+
+        // Without right-to-left text, the columns are always one range:
+        let job = LayoutJob::single_section("abc def".into(), TextFormat::default());
+        let galley = layout(&mut fonts, pixels_per_point, job.into());
+        let row = &galley.rows[0];
+        assert!(!row.has_rtl());
+        assert_eq!(
+            row.x_ranges(1..5).as_slice(),
+            [emath::Rangef::new(
+                row.glyphs[1].pos.x,
+                row.glyphs[4].max_x()
+            )]
+        );
+        assert!(row.x_ranges(7..9).is_empty());
+    }
+
+    #[cfg(feature = "text_shaping")]
+    #[test]
+    fn test_text_shaping() {
+        let pixels_per_point = 1.0;
+        let mut fonts = test_fonts();
+
+        // The default font has a ligature for `ffi`:
+        let job = LayoutJob::single_section("office".into(), TextFormat::default());
+        let galley = layout(&mut fonts, pixels_per_point, job.into());
+        assert_eq!(galley.rows.len(), 1);
+
+        let row = &galley.rows[0];
+        assert_eq!(row.text(), "office", "There should be one glyph per char");
+        let is_continuation: Vec<bool> = row
+            .glyphs
+            .iter()
+            .map(|g| g.is_cluster_continuation())
+            .collect();
+        assert_eq!(is_continuation, [false, false, true, true, false, false]);
+        assert_eq!(row.glyphs[2].advance_width, 0.0);
+        assert_eq!(row.glyphs[2].pos.x, row.glyphs[1].max_x());
+        assert!(row.glyphs[2].uv_rect.is_nothing());
+
+        // Clusters are never split when wrapping:
+        let mut job = LayoutJob::single_section("ffiffi".into(), TextFormat::default());
+        job.wrap.max_width = 1.0;
+        job.wrap.break_anywhere = true;
+        let galley = layout(&mut fonts, pixels_per_point, job.into());
+        assert_eq!(galley.rows.len(), 2);
+        for row in &galley.rows {
+            assert_eq!(row.text(), "ffi");
+        }
+    }
+
+    /// Lays out `text` without wrapping and returns its width.
+    fn unwrapped_width(fonts: &mut FontsImpl, text: &str) -> f32 {
+        let mut job = LayoutJob::single_section(text.into(), TextFormat::default());
+        job.round_output_to_gui = false;
+        layout(fonts, 1.0, job.into()).rows[0].size.x
+    }
+
+    fn wrapped_rows(
+        fonts: &mut FontsImpl,
+        text: &str,
+        wrap: TextWrapping,
+    ) -> Vec<(String, Option<char>)> {
+        let mut job = LayoutJob::single_section(text.into(), TextFormat::default());
+        job.wrap = wrap;
+        job.round_output_to_gui = false;
+        let galley = layout(fonts, 1.0, job.into());
+        galley
+            .rows
+            .iter()
+            .map(|row| (row.text(), row.hyphen.map(|hyphen| hyphen.chr)))
+            .collect()
+    }
+
+    #[test]
+    fn test_hyphenation() {
+        let mut fonts = test_fonts();
+        let hyphenated_width = unwrapped_width(&mut fonts, "hyphen-");
+        assert!(hyphenated_width < unwrapped_width(&mut fonts, "hyphenation"));
+
+        let mut job = LayoutJob::single_section("hyphen\u{AD}ation".into(), TextFormat::default());
+        job.wrap.max_width = hyphenated_width + 0.5;
+        job.wrap.hyphenation = Hyphenation::SoftHyphens;
+        job.round_output_to_gui = false;
+        let galley = layout(&mut fonts, 1.0, job.into());
+        assert_eq!(
+            galley.rows.iter().map(|row| row.text()).collect::<Vec<_>>(),
+            vec!["hyphen\u{AD}", "ation"]
+        );
+
+        let row = &galley.rows[0];
+        let hyphen = row.hyphen.expect("The first row should end with a hyphen");
+        assert_eq!(hyphen.chr, '-');
+        assert_eq!(hyphen.pos.x, row.glyphs.last().unwrap().max_x());
+        assert_eq!(row.size.x, hyphen.max_x());
+        assert!((row.size.x - hyphenated_width).abs() < 0.01);
+        assert!(galley.rows[1].hyphen.is_none());
+    }
+
+    #[test]
+    fn test_soft_hyphen_only_breaks_if_hyphen_fits() {
+        let mut fonts = test_fonts();
+        let text = "ab hyphen\u{AD}ation";
+
+        // Room for `ab hyphen`, but not for the hyphen after it:
+        let max_width = unwrapped_width(&mut fonts, "ab hyphen") + 0.5;
+        assert!(max_width < unwrapped_width(&mut fonts, "ab hyphen-"));
+
+        for unicode_line_breaks in [false, true] {
+            let wrap = TextWrapping {
+                max_width,
+                unicode_line_breaks,
+                hyphenation: Hyphenation::SoftHyphens,
+                ..Default::default()
+            };
+            assert_eq!(
+                wrapped_rows(&mut fonts, text, wrap),
+                vec![
+                    ("ab ".to_owned(), None),
+                    ("hyphen\u{AD}".to_owned(), Some('-')),
+                    ("ation".to_owned(), None),
+                ],
+                "unicode_line_breaks: {unicode_line_breaks}"
+            );
+
+            // Without hyphenation, a soft hyphen is never a break, even if the hyphen would fit:
+            let wrap = TextWrapping {
+                max_width: unwrapped_width(&mut fonts, "ab hyphen-")
+                    .max(unwrapped_width(&mut fonts, "hyphenation"))
+                    + 0.5,
+                unicode_line_breaks,
+                ..Default::default()
+            };
+            assert_eq!(
+                wrapped_rows(&mut fonts, text, wrap),
+                vec![
+                    ("ab ".to_owned(), None),
+                    ("hyphen\u{AD}ation".to_owned(), None),
+                ],
+                "unicode_line_breaks: {unicode_line_breaks}"
+            );
+        }
+    }
+
+    #[cfg(feature = "text_shaping")]
+    #[test]
+    fn test_unicode_line_breaks() {
+        let mut fonts = test_fonts();
+        let text = "Hello (world)! Some [more] text (here)!";
+        let min_width = unwrapped_width(&mut fonts, "(world)! ");
+        let max_width = unwrapped_width(&mut fonts, text);
+
+        let mut width = min_width;
+        while width < max_width {
+            let wrap = TextWrapping {
+                max_width: width,
+                unicode_line_breaks: true,
+                ..Default::default()
+            };
+            let rows = wrapped_rows(&mut fonts, text, wrap);
+            assert!(1 < rows.len());
+            for (row, _) in &rows {
+                assert!(
+                    !row.starts_with([')', ']', '!']),
+                    "Row {row:?} starts with closing punctuation at width {width}"
+                );
+            }
+            width += 1.0;
+        }
+    }
+
+    #[test]
+    fn test_justify() {
+        let mut fonts = test_fonts();
+        let text = "Some words that are long enough to wrap\nA new paragraph";
+        let max_width = 120.0;
+
+        let mut job = LayoutJob::single_section(text.into(), TextFormat::default());
+        job.wrap.max_width = max_width;
+        let unjustified = layout(&mut fonts, 1.0, job.clone().into());
+        job.justify = true;
+        job.justify_word_spacing = true;
+        let justified = layout(&mut fonts, 1.0, job.into());
+
+        assert_eq!(justified.rows.len(), unjustified.rows.len());
+        let num_rows = justified.rows.len();
+        for (i, (row, unjustified_row)) in justified.rows.iter().zip(&unjustified.rows).enumerate()
+        {
+            assert_eq!(row.text(), unjustified_row.text());
+            let last_non_space = row.glyphs.iter().rev().find(|g| !g.chr.is_whitespace());
+            let max_x = last_non_space.unwrap().max_x();
+            if row.ends_with_newline || i + 1 == num_rows {
+                assert_eq!(
+                    max_x,
+                    unjustified_row
+                        .glyphs
+                        .iter()
+                        .rev()
+                        .find(|g| !g.chr.is_whitespace())
+                        .unwrap()
+                        .max_x(),
+                    "The last row of a paragraph should not be justified"
+                );
+            } else {
+                assert!(
+                    (max_x - max_width).abs() <= 0.5,
+                    "Row {i} should be justified: {max_x}"
+                );
+            }
+
+            // Only the spaces are stretched:
+            for (glyphs, unjustified_glyphs) in
+                row.glyphs.windows(2).zip(unjustified_row.glyphs.windows(2))
+            {
+                if !glyphs[0].chr.is_whitespace() {
+                    assert_eq!(
+                        glyphs[1].pos.x - glyphs[0].pos.x,
+                        unjustified_glyphs[1].pos.x - unjustified_glyphs[0].pos.x,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tab_stops() {
+        use crate::text::cursor::CCursor;
+
+        let mut fonts = test_fonts();
+        let mut job =
+            LayoutJob::single_section("a\tleft\tright\t3.25".into(), TextFormat::default());
+        job.tab_stops = vec![
+            TabStop::left(50.0),
+            TabStop::right(150.0),
+            TabStop::decimal(200.0),
+        ];
+        let galley = layout(&mut fonts, 1.0, job.into());
+        assert_eq!(galley.rows.len(), 1);
+        let row = &galley.rows[0];
+
+        assert_eq!(row.text(), "a\tleft\tright\t3.25");
+        assert_eq!(
+            row.glyphs[2].pos.x, 50.0,
+            "Left tab stop: `left` starts at it"
+        );
+        assert_eq!(
+            row.glyphs[11].max_x().round(),
+            150.0,
+            "Right tab stop: `right` ends at it"
+        );
+        assert_eq!(
+            row.glyphs[14].pos.x, 200.0,
+            "Decimal tab stop: `.` is at it"
+        );
+
+        // Cursor math uses the tab stops:
+        assert_eq!(galley.pos_from_cursor(CCursor::new(2)).min.x, 50.0);
+        assert_eq!(galley.cursor_from_pos(vec2(51.0, 5.0)), CCursor::new(2));
+        assert_eq!(galley.cursor_from_pos(vec2(40.0, 5.0)), CCursor::new(2));
+        assert_eq!(galley.cursor_from_pos(vec2(10.0, 5.0)), CCursor::new(1));
+
+        // A wrapped row gets its own tab stops:
+        let mut job =
+            LayoutJob::single_section("first row\tx second\ty".into(), TextFormat::default());
+        job.tab_stops = vec![TabStop::left(100.0)];
+        job.wrap.max_width = 130.0;
+        let galley = layout(&mut fonts, 1.0, job.into());
+        assert_eq!(galley.rows.len(), 2);
+        for row in &galley.rows {
+            let tab = row
+                .glyphs
+                .iter()
+                .position(|glyph| glyph.chr == '\t')
+                .unwrap();
+            assert_eq!(row.glyphs[tab + 1].pos.x, 100.0, "{:?}", row.text());
+        }
+    }
+
+    #[test]
+    fn test_baseline_shift() {
+        let mut fonts = test_fonts();
+        let mut job = LayoutJob::default();
+        job.append("m", 0.0, TextFormat::default());
+        job.append(
+            "2",
+            0.0,
+            TextFormat {
+                baseline_shift: 4.0,
+                ..Default::default()
+            },
+        );
+        job.append(
+            "2",
+            0.0,
+            TextFormat {
+                baseline_shift: -3.0,
+                ..Default::default()
+            },
+        );
+        let galley = layout(&mut fonts, 1.0, job.into());
+        assert_eq!(galley.rows.len(), 1);
+        let row = &galley.rows[0];
+        assert_eq!(row.glyphs[1].pos.y, row.glyphs[0].pos.y - 4.0);
+        assert_eq!(row.glyphs[2].pos.y, row.glyphs[0].pos.y + 3.0);
+        assert_eq!(
+            row.size.y,
+            row.glyphs[0].line_height.round(),
+            "The row height should not change"
         );
+    }
+
+    #[test]
+    fn test_wavy_underline() {
+        let mut fonts = test_fonts();
+        let stroke = Stroke::new(1.0, Color32::RED);
+        let mut layout_with = |format: TextFormat| {
+            let job = LayoutJob::single_section("Mistake".into(), format);
+            layout(&mut fonts, 1.0, job.into())
+        };
+        let plain = layout_with(TextFormat::default());
+        let underlined = layout_with(TextFormat {
+            underline: stroke,
+            ..Default::default()
+        });
+        let wavy = layout_with(TextFormat {
+            wavy_underline: stroke,
+            ..Default::default()
+        });
+
+        let vertex_count = |galley: &Galley| galley.rows[0].visuals.mesh.vertices.len();
+        assert!(
+            vertex_count(&wavy) > vertex_count(&underlined),
+            "A zigzag needs more vertices than a straight line"
+        );
+        assert!(vertex_count(&underlined) > vertex_count(&plain));
+
+        let row = &wavy.rows[0];
+        let row_bottom = row.glyphs[0].logical_rect().bottom();
+        assert!(
+            row.visuals.mesh_bounds.bottom() <= row_bottom + 0.5,
+            "The waves should stay inside the row"
+        );
+    }
+
+    #[cfg(feature = "text_shaping")]
+    #[test]
+    fn test_font_features() {
+        let mut fonts = test_fonts();
+        let mut job = LayoutJob::single_section(
+            "office".into(),
+            TextFormat {
+                font_features: vec![FontFeature::disable(*b"liga")],
+                ..Default::default()
+            },
+        );
+        job.round_output_to_gui = false;
+        let galley = layout(&mut fonts, 1.0, job.into());
+        assert!(
+            galley.rows[0]
+                .glyphs
+                .iter()
+                .all(|glyph| !glyph.is_cluster_continuation()),
+            "The `ffi` ligature should be turned off"
+        );
+    }
+
+    #[test]
+    fn test_inline_box() {
+        let mut fonts = test_fonts();
+        let icon_size = vec2(20.0, 30.0);
+
+        let mut job = LayoutJob::default();
+        job.append("Hello ", 0.0, TextFormat::default());
+        let icon = job.append_inline_box(InlineBox::new(icon_size), TextFormat::default());
+        job.append(" world", 0.0, TextFormat::default());
+        job.round_output_to_gui = false;
+
+        let galley = layout(&mut fonts, 1.0, job.clone().into());
+        assert_eq!(galley.rows.len(), 1);
+        let row = &galley.rows[0];
+        assert_eq!(row.text(), "Hello \u{FFFC} world");
+        assert!(row.height() >= icon_size.y, "The row should fit the box");
+
+        let rects = galley.inline_box_rects();
+        assert_eq!(rects.len(), 1);
+        let (section_index, rect) = rects[0];
+        assert_eq!(section_index, icon);
+        assert_eq!(rect.size(), icon_size);
+        assert_eq!(rect.min.x, row.glyphs[6].pos.x);
+        assert_eq!(row.glyphs[6].advance_width, icon_size.x);
+        assert!((row.glyphs[7].pos.x - rect.max.x).abs() < 1.0);
+        assert_eq!(
+            rect.max.y,
+            row.pos.y + row.glyphs[0].pos.y,
+            "The box should sit on the baseline"
+        );
+
+        // The box wraps like a character, and the cursor can step over it:
+        job.wrap.max_width = row.glyphs[5].max_x() + 1.0;
+        let galley = layout(&mut fonts, 1.0, job.into());
+        assert_eq!(
+            galley.rows.iter().map(|row| row.text()).collect::<Vec<_>>(),
+            vec!["Hello ", "\u{FFFC} ", "world"]
+        );
+        let (_, rect) = galley.inline_box_rects()[0];
+        assert_eq!(rect.min, galley.rows[1].pos);
+        let cursor = galley.cursor_from_pos(rect.center().to_vec2() + vec2(1.0, 0.0));
+        assert_eq!(cursor.index, 7);
+    }
+
+    #[test]
+    fn test_end_with_newline() {
+        let pixels_per_point = 1.0;
+        let mut fonts = test_fonts();
 
         let font_id = FontId::default();
         let font_height = fonts
@@ -4,12 +4,19 @@
 use std::ops::Range;
 use std::sync::Arc;
 
+use smallvec::{SmallVec, smallvec};
+
 use super::{
     cursor::{CCursor, LayoutCursor},
     font::UvRect,
 };
-use crate::{Color32, FontId, Mesh, Stroke, text::FontsView};
-use emath::{Align, GuiRounding as _, NumExt as _, OrderedFloat, Pos2, Rect, Vec2, pos2, vec2};
+use crate::{
+    Color32, FontId, Mesh, Stroke,
+    text::{FontsView, Hyphenation},
+};
+use emath::{
+    Align, GuiRounding as _, NumExt as _, OrderedFloat, Pos2, Rangef, Rect, Vec2, pos2, vec2,
+};
 
 /// Describes the task of laying out text.
 ///
@@ -76,8 +83,28 @@ pub struct LayoutJob {
     pub halign: Align,
 
     /// Justify text so that word-wrapped rows fill the whole [`TextWrapping::max_width`].
+    ///
+    /// Only the text after the last tab of a row is stretched, so that tab stops stay put.
+    /// The last row of each paragraph is not justified.
+    /// See [`Self::justify_word_spacing`] for how a row is stretched.
     pub justify: bool,
 
+    /// How [`Self::justify`] stretches a row.
+    ///
+    /// If `true`, only the spaces between words are stretched
+    /// (or, for rows without spaces, the space between all glyphs).
+    /// If `false`, a whole number of pixels is added between all glyphs, and the rest to the spaces.
+    ///
+    /// Default: `false`.
+    pub justify_word_spacing: bool,
+
+    /// Where a `\t` moves the following text to, sorted by [`TabStop::x`].
+    ///
+    /// A `\t` after the last tab stop (or if there are none) is as wide as [`crate::text::TAB_SIZE`] spaces.
+    ///
+    /// Default: empty.
+    pub tab_stops: Vec<TabStop>,
+
     /// Round output sizes using [`emath::GuiRounding`], to avoid rounding errors in layout code.
     pub round_output_to_gui: bool,
 }
@@ -93,6 +120,8 @@ impl Default for LayoutJob {
             break_on_newline: true,
             halign: Align::LEFT,
             justify: false,
+            justify_word_spacing: false,
+            tab_stops: Vec::new(),
             round_output_to_gui: true,
         }
     }
@@ -107,6 +136,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format: TextFormat::simple(font_id, color),
+                inline_box: None,
             }],
             text,
             wrap: TextWrapping {
@@ -126,6 +156,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format,
+                inline_box: None,
             }],
             text,
             break_on_newline: true,
@@ -141,6 +172,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format: TextFormat::simple(font_id, color),
+                inline_box: None,
             }],
             text,
             wrap: Default::default(),
@@ -156,6 +188,7 @@ impl LayoutJob {
                 leading_space: 0.0,
                 byte_range: 0..text.len(),
                 format,
+                inline_box: None,
             }],
             text,
             wrap: Default::default(),
@@ -178,7 +211,31 @@ impl LayoutJob {
             leading_space,
             byte_range,
             format,
+            inline_box: None,
+        });
+    }
+
+    /// Reserve space in the text for something else, like an image or a widget.
+    ///
+    /// This adds a section with a single [`OBJECT_REPLACEMENT_CHARACTER`] to the text,
+    /// which is laid out (and wrapped) like a character of the given size.
+    ///
+    /// Returns the index of the section, to look up where the box ended up
+    /// with [`Galley::inline_box_rects`].
+    ///
+    /// With [`Align::BOTTOM`] (the default [`TextFormat::valign`]) the baseline of the box
+    /// is on the baseline of text in the font of `format`.
+    pub fn append_inline_box(&mut self, inline_box: InlineBox, format: TextFormat) -> usize {
+        let start = self.text.len();
+        self.text.push(OBJECT_REPLACEMENT_CHARACTER);
+        let byte_range = start..self.text.len();
+        self.sections.push(LayoutSection {
+            leading_space: 0.0,
+            byte_range,
+            format,
+            inline_box: Some(inline_box),
         });
+        self.sections.len() - 1
     }
 
     /// The height of the tallest font used in the job.
@@ -216,6 +273,8 @@ impl std::hash::Hash for LayoutJob {
             break_on_newline,
             halign,
             justify,
+            justify_word_spacing,
+            tab_stops,
             round_output_to_gui,
         } = self;
 
@@ -226,12 +285,83 @@ impl std::hash::Hash for LayoutJob {
         break_on_newline.hash(state);
         halign.hash(state);
         justify.hash(state);
+        justify_word_spacing.hash(state);
+        tab_stops.hash(state);
         round_output_to_gui.hash(state);
     }
 }
 
 // ----------------------------------------------------------------------------
 
+/// Where a `\t` moves the following text to, see [`LayoutJob::tab_stops`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct TabStop {
+    /// Position of the tab stop, in points from the left of the row.
+    pub x: f32,
+
+    /// How the text after the tab is aligned to [`Self::x`].
+    pub align: TabAlign,
+}
+
+impl TabStop {
+    /// The text after the tab starts at `x`.
+    #[inline]
+    pub fn left(x: f32) -> Self {
+        Self {
+            x,
+            align: TabAlign::Left,
+        }
+    }
+
+    /// The text after the tab ends at `x`.
+    #[inline]
+    pub fn right(x: f32) -> Self {
+        Self {
+            x,
+            align: TabAlign::Right,
+        }
+    }
+
+    /// The first `.` after the tab is at `x`.
+    #[inline]
+    pub fn decimal(x: f32) -> Self {
+        Self {
+            x,
+            align: TabAlign::Decimal('.'),
+        }
+    }
+}
+
+impl std::hash::Hash for TabStop {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let Self { x, align } = self;
+        OrderedFloat(*x).hash(state);
+        align.hash(state);
+    }
+}
+
+/// How the text after a `\t` is aligned to its [`TabStop`].
+///
+/// The text after a tab is everything up to the next tab or the end of the paragraph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub enum TabAlign {
+    /// The text starts at the tab stop.
+    Left,
+
+    /// The text ends at the tab stop.
+    Right,
+
+    /// The first occurrence of the given decimal separator is at the tab stop.
+    ///
+    /// Text without the separator ends at the tab stop, like [`Self::Right`].
+    Decimal(char),
+}
+
+// ----------------------------------------------------------------------------
+
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct LayoutSection {
@@ -242,6 +372,12 @@ pub struct LayoutSection {
     pub byte_range: Range<usize>,
 
     pub format: TextFormat,
+
+    /// If set, each character of this section is laid out as an empty box of this size
+    /// instead of a glyph.
+    ///
+    /// See [`LayoutJob::append_inline_box`].
+    pub inline_box: Option<InlineBox>,
 }
 
 impl std::hash::Hash for LayoutSection {
@@ -251,10 +387,52 @@ impl std::hash::Hash for LayoutSection {
             leading_space,
             byte_range,
             format,
+            inline_box,
         } = self;
         OrderedFloat(*leading_space).hash(state);
         byte_range.hash(state);
         format.hash(state);
+        inline_box.hash(state);
+    }
+}
+
+/// The character used in the text for an [`InlineBox`].
+pub const OBJECT_REPLACEMENT_CHARACTER: char = '\u{FFFC}';
+
+/// Space reserved in the text for something else, like an image or a widget.
+///
+/// See [`LayoutJob::append_inline_box`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct InlineBox {
+    /// Size of the box, in points.
+    pub size: Vec2,
+
+    /// Distance from the top of the box down to its baseline, in points.
+    ///
+    /// Use `size.y` for things that sit on the baseline (like most icons),
+    /// or less for things that should go below it.
+    pub baseline: f32,
+}
+
+impl InlineBox {
+    /// A box that sits on the baseline.
+    #[inline]
+    pub fn new(size: Vec2) -> Self {
+        Self {
+            size,
+            baseline: size.y,
+        }
+    }
+}
+
+impl std::hash::Hash for InlineBox {
+    #[inline]
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let Self { size, baseline } = self;
+        OrderedFloat(size.x).hash(state);
+        OrderedFloat(size.y).hash(state);
+        OrderedFloat(*baseline).hash(state);
     }
 }
 
@@ -294,6 +472,13 @@ pub struct TextFormat {
 
     pub underline: Stroke,
 
+    /// A zigzag line under the text, e.g. to mark a spelling error.
+    ///
+    /// This is painted in addition to [`Self::underline`].
+    ///
+    /// Default: [`Stroke::NONE`].
+    pub wavy_underline: Stroke,
+
     pub strikethrough: Stroke,
 
     /// If you use a small font and [`Align::TOP`] you
@@ -306,6 +491,22 @@ pub struct TextFormat {
     /// around a common center-line, which is nice when mixining emojis
     /// and normal text in e.g. a button.
     pub valign: Align,
+
+    /// Move the text up (positive) or down (negative) from its baseline, in points.
+    ///
+    /// Together with a smaller font size this gives you superscripts (`m²`) and subscripts (`H₂O`).
+    /// Use it with [`Align::BOTTOM`] so that the baseline of the text is the baseline of the row.
+    /// This does not change the height of the row.
+    ///
+    /// Default: 0.0.
+    pub baseline_shift: f32,
+
+    /// OpenType features to turn on or off, e.g. [`FontFeature::SMALL_CAPS`].
+    ///
+    /// Only has an effect with the `text_shaping` feature, and only for fonts that have the features.
+    ///
+    /// Default: empty, i.e. the default features of the font.
+    pub font_features: Vec<FontFeature>,
 }
 
 impl Default for TextFormat {
@@ -320,8 +521,11 @@ impl Default for TextFormat {
             expand_bg: 1.0,
             italics: false,
             underline: Stroke::NONE,
+            wavy_underline: Stroke::NONE,
             strikethrough: Stroke::NONE,
             valign: Align::BOTTOM,
+            baseline_shift: 0.0,
+            font_features: Vec::new(),
         }
     }
 }
@@ -338,8 +542,11 @@ impl std::hash::Hash for TextFormat {
             expand_bg,
             italics,
             underline,
+            wavy_underline,
             strikethrough,
             valign,
+            baseline_shift,
+            font_features,
         } = self;
         font_id.hash(state);
         emath::OrderedFloat(*extra_letter_spacing).hash(state);
@@ -351,8 +558,11 @@ impl std::hash::Hash for TextFormat {
         emath::OrderedFloat(*expand_bg).hash(state);
         italics.hash(state);
         underline.hash(state);
+        wavy_underline.hash(state);
         strikethrough.hash(state);
         valign.hash(state);
+        emath::OrderedFloat(*baseline_shift).hash(state);
+        font_features.hash(state);
     }
 }
 
@@ -367,6 +577,54 @@ impl TextFormat {
     }
 }
 
+/// An OpenType font feature, see [`TextFormat::font_features`].
+///
+/// See <https://learn.microsoft.com/en-us/typography/opentype/spec/featurelist>
+/// for the available features.
+///
+/// ```
+/// # use epaint::text::FontFeature;
+/// let old_style_numbers = FontFeature::enable(*b"onum");
+/// let no_ligatures = FontFeature::disable(*b"liga");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct FontFeature {
+    /// The four letter tag of the feature, e.g. `*b"smcp"`.
+    pub tag: [u8; 4],
+
+    /// `0` turns the feature off, `1` turns it on.
+    ///
+    /// Some features (like `salt`) use larger values to select between alternatives.
+    pub value: u32,
+}
+
+impl FontFeature {
+    /// Lowercase letters as small capitals.
+    pub const SMALL_CAPS: Self = Self::enable(*b"smcp");
+
+    /// Superscript glyphs (from the font, instead of using [`TextFormat::baseline_shift`]).
+    pub const SUPERSCRIPT: Self = Self::enable(*b"sups");
+
+    /// Subscript glyphs (from the font, instead of using [`TextFormat::baseline_shift`]).
+    pub const SUBSCRIPT: Self = Self::enable(*b"subs");
+
+    /// Digits that all have the same width, so that numbers line up in columns.
+    pub const TABULAR_NUMBERS: Self = Self::enable(*b"tnum");
+
+    /// Turn the feature with this tag on.
+    #[inline]
+    pub const fn enable(tag: [u8; 4]) -> Self {
+        Self { tag, value: 1 }
+    }
+
+    /// Turn the feature with this tag off.
+    #[inline]
+    pub const fn disable(tag: [u8; 4]) -> Self {
+        Self { tag, value: 0 }
+    }
+}
+
 // ----------------------------------------------------------------------------
 
 /// How to wrap and elide text.
@@ -432,6 +690,24 @@ pub struct TextWrapping {
     ///
     /// If not set, no character will be used (but the text will still be elided).
     pub overflow_character: Option<char>,
+
+    /// If `true`: break rows at the line break opportunities of the
+    /// [Unicode line breaking algorithm](https://www.unicode.org/reports/tr14/) (UAX #14).
+    /// If `false` (default): break rows at whitespace, between CJK characters, and after dashes and punctuation.
+    ///
+    /// The Unicode rules are better at e.g. not putting a closing bracket or an exclamation mark
+    /// at the start of a row, and at breaking after em dashes and slashes.
+    ///
+    /// Has no effect when [`Self::break_anywhere`] is `true`, or without the `text_shaping` feature.
+    pub unicode_line_breaks: bool,
+
+    /// Break long words at their hyphenation points, and show a hyphen at the end of the row.
+    ///
+    /// A hyphenation point is used if it allows fitting more of the word on the row
+    /// than breaking before the word would.
+    ///
+    /// Default: [`Hyphenation::None`].
+    pub hyphenation: Hyphenation,
 }
 
 impl std::hash::Hash for TextWrapping {
@@ -442,11 +718,15 @@ impl std::hash::Hash for TextWrapping {
             max_rows,
             break_anywhere,
             overflow_character,
+            unicode_line_breaks,
+            hyphenation,
         } = self;
         emath::OrderedFloat(*max_width).hash(state);
         max_rows.hash(state);
         break_anywhere.hash(state);
         overflow_character.hash(state);
+        unicode_line_breaks.hash(state);
+        hyphenation.hash(state);
     }
 }
 
@@ -457,6 +737,8 @@ impl Default for TextWrapping {
             max_rows: usize::MAX,
             break_anywhere: false,
             overflow_character: Some('…'),
+            unicode_line_breaks: false,
+            hyphenation: Hyphenation::None,
         }
     }
 }
@@ -611,6 +893,12 @@ pub struct Row {
     /// One for each `char`.
     pub glyphs: Vec<Glyph>,
 
+    /// The hyphen shown at the end of this row, if a word was hyphenated here.
+    ///
+    /// It is not part of the text, so it is not one of the [`Self::glyphs`].
+    /// See [`TextWrapping::hyphenation`].
+    pub hyphen: Option<Glyph>,
+
     /// Logical size based on font heights etc.
     /// Includes leading and trailing whitespace.
     pub size: Vec2,
@@ -647,6 +935,9 @@ pub struct RowVisuals {
     /// The range of vertices in the mesh that contain glyphs (as opposed to background, underlines, strikethorugh, etc).
     ///
     /// The glyph vertices comes after backgrounds (if any), but before any underlines and strikethrough.
+    ///
+    /// The colored parts of color glyphs (see [`Glyph::color_uv_rect`]) are not included,
+    /// since they are not painted with the text color. They come before the glyph vertices.
     pub glyph_vertex_range: Range<usize>,
 }
 
@@ -693,8 +984,32 @@ pub struct Glyph {
     pub font_impl_height: f32,
 
     /// Position and size of the glyph in the font texture, in texels.
+    ///
+    /// This is painted with the text color.
     pub uv_rect: UvRect,
 
+    /// For color glyphs (e.g. emoji): the parts of the glyph that are painted as-is,
+    /// underneath [`Self::uv_rect`].
+    ///
+    /// See [`Self::is_color`].
+    pub color_uv_rect: UvRect,
+
+    /// The bidirectional embedding level of this glyph.
+    ///
+    /// Odd levels are right-to-left, even levels left-to-right.
+    /// This is always `0` without the `text_shaping` feature.
+    /// See [`Self::is_rtl`].
+    pub bidi_level: u8,
+
+    /// Does this glyph continue the cluster of the glyph before it?
+    ///
+    /// With text shaping, several characters can be combined into one cluster
+    /// (e.g. a ligature, or a letter with combining marks).
+    /// The first glyph of a cluster then has the width and image of the whole cluster,
+    /// and each following character gets a zero-width glyph with this set.
+    /// See [`Self::is_cluster_continuation`].
+    pub cluster_continuation: bool,
+
     /// Index into [`LayoutJob::sections`]. Decides color etc.
     ///
     /// Only used during layout, then set to an invalid value in order to
@@ -714,6 +1029,29 @@ impl Glyph {
         self.pos.x + self.advance_width
     }
 
+    /// Is this glyph part of a right-to-left run of text (e.g. Arabic or Hebrew)?
+    #[inline]
+    pub fn is_rtl(&self) -> bool {
+        self.bidi_level % 2 == 1
+    }
+
+    /// Is this a color glyph (e.g. an emoji)?
+    ///
+    /// Color glyphs are not tinted by [`TextFormat::color`],
+    /// except for any parts of them that the font paints with the text color.
+    #[inline]
+    pub fn is_color(&self) -> bool {
+        !self.color_uv_rect.is_nothing()
+    }
+
+    /// Is this glyph part of a cluster started by the glyph before it?
+    ///
+    /// Such glyphs have zero width and no image of their own.
+    #[inline]
+    pub fn is_cluster_continuation(&self) -> bool {
+        self.cluster_continuation
+    }
+
     /// Same y range for all characters with the same [`TextFormat`].
     #[inline]
     pub fn logical_rect(&self) -> Rect {
@@ -741,25 +1079,95 @@ impl Row {
         self.glyphs.len() + (self.ends_with_newline as usize)
     }
 
+    /// Does this row contain any right-to-left text?
+    ///
+    /// If so, the glyphs are not sorted by their x coordinate.
+    pub fn has_rtl(&self) -> bool {
+        self.glyphs.iter().any(Glyph::is_rtl)
+    }
+
     /// Closest char at the desired x coordinate in row-relative coordinates.
     /// Returns something in the range `[0, char_count_excluding_newline()]`.
     pub fn char_at(&self, desired_x: f32) -> usize {
-        for (i, glyph) in self.glyphs.iter().enumerate() {
-            if desired_x < glyph.logical_rect().center().x {
-                return i;
+        if !self.has_rtl() {
+            for (i, glyph) in self.glyphs.iter().enumerate() {
+                if desired_x < glyph.logical_rect().center().x {
+                    return i;
+                }
             }
+            return self.char_count_excluding_newline();
+        }
+
+        // Bidirectional text: find the visually closest glyph.
+        let closest = self.glyphs.iter().enumerate().min_by_key(|(_, glyph)| {
+            OrderedFloat((glyph.pos.x - desired_x).max(desired_x - glyph.max_x()))
+        });
+        let Some((i, glyph)) = closest else {
+            return 0;
+        };
+
+        // The logical start of a right-to-left glyph is its right side:
+        let left_half = desired_x < glyph.logical_rect().center().x;
+        if left_half == glyph.is_rtl() {
+            i + 1
+        } else {
+            i
         }
-        self.char_count_excluding_newline()
     }
 
+    /// The x coordinate of the cursor before the given column, in row-relative coordinates.
     pub fn x_offset(&self, column: usize) -> f32 {
         if let Some(glyph) = self.glyphs.get(column) {
-            glyph.pos.x
+            if glyph.is_rtl() {
+                glyph.max_x()
+            } else {
+                glyph.pos.x
+            }
+        } else if let Some(last) = self.glyphs.last()
+            && last.is_rtl()
+        {
+            last.pos.x
         } else {
             self.size.x
         }
     }
 
+    /// The horizontal extents covered by the given range of columns, in row-relative coordinates.
+    ///
+    /// For left-to-right text this is at most one range, but a selection
+    /// in bidirectional text can be visually split up into several pieces.
+    pub fn x_ranges(&self, columns: Range<usize>) -> SmallVec<[Rangef; 1]> {
+        if !self.has_rtl() {
+            // The glyphs are in visual order, so the columns are next to each other:
+            let columns = columns.start..columns.end.min(self.glyphs.len());
+            return if columns.is_empty() {
+                SmallVec::new()
+            } else {
+                smallvec![Rangef::new(
+                    self.glyphs[columns.start].pos.x,
+                    self.glyphs[columns.end - 1].max_x(),
+                )]
+            };
+        }
+
+        let mut visual_order: Vec<usize> = (0..self.glyphs.len()).collect();
+        visual_order.sort_by(|&a, &b| self.glyphs[a].pos.x.total_cmp(&self.glyphs[b].pos.x));
+
+        let mut ranges: SmallVec<[Rangef; 1]> = SmallVec::new();
+        let mut current: Option<Rangef> = None;
+        for i in visual_order {
+            let glyph = &self.glyphs[i];
+            if columns.contains(&i) {
+                let current = current.get_or_insert(Rangef::point(glyph.pos.x));
+                current.max = current.max.max(glyph.max_x());
+            } else if let Some(range) = current.take() {
+                ranges.push(range);
+            }
+        }
+        ranges.extend(current);
+        ranges
+    }
+
     #[inline]
     pub fn height(&self) -> f32 {
         self.size.y
@@ -946,6 +1354,54 @@ impl Galley {
         self.pos_from_layout_cursor(&self.layout_from_cursor(cursor))
     }
 
+    /// Where the [`InlineBox`]es of the job ended up, relative to the galley.
+    ///
+    /// Returns the section index of each box (as returned by [`LayoutJob::append_inline_box`])
+    /// together with its rect, in the order of the text.
+    /// Boxes that were elided are not included.
+    pub fn inline_box_rects(&self) -> Vec<(usize, Rect)> {
+        // (char index, section index, box), starting out with the byte index instead of the char index:
+        let mut boxes: Vec<(usize, usize, InlineBox)> = vec![];
+        for (section_index, section) in self.job.sections.iter().enumerate() {
+            if let Some(inline_box) = section.inline_box {
+                for (offset, _) in self.job.text[section.byte_range.clone()].char_indices() {
+                    boxes.push((section.byte_range.start + offset, section_index, inline_box));
+                }
+            }
+        }
+        if boxes.is_empty() {
+            return vec![];
+        }
+        boxes.sort_by_key(|(byte_index, ..)| *byte_index);
+
+        let mut char_index = 0;
+        let mut byte_index = 0;
+        for (index, ..) in &mut boxes {
+            char_index += self.job.text[byte_index..*index].chars().count();
+            byte_index = *index;
+            *index = char_index;
+        }
+
+        let mut rects = vec![];
+        let mut boxes = boxes.into_iter().peekable();
+        let mut row_start = 0;
+        for placed_row in &self.rows {
+            let row_end = row_start + placed_row.char_count_excluding_newline();
+            while let Some((index, section_index, inline_box)) =
+                boxes.next_if(|(index, ..)| *index < row_end)
+            {
+                let glyph = &placed_row.glyphs[index - row_start];
+                if glyph.chr == OBJECT_REPLACEMENT_CHARACTER {
+                    // Not replaced by the overflow character
+                    let min = placed_row.pos + vec2(glyph.pos.x, glyph.pos.y - inline_box.baseline);
+                    rects.push((section_index, Rect::from_min_size(min, inline_box.size)));
+                }
+            }
+            row_start += placed_row.char_count_including_newline();
+        }
+        rects
+    }
+
     /// Cursor at the given position within the galley.
     ///
     /// A cursor above the galley is considered
@@ -0,0 +1,182 @@
+use egui::accesskit::Role;
+use egui::{Id, Key, Modifiers, OutputCommand, PointerButton, Pos2, vec2};
+use egui_extras::{DataColumn, DataTable, DataTableState};
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable as _;
+
+#[derive(Default)]
+struct State {
+    fruits: Vec<(String, u32)>,
+    selected: Vec<usize>,
+    edited: Vec<usize>,
+    table_id: Option<Id>,
+    table: DataTableState,
+}
+
+fn harness() -> Harness<'static, State> {
+    let fruits = [("Cherry", 5), ("Apple", 3), ("Banana", 12)]
+        .map(|(name, count)| (name.to_owned(), count))
+        .to_vec();
+    Harness::new_ui_state(
+        |ui, state: &mut State| {
+            let output = DataTable::new("fruits")
+                .column(
+                    DataColumn::new("Name", |fruit: &(String, u32)| fruit.0.clone())
+                        .editable(|ui, fruit| ui.text_edit_singleline(&mut fruit.0)),
+                )
+                .column(
+                    DataColumn::new("Count", |fruit: &(String, u32)| fruit.1.to_string())
+                        .sort_by(|a, b| a.1.cmp(&b.1)),
+                )
+                .show(ui, &mut state.fruits);
+            state.table = DataTableState::load(ui.ctx(), output.response.id).unwrap_or_default();
+            state.selected = state.table.selected().iter().copied().collect();
+            state.edited.extend(output.edited);
+            state.table_id = Some(output.response.id);
+        },
+        State {
+            fruits,
+            ..Default::default()
+        },
+    )
+}
+
+fn top(harness: &Harness<'_, State>, label: &str) -> f32 {
+    harness.get_by_label(label).rect().top()
+}
+
+#[test]
+fn sort_select_and_copy() {
+    let mut harness = harness();
+    assert!(top(&harness, "Cherry") < top(&harness, "Apple"));
+
+    harness.get_by_label("Name").click();
+    harness.run();
+    assert!(top(&harness, "Apple") < top(&harness, "Banana"));
+    assert!(top(&harness, "Banana") < top(&harness, "Cherry"));
+
+    harness.get_by_label("Name ⏶").click();
+    harness.run();
+    assert!(
+        top(&harness, "Cherry") < top(&harness, "Apple"),
+        "Descending"
+    );
+
+    harness.get_by_label("Count").click();
+    harness.run();
+    harness.get_by_label("Apple").click();
+    harness.run();
+    assert_eq!(harness.state().selected, [1]);
+
+    harness.key_press(Key::ArrowDown);
+    harness.run();
+    harness.key_press_modifiers(Modifiers::SHIFT, Key::ArrowDown);
+    harness.run();
+    assert_eq!(
+        harness.state().selected,
+        [0, 2],
+        "Cherry and Banana come after Apple, sorted by count"
+    );
+
+    harness.event(egui::Event::Copy);
+    harness.step();
+    let copied =
+        harness
+            .output()
+            .platform_output
+            .commands
+            .iter()
+            .find_map(|command| match command {
+                OutputCommand::CopyText(text) => Some(text.clone()),
+                _ => None,
+            });
+    assert_eq!(copied.as_deref(), Some("Cherry\t5\nBanana\t12\n"));
+}
+
+#[test]
+fn edit_cell() {
+    let mut harness = harness();
+    // Each click takes a few frames in the harness, so allow a slow double-click:
+    harness
+        .ctx
+        .options_mut(|o| o.input_options.max_double_click_delay = 1.0);
+
+    let apple = harness.get_by_label("Apple");
+    apple.click();
+    apple.click();
+    harness.run();
+    harness.get_by_role(Role::TextInput).type_text(" pie");
+    harness.run();
+    harness.key_press(Key::Enter);
+    harness.run();
+
+    assert_eq!(harness.state().fruits[1].0, "Apple pie");
+    assert!(harness.state().edited.contains(&1));
+    assert!(harness.query_by_role(Role::TextInput).is_none());
+}
+
+fn drag(harness: &mut Harness<'_, State>, from: Pos2, to: Pos2) {
+    harness.event(egui::Event::PointerMoved(from));
+    harness.run();
+    harness.event(egui::Event::PointerButton {
+        pos: from,
+        button: PointerButton::Primary,
+        pressed: true,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    harness.event(egui::Event::PointerMoved(from.lerp(to, 0.5)));
+    harness.run();
+    harness.event(egui::Event::PointerMoved(to));
+    harness.run();
+    harness.event(egui::Event::PointerButton {
+        pos: to,
+        button: PointerButton::Primary,
+        pressed: false,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+}
+
+fn update_table(harness: &mut Harness<'_, State>, update: impl FnOnce(&mut DataTableState)) {
+    let id = harness.state().table_id.expect("The table was shown");
+    let mut table = DataTableState::load(&harness.ctx, id).expect("The table was stored");
+    update(&mut table);
+    table.store(&harness.ctx, id);
+    harness.run();
+}
+
+#[test]
+fn column_keeps_its_width() {
+    let mut harness = harness();
+    let width = |harness: &Harness<'_, State>, column| {
+        harness.state().table.column_width(column).expect("Shown")
+    };
+    let name_width = width(&harness, "Name");
+    let count_width = width(&harness, "Count");
+
+    // The resize handle of the name column is halfway between it and the count column:
+    let count = harness.get_by_label("Count").rect();
+    let handle = count.left_center() - vec2(0.5 * harness.ctx.style().spacing.item_spacing.x, 0.0);
+    drag(&mut harness, handle, handle + vec2(40.0, 0.0));
+    let resized_width = width(&harness, "Name");
+    assert!(
+        name_width + 30.0 < resized_width,
+        "{name_width} was resized to {resized_width}"
+    );
+
+    update_table(&mut harness, |table| {
+        table.move_column("Name", "Count", true);
+    });
+    assert!(
+        harness.get_by_label("Count").rect().left() < harness.get_by_label("Name").rect().left()
+    );
+    assert_eq!(width(&harness, "Name"), resized_width);
+    assert_eq!(width(&harness, "Count"), count_width);
+
+    update_table(&mut harness, |table| table.set_hidden("Count", true));
+    assert_eq!(width(&harness, "Name"), resized_width);
+    update_table(&mut harness, |table| table.set_hidden("Count", false));
+    assert_eq!(width(&harness, "Name"), resized_width);
+    assert_eq!(width(&harness, "Count"), count_width);
+}
@@ -0,0 +1,95 @@
+use egui::{DockArea, DockNode, DockState, Modifiers};
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable as _;
+
+#[test]
+fn test_dock_area() {
+    // With the `persistence` feature, panes need to be deserializable, so they can't be `&str`:
+    fn tabs(panes: &[&str]) -> DockNode<String> {
+        DockNode::tabs(panes.iter().map(|pane| (*pane).to_owned()).collect())
+    }
+
+    let dock_id = egui::Id::new("dock");
+    let mut harness = Harness::new_ui_state(
+        |ui, closed: &mut Vec<String>| {
+            let output = DockArea::new(dock_id).closable(true).show(
+                ui,
+                || DockState::new(DockNode::horizontal(0.5, tabs(&["a", "b"]), tabs(&["c"]))),
+                |pane| pane.into(),
+                |ui, pane| {
+                    ui.label(format!("Contents of {pane}"));
+                },
+            );
+            closed.extend(output.closed);
+        },
+        Vec::new(),
+    );
+    let layout = |harness: &Harness<'_, Vec<String>>| {
+        DockState::<String>::load(&harness.ctx, dock_id).unwrap()
+    };
+
+    assert_eq!(
+        harness
+            .get_all_by_role(egui::accesskit::Role::TabList)
+            .count(),
+        2
+    );
+    assert_eq!(
+        harness.get_all_by_role(egui::accesskit::Role::Tab).count(),
+        3
+    );
+    assert!(harness.query_by_label("Contents of b").is_none());
+    harness.get_by_label("b").click();
+    harness.run();
+    assert!(harness.query_by_label("Contents of b").is_some());
+
+    // Drag "b" to the bottom of "c":
+    let from = harness.get_by_label("b").rect().center();
+    let screen = harness.ctx.content_rect();
+    let to = egui::pos2(
+        0.5 * (harness.get_by_label("c").rect().left() + screen.right()),
+        screen.bottom() - 20.0,
+    );
+    harness.event(egui::Event::PointerMoved(from));
+    harness.event(egui::Event::PointerButton {
+        pos: from,
+        button: egui::PointerButton::Primary,
+        pressed: true,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    for pos in [from + egui::vec2(10.0, 0.0), to] {
+        harness.event(egui::Event::PointerMoved(pos));
+        harness.run();
+    }
+    harness.event(egui::Event::PointerButton {
+        pos: to,
+        button: egui::PointerButton::Primary,
+        pressed: false,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    assert_eq!(
+        layout(&harness).root(),
+        Some(&DockNode::horizontal(
+            0.5,
+            tabs(&["a"]),
+            DockNode::vertical(0.5, tabs(&["c"]), tabs(&["b"])),
+        ))
+    );
+
+    // Undock "a" from the context menu, and close its window:
+    harness.get_by_label("a").click_secondary();
+    harness.run();
+    harness.get_by_label("Undock").click();
+    harness.run();
+    assert_eq!(
+        layout(&harness).floating_panes().collect::<Vec<_>>(),
+        [&"a"]
+    );
+    assert!(harness.query_by_label("Contents of a").is_some());
+    harness.get_by_label("Close window").click();
+    harness.run();
+    assert_eq!(harness.state(), &["a"]);
+    assert!(!layout(&harness).contains(&"a".to_owned()));
+}
@@ -0,0 +1,77 @@
+use egui::{Flex, FlexAlign, FlexItem, Rect, Vec2};
+use egui_kittest::Harness;
+
+#[test]
+fn test_flex() {
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(300.0, 200.0))
+        .build_ui_state(
+            |ui, rects: &mut Vec<Rect>| {
+                rects.clear();
+                Flex::horizontal()
+                    .wrap(true)
+                    .align_items(FlexAlign::Stretch)
+                    .show(ui, |flex| {
+                        let frame = egui::Frame::group(flex.ui().style());
+                        let items = [
+                            FlexItem::new().basis(100.0),
+                            FlexItem::new().grow(1.0),
+                            FlexItem::new().basis(200.0),
+                            FlexItem::new().grow(1.0),
+                        ];
+                        for (index, item) in items.into_iter().enumerate() {
+                            let response = flex.add_ui(item.frame(frame), |ui| {
+                                ui.label("Item\n".repeat(index + 1).trim_end());
+                            });
+                            rects.push(response.response.rect);
+                        }
+                    });
+            },
+            vec![],
+        );
+    harness.run();
+
+    let rects = harness.state();
+    let content = harness.ctx.content_rect().shrink(8.0);
+    assert_eq!(rects[0].top(), rects[1].top(), "Same line");
+    assert_eq!(rects[0].left(), content.left());
+    assert_eq!(rects[0].width(), 100.0);
+    assert_eq!(rects[1].right(), content.right(), "Grows to fill the line");
+    assert_eq!(
+        rects[0].height(),
+        rects[1].height(),
+        "Stretched to the line"
+    );
+    assert!(
+        rects[1].bottom() < rects[2].top(),
+        "Doesn't fit, so it wraps"
+    );
+    assert_eq!(rects[2].top(), rects[3].top());
+    assert_eq!(rects[2].height(), rects[3].height());
+    assert_eq!(rects[3].right(), content.right());
+}
+
+#[test]
+fn test_flex_id_is_stable() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (show_label, passes): &mut (bool, usize)| {
+            *passes += 1;
+            if *show_label {
+                ui.label("Shown before the flex");
+            }
+            Flex::horizontal().show(ui, |flex| {
+                flex.add(FlexItem::new(), egui::Label::new("Item"));
+            });
+        },
+        (false, 0),
+    );
+    harness.run();
+
+    *harness.state_mut() = (true, 0);
+    harness.step();
+    assert_eq!(
+        harness.state().1,
+        1,
+        "A widget before the flex doesn't make it lay out anew"
+    );
+}
@@ -0,0 +1,149 @@
+use egui::epaint::ClippedShape;
+use egui::{Align, Grid, GridCell, Shape, Vec2};
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable as _;
+
+#[test]
+fn test_grid_cells() {
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(400.0, 300.0))
+        .build_ui(|ui| {
+            Grid::new("grid")
+                .num_columns(3)
+                .col_width_fraction(0, 0.5)
+                .col_align(2, Align::RIGHT)
+                .show(ui, |ui| {
+                    ui.set_grid_cell(GridCell::new().col_span(3));
+                    ui.label("A long header, spanning all three columns");
+                    ui.end_row();
+
+                    ui.set_grid_cell(GridCell::new().row_span(2));
+                    ui.label("Tall");
+                    ui.label("B1");
+                    ui.label("C1");
+                    ui.end_row();
+
+                    ui.label("B2");
+                    ui.label("Wide C2");
+                    ui.end_row();
+
+                    ui.set_grid_cell(GridCell::at(4, 1));
+                    ui.label("B4");
+                });
+        });
+    harness.run();
+
+    let rect = |label: &str| harness.get_by_label(label).rect();
+    let content = harness.ctx.content_rect().shrink(8.0);
+    let header = rect("A long header, spanning all three columns");
+    assert_eq!(header.left(), content.left());
+    assert!(
+        rect("B1").left() < header.right(),
+        "The header spans the columns, instead of widening the first one"
+    );
+    assert_eq!(
+        rect("B2").left(),
+        rect("B1").left(),
+        "The tall cell covers the first column of the next row"
+    );
+    let tall = rect("Tall").center().y;
+    assert!(
+        rect("B1").center().y < tall && tall < rect("B2").center().y,
+        "The tall cell is centered in its two rows"
+    );
+    assert!(
+        rect("B1").left() - content.left() >= 0.5 * (content.width() - 2.0 * 8.0),
+        "The first column takes half of the width"
+    );
+    assert_eq!(
+        rect("C1").right(),
+        rect("Wide C2").right(),
+        "The last column is right-aligned"
+    );
+    assert_eq!(rect("B4").left(), rect("B1").left());
+    assert!(rect("B2").bottom() < rect("B4").top(), "Row 3 is skipped");
+}
+
+#[test]
+fn test_grid_stripes_rows_jumped_over() {
+    let mut harness = Harness::new_ui(|ui| {
+        Grid::new("grid").striped(true).show(ui, |ui| {
+            ui.label("Row 0");
+            ui.end_row();
+
+            ui.label("Row 1");
+            ui.set_grid_cell(GridCell::at(3, 0));
+            ui.label("Row 3");
+            ui.end_row();
+
+            ui.label("Row 4");
+        });
+    });
+    harness.run();
+
+    let stripe_color = harness.ctx.style().visuals.faint_bg_color;
+    let stripes = harness
+        .output()
+        .shapes
+        .iter()
+        .filter(|ClippedShape { shape, .. }| {
+            matches!(shape, Shape::Rect(rect) if rect.fill == stripe_color)
+        })
+        .count();
+    assert_eq!(stripes, 2, "Rows 1 and 3 are striped");
+}
+
+#[test]
+fn test_grid_cell_rows_count_from_start_row() {
+    let mut harness = Harness::new_ui(|ui| {
+        Grid::new("grid").start_row(10).show(ui, |ui| {
+            ui.label("Row 0");
+            ui.end_row();
+
+            ui.set_grid_cell(GridCell::at(2, 0));
+            ui.label("Row 2");
+        });
+    });
+    harness.run();
+
+    let row_0 = harness.get_by_label("Row 0").rect();
+    let row_2 = harness.get_by_label("Row 2").rect();
+    assert!(
+        row_0.bottom() < row_2.top(),
+        "Row 2 is below the start row, instead of on top of it"
+    );
+}
+
+#[test]
+fn test_grid_col_width_fraction_is_exact() {
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(400.0, 300.0))
+        .build_ui(|ui| {
+            Grid::new("grid")
+                .num_columns(2)
+                .col_width_fraction(0, 0.25)
+                .show(ui, |ui| {
+                    ui.label("A label that is much wider than a quarter of the grid");
+                    ui.label("B");
+                    ui.end_row();
+                });
+        });
+    harness.run();
+
+    let content = harness.ctx.content_rect().shrink(8.0);
+    let spacing = harness.ctx.style().spacing.item_spacing.x;
+    let quarter = 0.25 * (content.width() - spacing);
+    let wide = harness
+        .get_by_label("A label that is much wider than a quarter of the grid")
+        .rect();
+    assert!(wide.width() <= quarter + 0.5, "The text wraps to fit");
+    assert!(
+        wide.height() > 2.0 * harness.get_by_label("B").rect().height(),
+        "The text is wrapped over several lines"
+    );
+    let b_left = harness.get_by_label("B").rect().left();
+    assert!(
+        (b_left - (content.left() + quarter + spacing)).abs() < 1.0,
+        "The column doesn't grow: {b_left}"
+    );
+}
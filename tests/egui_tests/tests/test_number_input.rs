@@ -0,0 +1,53 @@
+use egui_kittest::Harness;
+use egui_kittest::kittest::{NodeT as _, Queryable as _};
+
+#[test]
+fn test_number_input() {
+    let mut harness = Harness::new_ui_state(
+        |ui, length_mm: &mut f64| {
+            ui.add(
+                egui::NumberInput::new(length_mm)
+                    .range(0.0..=100.0)
+                    .step(5.0)
+                    .unit("mm")
+                    .accept_unit("cm", 10.0),
+            );
+        },
+        95.0,
+    );
+
+    harness.get_by_label("+").click();
+    harness.run();
+    assert_eq!(*harness.state(), 100.0);
+    assert!(
+        harness.get_by_label("+").accesskit_node().is_disabled(),
+        "The + button is disabled at the end of the range"
+    );
+    harness.get_by_label("−").click();
+    harness.run();
+    assert_eq!(*harness.state(), 95.0);
+
+    harness
+        .get_by_role(egui::accesskit::Role::SpinButton)
+        .click();
+    harness.run();
+    harness.key_press(egui::Key::Backspace);
+    harness
+        .get_by_role(egui::accesskit::Role::SpinButton)
+        .type_text("2 cm + 3");
+    harness.key_press(egui::Key::Enter);
+    harness.run();
+    assert_eq!(*harness.state(), 23.0);
+
+    harness
+        .get_by_role(egui::accesskit::Role::SpinButton)
+        .click();
+    harness.run();
+    harness.key_press(egui::Key::Backspace);
+    harness
+        .get_by_role(egui::accesskit::Role::SpinButton)
+        .type_text("=3*4");
+    harness.key_press(egui::Key::Enter);
+    harness.run();
+    assert_eq!(*harness.state(), 12.0);
+}
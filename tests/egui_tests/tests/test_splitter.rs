@@ -0,0 +1,61 @@
+use egui::{Modifiers, SplitDirection, Splitter};
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable as _;
+
+#[test]
+fn test_splitter() {
+    let mut harness = Harness::new_ui(|ui| {
+        Splitter::new("splitter", SplitDirection::Horizontal)
+            .first_size_range(50.0..=f32::INFINITY)
+            .show(ui, |ui| ui.label("Left"), |ui| ui.label("Right"));
+    });
+    let right_x = harness.get_by_label("Right").rect().left();
+
+    // Drag the divider to the left, as far as the first pane allows:
+    let from = harness
+        .get_by_role(egui::accesskit::Role::Splitter)
+        .rect()
+        .center();
+    harness.event(egui::Event::PointerMoved(from));
+    harness.event(egui::Event::PointerButton {
+        pos: from,
+        button: egui::PointerButton::Primary,
+        pressed: true,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    for pos in [from - egui::vec2(10.0, 0.0), egui::pos2(0.0, from.y)] {
+        harness.event(egui::Event::PointerMoved(pos));
+        harness.run();
+    }
+    harness.event(egui::Event::PointerButton {
+        pos: egui::pos2(0.0, from.y),
+        button: egui::PointerButton::Primary,
+        pressed: false,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    let new_right_x = harness.get_by_label("Right").rect().left();
+    assert!(new_right_x < right_x);
+    assert!(
+        harness.get_by_label("Left").rect().left() + 50.0 < new_right_x,
+        "The first pane keeps its minimum size"
+    );
+
+    // Double-clicking collapses the smaller pane, and restores it.
+    // Each click takes a few frames in the harness, so allow a slow double-click:
+    harness
+        .ctx
+        .options_mut(|o| o.input_options.max_double_click_delay = 1.0);
+    let divider = harness.get_by_role(egui::accesskit::Role::Splitter);
+    divider.click();
+    divider.click();
+    harness.run();
+    assert!(harness.query_by_label("Left").is_none());
+    let divider = harness.get_by_role(egui::accesskit::Role::Splitter);
+    divider.click();
+    divider.click();
+    harness.run();
+    assert!(harness.query_by_label("Left").is_some());
+    assert_eq!(harness.get_by_label("Right").rect().left(), new_right_x);
+}
@@ -0,0 +1,93 @@
+use egui::{Event, Modifiers, MouseWheelUnit, vec2};
+use egui_extras::{Column, TableBuilder};
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable as _;
+
+fn wide_table() -> Harness<'static> {
+    Harness::builder()
+        .with_size(vec2(400.0, 300.0))
+        .build_ui(|ui| {
+            TableBuilder::new(ui)
+                .hscroll(true)
+                .frozen_columns(1)
+                .columns(Column::exact(50.0), 100)
+                .header(20.0, |mut header| {
+                    header.cols(|ui, col| {
+                        ui.label(format!("Column {col}"));
+                    });
+                })
+                .body(|body| {
+                    body.rows(20.0, 100, |mut row| {
+                        let row_index = row.index();
+                        row.cols(|ui, col| {
+                            ui.label(format!("{row_index}:{col}"));
+                        });
+                    });
+                });
+        })
+}
+
+#[test]
+fn only_columns_in_view_are_added() {
+    let harness = wide_table();
+
+    assert!(harness.query_by_label("Column 1").is_some());
+    assert!(harness.query_by_label("0:1").is_some());
+    assert!(harness.query_by_label("Column 50").is_none());
+    assert!(harness.query_by_label("0:50").is_none());
+}
+
+#[test]
+fn frozen_columns_stay_in_view() {
+    let mut harness = wide_table();
+    let frozen_left = harness.get_by_label("Column 0").rect().left();
+
+    harness.get_by_label("0:3").hover();
+    harness.event(Event::MouseWheel {
+        unit: MouseWheelUnit::Point,
+        delta: vec2(-1000.0, 0.0),
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+
+    assert_eq!(harness.get_by_label("Column 0").rect().left(), frozen_left);
+    assert!(harness.query_by_label("0:0").is_some());
+    assert!(
+        harness.query_by_label("Column 1").is_none(),
+        "Scrolled out of view"
+    );
+
+    let col = (2..100)
+        .find(|col| harness.query_by_label(&format!("Column {col}")).is_some())
+        .expect("Some columns are in view");
+    assert!(10 < col, "Scrolled to column {col}");
+    assert_eq!(
+        harness.get_by_label(&format!("Column {col}")).rect().left(),
+        harness.get_by_label(&format!("0:{col}")).rect().left(),
+        "The header scrolls along with the body"
+    );
+}
+
+#[test]
+fn header_scrolls_in_the_same_frame_as_the_body() {
+    let mut harness = wide_table();
+
+    harness.get_by_label("0:3").hover();
+    harness.event(Event::MouseWheel {
+        unit: MouseWheelUnit::Point,
+        delta: vec2(-200.0, 0.0),
+        modifiers: Modifiers::NONE,
+    });
+
+    for _ in 0..10 {
+        harness.step();
+        for col in 1..100 {
+            if let (Some(header), Some(cell)) = (
+                harness.query_by_label(&format!("Column {col}")),
+                harness.query_by_label(&format!("0:{col}")),
+            ) {
+                assert_eq!(header.rect().left(), cell.rect().left(), "Column {col}");
+            }
+        }
+    }
+}
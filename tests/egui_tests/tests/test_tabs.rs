@@ -0,0 +1,168 @@
+use egui::Modifiers;
+use egui_kittest::Harness;
+use egui_kittest::kittest::{NodeT as _, Queryable as _};
+
+#[test]
+fn test_tabs() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (tabs, closed): &mut (Vec<&'static str>, Vec<&'static str>)| {
+            let output = egui::Tabs::new(egui::Id::new("tabs")).closable(true).show(
+                ui,
+                tabs,
+                |name| egui::Id::new(name),
+                |name| (*name).into(),
+                |ui, name| ui.label(format!("Contents of {name}")),
+            );
+            closed.extend(output.closed);
+        },
+        (vec!["a", "b", "c"], Vec::new()),
+    );
+
+    assert!(harness.query_by_label("Contents of a").is_some());
+    assert_eq!(
+        harness.get_all_by_role(egui::accesskit::Role::Tab).count(),
+        3
+    );
+    harness.get_by_role(egui::accesskit::Role::TabList);
+    harness.get_by_role(egui::accesskit::Role::TabPanel);
+
+    // Click the title, since the close button is on the right of the tab:
+    let pos = harness.get_by_label("b").rect().left_center() + egui::vec2(4.0, 0.0);
+    for pressed in [true, false] {
+        harness.event(egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::NONE,
+        });
+    }
+    harness.run();
+    assert!(harness.query_by_label("Contents of b").is_some());
+    assert!(
+        harness
+            .get_by_label("b")
+            .accesskit_node()
+            .is_selected()
+            .unwrap_or_default()
+    );
+
+    // Ctrl+tab and ctrl+shift+tab wrap around:
+    harness.key_press_modifiers(Modifiers::CTRL, egui::Key::Tab);
+    harness.run();
+    assert!(harness.query_by_label("Contents of c").is_some());
+    harness.key_press_modifiers(Modifiers::CTRL, egui::Key::Tab);
+    harness.run();
+    assert!(harness.query_by_label("Contents of a").is_some());
+    harness.key_press_modifiers(Modifiers::CTRL | Modifiers::SHIFT, egui::Key::Tab);
+    harness.run();
+    assert!(harness.query_by_label("Contents of c").is_some());
+
+    // Drag "c" before "a":
+    let from = harness.get_by_label("c").rect().center();
+    let to = harness.get_by_label("a").rect().left_center() + egui::vec2(2.0, 0.0);
+    harness.event(egui::Event::PointerMoved(from));
+    harness.event(egui::Event::PointerButton {
+        pos: from,
+        button: egui::PointerButton::Primary,
+        pressed: true,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    for pos in [from + egui::vec2(-10.0, 0.0), to] {
+        harness.event(egui::Event::PointerMoved(pos));
+        harness.run();
+    }
+    harness.event(egui::Event::PointerButton {
+        pos: to,
+        button: egui::PointerButton::Primary,
+        pressed: false,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    assert_eq!(harness.state().0, ["c", "a", "b"]);
+    assert!(harness.query_by_label("Contents of c").is_some());
+
+    // Closing the active tab activates the next one:
+    harness
+        .get_all_by_label("Close tab")
+        .next()
+        .unwrap()
+        .click();
+    harness.run();
+    assert_eq!(harness.state().0, ["a", "b"]);
+    assert_eq!(harness.state().1, ["c"]);
+    assert!(harness.query_by_label("Contents of a").is_some());
+}
+
+#[test]
+fn test_tabs_keyboard_focus() {
+    let mut harness = Harness::new_ui_state(
+        |ui, tabs: &mut Vec<(&'static str, String)>| {
+            egui::Tabs::new(egui::Id::new("tabs")).show(
+                ui,
+                tabs,
+                |(name, _)| egui::Id::new(name),
+                |(name, _)| (*name).into(),
+                |ui, (_, text)| ui.text_edit_singleline(text),
+            );
+        },
+        vec![("a", "in a".to_owned()), ("b", "in b".to_owned())],
+    );
+
+    // Ctrl+tab also switches tabs from within the contents:
+    harness
+        .get_by_role(egui::accesskit::Role::TextInput)
+        .focus();
+    harness.run();
+    harness.key_press_modifiers(Modifiers::CTRL, egui::Key::Tab);
+    harness.run();
+    let text_input = harness.get_by_role(egui::accesskit::Role::TextInput);
+    assert_eq!(text_input.value().as_deref(), Some("in b"));
+    assert!(harness.get_by_label("b").is_focused());
+
+    // Tab moves the focus from the tab to the contents, as for any other widget:
+    harness.key_press(egui::Key::Tab);
+    harness.run();
+    assert!(
+        harness
+            .get_by_role(egui::accesskit::Role::TextInput)
+            .is_focused()
+    );
+}
+
+#[test]
+fn test_tabs_contents_id() {
+    type State = (Vec<&'static str>, Vec<(&'static str, egui::Id)>);
+    let mut harness = Harness::new_ui_state(
+        |ui, (tabs, contents_ids): &mut State| {
+            egui::Tabs::new(egui::Id::new("tabs")).show(
+                ui,
+                tabs,
+                |name| egui::Id::new(name),
+                |name| (*name).into(),
+                |ui, name| contents_ids.push((*name, ui.id())),
+            );
+        },
+        (vec!["a", "b"], Vec::new()),
+    );
+    harness.get_by_label("b").click();
+    harness.run();
+    let contents_id = |harness: &Harness<'_, State>, name| {
+        harness
+            .state()
+            .1
+            .iter()
+            .rev()
+            .find(|(n, _)| *n == name)
+            .unwrap()
+            .1
+    };
+    let a_id = contents_id(&harness, "a");
+    let b_id = contents_id(&harness, "b");
+    assert_ne!(a_id, b_id, "Each tab has its own contents id");
+
+    // Reordering the tabs doesn't change the id of the contents:
+    harness.state_mut().0.reverse();
+    harness.run();
+    assert_eq!(contents_id(&harness, "b"), b_id);
+}
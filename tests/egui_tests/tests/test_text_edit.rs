@@ -0,0 +1,303 @@
+use egui::Modifiers;
+use egui_kittest::Harness;
+use egui_kittest::kittest::{NodeT as _, Queryable as _};
+
+#[test]
+fn test_text_edit_multiple_cursors() {
+    let mut harness = Harness::new_ui_state(
+        |ui, text| {
+            ui.text_edit_multiline(text);
+        },
+        "foo bar foo\nfoo".to_owned(),
+    );
+
+    harness.run();
+    harness
+        .get_by_role(egui::accesskit::Role::MultilineTextInput)
+        .focus();
+    harness.run();
+
+    // Select the word at the cursor, then add the other two occurrences of it:
+    for _ in 0..3 {
+        harness.key_press_modifiers(Modifiers::COMMAND, egui::Key::D);
+        harness.run();
+    }
+
+    harness
+        .get_by_role(egui::accesskit::Role::MultilineTextInput)
+        .type_text("x");
+    harness.run();
+    assert_eq!(harness.state(), "x bar x\nx");
+
+    harness.key_press(egui::Key::Backspace);
+    harness.run();
+    assert_eq!(harness.state(), " bar \n");
+
+    // Escape goes back to the main cursor, without losing focus:
+    harness.key_press(egui::Key::Escape);
+    harness.run();
+    harness.event(egui::Event::Text("y".to_owned()));
+    harness.run();
+    assert_eq!(harness.state(), " bar y\n");
+}
+
+#[test]
+fn test_text_edit_search_and_replace() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (text, search): &mut (String, egui::text_edit::TextEditSearch)| {
+            egui::TextEdit::multiline(text).search(search).show(ui);
+        },
+        (
+            "One two one".to_owned(),
+            egui::text_edit::TextEditSearch::new("one"),
+        ),
+    );
+    harness.state_mut().1.replacement = "1".to_owned();
+
+    // Replace selects the first match, then replaces it:
+    for _ in 0..2 {
+        harness.state_mut().1.replace();
+        harness.run();
+    }
+    assert_eq!(harness.state().0, "1 two one");
+
+    harness.state_mut().1.replace_all();
+    harness.run();
+    assert_eq!(harness.state().0, "1 two 1");
+
+    // Each replacement can be undone:
+    harness
+        .get_by_role(egui::accesskit::Role::MultilineTextInput)
+        .focus();
+    harness.run();
+    harness.key_press_modifiers(Modifiers::COMMAND, egui::Key::Z);
+    harness.run();
+    assert_eq!(harness.state().0, "1 two one");
+    harness.key_press_modifiers(Modifiers::COMMAND, egui::Key::Z);
+    harness.run();
+    assert_eq!(harness.state().0, "One two one");
+}
+
+#[test]
+fn test_text_edit_autocomplete() {
+    let mut harness = Harness::new_ui_state(
+        |ui, text| {
+            let mut suggest = |query: &str| {
+                ["hello", "help", "world"]
+                    .into_iter()
+                    .filter(|word| word.starts_with(query))
+                    .map(ToOwned::to_owned)
+                    .collect()
+            };
+            ui.add(egui::TextEdit::multiline(text).autocomplete(&mut suggest));
+        },
+        "say ".to_owned(),
+    );
+
+    harness.run();
+    harness
+        .get_by_role(egui::accesskit::Role::MultilineTextInput)
+        .focus();
+    harness.run();
+    harness
+        .get_by_role(egui::accesskit::Role::MultilineTextInput)
+        .type_text("he");
+    harness.run();
+    assert!(harness.query_by_label("hello").is_some());
+    assert!(harness.query_by_label("world").is_none());
+
+    harness.key_press(egui::Key::ArrowDown);
+    harness.key_press(egui::Key::Enter);
+    harness.run();
+    assert_eq!(harness.state(), "say help");
+    assert!(harness.query_by_label("hello").is_none());
+
+    // Choosing a suggestion is one undo step:
+    harness.key_press_modifiers(Modifiers::COMMAND, egui::Key::Z);
+    harness.run();
+    assert_eq!(harness.state(), "say he");
+
+    // Escape closes the popup, but keeps the focus:
+    harness
+        .get_by_role(egui::accesskit::Role::MultilineTextInput)
+        .type_text("l");
+    harness.run();
+    assert!(harness.query_by_label("hello").is_some());
+    harness.key_press(egui::Key::Escape);
+    harness.run();
+    assert!(harness.query_by_label("hello").is_none());
+    harness.key_press(egui::Key::Enter);
+    harness.run();
+    assert_eq!(harness.state(), "say hel\n");
+}
+
+#[test]
+fn test_text_edit_mask_and_filter() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (phone, digits): &mut (String, String)| {
+            let mask = egui::text_edit::InputMask::new("(999) 999-9999");
+            let complete = mask.is_complete(phone);
+            ui.add(
+                egui::TextEdit::singleline(phone)
+                    .id_salt("phone")
+                    .mask(mask)
+                    .invalid_opt((!complete).then(|| "Incomplete phone number".to_owned())),
+            );
+
+            let mut only_digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+            ui.add(
+                egui::TextEdit::singleline(digits)
+                    .id_salt("digits")
+                    .input_filter(&mut only_digits),
+            );
+        },
+        (String::new(), String::new()),
+    );
+
+    harness.run();
+    text_input(&harness, 0).focus();
+    harness.run();
+    text_input(&harness, 0).type_text("555123");
+    harness.run();
+    assert_eq!(harness.state().0, "(555) 123");
+    assert_eq!(
+        text_input(&harness, 0).accesskit_node().data().invalid(),
+        Some(egui::accesskit::Invalid::True)
+    );
+    assert_eq!(
+        text_input(&harness, 0)
+            .accesskit_node()
+            .description()
+            .as_deref(),
+        Some("Incomplete phone number")
+    );
+
+    // Letters don't fit the mask:
+    text_input(&harness, 0).type_text("x");
+    harness.run();
+    text_input(&harness, 0).type_text("4567");
+    harness.run();
+    assert_eq!(harness.state().0, "(555) 123-4567");
+    assert_eq!(
+        text_input(&harness, 0).accesskit_node().data().invalid(),
+        None
+    );
+
+    text_input(&harness, 1).focus();
+    harness.run();
+    text_input(&harness, 1).type_text("12");
+    harness.run();
+    text_input(&harness, 1).type_text("a");
+    harness.run();
+    text_input(&harness, 1).type_text("3");
+    harness.run();
+    assert_eq!(harness.state().1, "123");
+}
+
+fn text_input<'h, State>(harness: &'h Harness<'_, State>, index: usize) -> egui_kittest::Node<'h> {
+    harness
+        .get_all_by_role(egui::accesskit::Role::TextInput)
+        .nth(index)
+        .unwrap()
+}
+
+#[test]
+fn test_text_edit_filter_applies_to_replacements() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (digits, search): &mut (String, egui::text_edit::TextEditSearch)| {
+            let mut only_digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+            let mut suggest = |_: &str| vec!["12ab".to_owned()];
+            egui::TextEdit::singleline(digits)
+                .input_filter(&mut only_digits)
+                .autocomplete(&mut suggest)
+                .search(search)
+                .show(ui);
+        },
+        ("123".to_owned(), egui::text_edit::TextEditSearch::new("2")),
+    );
+
+    harness.state_mut().1.replacement = "x".to_owned();
+    harness.state_mut().1.replace_all();
+    harness.run();
+    assert_eq!(harness.state().0, "123");
+
+    harness.state_mut().1.replacement = "4".to_owned();
+    harness.state_mut().1.replace_all();
+    harness.run();
+    assert_eq!(harness.state().0, "143");
+
+    // Nor can a suggestion make it through the filter:
+    harness
+        .get_by_role(egui::accesskit::Role::TextInput)
+        .focus();
+    harness.run();
+    harness
+        .get_by_role(egui::accesskit::Role::TextInput)
+        .type_text("1");
+    harness.run();
+    assert!(harness.query_by_label("12ab").is_some());
+    harness.key_press(egui::Key::Enter);
+    harness.run();
+    assert_eq!(harness.state().0, "1143");
+}
+
+#[test]
+fn test_text_edit_diagnostics() {
+    let mut harness = Harness::new_ui_state(
+        |ui, text: &mut String| {
+            let diagnostics: Vec<_> = text
+                .find("teh")
+                .map(|start| {
+                    let start = text[..start].chars().count();
+                    egui::text_edit::TextEditDiagnostic::new(start..start + 3, "Unknown word")
+                        .suggestions(vec!["the".to_owned(), "ten".to_owned()])
+                })
+                .into_iter()
+                .collect();
+            ui.add(egui::TextEdit::singleline(text).diagnostics(&diagnostics));
+        },
+        "teh".to_owned(),
+    );
+
+    harness.run();
+    text_input(&harness, 0).click_secondary();
+    harness.run();
+    assert!(harness.query_by_label("Unknown word").is_some());
+    harness.get_by_label("ten").click();
+    harness.step();
+    // The menu has the focus, but the edit is still reported with the text from before it:
+    let value_changed = harness
+        .output()
+        .platform_output
+        .events
+        .iter()
+        .find_map(|event| match event {
+            egui::output::OutputEvent::ValueChanged(info) => Some(info.clone()),
+            _ => None,
+        })
+        .expect("Picking a suggestion should report a change");
+    assert_eq!(value_changed.prev_text_value.as_deref(), Some("teh"));
+    assert_eq!(value_changed.current_text_value.as_deref(), Some("ten"));
+    harness.run();
+    assert_eq!(harness.state(), "ten");
+    assert!(harness.query_by_label("Unknown word").is_none());
+
+    // Right-clicking where there is no diagnostic shows no menu:
+    text_input(&harness, 0).click_secondary();
+    harness.run();
+    assert!(harness.query_by_label("the").is_none());
+
+    // Picking a suggestion is one undo step:
+    harness.state_mut().replace_range(.., "teh");
+    harness.run();
+    text_input(&harness, 0).click_secondary();
+    harness.run();
+    harness.get_by_label("the").click();
+    harness.run();
+    assert_eq!(harness.state(), "the");
+    text_input(&harness, 0).focus();
+    harness.run();
+    harness.key_press_modifiers(Modifiers::COMMAND, egui::Key::Z);
+    harness.run();
+    assert_eq!(harness.state(), "teh");
+}
@@ -0,0 +1,108 @@
+use egui::{DropPosition, Modifiers, TreeView, TreeViewAction};
+use egui_kittest::Harness;
+use egui_kittest::kittest::{NodeT as _, Queryable as _};
+
+#[test]
+fn test_tree_view() {
+    let mut harness = Harness::new_ui_state(
+        |ui, (selected, actions): &mut (Vec<&'static str>, Vec<TreeViewAction<&'static str>>)| {
+            let output = TreeView::new(egui::Id::new("tree")).show(ui, |tree| {
+                tree.dir("src", "src", |tree| {
+                    tree.leaf("lib.rs", "lib.rs");
+                    tree.leaf("main.rs", "main.rs");
+                });
+                tree.leaf("Cargo.toml", "Cargo.toml");
+            });
+            *selected = output.state.selected().to_vec();
+            actions.extend(output.actions);
+        },
+        Default::default(),
+    );
+
+    assert_eq!(
+        harness
+            .get_all_by_role(egui::accesskit::Role::TreeItem)
+            .count(),
+        2,
+        "The children of a closed directory are not shown"
+    );
+    assert!(harness.query_by_label("lib.rs").is_none());
+
+    // Open the directory and walk into it with the keyboard:
+    harness.get_by_label("src").click();
+    harness.run();
+    assert_eq!(harness.state().0, ["src"]);
+    harness.key_press(egui::Key::ArrowRight);
+    harness.run();
+    assert_eq!(
+        harness
+            .get_by_label("src")
+            .accesskit_node()
+            .data()
+            .is_expanded(),
+        Some(true)
+    );
+    harness.key_press(egui::Key::ArrowRight);
+    harness.run();
+    assert_eq!(harness.state().0, ["lib.rs"]);
+    harness.key_press_modifiers(Modifiers::SHIFT, egui::Key::ArrowDown);
+    harness.run();
+    assert_eq!(harness.state().0, ["lib.rs", "main.rs"]);
+    assert!(
+        harness
+            .get_by_label("main.rs")
+            .accesskit_node()
+            .is_selected()
+            .unwrap_or_default()
+    );
+    harness.key_press(egui::Key::Enter);
+    harness.run();
+    assert_eq!(
+        harness.state().1,
+        [TreeViewAction::Activate(vec!["lib.rs", "main.rs"])]
+    );
+
+    // Left goes back to the parent:
+    harness.key_press(egui::Key::ArrowLeft);
+    harness.run();
+    assert_eq!(harness.state().0, ["src"]);
+
+    // Ctrl/cmd-click adds to the selection:
+    harness
+        .get_by_label("Cargo.toml")
+        .click_modifiers(Modifiers::COMMAND);
+    harness.run();
+    assert_eq!(harness.state().0, ["src", "Cargo.toml"]);
+
+    // Drag a file into the directory:
+    harness.state_mut().1.clear();
+    let from = harness.get_by_label("main.rs").rect().center();
+    let to = harness.get_by_label("src").rect().center();
+    harness.event(egui::Event::PointerMoved(from));
+    harness.event(egui::Event::PointerButton {
+        pos: from,
+        button: egui::PointerButton::Primary,
+        pressed: true,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    for pos in [from + egui::vec2(0.0, -10.0), to] {
+        harness.event(egui::Event::PointerMoved(pos));
+        harness.run();
+    }
+    harness.event(egui::Event::PointerButton {
+        pos: to,
+        button: egui::PointerButton::Primary,
+        pressed: false,
+        modifiers: Modifiers::NONE,
+    });
+    harness.run();
+    assert_eq!(
+        harness.state().1,
+        [TreeViewAction::Move {
+            nodes: vec!["main.rs"],
+            target: "src",
+            position: DropPosition::Inside,
+        }]
+    );
+}
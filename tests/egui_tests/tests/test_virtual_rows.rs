@@ -0,0 +1,143 @@
+use egui::ScrollArea;
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable as _;
+
+#[test]
+fn test_virtual_rows() {
+    // Each row has an id and a number of extra lines:
+    let rows: Vec<(u64, usize)> = (0..1000).map(|i| (i, i as usize % 3)).collect();
+    let mut harness = Harness::new_ui_state(
+        |ui, (rows, scroll_to): &mut (Vec<(u64, usize)>, Option<f32>)| {
+            let mut scroll_area = ScrollArea::vertical();
+            if let Some(offset) = scroll_to.take() {
+                scroll_area = scroll_area.vertical_scroll_offset(offset);
+            }
+            scroll_area.show_virtual_rows(
+                ui,
+                rows.len(),
+                |row| egui::Id::new(rows[row].0),
+                |ui, row| {
+                    ui.label(format!("Row {}", rows[row].0));
+                    for _ in 0..rows[row].1 {
+                        ui.label("More");
+                    }
+                },
+            );
+        },
+        (rows, None),
+    );
+    assert!(harness.query_by_label("Row 0").is_some());
+    assert!(
+        harness.query_by_label("Row 999").is_none(),
+        "Only the visible rows are shown"
+    );
+
+    harness.state_mut().1 = Some(2000.0);
+    harness.run();
+    assert!(harness.query_by_label("Row 0").is_none());
+    let row = (0..1000)
+        .map(|i| format!("Row {i}"))
+        .find(|label| harness.query_by_label(label).is_some())
+        .expect("Some row should be visible");
+    let top = harness.get_by_label(&row).rect().top();
+
+    // Rows inserted above the viewport don't move it:
+    harness
+        .state_mut()
+        .0
+        .splice(0..0, (1000..1010).map(|i| (i, 5)));
+    harness.run();
+    assert_eq!(harness.get_by_label(&row).rect().top(), top);
+}
+
+#[test]
+fn test_virtual_rows_skip_unchanged_rows() {
+    // A log that keeps its last 1000 lines, and counts how often the id of a row is asked for:
+    let mut harness = Harness::new_ui_state(
+        |ui, (first_line, row_id_calls): &mut (u64, usize)| {
+            let first_line = *first_line;
+            let calls = std::cell::Cell::new(0);
+            ScrollArea::vertical().show_virtual_rows(
+                ui,
+                1000,
+                |row| {
+                    calls.set(calls.get() + 1);
+                    egui::Id::new(first_line + row as u64)
+                },
+                |ui, row| {
+                    ui.label(format!("Line {}", first_line + row as u64));
+                },
+            );
+            *row_id_calls += calls.get();
+        },
+        (0, 0),
+    );
+    assert!(harness.query_by_label("Line 0").is_some());
+
+    harness.state_mut().1 = 0;
+    harness.step();
+    assert!(
+        harness.state().1 < 100,
+        "Only the shown rows are asked for, not all {} calls",
+        harness.state().1
+    );
+
+    // The oldest line is dropped and a new one added, so there are as many rows as before:
+    harness.state_mut().0 = 1;
+    harness.run();
+    assert!(harness.query_by_label("Line 0").is_none());
+    assert!(harness.query_by_label("Line 1").is_some());
+}
+
+#[test]
+fn test_virtual_rows_discard_only_when_rows_move() {
+    // Rows of the same height, and how many passes each frame took:
+    let mut harness = Harness::new_ui_state(
+        |ui, (scroll_to, passes): &mut (Option<f32>, usize)| {
+            *passes += 1;
+            let mut scroll_area = ScrollArea::vertical();
+            if let Some(offset) = scroll_to.take() {
+                scroll_area = scroll_area.vertical_scroll_offset(offset);
+            }
+            scroll_area.show_virtual_rows(ui, 1000, egui::Id::new, |ui, row| {
+                ui.label(format!("Row {row}"));
+            });
+        },
+        (None, 0),
+    );
+    assert!(harness.query_by_label("Row 0").is_some());
+
+    // The rows scrolled into view are as high as estimated from the rows measured so far:
+    *harness.state_mut() = (Some(5000.0), 0);
+    harness.step();
+    assert!(harness.query_by_label("Row 0").is_none());
+    assert_eq!(
+        harness.state().1,
+        1,
+        "Measuring new rows should not discard"
+    );
+}
+
+#[test]
+fn test_virtual_rows_empty() {
+    // Rows can be removed until there are none, without asking for the id of a row that doesn't exist:
+    let mut harness = Harness::new_ui_state(
+        |ui, rows: &mut Vec<u64>| {
+            ScrollArea::vertical().show_virtual_rows(
+                ui,
+                rows.len(),
+                |row| egui::Id::new(rows[row]),
+                |ui, row| {
+                    ui.label(format!("Row {}", rows[row]));
+                },
+            );
+        },
+        vec![0, 1, 2],
+    );
+    assert!(harness.query_by_label("Row 0").is_some());
+
+    harness.state_mut().clear();
+    harness.run();
+    harness.run();
+    assert!(harness.query_by_label("Row 0").is_none());
+}
@@ -0,0 +1,145 @@
+use egui::accesskit::Role;
+use egui::text_edit::VirtualTextEdit;
+use egui::{Event, ImeEvent, Key, Modifiers, ScrollArea, Vec2};
+use egui_kittest::Harness;
+use egui_kittest::kittest::Queryable as _;
+
+#[test]
+fn test_virtual_text_edit() {
+    let text: String = (0..10_000).map(|i| format!("line {i}\n")).collect();
+    let mut harness = Harness::builder()
+        .with_size(Vec2::new(300.0, 200.0))
+        .build_ui_state(
+            |ui, text| {
+                ScrollArea::both().show(ui, |ui| {
+                    ui.add(VirtualTextEdit::new(text));
+                });
+            },
+            text,
+        );
+
+    harness.run();
+    harness.get_by_role(Role::MultilineTextInput).focus();
+    harness.run();
+
+    harness.key_press(Key::End);
+    harness.key_press(Key::ArrowDown);
+    harness.run();
+    harness.get_by_role(Role::MultilineTextInput).type_text("!");
+    harness.run();
+    assert!(harness.state().starts_with("line 0\nline 1!\nline 2\n"));
+
+    // Jump to the end, far outside the visible lines:
+    harness.key_press_modifiers(Modifiers::COMMAND, Key::ArrowDown);
+    harness.key_press(Key::Backspace);
+    harness.run();
+    assert!(harness.state().ends_with("line 9999"));
+
+    harness.key_press_modifiers(Modifiers::COMMAND, Key::Z);
+    harness.run();
+    assert!(harness.state().ends_with("line 9999\n"));
+    harness.key_press_modifiers(Modifiers::COMMAND, Key::Z);
+    harness.run();
+    assert!(harness.state().starts_with("line 0\nline 1\nline 2\n"));
+}
+
+/// The text, and whether any frame reported a change.
+fn harness(text: &str) -> Harness<'static, (String, bool)> {
+    let mut harness = Harness::new_ui_state(
+        |ui, (text, changed): &mut (String, bool)| {
+            *changed |= ui.add(VirtualTextEdit::new(text)).changed();
+        },
+        (text.to_owned(), false),
+    );
+    harness.run();
+    harness.get_by_role(Role::MultilineTextInput).focus();
+    harness.run();
+    harness
+}
+
+#[test]
+fn test_virtual_text_edit_tab_and_boundaries() {
+    let mut harness = harness("ab");
+
+    // Nothing to delete before the start:
+    harness.key_press(Key::Backspace);
+    harness.run();
+    assert_eq!(harness.state(), &("ab".to_owned(), false));
+
+    harness.key_press(Key::Tab);
+    harness.run();
+    assert_eq!(harness.state(), &("\tab".to_owned(), true));
+    harness.state_mut().1 = false;
+
+    harness.key_press_modifiers(Modifiers::SHIFT, Key::Tab);
+    harness.run();
+    assert_eq!(harness.state().0, "ab");
+    harness.state_mut().1 = false;
+
+    // Nothing to delete after the end:
+    harness.key_press(Key::End);
+    harness.run();
+    harness.key_press(Key::Delete);
+    harness.run();
+    assert_eq!(harness.state(), &("ab".to_owned(), false));
+}
+
+#[test]
+fn test_virtual_text_edit_undo_restores_selection() {
+    let mut harness = harness("hello");
+
+    harness.key_press_modifiers(Modifiers::COMMAND, Key::A);
+    harness.run();
+    harness.event(Event::Text("x".to_owned()));
+    harness.run();
+    assert_eq!(harness.state().0, "x");
+
+    harness.key_press_modifiers(Modifiers::COMMAND, Key::Z);
+    harness.run();
+    assert_eq!(harness.state().0, "hello");
+
+    // The selection from before the edit is back, so typing replaces it:
+    harness.event(Event::Text("y".to_owned()));
+    harness.run();
+    assert_eq!(harness.state().0, "y");
+}
+
+#[test]
+fn test_virtual_text_edit_ime() {
+    let mut harness = harness("");
+
+    harness.event(Event::Ime(ImeEvent::Enabled));
+    harness.event(Event::Ime(ImeEvent::Preedit("ni".to_owned())));
+    harness.run();
+    assert_eq!(harness.state().0, "ni");
+
+    harness.event(Event::Ime(ImeEvent::Preedit("に".to_owned())));
+    harness.run();
+    assert_eq!(harness.state().0, "に");
+
+    harness.event(Event::Ime(ImeEvent::Commit("日本".to_owned())));
+    harness.event(Event::Ime(ImeEvent::Disabled));
+    harness.run();
+    assert_eq!(harness.state().0, "日本");
+}
+
+#[test]
+fn test_virtual_text_edit_multiple_cursors() {
+    let mut harness = harness("foo bar\nfoo");
+
+    // Select the word at the cursor, then add the next occurrence of it:
+    for _ in 0..2 {
+        harness.key_press_modifiers(Modifiers::COMMAND, Key::D);
+        harness.run();
+    }
+    harness.event(Event::Text("x".to_owned()));
+    harness.run();
+    assert_eq!(harness.state().0, "x bar\nx");
+
+    // Escape goes back to the main cursor, the one added last:
+    harness.key_press(Key::Escape);
+    harness.run();
+    harness.event(Event::Text("y".to_owned()));
+    harness.run();
+    assert_eq!(harness.state().0, "x bar\nxy");
+}